@@ -1,24 +1,26 @@
-use bincode::{
-    config::standard,
-    serde::{decode_from_slice, encode_to_vec},
-};
+use std::time::Instant;
+
+use bincode::serde::{decode_from_slice, encode_to_vec};
 use glam::Vec3;
 use macroquad::prelude::*;
 
 use crate::{
     assets::Assets,
     info::map::{self, after_game::AfterGameMap},
-    lobby::ui::{LobbyUi, UiErrorKind, UiInputError},
+    lobby::{
+        state_handlers::{chat::CLEAR_COMMAND, start_countdown::handle_countdown_started},
+        ui::{LobbyUi, UiErrorKind, UiInputError},
+    },
     net::NetworkHandle,
     session::ClientSession,
-    state::{ClientState, InputMode},
+    state::{ClientState, InputMode, Lobby},
 };
 use common::{
     chat::MAX_CHAT_MESSAGE_BYTES,
     constants::TICK_SECS,
     net::AppChannel,
-    player::{self, Color, Color::YELLOW},
-    protocol::{ClientMessage, ServerMessage},
+    player::{Color, Color::YELLOW},
+    protocol::{ClientMessage, RoundSummary, ServerMessage, config},
     snapshot::Snapshot,
 };
 
@@ -40,7 +42,7 @@ pub fn update(
 
     let result = match state {
         ClientState::AfterGameChat(mut chat_state) => {
-            let result = handle(&mut chat_state, session, ui, network);
+            let result = handle(&mut chat_state, session, ui, network, assets);
             session.state = ClientState::AfterGameChat(chat_state);
             result
         }
@@ -80,16 +82,36 @@ pub fn update(
 fn apply_snapshot_to_positions(positions: &mut [(Vec3, Color)], snapshot: &Snapshot) {
     for (i, pos_color) in positions.iter_mut().enumerate() {
         if let Some(remote) = snapshot.remote.get(i) {
-            pos_color.0 = vec3(remote.position.x, player::HEIGHT, remote.position.y);
+            pos_color.0 = vec3(remote.position.x, remote.height, remote.position.y);
         }
     }
 }
 
+fn show_round_standings(ui: &mut dyn LobbyUi, summary: &RoundSummary) {
+    if summary.next_round_follows {
+        ui.show_sanitized_message(&format!(
+            "Standings after round {} of {}:",
+            summary.round, summary.total_rounds
+        ));
+    } else {
+        ui.show_sanitized_message("Final standings:");
+    }
+    for (rank, standing) in summary.standings.iter().enumerate() {
+        ui.show_sanitized_message(&format!(
+            "  {}. {}  {} pts",
+            rank + 1,
+            standing.username,
+            standing.points
+        ));
+    }
+}
+
 fn handle(
     chat_state: &mut AfterGameChat,
     session: &mut ClientSession,
     ui: &mut dyn LobbyUi,
     network: &mut dyn NetworkHandle,
+    assets: Option<&Assets>,
 ) -> Option<ClientState> {
     let AfterGameChat {
         awaiting_initial_roster,
@@ -100,7 +122,7 @@ fn handle(
 
     let input_enabled = !*leaderboard_received && !*waiting_for_server;
     if input_enabled {
-        match ui.poll_input(MAX_CHAT_MESSAGE_BYTES, session.is_host) {
+        match ui.poll_input(session.max_chat_bytes, session.is_host) {
             Ok(Some(input)) => session.add_input(input),
             Err(UiInputError::Disconnected) => {
                 ui.show_sanitized_error(&format!("No connection: {}.", UiInputError::Disconnected));
@@ -115,7 +137,7 @@ fn handle(
     while let Some(data) = network.receive_message(AppChannel::Unreliable) {
         if let Some(map_data) = map_for_after_game {
             if let Ok((ServerMessage::Snapshot(wire), _)) =
-                decode_from_slice::<ServerMessage, _>(&data, standard())
+                decode_from_slice::<ServerMessage, _>(&data, config())
             {
                 apply_snapshot_to_positions(&mut map_data.positions, &wire.data);
             }
@@ -125,7 +147,7 @@ fn handle(
     while let Some(data) = network.receive_message(AppChannel::ReliableOrdered) {
         *waiting_for_server = false;
 
-        match decode_from_slice::<ServerMessage, _>(&data, standard()) {
+        match decode_from_slice::<ServerMessage, _>(&data, config()) {
             Ok((
                 ServerMessage::ChatMessage {
                     username,
@@ -134,7 +156,11 @@ fn handle(
                 },
                 _,
             )) => {
-                if *awaiting_initial_roster {
+                if *awaiting_initial_roster
+                    || session
+                        .recent_chat
+                        .is_duplicate(&username, &content, Instant::now())
+                {
                     continue;
                 }
                 ui.show_sanitized_message_with_color(&format!("{}: {}", username, content), color);
@@ -165,7 +191,14 @@ fn handle(
                 }
                 *awaiting_initial_roster = false;
             }
-            Ok((ServerMessage::AfterGameLeaderboard { entries }, _)) => {
+            Ok((
+                ServerMessage::AfterGameLeaderboard {
+                    entries,
+                    awards,
+                    rounds,
+                },
+                _,
+            )) => {
                 *leaderboard_received = true;
                 *map_for_after_game = None;
                 ui.show_message(" ");
@@ -181,20 +214,104 @@ fn handle(
                     let seconds = (entry.ticks_survived as f64 * TICK_SECS) as u64;
                     let minutes = seconds / 60;
                     let remainder = seconds % 60;
+                    let accuracy = (entry.hits_landed * 100)
+                        .checked_div(entry.shots_fired)
+                        .unwrap_or(0);
+                    let self_kills = if entry.self_kills > 0 {
+                        format!(" ({} self)", entry.self_kills)
+                    } else {
+                        String::new()
+                    };
                     ui.show_sanitized_message_with_color(
                         &format!(
-                            "  {}. {}  {:02}:{:02}  ({})",
-                            current_rank, entry.username, minutes, remainder, entry.exit_reason
+                            "  {}. {}  {:02}:{:02}  ({})  K/D: {}/{}{}  accuracy: {}% ({}/{})  damage: {}",
+                            current_rank,
+                            entry.username,
+                            minutes,
+                            remainder,
+                            entry.exit_reason,
+                            entry.kills,
+                            entry.deaths,
+                            self_kills,
+                            accuracy,
+                            entry.hits_landed,
+                            entry.shots_fired,
+                            entry.damage_dealt
                         ),
                         entry.color,
                     );
                 }
+                if !awards.is_empty() {
+                    ui.show_message(" ");
+                    ui.show_sanitized_message("Awards:");
+                    for award in awards.iter() {
+                        ui.show_sanitized_message(&format!(
+                            "  {}: {}",
+                            award.title,
+                            award.recipients.join(", ")
+                        ));
+                    }
+                }
                 ui.show_message(" ");
-                ui.show_message_with_color("That's your lot. Press escape to exit.", YELLOW);
+                match rounds {
+                    Some(summary) => {
+                        show_round_standings(ui, &summary);
+                        ui.show_message(" ");
+                        if summary.next_round_follows {
+                            ui.show_message_with_color("The next round starts soon.", YELLOW);
+                        } else {
+                            ui.show_message_with_color(
+                                "That's your lot. Press escape to exit.",
+                                YELLOW,
+                            );
+                        }
+                    }
+                    None => {
+                        ui.show_message_with_color("That's your lot. Press escape to exit.", YELLOW)
+                    }
+                }
+            }
+            Ok((
+                ServerMessage::CountdownStarted {
+                    end_time,
+                    game_data,
+                },
+                _,
+            )) => {
+                return Some(handle_countdown_started(
+                    end_time,
+                    game_data,
+                    assets,
+                    session.floor_theme,
+                    session.shadows,
+                ));
+            }
+            Ok((
+                ServerMessage::Welcome {
+                    color,
+                    max_chat_bytes,
+                    ..
+                },
+                _,
+            )) => {
+                session.max_chat_bytes = max_chat_bytes.min(MAX_CHAT_MESSAGE_BYTES);
+                ui.set_local_player_color(color);
+                ui.show_message(" ");
+                ui.show_message_with_color("Server: Back in the lobby for another game.", YELLOW);
+                return Some(ClientState::Lobby(Lobby::Chat {
+                    awaiting_initial_roster: true,
+                    waiting_for_server: false,
+                }));
+            }
+            Ok((ServerMessage::DisconnectReason(reason), _)) => {
+                return Some(session.disconnect_for(reason));
             }
             Ok((ServerMessage::ServerInfo { message }, _)) => {
                 ui.show_sanitized_message(&format!("Server: {}", message));
             }
+            Ok((ServerMessage::SpectatorCount { watching }, _)) => {
+                ui.show_sanitized_message(&format!("Server: {} watching.", watching));
+            }
             Ok((_, _)) => {}
             Err(error) => ui.show_typed_error(
                 UiErrorKind::Deserialization,
@@ -211,9 +328,14 @@ fn handle(
                 continue;
             }
 
+            if trimmed_input == CLEAR_COMMAND {
+                ui.clear_history();
+                continue;
+            }
+
             let message = ClientMessage::SendChat(trimmed_input.to_string());
 
-            let payload = encode_to_vec(&message, standard()).expect("failed to serialize chat");
+            let payload = encode_to_vec(&message, config()).expect("failed to serialize chat");
             network.send_message(AppChannel::ReliableOrdered, payload);
 
             *waiting_for_server = true;
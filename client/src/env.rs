@@ -34,3 +34,19 @@ pub fn default_server_address() -> SocketAddr {
         .parse()
         .expect("invalid IP or PORT in embedded .env")
 }
+
+pub fn server_address_from_args(
+    args: impl IntoIterator<Item = String>,
+    default: SocketAddr,
+) -> SocketAddr {
+    let mut addr = default;
+    for arg in args {
+        if let Some(value) = arg.strip_prefix("--server=") {
+            match common::net::parse_socket_addr(value, default.port()) {
+                Ok(parsed) => addr = parsed,
+                Err(e) => eprintln!("{}; ignoring", e),
+            }
+        }
+    }
+    addr
+}
@@ -1,7 +1,34 @@
 use macroquad::prelude::*;
 
 // While Macroquad does provide a `get_fps` function, it fluctuates wildly for me and gives unrealistic values, hence this struct to track the frame rate. It takes an average of the last 60 frames.
-use std::collections::VecDeque;
+use std::{collections::VecDeque, env, time::Duration};
+
+const FPS_CAP_ENV: &str = "FPS_CAP";
+
+pub fn fps_cap_from_env() -> Option<u32> {
+    env::var(FPS_CAP_ENV)
+        .ok()
+        .and_then(|value| parse_fps_cap(&value))
+}
+
+fn parse_fps_cap(value: &str) -> Option<u32> {
+    match value.trim().parse::<u32>() {
+        Ok(0) => None,
+        Ok(fps) => Some(fps),
+        Err(_) => {
+            eprintln!("invalid {} '{}'; ignoring", FPS_CAP_ENV, value);
+            None
+        }
+    }
+}
+
+pub fn frame_sleep_duration(target_fps: u32, elapsed: Duration) -> Duration {
+    if target_fps == 0 {
+        return Duration::ZERO;
+    }
+    let target_frame_time = Duration::from_secs(1) / target_fps;
+    target_frame_time.saturating_sub(elapsed)
+}
 
 #[derive(Debug)]
 pub struct FrameRate {
@@ -47,3 +74,37 @@ impl FrameRate {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sleeps_for_remainder_of_frame() {
+        let sleep = frame_sleep_duration(60, Duration::from_millis(10));
+        assert_eq!(
+            sleep,
+            Duration::from_secs(1) / 60 - Duration::from_millis(10)
+        );
+    }
+
+    #[test]
+    fn does_not_sleep_when_frame_overran() {
+        assert_eq!(
+            frame_sleep_duration(60, Duration::from_millis(20)),
+            Duration::ZERO
+        );
+        assert_eq!(
+            frame_sleep_duration(0, Duration::from_millis(1)),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn parses_fps_cap() {
+        assert_eq!(parse_fps_cap("144"), Some(144));
+        assert_eq!(parse_fps_cap(" 60 "), Some(60));
+        assert_eq!(parse_fps_cap("0"), None);
+        assert_eq!(parse_fps_cap("fast"), None);
+    }
+}
@@ -1,5 +1,8 @@
 pub mod input;
+pub mod link;
 pub mod obe;
+pub mod practice;
+pub mod spectator;
 pub mod state;
 pub mod victory;
 pub mod world;
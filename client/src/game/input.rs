@@ -1,13 +1,18 @@
 use macroquad::prelude::*;
 
-use common::player::PlayerInput;
+use crate::game::spectator::SpectatorInput;
+use common::player::{PlayerInput, RotationSensitivity};
 
 // TODO: Stop inputs when the window loses focus. I'm told that "Macroquad
 // usually handles loss of focus, but you can explicitly check
 // `miniquad::window::order_quit_event()` or similar focus events to force-clear
 // your local input state." In practice, it seems not to automatically stop
 // inputs on loss of focus.
-pub fn player_input_from_keys(sim_tick: u64) -> PlayerInput {
+pub fn player_input_from_keys(
+    sim_tick: u64,
+    crouch: bool,
+    rotation_sensitivity: RotationSensitivity,
+) -> PlayerInput {
     PlayerInput {
         sim_tick,
         forward: is_key_down(KeyCode::W),
@@ -21,5 +26,52 @@ pub fn player_input_from_keys(sim_tick: u64) -> PlayerInput {
         fire: is_key_down(KeyCode::Space),
         fire_nonce: None,
         is_zoomed: is_key_down(KeyCode::LeftShift),
+        jump: is_key_down(KeyCode::E),
+        crouch,
+        rotation_sensitivity,
+    }
+}
+
+pub fn rotation_sensitivity_from_args(
+    args: impl IntoIterator<Item = String>,
+) -> RotationSensitivity {
+    let mut sensitivity = RotationSensitivity::default();
+    for arg in args {
+        if let Some(value) = arg.strip_prefix("--sensitivity=") {
+            match value.parse() {
+                Ok(parsed) => sensitivity = parsed,
+                Err(e) => eprintln!("{}; ignoring", e),
+            }
+        }
+    }
+    sensitivity
+}
+
+pub fn auto_center_from_args(args: impl IntoIterator<Item = String>) -> bool {
+    args.into_iter().any(|arg| arg == "--auto-center")
+}
+
+pub fn crouch_toggle_pressed() -> bool {
+    is_key_pressed(KeyCode::C)
+}
+
+pub fn stats_toggle_pressed() -> bool {
+    is_key_pressed(KeyCode::I)
+}
+
+pub fn spectator_input_from_keys() -> SpectatorInput {
+    SpectatorInput {
+        forward: is_key_down(KeyCode::W),
+        backward: is_key_down(KeyCode::S),
+        left: is_key_down(KeyCode::A),
+        right: is_key_down(KeyCode::D),
+        up: is_key_down(KeyCode::E),
+        down: is_key_down(KeyCode::C),
+        yaw_left: is_key_down(KeyCode::Left),
+        yaw_right: is_key_down(KeyCode::Right),
+        pitch_up: is_key_down(KeyCode::Up),
+        pitch_down: is_key_down(KeyCode::Down),
+        next_player: is_key_pressed(KeyCode::RightBracket),
+        previous_player: is_key_pressed(KeyCode::LeftBracket),
     }
 }
@@ -0,0 +1,64 @@
+pub const MAX_CONSECUTIVE_SNAPSHOT_FAILURES: u32 = 10;
+
+#[derive(Debug, Default)]
+pub struct LinkHealth {
+    consecutive_failures: u32,
+    unhealthy: bool,
+}
+
+impl LinkHealth {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_unhealthy(&self) -> bool {
+        self.unhealthy
+    }
+
+    pub fn record_success(&mut self) {
+        self.consecutive_failures = 0;
+        self.unhealthy = false;
+    }
+
+    pub fn record_failure(&mut self) -> Option<String> {
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        if self.unhealthy || self.consecutive_failures <= MAX_CONSECUTIVE_SNAPSHOT_FAILURES {
+            return None;
+        }
+
+        self.unhealthy = true;
+        Some(format!(
+            "[DESERIALIZATION ERROR: {} consecutive snapshots could not be decoded]",
+            self.consecutive_failures
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_failures_surface_exactly_one_warning() {
+        let mut link = LinkHealth::new();
+
+        let warnings = (0..MAX_CONSECUTIVE_SNAPSHOT_FAILURES * 3)
+            .filter_map(|_| link.record_failure())
+            .count();
+
+        assert_eq!(warnings, 1);
+        assert!(link.is_unhealthy());
+    }
+
+    #[test]
+    fn a_good_snapshot_resets_the_failure_count() {
+        let mut link = LinkHealth::new();
+
+        for _ in 0..MAX_CONSECUTIVE_SNAPSHOT_FAILURES {
+            assert!(link.record_failure().is_none());
+        }
+        link.record_success();
+        assert!(link.record_failure().is_none());
+        assert!(!link.is_unhealthy());
+    }
+}
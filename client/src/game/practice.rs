@@ -0,0 +1,319 @@
+use ::rand::rng;
+use macroquad::prelude::*;
+
+use crate::{
+    assets::Assets,
+    game::{
+        input,
+        world::maze::{self, FloorTheme, MazeExtension, vision_radius},
+    },
+};
+use common::{
+    bullets::{self, Bullet, BulletTuning, check_player_collision},
+    constants::TICK_SECS,
+    maze::{Algorithm, Maze},
+    player::{self, MovementProfile, PlayerInput, PlayerState, RotationSensitivity},
+    spawn::{self, SpawnLayout},
+};
+
+const PRACTICE_DIFFICULTY: u8 = 1;
+const FOV: f32 = 1.0;
+const DUMMY_COUNT: usize = 6;
+
+pub struct Practice {
+    pub maze: Maze,
+    pub state: PlayerState,
+    pub bullets: Vec<Bullet>,
+    pub tick: u64,
+    pub dummies: Vec<Vec3>,
+    pub shots_fired: u32,
+    pub hits: u32,
+    tuning: BulletTuning,
+    last_fire_tick: Option<u64>,
+    next_bullet_id: u32,
+}
+
+impl Practice {
+    pub fn new(maze: Maze) -> Self {
+        let (z, x) = maze.spaces[0];
+        let position = maze
+            .position_from_grid_coordinates(player::HEIGHT, z, x)
+            .expect("practice maze should have open cells");
+        let dummies = spawn::spawn_points(&maze, SpawnLayout::Spread, DUMMY_COUNT + 1, &mut rng())
+            .into_iter()
+            .filter(|&cell| cell != (z, x))
+            .take(DUMMY_COUNT)
+            .filter_map(|(z, x)| maze.position_from_grid_coordinates(player::HEIGHT, z, x))
+            .collect();
+
+        Self {
+            maze,
+            state: PlayerState::new(position),
+            bullets: Vec::new(),
+            tick: 0,
+            dummies,
+            shots_fired: 0,
+            hits: 0,
+            tuning: BulletTuning::default(),
+            last_fire_tick: None,
+            next_bullet_id: 0,
+        }
+    }
+
+    pub fn step(&mut self, input: &PlayerInput) {
+        self.state.update(
+            &self.maze,
+            input,
+            &MovementProfile::default(),
+            0,
+            &Vec::new(),
+            0.0,
+        );
+        if input.fire {
+            self.fire();
+        }
+
+        let maze = &self.maze;
+        let tick = self.tick;
+        let tuning = &self.tuning;
+        let dummies = &self.dummies;
+        let hits = &mut self.hits;
+        self.bullets.retain_mut(|bullet| {
+            if bullets::update_bullet_position(bullet, maze, tick, tuning).should_remove {
+                return false;
+            }
+            let hit_dummy = dummies.iter().any(|&dummy| {
+                check_player_collision(bullet, dummy, 1, None, None, true, &tuning.radii).hit_player
+            });
+            if hit_dummy {
+                *hits += 1;
+            }
+            !hit_dummy
+        });
+
+        self.tick += 1;
+    }
+
+    pub fn accuracy(&self) -> f32 {
+        if self.shots_fired == 0 {
+            0.0
+        } else {
+            self.hits as f32 / self.shots_fired as f32
+        }
+    }
+
+    fn fire(&mut self) {
+        let can_fire = self
+            .last_fire_tick
+            .map(|tick| self.tick.saturating_sub(tick) >= bullets::cooldown_ticks())
+            .unwrap_or(true);
+        if !can_fire || self.bullets.len() >= bullets::MAX_BULLETS_PER_PLAYER {
+            return;
+        }
+
+        let direction = bullets::direction_from_yaw_pitch(self.state.yaw, self.state.pitch);
+        if direction == Vec3::ZERO {
+            return;
+        }
+
+        self.bullets.push(Bullet::new(
+            self.next_bullet_id,
+            0,
+            bullets::spawn_position(self.state.position, direction, &self.tuning.radii),
+            bullets::spawn_velocity(direction, &self.tuning),
+            self.tick,
+        ));
+        self.next_bullet_id = self.next_bullet_id.wrapping_add(1);
+        self.last_fire_tick = Some(self.tick);
+        self.shots_fired += 1;
+    }
+
+    fn draw(&self, meshes: &maze::MazeMeshes, font: &Font) {
+        clear_background(BEIGE);
+
+        let position = self.state.position;
+        let (yaw, pitch) = (self.state.yaw, self.state.pitch);
+        set_camera(&Camera3D {
+            position,
+            target: position
+                + vec3(
+                    -yaw.sin() * pitch.cos(),
+                    pitch.sin(),
+                    -yaw.cos() * pitch.cos(),
+                ),
+            up: vec3(0.0, 1.0, 0.0),
+            z_near: 0.1,
+            z_far: 10000.0,
+            fovy: FOV,
+            ..Default::default()
+        });
+
+        self.maze
+            .draw(meshes, position, vision_radius(PRACTICE_DIFFICULTY));
+        for &dummy in &self.dummies {
+            draw_sphere(dummy, player::RADIUS, None, ORANGE);
+        }
+        for bullet in &self.bullets {
+            draw_sphere(bullet.position, self.tuning.radii.shell(), None, RED);
+        }
+
+        set_default_camera();
+        let lines = [
+            "Practice mode: ESC to quit".to_string(),
+            format!(
+                "Hits: {} / {} shots ({:.0}%)",
+                self.hits,
+                self.shots_fired,
+                self.accuracy() * 100.0
+            ),
+        ];
+        for (i, line) in lines.iter().enumerate() {
+            draw_text_ex(
+                line,
+                20.0,
+                40.0 + i as f32 * 30.0,
+                TextParams {
+                    font: Some(font),
+                    font_size: 24,
+                    color: BLACK,
+                    ..Default::default()
+                },
+            );
+        }
+    }
+}
+
+pub async fn run(
+    assets: &Assets,
+    floor_theme: FloorTheme,
+    shadows: bool,
+    rotation_sensitivity: RotationSensitivity,
+    auto_center: bool,
+) {
+    let maze = Maze::new(Algorithm::Backtrack);
+    let meshes = maze::build_maze_meshes(
+        &maze,
+        &assets.griffin_texture,
+        PRACTICE_DIFFICULTY,
+        floor_theme,
+        shadows,
+    );
+    let mut practice = Practice::new(maze);
+    let mut accumulated_time = 0.0;
+    let mut crouch = false;
+
+    loop {
+        if is_quit_requested() || is_key_pressed(KeyCode::Escape) {
+            break;
+        }
+        if input::crouch_toggle_pressed() {
+            crouch = !crouch;
+        }
+
+        accumulated_time += get_frame_time() as f64;
+        while accumulated_time >= TICK_SECS {
+            let mut input =
+                input::player_input_from_keys(practice.tick, crouch, rotation_sensitivity);
+            if auto_center {
+                input.auto_center_pitch(&practice.state);
+            }
+            practice.step(&input);
+            accumulated_time -= TICK_SECS;
+        }
+
+        practice.draw(&meshes, &assets.font);
+        next_frame().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::maze::{CELL_SIZE, SPACE, WALL};
+
+    fn firing_forward() -> PlayerInput {
+        PlayerInput {
+            forward: true,
+            fire: true,
+            ..Default::default()
+        }
+    }
+
+    fn run_ticks(maze: Maze, ticks: u64) -> Practice {
+        let mut practice = Practice::new(maze);
+        for _ in 0..ticks {
+            practice.step(&firing_forward());
+        }
+        practice
+    }
+
+    #[test]
+    fn practice_loop_advances_player_and_bullets_deterministically() {
+        let maze = Maze::new(Algorithm::Backtrack);
+        let start = Practice::new(maze.clone()).state.position;
+
+        let mut first = run_ticks(maze.clone(), 1);
+        assert_eq!(first.bullets.len(), 1);
+        let bullet_start = first.bullets[0].position;
+        for _ in 0..2 {
+            first.step(&firing_forward());
+        }
+        let second = run_ticks(maze, 3);
+
+        assert_eq!(first.tick, 3);
+        assert_eq!(first.state, second.state);
+        assert_eq!(first.bullets, second.bullets);
+        assert_ne!(first.state.position, start);
+        assert_eq!(first.bullets.len(), 1);
+        assert_ne!(first.bullets[0].position, bullet_start);
+    }
+
+    fn open_maze() -> Maze {
+        let size = 9;
+        let mut grid = vec![vec![WALL; size]; size];
+        let mut spaces = Vec::new();
+        for (z, row) in grid.iter_mut().enumerate().take(size - 1).skip(1) {
+            for (x, cell) in row.iter_mut().enumerate().take(size - 1).skip(1) {
+                *cell = SPACE;
+                spaces.push((z, x));
+            }
+        }
+        Maze {
+            grid,
+            spaces,
+            teleporters: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn bullet_hitting_a_dummy_counts_as_a_hit() {
+        let mut practice = Practice::new(open_maze());
+        practice.state.position = vec3(4.5 * CELL_SIZE, player::HEIGHT, 6.5 * CELL_SIZE);
+        practice.state.yaw = 0.0;
+        practice.state.pitch = 0.0;
+        practice.dummies = vec![practice.state.position - vec3(0.0, 0.0, 2.0 * CELL_SIZE)];
+
+        practice.step(&PlayerInput {
+            fire: true,
+            ..Default::default()
+        });
+        for _ in 0..30 {
+            practice.step(&PlayerInput::default());
+        }
+
+        assert_eq!(practice.shots_fired, 1);
+        assert_eq!(practice.hits, 1);
+        assert!(practice.bullets.is_empty());
+        assert_eq!(practice.accuracy(), 1.0);
+    }
+
+    #[test]
+    fn accuracy_is_hits_over_shots_fired() {
+        let mut practice = Practice::new(open_maze());
+        assert_eq!(practice.accuracy(), 0.0);
+
+        practice.shots_fired = 4;
+        practice.hits = 1;
+        assert_eq!(practice.accuracy(), 0.25);
+    }
+}
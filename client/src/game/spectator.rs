@@ -0,0 +1,230 @@
+use std::f32::consts::FRAC_PI_2;
+
+use macroquad::prelude::*;
+
+use common::player::PlayerState;
+
+const FLY_SPEED: f32 = 300.0; // Units per second.
+const TURN_SPEED: f32 = 2.0; // Radians per second.
+const MAX_PITCH: f32 = FRAC_PI_2 - 0.01;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SpectatorInput {
+    pub forward: bool,
+    pub backward: bool,
+    pub left: bool,
+    pub right: bool,
+    pub up: bool,
+    pub down: bool,
+    pub yaw_left: bool,
+    pub yaw_right: bool,
+    pub pitch_up: bool,
+    pub pitch_down: bool,
+    pub next_player: bool,
+    pub previous_player: bool,
+}
+
+impl SpectatorInput {
+    fn moves(&self) -> bool {
+        self.forward
+            || self.backward
+            || self.left
+            || self.right
+            || self.up
+            || self.down
+            || self.yaw_left
+            || self.yaw_right
+            || self.pitch_up
+            || self.pitch_down
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpectatorCamera {
+    pub position: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub followed_index: Option<usize>,
+}
+
+impl SpectatorCamera {
+    pub fn new(state: &PlayerState) -> Self {
+        Self {
+            position: state.position,
+            yaw: state.yaw,
+            pitch: state.pitch.clamp(-MAX_PITCH, MAX_PITCH),
+            followed_index: None,
+        }
+    }
+
+    pub fn follow_next(&mut self, alive: &[bool]) {
+        self.cycle(alive, true);
+    }
+
+    pub fn follow_previous(&mut self, alive: &[bool]) {
+        self.cycle(alive, false);
+    }
+
+    fn cycle(&mut self, alive: &[bool], forward: bool) {
+        let count = alive.len();
+        let start = match self.followed_index {
+            Some(index) => index,
+            None if forward => count.saturating_sub(1),
+            None => 0,
+        };
+        self.followed_index = (1..=count)
+            .map(|offset| {
+                if forward {
+                    (start + offset) % count
+                } else {
+                    (start + count - offset) % count
+                }
+            })
+            .find(|&index| alive[index]);
+    }
+
+    pub fn snap_to(&mut self, state: &PlayerState) {
+        self.position = state.position;
+        self.yaw = state.yaw;
+        self.pitch = state.pitch.clamp(-MAX_PITCH, MAX_PITCH);
+    }
+
+    pub fn look_direction(&self) -> Vec3 {
+        vec3(
+            -self.yaw.sin() * self.pitch.cos(),
+            self.pitch.sin(),
+            -self.yaw.cos() * self.pitch.cos(),
+        )
+    }
+
+    pub fn update(&mut self, input: SpectatorInput, dt: f32) {
+        if input.moves() {
+            self.followed_index = None;
+        }
+        if self.followed_index.is_some() {
+            return;
+        }
+
+        let turn = TURN_SPEED * dt;
+        if input.yaw_left {
+            self.yaw += turn;
+        }
+        if input.yaw_right {
+            self.yaw -= turn;
+        }
+        if input.pitch_up {
+            self.pitch += turn;
+        }
+        if input.pitch_down {
+            self.pitch -= turn;
+        }
+        self.pitch = self.pitch.clamp(-MAX_PITCH, MAX_PITCH);
+
+        let forward = self.look_direction();
+        let right = vec3(self.yaw.cos(), 0.0, -self.yaw.sin());
+        let mut direction = Vec3::ZERO;
+        if input.forward {
+            direction += forward;
+        }
+        if input.backward {
+            direction -= forward;
+        }
+        if input.right {
+            direction += right;
+        }
+        if input.left {
+            direction -= right;
+        }
+        if input.up {
+            direction += Vec3::Y;
+        }
+        if input.down {
+            direction -= Vec3::Y;
+        }
+
+        self.position += direction.normalize_or_zero() * FLY_SPEED * dt;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn camera() -> SpectatorCamera {
+        SpectatorCamera {
+            position: vec3(100.0, 24.0, 100.0),
+            yaw: 0.0,
+            pitch: 0.0,
+            followed_index: None,
+        }
+    }
+
+    #[test]
+    fn free_camera_flies_along_look_direction_and_vertically() {
+        let mut camera = camera();
+        let input = SpectatorInput {
+            forward: true,
+            ..Default::default()
+        };
+        for _ in 0..60 {
+            camera.update(input, 1.0 / 60.0);
+        }
+        assert!((camera.position.z - (100.0 - FLY_SPEED)).abs() < 0.01);
+        assert!((camera.position.x - 100.0).abs() < 0.01);
+
+        let input = SpectatorInput {
+            up: true,
+            ..Default::default()
+        };
+        camera.update(input, 0.5);
+        assert!((camera.position.y - (24.0 + FLY_SPEED * 0.5)).abs() < 0.01);
+    }
+
+    #[test]
+    fn cycling_skips_dead_players_and_wraps_around() {
+        let mut camera = camera();
+        let alive = [true, false, true, false];
+
+        camera.follow_next(&alive);
+        assert_eq!(camera.followed_index, Some(0));
+        camera.follow_next(&alive);
+        assert_eq!(camera.followed_index, Some(2));
+        camera.follow_next(&alive);
+        assert_eq!(camera.followed_index, Some(0));
+        camera.follow_previous(&alive);
+        assert_eq!(camera.followed_index, Some(2));
+
+        camera.follow_next(&[false; 4]);
+        assert_eq!(camera.followed_index, None);
+    }
+
+    #[test]
+    fn moving_releases_a_followed_player() {
+        let mut camera = camera();
+        camera.follow_next(&[true]);
+        camera.update(SpectatorInput::default(), 1.0);
+        assert_eq!(camera.followed_index, Some(0));
+
+        let input = SpectatorInput {
+            forward: true,
+            ..Default::default()
+        };
+        camera.update(input, 1.0);
+        assert_eq!(camera.followed_index, None);
+        assert!(camera.position.z < 100.0);
+    }
+
+    #[test]
+    fn free_camera_turns_and_clamps_pitch() {
+        let mut camera = camera();
+        let input = SpectatorInput {
+            yaw_left: true,
+            pitch_up: true,
+            ..Default::default()
+        };
+        camera.update(input, 10.0);
+        assert!((camera.yaw - TURN_SPEED * 10.0).abs() < 0.001);
+        assert_eq!(camera.pitch, MAX_PITCH);
+        assert_eq!(camera.position, vec3(100.0, 24.0, 100.0));
+    }
+}
@@ -3,10 +3,7 @@ use std::{
     time::{Duration, Instant},
 };
 
-use bincode::{
-    config::standard,
-    serde::{decode_from_slice, encode_to_vec},
-};
+use bincode::serde::{decode_from_slice, encode_to_vec};
 use macroquad::{
     audio::{PlaySoundParams, play_sound, play_sound_once},
     prelude::*,
@@ -19,12 +16,14 @@ use crate::{
     frame::FrameRate,
     game::input,
     game::{
+        link::LinkHealth,
         obe::ObeEffect,
+        spectator::SpectatorCamera,
         victory::VictoryEffect,
         world::{
             avatar::{DiskMesh, OrientedSphereMesh},
             bullet::{self, BULLET_COLOR_MODE, BulletColorMode, ClientBullet},
-            maze::{MazeExtension, MazeMeshes},
+            maze::{MazeExtension, MazeMeshes, vision_radius},
             sky::Sky,
         },
     },
@@ -32,15 +31,16 @@ use crate::{
     net::NetworkHandle,
     session::Clock,
     state::ClientState,
-    time::INTERPOLATION_DELAY_SECS,
+    time::interpolation_delay_secs,
 };
 use common::{
-    bullets::{self, BULLET_SHELL_RADIUS},
+    bullets::{self, Ammo, BulletTuning, Magazine},
     constants::{INPUT_HISTORY_LENGTH, SNAPSHOT_BUFFER_LENGTH, TICK_SECS},
     maze::{self, CELL_SIZE, Maze},
     net::AppChannel,
-    player::{self, Player, PlayerInput},
-    protocol::{BulletEvent, ClientMessage, ServerMessage},
+    pickups::{self, Pickup, PickupKind},
+    player::{self, CorrectionOffset, MovementProfile, Player, PlayerInput, RotationSensitivity},
+    protocol::{BulletEvent, ClientMessage, DisconnectReason, PickupEvent, ServerMessage, config},
     ring::WireItem,
     ring::{NetworkBuffer, Ring},
     snapshot::{InitialData, Snapshot},
@@ -52,6 +52,8 @@ const NETWORK_TIME_BUDGET: Duration = Duration::from_millis(2);
 const REPULSION_STRENGTH: f32 = 0.5; // For collisions.
 const NORMAL_FOV: f32 = 45.0_f32.to_radians();
 const ZOOMED_FOV: f32 = 10.0_f32.to_radians();
+const KILL_FEED_DURATION: Duration = Duration::from_secs(5);
+const KILL_FEED_MAX_LINES: usize = 4;
 
 pub struct Game {
     pub local_player_index: usize,
@@ -62,13 +64,29 @@ pub struct Game {
     pub maze: Maze,
     pub start_time: f64,
     pub timer_duration: f32,
+    bullet_tuning: BulletTuning,
+    ammo: Option<Ammo>,
+    movement_profile: MovementProfile,
+    ticks_per_snapshot: u64,
+    pub spectators: usize,
+    kill_feed: Vec<(String, Instant)>,
+    local_kills: u32,
+    pub show_stats: bool,
+    pub rotation_sensitivity: RotationSensitivity,
+    pub auto_center: bool,
+    pub paused: bool,
+    paused_server_time: Option<f64>,
+    crouching: bool,
     maze_meshes: MazeMeshes,
     sky: Sky,
     input_history: Ring<PlayerInput, INPUT_HISTORY_LENGTH>, // 256: ~4.3s at 60Hz.
     snapshot_buffer: NetworkBuffer<Snapshot, SNAPSHOT_BUFFER_LENGTH>, // 16 broadcasts, 0.8s at 20Hz.
     is_first_snapshot_received: bool,
+    link_health: LinkHealth,
+    link_warning: Option<String>,
     last_reconciled_tick: Option<u64>,
     bullets: Vec<ClientBullet>,
+    pickups: Vec<Pickup>,
     flash: Option<Fade>,
     fade_to_black: Option<Fade>,
     fade_to_black_finished: bool,
@@ -78,13 +96,18 @@ pub struct Game {
     pending_bullet_events: Vec<BulletEvent>,
     after_game_chat_sent: bool,
     victory_in_progress: bool,
+    game_over: bool,
+    disconnect_reason: Option<DisconnectReason>,
+    spectator_camera: Option<SpectatorCamera>,
     obe_effect: Option<ObeEffect>,
     victory_effect: Option<VictoryEffect>,
     player_avatar_mesh: OrientedSphereMesh,
     player_shadow_mesh: DiskMesh,
     previous_local_state: StaticState,
+    correction: CorrectionOffset,
     fov: f32,
     pub needle_textures: info::circles::NeedleTextures,
+    pub compass_yaw: f32,
 }
 
 impl Game {
@@ -103,8 +126,13 @@ impl Game {
         let players = initial_data.players;
         let maze = initial_data.maze;
         let timer_duration = initial_data.timer_duration;
+        let bullet_tuning = initial_data.bullet_tuning;
+        let ammo = bullet_tuning.magazine.as_ref().map(Ammo::full);
+        let movement_profile = initial_data.movement_profile;
+        let ticks_per_snapshot = initial_data.ticks_per_snapshot;
         let difficulty = initial_data.difficulty;
         let previous_local_state = StaticState::new(&players[local_player_index]);
+        let compass_yaw = players[local_player_index].state.yaw;
 
         Self {
             // `snapshot_buffer.head` will be reset when the first snapshot is
@@ -122,10 +150,26 @@ impl Game {
             players,
             timer_markers,
             timer_duration,
+            bullet_tuning,
+            ammo,
+            movement_profile,
+            ticks_per_snapshot,
+            spectators: 0,
+            kill_feed: Vec::new(),
+            local_kills: 0,
+            show_stats: false,
+            rotation_sensitivity: RotationSensitivity::default(),
+            auto_center: false,
+            paused: false,
+            paused_server_time: None,
+            crouching: false,
             input_history: Ring::new(),
             is_first_snapshot_received: false,
+            link_health: LinkHealth::new(),
+            link_warning: None,
             last_reconciled_tick: None,
             bullets: Vec::new(),
+            pickups: initial_data.pickups,
             flash: None,
             fade_to_black: None,
             fade_to_black_finished: false,
@@ -135,14 +179,19 @@ impl Game {
             pending_bullet_events: Vec::new(),
             after_game_chat_sent: false,
             victory_in_progress: false,
+            game_over: false,
+            disconnect_reason: None,
+            spectator_camera: None,
             obe_effect: None,
             victory_effect: None,
             player_avatar_mesh: OrientedSphereMesh::new(),
             player_shadow_mesh: DiskMesh::new(),
             previous_local_state,
+            correction: CorrectionOffset::default(),
             fov: NORMAL_FOV,
             start_time,
             needle_textures,
+            compass_yaw,
         }
     }
 
@@ -152,11 +201,20 @@ impl Game {
         network: &mut dyn NetworkHandle,
         assets: &Assets,
     ) -> Option<ClientState> {
+        if self.fade_to_black_finished
+            && !self.after_game_chat_sent
+            && !self.victory_in_progress
+            && self.spectator_camera.is_none()
+            && !self.is_game_over()
+        {
+            self.start_spectating();
+        }
+
         if self.fade_to_black_finished && !self.after_game_chat_sent {
             self.after_game_chat_sent = true;
             let message = ClientMessage::EnterAfterGameChat;
             let payload =
-                encode_to_vec(&message, standard()).expect("failed to encode after-game chat");
+                encode_to_vec(&message, config()).expect("failed to encode after-game chat");
             network.send_message(AppChannel::ReliableOrdered, payload);
 
             return Some(ClientState::AfterGameChat(AfterGameChat {
@@ -168,6 +226,11 @@ impl Game {
         }
 
         self.receive_game_messages(network);
+        if let Some(reason) = self.disconnect_reason {
+            return Some(ClientState::Disconnected {
+                message: reason.message().to_string(),
+            });
+        }
         if let Some(new_tail) = self.interpolate(clock.estimated_server_time) {
             self.snapshot_buffer.advance_tail(new_tail);
         }
@@ -176,9 +239,48 @@ impl Game {
         }
         self.advance_simulation(clock, network, assets);
 
+        if let Some(camera) = &mut self.spectator_camera {
+            let input = input::spectator_input_from_keys();
+            camera.update(input, get_frame_time());
+            let alive: Vec<bool> = self.players.iter().map(|p| p.is_alive()).collect();
+            if input.next_player {
+                camera.follow_next(&alive);
+            }
+            if input.previous_player {
+                camera.follow_previous(&alive);
+            }
+            if camera.followed_index.is_some_and(|index| !alive[index]) {
+                camera.follow_next(&alive);
+            }
+            if let Some(index) = camera.followed_index {
+                camera.snap_to(&self.players[index].state);
+            }
+            if self.fade_to_black.is_none() && self.is_game_over() {
+                self.fade_to_black = Some(fade::new_fade_to_black());
+            }
+        }
+
         None
     }
 
+    pub fn is_spectating(&self) -> bool {
+        self.spectator_camera.is_some()
+    }
+
+    fn is_game_over(&self) -> bool {
+        self.game_over || self.players.iter().all(|player| !player.is_alive())
+    }
+
+    fn start_spectating(&mut self) {
+        let state = self.players[self.local_player_index].state;
+        self.spectator_camera = Some(SpectatorCamera::new(&state));
+        self.fade_to_black = None;
+        self.fade_to_black_finished = false;
+        self.obe_effect = None;
+        self.flash = None;
+        self.fov = NORMAL_FOV;
+    }
+
     pub fn consume_for_after_game(self, chat_state: AfterGameChat) -> AfterGameChat {
         let positions = self
             .players
@@ -209,6 +311,7 @@ impl Game {
         let mut ticks_processed = 0;
 
         let head = self.snapshot_buffer.head;
+        let predicted = self.players[self.local_player_index].state.position;
         if self.reconcile(head) {
             let start_replay = head + 1;
             let end_replay = clock.sim_tick + 1;
@@ -216,13 +319,33 @@ impl Game {
             if start_replay <= end_replay {
                 self.apply_input_range_inclusive(start_replay, end_replay);
             }
+            let corrected = self.players[self.local_player_index].state.position;
+            self.correction.absorb(predicted, corrected);
+        }
+
+        if input::crouch_toggle_pressed() {
+            self.crouching = !self.crouching;
+        }
+
+        if input::stats_toggle_pressed() {
+            self.show_stats = !self.show_stats;
         }
 
         while clock.accumulated_time >= TICK_SECS && ticks_processed < MAX_TICKS_PER_FRAME {
             let sim_tick = clock.sim_tick;
 
-            if self.players[self.local_player_index].health > 0 && !self.victory_in_progress {
-                let mut input = input::player_input_from_keys(sim_tick);
+            if self.players[self.local_player_index].health > 0
+                && !self.victory_in_progress
+                && !self.paused
+            {
+                let mut input = input::player_input_from_keys(
+                    sim_tick,
+                    self.crouching,
+                    self.rotation_sensitivity,
+                );
+                if self.auto_center {
+                    input.auto_center_pitch(&self.players[self.local_player_index].state);
+                }
                 self.prepare_fire_input(sim_tick, &mut input, assets);
                 self.send_input(network, input, sim_tick);
                 self.input_history.insert(sim_tick, input);
@@ -230,8 +353,11 @@ impl Game {
             }
 
             self.last_sim_tick = sim_tick;
-            self.update_bullets(sim_tick);
+            if !self.paused {
+                self.update_bullets(sim_tick);
+            }
             clock.accumulated_time -= TICK_SECS;
+            self.correction.decay();
             clock.sim_tick += 1;
             ticks_processed += 1;
 
@@ -254,6 +380,12 @@ impl Game {
         }
     }
 
+    pub fn request_toggle_pause(&self, network: &mut dyn NetworkHandle) {
+        let message = ClientMessage::TogglePause;
+        let payload = encode_to_vec(&message, config()).expect("failed to encode TogglePause");
+        network.send_message(AppChannel::ReliableOrdered, payload);
+    }
+
     // We send the last four inputs for redundancy to mitigate possible loss of
     // messages on the unreliable channel.
     pub fn send_input(
@@ -272,11 +404,32 @@ impl Game {
             };
             let client_message = ClientMessage::Input(wire_input);
             let payload =
-                encode_to_vec(&client_message, standard()).expect("failed to encode player input");
+                encode_to_vec(&client_message, config()).expect("failed to encode player input");
             network.send_message(AppChannel::Unreliable, payload);
         }
     }
 
+    pub fn local_bullets_in_air(&self) -> usize {
+        self.bullets.iter().filter(|bullet| bullet.is_local).count()
+    }
+
+    fn current_ammo(&self) -> Option<(Magazine, Ammo)> {
+        let magazine = self.bullet_tuning.magazine?;
+        let mut ammo = self.ammo?;
+        ammo.refill_if_reloaded(&magazine, self.last_sim_tick);
+        Some((magazine, ammo))
+    }
+
+    pub fn magazine_rounds(&self) -> Option<(u8, u8)> {
+        self.current_ammo()
+            .map(|(magazine, ammo)| (ammo.rounds, magazine.size))
+    }
+
+    pub fn reload_progress(&self) -> Option<f32> {
+        let (magazine, ammo) = self.current_ammo()?;
+        ammo.reload_progress(&magazine, self.last_sim_tick)
+    }
+
     pub fn prepare_fire_input(&mut self, sim_tick: u64, input: &mut PlayerInput, assets: &Assets) {
         if !input.fire {
             return;
@@ -287,9 +440,14 @@ impl Game {
             .last_fire_tick
             .map(|tick| sim_tick.saturating_sub(tick) >= cooldown_ticks)
             .unwrap_or(true);
-        let bullets_in_air = self.bullets.iter().filter(|bullet| bullet.is_local).count();
+        if !can_fire || self.local_bullets_in_air() >= bullets::MAX_BULLETS_PER_PLAYER {
+            return;
+        }
 
-        if !can_fire || bullets_in_air >= bullets::MAX_BULLETS_PER_PLAYER {
+        let magazine = self.bullet_tuning.magazine;
+        if let (Some(magazine), Some(ammo)) = (&magazine, &mut self.ammo)
+            && !ammo.ready(magazine, sim_tick)
+        {
             return;
         }
 
@@ -302,10 +460,14 @@ impl Game {
         let fire_nonce = self.fire_nonce_counter;
         self.fire_nonce_counter = self.fire_nonce_counter.wrapping_add(1);
         self.last_fire_tick = Some(sim_tick);
+        if let (Some(magazine), Some(ammo)) = (&magazine, &mut self.ammo) {
+            ammo.spend(magazine, sim_tick);
+        }
         input.fire_nonce = Some(fire_nonce);
 
-        let position = bullets::spawn_position(local_state.position, direction);
-        let velocity = direction * bullets::SPEED;
+        let position =
+            bullets::spawn_position(local_state.position, direction, &self.bullet_tuning.radii);
+        let velocity = bullets::spawn_velocity(direction, &self.bullet_tuning);
         self.bullets.push(ClientBullet::new_provisional(
             fire_nonce, position, velocity, sim_tick,
         ));
@@ -335,8 +497,9 @@ impl Game {
 
             messages_received += 1;
 
-            match decode_from_slice::<ServerMessage, _>(&data, standard()) {
+            match decode_from_slice::<ServerMessage, _>(&data, config()) {
                 Ok((ServerMessage::Snapshot(snapshot), _)) => {
+                    self.link_health.record_success();
                     self.snapshot_buffer.insert(snapshot);
                 }
                 Ok((ServerMessage::BulletEvent(event), _)) => {
@@ -355,22 +518,64 @@ impl Game {
                 }
                 Err(error) => {
                     eprintln!("failed to decode server message: {}", error);
+                    if let Some(warning) = self.link_health.record_failure() {
+                        self.link_warning = Some(warning);
+                    }
                 }
             }
         }
 
         while let Some(data) = network.receive_message(AppChannel::ReliableOrdered) {
-            match decode_from_slice::<ServerMessage, _>(&data, standard()) {
+            match decode_from_slice::<ServerMessage, _>(&data, config()) {
                 Ok((ServerMessage::BulletEvent(event), _)) => {
                     self.pending_bullet_events.push(event);
                 }
+                Ok((ServerMessage::PickupEvent(event), _)) => {
+                    self.handle_pickup_event(event);
+                }
                 Ok((ServerMessage::UserLeft { username }, _)) => {
                     if let Some(player) = self.players.iter_mut().find(|p| p.name == username) {
                         player.disconnected = true;
                     }
                 }
-                Ok((ServerMessage::Victory { winner_index }, _)) => {
-                    self.handle_victory(winner_index);
+                Ok((
+                    ServerMessage::GameOver {
+                        winner: Some(winner),
+                    },
+                    _,
+                )) => {
+                    self.handle_victory(&winner);
+                }
+                Ok((ServerMessage::GameOver { winner: None }, _)) => {
+                    self.game_over = true;
+                    self.push_feed_line("Nobody survived: it's a draw".to_string());
+                }
+                Ok((ServerMessage::SpectatorCount { watching }, _)) => {
+                    self.spectators = watching;
+                }
+                Ok((ServerMessage::PlayerKilled { victim, killer }, _)) => {
+                    self.handle_player_killed(victim, killer);
+                }
+                Ok((ServerMessage::PlayerAfk { index, afk }, _)) => {
+                    self.handle_player_afk(index, afk);
+                }
+                Ok((ServerMessage::ServerInfo { message }, _)) => {
+                    self.push_feed_line(message);
+                }
+                Ok((ServerMessage::DisconnectReason(reason), _)) => {
+                    self.disconnect_reason = Some(reason);
+                }
+                Ok((
+                    ServerMessage::GamePaused {
+                        paused,
+                        timer_shift_secs,
+                    },
+                    _,
+                )) => {
+                    self.paused = paused;
+                    if !paused {
+                        self.start_time += timer_shift_secs;
+                    }
                 }
                 Ok((other, _)) => {
                     eprintln!(
@@ -385,14 +590,94 @@ impl Game {
         }
     }
 
-    fn handle_victory(&mut self, winner_index: usize) {
-        if winner_index == self.local_player_index {
+    fn handle_pickup_event(&mut self, event: PickupEvent) {
+        match event {
+            PickupEvent::Spawned(pickup) => self.pickups.push(pickup),
+            PickupEvent::Collected {
+                pickup_id,
+                player_index,
+            } => {
+                let Some(position) = self.pickups.iter().position(|p| p.id == pickup_id) else {
+                    return;
+                };
+                let pickup = self.pickups.swap_remove(position);
+                if player_index == self.local_player_index && pickup.kind == PickupKind::Ammo {
+                    self.ammo = self.bullet_tuning.magazine.as_ref().map(Ammo::full);
+                }
+            }
+        }
+    }
+
+    fn handle_player_killed(&mut self, victim: usize, killer: usize) {
+        let (Some(victim), Some(killer)) = (self.players.get(victim), self.players.get(killer))
+        else {
+            eprintln!("kill event refers to unknown player index");
+            return;
+        };
+
+        let line = if victim.index == killer.index {
+            format!("{} was shot by their own bullet", victim.name)
+        } else {
+            if killer.index == self.local_player_index {
+                self.local_kills += 1;
+            }
+            format!("{} shot {}", killer.name, victim.name)
+        };
+
+        self.push_feed_line(line);
+    }
+
+    fn handle_player_afk(&mut self, index: usize, afk: bool) {
+        let Some(player) = self.players.get(index) else {
+            eprintln!("afk event refers to unknown player index");
+            return;
+        };
+
+        let line = if afk {
+            format!("{} is AFK", player.name)
+        } else {
+            format!("{} is back", player.name)
+        };
+        self.push_feed_line(line);
+    }
+
+    fn push_feed_line(&mut self, line: String) {
+        self.kill_feed.push((line, Instant::now()));
+        if self.kill_feed.len() > KILL_FEED_MAX_LINES {
+            self.kill_feed.remove(0);
+        }
+    }
+
+    pub fn is_link_unhealthy(&self) -> bool {
+        self.link_health.is_unhealthy()
+    }
+
+    pub fn take_link_warning(&mut self) -> Option<String> {
+        self.link_warning.take()
+    }
+
+    pub fn local_kills(&self) -> u32 {
+        self.local_kills
+    }
+
+    pub fn kill_feed(&self) -> impl Iterator<Item = &str> {
+        self.kill_feed
+            .iter()
+            .filter(|(_, at)| at.elapsed() < KILL_FEED_DURATION)
+            .map(|(line, _)| line.as_str())
+    }
+
+    fn handle_victory(&mut self, winner: &str) {
+        if self.players[self.local_player_index].name == winner {
             self.victory_in_progress = true;
             self.fade_to_black = Some(fade::new_fade_to_black());
             self.fade_to_black_finished = false;
             if self.victory_effect.is_none() {
                 self.victory_effect = Some(VictoryEffect::new());
             }
+        } else {
+            self.game_over = true;
+            self.push_feed_line(format!("{} is the last one standing", winner));
         }
         // Winner remains visible to other players (health stays > 0 on client).
         // Server tracks them as Dead for leaderboard purposes.
@@ -412,19 +697,8 @@ impl Game {
             let local_player = &mut self.players[self.local_player_index];
             self.previous_local_state = StaticState::new(&local_player);
 
-            let local_state = &mut local_player.state;
-
-            local_state.position = vec3(
-                snapshot.local.position.x,
-                player::HEIGHT,
-                snapshot.local.position.y,
-            );
-            local_state.velocity = vec3(snapshot.local.velocity.x, 0.0, snapshot.local.velocity.y);
-            local_state.yaw = snapshot.local.yaw;
-            local_state.pitch = snapshot.local.pitch;
-            local_state.yaw_velocity = snapshot.local.yaw_velocity;
-            local_state.pitch_velocity = snapshot.local.pitch_velocity;
-            local_state.is_zoomed = snapshot.local.is_zoomed;
+            local_player.state.correct_to(&snapshot.local);
+            self.ammo = snapshot.ammo;
 
             true
         } else {
@@ -435,6 +709,20 @@ impl Game {
     pub fn apply_input_range_inclusive(&mut self, from: u64, to: u64) {
         for tick in from..=to {
             self.apply_input(tick);
+            self.replay_shot(tick);
+        }
+    }
+
+    fn replay_shot(&mut self, tick: u64) {
+        let (Some(magazine), Some(ammo)) = (&self.bullet_tuning.magazine, &mut self.ammo) else {
+            return;
+        };
+        let fired = self
+            .input_history
+            .get(tick)
+            .is_some_and(|input| input.fire_nonce.is_some());
+        if fired && ammo.ready(magazine, tick) {
+            ammo.spend(magazine, tick);
         }
     }
 
@@ -458,6 +746,7 @@ impl Game {
             local_player.state.update(
                 &self.maze,
                 input,
+                &self.movement_profile,
                 own_index,
                 &player_positions,
                 REPULSION_STRENGTH,
@@ -466,10 +755,11 @@ impl Game {
     }
 
     pub fn interpolate(&mut self, estimated_server_time: f64) -> Option<u64> {
-        let interpolation_time = estimated_server_time - INTERPOLATION_DELAY_SECS;
+        let interpolation_time =
+            estimated_server_time - interpolation_delay_secs(self.ticks_per_snapshot);
         let start_search_tick = crate::time::tick_from_time(interpolation_time);
         let mut tick_a = start_search_tick;
-        let limit = 8;
+        let limit = (3 * self.ticks_per_snapshot).max(8);
 
         while self.snapshot_buffer.get(tick_a).is_none() {
             if start_search_tick - tick_a > limit {
@@ -511,8 +801,8 @@ impl Game {
                 return None;
             };
 
-            let a_pos = vec3(a.position.x, player::HEIGHT, a.position.y);
-            let b_pos = vec3(b.position.x, player::HEIGHT, b.position.y);
+            let a_pos = vec3(a.position.x, a.height, a.position.y);
+            let b_pos = vec3(b.position.x, b.height, b.position.y);
 
             let state = &mut player.state;
             state.position = a_pos + (b_pos - a_pos) * alpha;
@@ -547,10 +837,28 @@ impl Game {
         self.set_camera(tick_fraction);
 
         self.sky.draw();
-        self.maze.draw(&self.maze_meshes);
+        let viewer = match &self.spectator_camera {
+            Some(camera) => camera.position,
+            None => self.players[self.local_player_index].state.position,
+        };
+        self.maze
+            .draw(&self.maze_meshes, viewer, vision_radius(self.difficulty));
         self.draw_players(assets);
         self.draw_bullets(tick_fraction);
-        info::draw(self, assets, fps, estimated_server_time);
+        self.draw_pickups();
+
+        let timer_time = if self.paused {
+            *self.paused_server_time.get_or_insert(estimated_server_time)
+        } else {
+            self.paused_server_time = None;
+            estimated_server_time
+        };
+        self.compass_yaw = info::circles::smooth_yaw(
+            self.compass_yaw,
+            self.players[self.local_player_index].state.yaw,
+            get_frame_time(),
+        );
+        info::draw(self, assets, fps, timer_time);
 
         // This function must be called after drawing the scene so that the fade
         // covers everything and not just the background. If this becomes a
@@ -560,12 +868,26 @@ impl Game {
     }
 
     fn set_camera(&mut self, tick_fraction: f32) {
+        if let Some(camera) = &self.spectator_camera {
+            set_camera(&Camera3D {
+                position: camera.position,
+                target: camera.position + camera.look_direction(),
+                up: vec3(0.0, 1.0, 0.0),
+                z_near: 0.1,
+                z_far: 10000.0,
+                fovy: NORMAL_FOV,
+                ..Default::default()
+            });
+            return;
+        }
+
         let i = self.local_player_index;
         let local_player_state = self.players[i].state;
         let prev_state = &self.previous_local_state;
         let curr_state = &local_player_state;
 
-        let mut position = prev_state.position.lerp(curr_state.position, tick_fraction);
+        let mut position =
+            prev_state.position.lerp(curr_state.position, tick_fraction) + self.correction.0;
         let mut yaw = prev_state.yaw + (curr_state.yaw - prev_state.yaw) * tick_fraction;
         let mut pitch = prev_state.pitch + (curr_state.pitch - prev_state.pitch) * tick_fraction;
 
@@ -615,7 +937,10 @@ impl Game {
             let position = self.players[index].state.position;
             self.draw_player_shadow(position);
 
-            if index == self.local_player_index {
+            let followed = self
+                .spectator_camera
+                .is_some_and(|camera| camera.followed_index == Some(index));
+            if index == self.local_player_index || followed {
                 continue;
             }
 
@@ -660,6 +985,16 @@ impl Game {
         }
     }
 
+    fn draw_pickups(&self) {
+        for pickup in &self.pickups {
+            let color = match pickup.kind {
+                PickupKind::Health => GREEN,
+                PickupKind::Ammo => GOLD,
+            };
+            draw_sphere(pickup.position, pickups::RADIUS, None, color);
+        }
+    }
+
     fn draw_bullets(&self, tick_fraction: f32) {
         for bullet in &self.bullets {
             let color = if bullet.blend_ticks_left > 0 {
@@ -674,7 +1009,7 @@ impl Game {
                         }
                     }
                     BulletColorMode::FadeToRed => {
-                        let fade = bullet.fade_amount(self.last_sim_tick);
+                        let fade = bullet.fade_amount(self.last_sim_tick, &self.bullet_tuning);
                         Color::new(1.0, fade, fade, fade)
                     }
                 }
@@ -684,13 +1019,19 @@ impl Game {
                 .previous_position
                 .lerp(bullet.position, tick_fraction);
 
-            draw_sphere(smoothed_position, BULLET_SHELL_RADIUS, None, color);
+            draw_sphere(
+                smoothed_position,
+                self.bullet_tuning.radii.shell(),
+                None,
+                color,
+            );
         }
     }
 
     fn update_bullets(&mut self, sim_tick: u64) {
         const PROVISIONAL_TIMEOUT_TICKS: u64 = 30;
-        let lifespan_ticks = (bullets::LIFESPAN_SECS / TICK_SECS).ceil() as u64;
+        let lifespan_ticks = self.bullet_tuning.lifespan_ticks();
+        let tuning = &self.bullet_tuning;
         let maze = &self.maze;
 
         self.bullets.retain_mut(|bullet| {
@@ -699,17 +1040,20 @@ impl Game {
                 let ticks = sim_tick - bullet.last_update_tick;
                 if bullet.confirmed {
                     for _ in 0..ticks {
+                        bullets::fall(&mut bullet.velocity, tuning);
                         bullet.advance(1);
                         bullets::bounce_off_ground(
                             &mut bullet.position,
                             &mut bullet.velocity,
                             &mut bullet.bounces,
+                            &tuning.radii,
                         );
                         match bullets::bounce_off_wall(
                             &mut bullet.position,
                             &mut bullet.velocity,
                             &mut bullet.bounces,
                             maze,
+                            &tuning.radii,
                         ) {
                             bullets::WallBounce::Stuck => {}
                             bullets::WallBounce::Bounce => {}
@@ -719,17 +1063,20 @@ impl Game {
                     bullet.apply_blend(ticks);
                 } else {
                     for _ in 0..ticks {
+                        bullets::fall(&mut bullet.velocity, tuning);
                         bullet.advance(1);
                         bullets::bounce_off_ground(
                             &mut bullet.position,
                             &mut bullet.velocity,
                             &mut bullet.bounces,
+                            &tuning.radii,
                         );
                         match bullets::bounce_off_wall(
                             &mut bullet.position,
                             &mut bullet.velocity,
                             &mut bullet.bounces,
                             maze,
+                            &tuning.radii,
                         ) {
                             bullets::WallBounce::Stuck => return false,
                             bullets::WallBounce::Bounce => {}
@@ -780,6 +1127,7 @@ impl Game {
                 tick,
                 position,
                 velocity,
+                ..
             } => self.handle_bullet_hit_inanimate_event(bullet_id, tick, position, velocity),
             BulletEvent::HitPlayer {
                 bullet_id,
@@ -797,7 +1145,7 @@ impl Game {
                 target_health,
                 assets,
             ),
-            BulletEvent::Expire { bullet_id, .. } => {
+            BulletEvent::Expire { bullet_id, .. } | BulletEvent::HitBullet { bullet_id, .. } => {
                 self.handle_bullet_expire_event(bullet_id);
             }
         }
@@ -1,8 +1,8 @@
 use macroquad::prelude::*;
 
 use common::{
-    bullets,
-    constants::{TICK_SECS, TICK_SECS_F32},
+    bullets::{self, BulletTuning},
+    constants::TICK_SECS_F32,
 };
 
 // `ConfirmOnRed` mode is for debugging. When `BULLET_COLOR_MODE` is in this
@@ -119,9 +119,9 @@ impl ClientBullet {
         !self.confirmed && self.fire_nonce == Some(fire_nonce)
     }
 
-    pub fn fade_amount(&self, sim_tick: u64) -> f32 {
+    pub fn fade_amount(&self, sim_tick: u64, tuning: &BulletTuning) -> f32 {
         let age = sim_tick.saturating_sub(self.spawn_tick);
-        let lifespan_ticks = (bullets::LIFESPAN_SECS / TICK_SECS).ceil() as u64;
+        let lifespan_ticks = tuning.lifespan_ticks();
         if lifespan_ticks == 0 {
             return 1.0;
         }
@@ -1,13 +1,26 @@
-use std::fmt;
+use std::{fmt, str::FromStr};
 
 use macroquad::prelude::*;
 
-pub use common::maze::{CELL_SIZE, Maze};
+pub use common::maze::{CELL_SIZE, Maze, TELEPORTER, WALL, WALL_HEIGHT};
+
+pub const VISION_RADIUS: f32 = 8.0 * CELL_SIZE;
+const MIN_VISION_RADIUS: f32 = 3.0 * CELL_SIZE;
+const VISION_LOSS_PER_LEVEL: f32 = 2.0 * CELL_SIZE;
+const ACCENT_WALL_TINT: Color = Color::new(1.0, 0.8, 0.55, 1.0);
+const CHUNK_CELLS: usize = 4;
+const MAX_VERTICES: usize = 2_000;
+
+pub struct ChunkMesh {
+    pub chunk: (usize, usize),
+    pub mesh: Mesh,
+}
 
 pub struct MazeMeshes {
-    pub walls: Vec<Mesh>,
-    pub floor: Vec<Mesh>,
-    pub shadows: Vec<Mesh>,
+    pub walls: Vec<ChunkMesh>,
+    pub floor: Vec<ChunkMesh>,
+    pub shadows: Vec<ChunkMesh>,
+    pub pads: Vec<ChunkMesh>,
 }
 
 impl fmt::Debug for MazeMeshes {
@@ -16,27 +29,72 @@ impl fmt::Debug for MazeMeshes {
             .field("walls_count", &self.walls.len())
             .field("floor_count", &self.floor.len())
             .field("shadows_count", &self.shadows.len())
+            .field("pads_count", &self.pads.len())
             .finish()
     }
 }
 
+pub fn vision_radius(difficulty: u8) -> f32 {
+    let loss = difficulty.saturating_sub(1) as f32 * VISION_LOSS_PER_LEVEL;
+    (VISION_RADIUS - loss).max(MIN_VISION_RADIUS)
+}
+
+pub fn is_cell_visible(cell: (usize, usize), viewer: Vec3, radius: f32) -> bool {
+    let (x, z) = cell;
+    let min_x = x as f32 * CELL_SIZE;
+    let min_z = z as f32 * CELL_SIZE;
+    let nearest_x = viewer.x.clamp(min_x, min_x + CELL_SIZE);
+    let nearest_z = viewer.z.clamp(min_z, min_z + CELL_SIZE);
+    let dx = viewer.x - nearest_x;
+    let dz = viewer.z - nearest_z;
+
+    dx * dx + dz * dz <= radius * radius
+}
+
+pub fn is_chunk_visible(chunk: (usize, usize), viewer: Vec3, radius: f32) -> bool {
+    let (chunk_x, chunk_z) = chunk;
+    (0..CHUNK_CELLS).any(|dz| {
+        (0..CHUNK_CELLS).any(|dx| {
+            let cell = (chunk_x * CHUNK_CELLS + dx, chunk_z * CHUNK_CELLS + dz);
+            is_cell_visible(cell, viewer, radius)
+        })
+    })
+}
+
+fn cells_by_chunk(width: usize, height: usize) -> impl Iterator<Item = (usize, usize)> {
+    (0..height.div_ceil(CHUNK_CELLS)).flat_map(move |chunk_z| {
+        (0..width.div_ceil(CHUNK_CELLS)).flat_map(move |chunk_x| {
+            let xs = chunk_x * CHUNK_CELLS..((chunk_x + 1) * CHUNK_CELLS).min(width);
+            let zs = chunk_z * CHUNK_CELLS..((chunk_z + 1) * CHUNK_CELLS).min(height);
+            zs.flat_map(move |z| xs.clone().map(move |x| (x, z)))
+        })
+    })
+}
+
 pub trait MazeExtension {
-    fn draw(&self, meshes: &MazeMeshes);
+    fn draw(&self, meshes: &MazeMeshes, viewer: Vec3, vision_radius: f32);
 }
 
 impl MazeExtension for Maze {
-    fn draw(&self, meshes: &MazeMeshes) {
+    fn draw(&self, meshes: &MazeMeshes, viewer: Vec3, vision_radius: f32) {
+        let visible =
+            |chunk_mesh: &&ChunkMesh| is_chunk_visible(chunk_mesh.chunk, viewer, vision_radius);
+
         // Floor.
-        for mesh in &meshes.floor {
-            draw_mesh(mesh);
+        for chunk_mesh in meshes.floor.iter().filter(visible) {
+            draw_mesh(&chunk_mesh.mesh);
+        }
+        // Teleporter pads.
+        for chunk_mesh in meshes.pads.iter().filter(visible) {
+            draw_mesh(&chunk_mesh.mesh);
         }
         // Shadows.
-        for mesh in &meshes.shadows {
-            draw_mesh(mesh);
+        for chunk_mesh in meshes.shadows.iter().filter(visible) {
+            draw_mesh(&chunk_mesh.mesh);
         }
         // Walls.
-        for mesh in &meshes.walls {
-            draw_mesh(mesh);
+        for chunk_mesh in meshes.walls.iter().filter(visible) {
+            draw_mesh(&chunk_mesh.mesh);
         }
     }
 }
@@ -55,18 +113,31 @@ struct WallNeighbors {
 impl WallNeighbors {
     fn from_grid(maze: &Maze, x: usize, z: usize, width: usize, height: usize) -> Self {
         Self {
-            left: x > 0 && maze.grid[z][x - 1] != 0,
-            right: x + 1 < width && maze.grid[z][x + 1] != 0,
-            up: z > 0 && maze.grid[z - 1][x] != 0,
-            down: z + 1 < height && maze.grid[z + 1][x] != 0,
-            up_left: z > 0 && x > 0 && maze.grid[z - 1][x - 1] != 0,
-            up_right: z > 0 && x + 1 < width && maze.grid[z - 1][x + 1] != 0,
-            down_left: z + 1 < height && x > 0 && maze.grid[z + 1][x - 1] != 0,
-            down_right: z + 1 < height && x + 1 < width && maze.grid[z + 1][x + 1] != 0,
+            left: x > 0 && maze.grid[z][x - 1] == WALL,
+            right: x + 1 < width && maze.grid[z][x + 1] == WALL,
+            up: z > 0 && maze.grid[z - 1][x] == WALL,
+            down: z + 1 < height && maze.grid[z + 1][x] == WALL,
+            up_left: z > 0 && x > 0 && maze.grid[z - 1][x - 1] == WALL,
+            up_right: z > 0 && x + 1 < width && maze.grid[z - 1][x + 1] == WALL,
+            down_left: z + 1 < height && x > 0 && maze.grid[z + 1][x - 1] == WALL,
+            down_right: z + 1 < height && x + 1 < width && maze.grid[z + 1][x + 1] == WALL,
         }
     }
 }
 
+// Faces shared with a neighboring wall are never visible, so we skip them. The
+// order matches `WALL_FACES`.
+fn exposed_wall_faces(neighbors: &WallNeighbors) -> [bool; 6] {
+    [
+        !neighbors.down,
+        !neighbors.up,
+        !neighbors.right,
+        !neighbors.left,
+        true,
+        true,
+    ]
+}
+
 struct ShadowConfig {
     height: f32,
     color: Color,
@@ -84,24 +155,30 @@ fn add_floor_quad(
     builder.add_quad(verts_local, uvs, offset, WHITE);
 }
 
+const WALL_FACES: [(usize, usize, usize, usize, Vec3); 6] = [
+    (0, 1, 2, 3, Vec3::Z),
+    (5, 4, 7, 6, Vec3::NEG_Z),
+    (1, 5, 6, 2, Vec3::X),
+    (4, 0, 3, 7, Vec3::NEG_X),
+    (3, 2, 6, 7, Vec3::Y),
+    (4, 5, 1, 0, Vec3::NEG_Y),
+];
+
 fn add_wall_faces(
     builder: &mut MeshBuilder,
     wall_verts: &[Vec3; 8],
     wall_uvs: &[Vec2; 4],
     offset: Vec3,
+    color: Color,
+    exposed: [bool; 6],
 ) {
-    let faces = [
-        (0, 1, 2, 3, vec3(0., 0., 1.)),
-        (5, 4, 7, 6, vec3(0., 0., -1.)),
-        (1, 5, 6, 2, vec3(1., 0., 0.)),
-        (4, 0, 3, 7, vec3(-1., 0., 0.)),
-        (3, 2, 6, 7, vec3(0., 1., 0.)),
-        (4, 5, 1, 0, vec3(0., -1., 0.)),
-    ];
-
-    for (v1, v2, v3, v4, norm) in faces.iter() {
+    let faces = WALL_FACES
+        .iter()
+        .zip(exposed)
+        .filter(|(_, exposed)| *exposed);
+    for ((v1, v2, v3, v4, norm), _) in faces {
         builder.add_face_from_indices(
-            wall_verts, *v1, *v2, *v3, *v4, *norm, wall_uvs, offset, WHITE,
+            wall_verts, *v1, *v2, *v3, *v4, *norm, wall_uvs, offset, color,
         );
     }
 }
@@ -330,20 +407,77 @@ fn add_wall_shadow(
     }
 }
 
-pub fn generate_floor_texture(difficulty: u8) -> Texture2D {
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FloorTheme {
+    #[default]
+    Auto,
+    Beige,
+    Slate,
+    Grass,
+}
+
+impl FromStr for FloorTheme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "auto" => Ok(FloorTheme::Auto),
+            "beige" => Ok(FloorTheme::Beige),
+            "slate" => Ok(FloorTheme::Slate),
+            "grass" => Ok(FloorTheme::Grass),
+            _ => Err(format!("unknown floor theme '{}'", s)),
+        }
+    }
+}
+
+impl FloorTheme {
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Self {
+        let mut theme = FloorTheme::default();
+        for arg in args {
+            if let Some(value) = arg.strip_prefix("--floor-theme=") {
+                match value.parse() {
+                    Ok(parsed) => theme = parsed,
+                    Err(e) => eprintln!("{}; ignoring", e),
+                }
+            }
+        }
+        theme
+    }
+}
+
+pub fn shadows_from_args(args: impl IntoIterator<Item = String>) -> bool {
+    !args.into_iter().any(|arg| arg == "--no-shadows")
+}
+
+pub fn floor_colors(theme: FloorTheme, difficulty: u8) -> (Color, Color) {
+    match theme {
+        FloorTheme::Auto => match difficulty {
+            9 => (WHITE, BLACK),
+            5 => (WHITE, BROWN),
+            3 => (
+                Color::new(0.4, 0.7, 0.5, 1.0), // Seafoam green.
+                Color::new(0.0, 0.5, 0.5, 1.0), // Teal.
+            ),
+            _ => (BEIGE, BROWN),
+        },
+        FloorTheme::Beige => (BEIGE, BROWN),
+        FloorTheme::Slate => (
+            Color::new(0.44, 0.5, 0.56, 1.0),
+            Color::new(0.18, 0.22, 0.27, 1.0),
+        ),
+        FloorTheme::Grass => (
+            Color::new(0.45, 0.7, 0.3, 1.0),
+            Color::new(0.25, 0.5, 0.2, 1.0),
+        ),
+    }
+}
+
+pub fn generate_floor_image(theme: FloorTheme, difficulty: u8) -> Image {
     let half_check_size = 8.0;
     let check_size = 2.0 * half_check_size;
     let checks_per_cell = (CELL_SIZE / check_size).round() as u16;
 
-    let (base_color, alternate_color) = match difficulty {
-        9 => (WHITE, BLACK),
-        5 => (WHITE, BROWN),
-        3 => (
-            Color::new(0.4, 0.7, 0.5, 1.0), // Seafoam green.
-            Color::new(0.0, 0.5, 0.5, 1.0), // Teal.
-        ),
-        _ => (BEIGE, BROWN),
-    };
+    let (base_color, alternate_color) = floor_colors(theme, difficulty);
     let mut image = Image::gen_image_color(checks_per_cell, checks_per_cell, base_color);
     for y in 0..checks_per_cell {
         for x in 0..checks_per_cell {
@@ -352,23 +486,88 @@ pub fn generate_floor_texture(difficulty: u8) -> Texture2D {
             }
         }
     }
-    let texture = Texture2D::from_image(&image);
+    image
+}
+
+pub fn generate_floor_texture(theme: FloorTheme, difficulty: u8) -> Texture2D {
+    let texture = Texture2D::from_image(&generate_floor_image(theme, difficulty));
     texture.set_filter(FilterMode::Nearest);
     texture
 }
 
-pub fn build_maze_meshes(maze: &Maze, wall_texture: &Texture2D, difficulty: u8) -> MazeMeshes {
+pub fn build_maze_meshes(
+    maze: &Maze,
+    wall_texture: &Texture2D,
+    difficulty: u8,
+    floor_theme: FloorTheme,
+    shadows: bool,
+) -> MazeMeshes {
     let height = maze.grid.len();
     let width = if height > 0 { maze.grid[0].len() } else { 0 };
 
-    const MAX_VERTICES: usize = 2_000;
+    let floor_texture = generate_floor_texture(floor_theme, difficulty);
+    let mut floor_builder = MeshBuilder::new(Some(floor_texture), MAX_VERTICES);
+    let mut pad_builder = MeshBuilder::new(None, MAX_VERTICES);
+
+    let f_hw = CELL_SIZE / 2.0;
+    let f_hd = CELL_SIZE / 2.0;
+    let floor_verts_local = [
+        vec3(-f_hw, 0.0, f_hd),
+        vec3(f_hw, 0.0, f_hd),
+        vec3(f_hw, 0.0, -f_hd),
+        vec3(-f_hw, 0.0, -f_hd),
+    ];
+    let floor_uvs = [
+        vec2(0.0, 1.0),
+        vec2(1.0, 1.0),
+        vec2(1.0, 0.0),
+        vec2(0.0, 0.0),
+    ];
+
+    let p_hw = CELL_SIZE * 0.35;
+    let pad_verts_local = [
+        vec3(-p_hw, 0.0, p_hw),
+        vec3(p_hw, 0.0, p_hw),
+        vec3(p_hw, 0.0, -p_hw),
+        vec3(-p_hw, 0.0, -p_hw),
+    ];
+    let pad_color = Color::new(0.2, 0.8, 1.0, 0.8);
+
+    for (x, z) in cells_by_chunk(width, height) {
+        let cell_type = maze.grid[z][x];
+        if cell_type == WALL {
+            continue;
+        }
+        let cx = (x as f32 * CELL_SIZE) + CELL_SIZE / 2.0;
+        let cz = (z as f32 * CELL_SIZE) + CELL_SIZE / 2.0;
+
+        floor_builder.begin_cell(x, z);
+        let offset = vec3(cx, 0.0, cz);
+        add_floor_quad(&mut floor_builder, &floor_verts_local, &floor_uvs, offset);
+
+        if cell_type == TELEPORTER {
+            pad_builder.begin_cell(x, z);
+            let offset = vec3(cx, 0.1, cz);
+            pad_builder.add_quad(&pad_verts_local, &floor_uvs, offset, pad_color);
+        }
+    }
+
+    MazeMeshes {
+        walls: build_wall_meshes(maze, Some(wall_texture)),
+        floor: floor_builder.finalize(),
+        shadows: build_shadow_meshes(maze, shadows),
+        pads: pad_builder.finalize(),
+    }
+}
 
-    let floor_texture = generate_floor_texture(difficulty);
-    let mut wall_builder = MeshBuilder::new(wall_texture.clone(), MAX_VERTICES);
-    let mut floor_builder = MeshBuilder::new(floor_texture.clone(), MAX_VERTICES);
-    let mut shadow_builder = MeshBuilder::new(Texture2D::empty(), MAX_VERTICES);
+fn build_wall_meshes(maze: &Maze, wall_texture: Option<&Texture2D>) -> Vec<ChunkMesh> {
+    let height = maze.grid.len();
+    let width = if height > 0 { maze.grid[0].len() } else { 0 };
+
+    let mut wall_builder = MeshBuilder::new(wall_texture.cloned(), MAX_VERTICES);
+    let mut accent_builder = MeshBuilder::new(wall_texture.cloned(), MAX_VERTICES);
 
-    let w_size = vec3(CELL_SIZE, CELL_SIZE, CELL_SIZE);
+    let w_size = vec3(CELL_SIZE, WALL_HEIGHT, CELL_SIZE);
     let w_hw = w_size.x / 2.0;
     let w_hh = w_size.y / 2.0;
     let w_hd = w_size.z / 2.0;
@@ -390,15 +589,49 @@ pub fn build_maze_meshes(maze: &Maze, wall_texture: &Texture2D, difficulty: u8)
         vec2(0.0, 0.0),
     ];
 
-    let f_hw = CELL_SIZE / 2.0;
-    let f_hd = CELL_SIZE / 2.0;
-    let floor_verts_local = [
-        vec3(-f_hw, 0.0, f_hd),
-        vec3(f_hw, 0.0, f_hd),
-        vec3(f_hw, 0.0, -f_hd),
-        vec3(-f_hw, 0.0, -f_hd),
-    ];
-    let floor_uvs = [
+    for (x, z) in cells_by_chunk(width, height) {
+        if maze.grid[z][x] != WALL {
+            continue;
+        }
+        let cx = (x as f32 * CELL_SIZE) + CELL_SIZE / 2.0;
+        let cz = (z as f32 * CELL_SIZE) + CELL_SIZE / 2.0;
+        let cy = WALL_HEIGHT / 2.0;
+        let offset = vec3(cx, cy, cz);
+        let neighbors = WallNeighbors::from_grid(maze, x, z, width, height);
+        let exposed = exposed_wall_faces(&neighbors);
+
+        let (builder, color) = if is_accent_wall(maze, x, z) {
+            (&mut accent_builder, ACCENT_WALL_TINT)
+        } else {
+            (&mut wall_builder, WHITE)
+        };
+        builder.begin_cell(x, z);
+        add_wall_faces(builder, &wall_verts, &wall_uvs, offset, color, exposed);
+    }
+
+    let mut walls = wall_builder.finalize();
+    walls.extend(accent_builder.finalize());
+    walls
+}
+
+// The outer wall is tinted so that players can tell when they've reached the
+// edge of the maze.
+pub fn is_accent_wall(maze: &Maze, x: usize, z: usize) -> bool {
+    let height = maze.grid.len();
+    let width = maze.grid.first().map_or(0, Vec::len);
+    maze.grid[z][x] == WALL && (x == 0 || z == 0 || x + 1 == width || z + 1 == height)
+}
+
+fn build_shadow_meshes(maze: &Maze, enabled: bool) -> Vec<ChunkMesh> {
+    if !enabled {
+        return Vec::new();
+    }
+
+    let height = maze.grid.len();
+    let width = if height > 0 { maze.grid[0].len() } else { 0 };
+
+    let mut shadow_builder = MeshBuilder::new(None, MAX_VERTICES);
+    let uvs = [
         vec2(0.0, 1.0),
         vec2(1.0, 1.0),
         vec2(1.0, 0.0),
@@ -415,57 +648,47 @@ pub fn build_maze_meshes(maze: &Maze, wall_texture: &Texture2D, difficulty: u8)
         overhang: shadow_outer - shadow_inner,
     };
 
-    for z in 0..height {
-        for x in 0..width {
-            let cell_type = maze.grid[z][x];
-            let cx = (x as f32 * CELL_SIZE) + CELL_SIZE / 2.0;
-            let cz = (z as f32 * CELL_SIZE) + CELL_SIZE / 2.0;
-
-            if cell_type == 0 {
-                let offset = vec3(cx, 0.0, cz);
-                add_floor_quad(&mut floor_builder, &floor_verts_local, &floor_uvs, offset);
-            } else {
-                let cy = CELL_SIZE / 2.0;
-                let offset = vec3(cx, cy, cz);
-
-                add_wall_faces(&mut wall_builder, &wall_verts, &wall_uvs, offset);
-
-                // We need to know what neighbors a wall has so that we can
-                // avoid drawing overlapping shadows, which result in a flickery
-                // effect.
-                let neighbors = WallNeighbors::from_grid(maze, x, z, width, height);
-                let shadow_offset = vec3(cx, 0.0, cz);
-                add_wall_shadow(
-                    &mut shadow_builder,
-                    &floor_uvs,
-                    shadow_offset,
-                    neighbors,
-                    &shadow_config,
-                );
-            }
+    for (x, z) in cells_by_chunk(width, height) {
+        if maze.grid[z][x] != WALL {
+            continue;
         }
+        let cx = (x as f32 * CELL_SIZE) + CELL_SIZE / 2.0;
+        let cz = (z as f32 * CELL_SIZE) + CELL_SIZE / 2.0;
+
+        shadow_builder.begin_cell(x, z);
+
+        // We need to know what neighbors a wall has so that we can
+        // avoid drawing overlapping shadows, which result in a flickery
+        // effect.
+        let neighbors = WallNeighbors::from_grid(maze, x, z, width, height);
+        let shadow_offset = vec3(cx, 0.0, cz);
+        add_wall_shadow(
+            &mut shadow_builder,
+            &uvs,
+            shadow_offset,
+            neighbors,
+            &shadow_config,
+        );
     }
 
-    MazeMeshes {
-        walls: wall_builder.finalize(),
-        floor: floor_builder.finalize(),
-        shadows: shadow_builder.finalize(),
-    }
+    shadow_builder.finalize()
 }
 
 struct MeshBuilder {
-    meshes: Vec<Mesh>,
+    meshes: Vec<ChunkMesh>,
+    chunk: (usize, usize),
     vertices: Vec<Vertex>,
     indices: Vec<u16>,
     index_offset: u16,
-    texture: Texture2D,
+    texture: Option<Texture2D>,
     max_verts: usize,
 }
 
 impl MeshBuilder {
-    fn new(texture: Texture2D, max_verts: usize) -> Self {
+    fn new(texture: Option<Texture2D>, max_verts: usize) -> Self {
         Self {
             meshes: Vec::new(),
+            chunk: (0, 0),
             vertices: Vec::with_capacity(max_verts),
             indices: Vec::with_capacity(max_verts * 3 / 2),
             index_offset: 0,
@@ -485,10 +708,13 @@ impl MeshBuilder {
             return;
         }
 
-        self.meshes.push(Mesh {
-            vertices: std::mem::take(&mut self.vertices),
-            indices: std::mem::take(&mut self.indices),
-            texture: Some(self.texture.clone()),
+        self.meshes.push(ChunkMesh {
+            chunk: self.chunk,
+            mesh: Mesh {
+                vertices: std::mem::take(&mut self.vertices),
+                indices: std::mem::take(&mut self.indices),
+                texture: self.texture.clone(),
+            },
         });
 
         self.index_offset = 0;
@@ -496,7 +722,15 @@ impl MeshBuilder {
         self.indices.reserve(self.max_verts * 3 / 2);
     }
 
-    fn finalize(mut self) -> Vec<Mesh> {
+    fn begin_cell(&mut self, x: usize, z: usize) {
+        let chunk = (x / CHUNK_CELLS, z / CHUNK_CELLS);
+        if chunk != self.chunk {
+            self.flush();
+            self.chunk = chunk;
+        }
+    }
+
+    fn finalize(mut self) -> Vec<ChunkMesh> {
         self.flush();
         self.meshes
     }
@@ -561,3 +795,217 @@ impl MeshBuilder {
         self.index_offset += 4;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::maze::Algorithm;
+
+    #[test]
+    fn cell_at_exactly_the_vision_radius_is_visible() {
+        let viewer = vec3(CELL_SIZE / 2.0, 0.0, CELL_SIZE / 2.0);
+        let radius = 2.5 * CELL_SIZE;
+
+        assert!(is_cell_visible((3, 0), viewer, radius));
+        assert!(!is_cell_visible((4, 0), viewer, radius));
+        assert!(is_cell_visible((0, 3), viewer, radius));
+        assert!(!is_cell_visible((0, 4), viewer, radius));
+    }
+
+    #[test]
+    fn cell_just_beyond_the_vision_radius_is_hidden() {
+        let viewer = vec3(CELL_SIZE / 2.0, 0.0, CELL_SIZE / 2.0);
+        let radius = 2.5 * CELL_SIZE - 0.01;
+
+        assert!(!is_cell_visible((3, 0), viewer, radius));
+        assert!(is_cell_visible((2, 0), viewer, radius));
+    }
+
+    #[test]
+    fn diagonal_visibility_uses_distance_to_nearest_corner() {
+        let viewer = vec3(0.0, 0.0, 0.0);
+        let radius = CELL_SIZE * std::f32::consts::SQRT_2;
+
+        assert!(is_cell_visible((1, 1), viewer, radius + 0.01));
+        assert!(!is_cell_visible((1, 1), viewer, radius - 0.01));
+    }
+
+    #[test]
+    fn viewer_cell_is_always_visible() {
+        let viewer = vec3(5.5 * CELL_SIZE, 0.0, 7.5 * CELL_SIZE);
+
+        assert!(is_cell_visible((5, 7), viewer, 0.0));
+        assert!(!is_cell_visible((6, 7), viewer, 0.0));
+    }
+
+    #[test]
+    fn chunk_is_visible_when_any_of_its_cells_is() {
+        let viewer = vec3(CELL_SIZE / 2.0, 0.0, CELL_SIZE / 2.0);
+        let radius = (CHUNK_CELLS as f32 - 0.5) * CELL_SIZE;
+
+        assert!(is_chunk_visible((0, 0), viewer, 0.0));
+        assert!(is_chunk_visible((1, 0), viewer, radius));
+        assert!(!is_chunk_visible((1, 0), viewer, radius - 0.01));
+        assert!(!is_chunk_visible((1, 1), viewer, radius));
+    }
+
+    #[test]
+    fn vision_radius_shrinks_with_difficulty_down_to_a_minimum() {
+        assert_eq!(vision_radius(1), VISION_RADIUS);
+        assert!(vision_radius(2) < vision_radius(1));
+        assert!(vision_radius(3) < vision_radius(2));
+        assert_eq!(vision_radius(9), MIN_VISION_RADIUS);
+    }
+
+    fn assert_close(actual: Color, expected: Color) {
+        let channels = |c: Color| [c.r, c.g, c.b, c.a];
+        for (a, e) in channels(actual).into_iter().zip(channels(expected)) {
+            assert!(
+                (a - e).abs() <= 1.0 / 255.0,
+                "{:?} != {:?}",
+                actual,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn each_floor_theme_is_a_checker_of_its_two_colors() {
+        for theme in [FloorTheme::Beige, FloorTheme::Slate, FloorTheme::Grass] {
+            let (base, alternate) = floor_colors(theme, 1);
+            let image = generate_floor_image(theme, 1);
+            let size = image.width() as u32;
+            assert_eq!(size, (CELL_SIZE / 16.0).round() as u32);
+            for y in 0..size {
+                for x in 0..size {
+                    let expected = if (x + y) % 2 == 0 { base } else { alternate };
+                    assert_close(image.get_pixel(x, y), expected);
+                }
+            }
+        }
+        assert_ne!(
+            floor_colors(FloorTheme::Slate, 1),
+            floor_colors(FloorTheme::Grass, 1)
+        );
+    }
+
+    #[test]
+    fn border_walls_are_accented_and_inner_walls_are_not() {
+        let maze = Maze::new(Algorithm::Backtrack);
+        let height = maze.grid.len();
+        let width = maze.grid[0].len();
+
+        for x in 0..width {
+            assert!(is_accent_wall(&maze, x, 0));
+            assert!(is_accent_wall(&maze, x, height - 1));
+        }
+        for z in 0..height {
+            assert!(is_accent_wall(&maze, 0, z));
+            assert!(is_accent_wall(&maze, width - 1, z));
+        }
+        for z in 1..height - 1 {
+            for x in 1..width - 1 {
+                assert!(!is_accent_wall(&maze, x, z));
+            }
+        }
+    }
+
+    #[test]
+    fn faces_between_adjacent_walls_are_culled() {
+        let size = 3;
+        let maze = Maze {
+            grid: vec![vec![WALL; size]; size],
+            spaces: Vec::new(),
+            teleporters: Vec::new(),
+        };
+
+        let walls = build_wall_meshes(&maze, None);
+        let vertices: Vec<&Vertex> = walls.iter().flat_map(|wall| &wall.mesh.vertices).collect();
+
+        let unculled = size * size * WALL_FACES.len() * 4;
+        assert!(vertices.len() < unculled);
+
+        let facing = |normal: Vec3| {
+            vertices
+                .iter()
+                .filter(|vertex| vertex.normal.truncate() == normal)
+                .collect::<Vec<_>>()
+        };
+        let edge = size as f32 * CELL_SIZE;
+        for normal in [Vec3::X, Vec3::NEG_X, Vec3::Z, Vec3::NEG_Z] {
+            let boundary = if normal.max_element() > 0.0 {
+                edge
+            } else {
+                0.0
+            };
+            let sides = facing(normal);
+            assert_eq!(sides.len(), size * 4);
+            assert!(
+                sides
+                    .iter()
+                    .all(|vertex| vertex.position.dot(normal).abs() == boundary)
+            );
+        }
+        assert_eq!(facing(Vec3::Y).len(), size * size * 4);
+        assert_eq!(facing(Vec3::NEG_Y).len(), size * size * 4);
+
+        let corner = WallNeighbors::from_grid(&maze, 0, 0, size, size);
+        assert_eq!(
+            exposed_wall_faces(&corner),
+            [false, true, false, true, true, true]
+        );
+        let center = WallNeighbors::from_grid(&maze, 1, 1, size, size);
+        assert_eq!(
+            exposed_wall_faces(&center),
+            [false, false, false, false, true, true]
+        );
+    }
+
+    #[test]
+    fn wall_meshes_are_batched_per_chunk() {
+        let size = CHUNK_CELLS * 2 + 1;
+        let maze = Maze {
+            grid: vec![vec![WALL; size]; size],
+            spaces: Vec::new(),
+            teleporters: Vec::new(),
+        };
+
+        let walls = build_wall_meshes(&maze, None);
+
+        let mut chunks: Vec<(usize, usize)> = walls.iter().map(|wall| wall.chunk).collect();
+        chunks.sort_unstable();
+        chunks.dedup();
+        assert_eq!(chunks.len(), 9);
+        assert!(walls.len() <= 2 * chunks.len());
+        let chunk_size = CHUNK_CELLS as f32 * CELL_SIZE;
+        for wall in &walls {
+            let min_x = wall.chunk.0 as f32 * chunk_size;
+            let min_z = wall.chunk.1 as f32 * chunk_size;
+            assert!(wall.mesh.vertices.iter().all(|vertex| {
+                (min_x..=min_x + chunk_size).contains(&vertex.position.x)
+                    && (min_z..=min_z + chunk_size).contains(&vertex.position.z)
+            }));
+        }
+    }
+
+    #[test]
+    fn disabling_shadows_builds_no_shadow_meshes() {
+        let maze = Maze::new(Algorithm::Backtrack);
+
+        assert!(build_shadow_meshes(&maze, false).is_empty());
+    }
+
+    #[test]
+    fn shadows_are_disabled_from_args() {
+        assert!(shadows_from_args(["--practice".to_string()]));
+        assert!(!shadows_from_args(["--no-shadows".to_string()]));
+    }
+
+    #[test]
+    fn floor_theme_is_parsed_from_args() {
+        let args = ["--practice", "--floor-theme=Slate"].map(String::from);
+        assert_eq!(FloorTheme::from_args(args), FloorTheme::Slate);
+        let args = ["--floor-theme=lava".to_string()];
+        assert_eq!(FloorTheme::from_args(args), FloorTheme::Auto);
+    }
+}
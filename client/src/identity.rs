@@ -0,0 +1,60 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+pub fn client_id_from_args(args: impl IntoIterator<Item = String>) -> u64 {
+    match id_file_from_args(args) {
+        Some(path) => load_or_create_client_id(&path),
+        None => rand::random(),
+    }
+}
+
+pub fn id_file_from_args(args: impl IntoIterator<Item = String>) -> Option<PathBuf> {
+    args.into_iter()
+        .filter_map(|arg| arg.strip_prefix("--id-file=").map(PathBuf::from))
+        .last()
+}
+
+pub fn load_or_create_client_id(path: &Path) -> u64 {
+    if let Some(id) = fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| u64::from_str_radix(contents.trim(), 16).ok())
+    {
+        return id;
+    }
+
+    let id = rand::random();
+    if let Err(e) = fs::write(path, format!("{:016x}\n", id)) {
+        eprintln!("failed to save client id to {}: {}", path.display(), e);
+    }
+    id
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn saved_id_is_read_back_on_the_next_call() {
+        let path = env::temp_dir().join(format!("by-a-thread-id-test-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let first = load_or_create_client_id(&path);
+        let second = load_or_create_client_id(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn id_file_is_only_used_when_requested() {
+        assert_eq!(id_file_from_args(["client".to_string()]), None);
+        assert_eq!(
+            id_file_from_args(["client".to_string(), "--id-file=alice.id".to_string()]),
+            Some(PathBuf::from("alice.id"))
+        );
+    }
+}
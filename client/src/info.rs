@@ -1,12 +1,15 @@
 pub mod circles;
 mod crosshairs;
 pub mod map;
+mod stats;
 
 use glam::Vec3;
 use macroquad::prelude::*;
 
 use crate::{assets::Assets, frame::FrameRate, game::state::Game};
-use common::{maze::Maze, player::Color as PlayerColor, player::MAX_HEALTH};
+use common::{
+    bullets::MAX_BULLETS_PER_PLAYER, maze::Maze, player::Color as PlayerColor, player::MAX_HEALTH,
+};
 
 pub const FONT_SIZE: f32 = 6.0;
 pub const MAP_FRACTION_OF_SCREEN_HEIGHT: f32 = 0.5;
@@ -95,14 +98,14 @@ pub fn draw(game_state: &Game, assets: &Assets, fps: &FrameRate, estimated_serve
     let circle_radius = BASE_CIRCLE_RADIUS * map_scale;
     let circle_gap = BASE_CIRCLE_GAP * map_scale;
     let map_height = map_overlay.rect.h * map_scale;
-    // The vertical span of the 4 circles, from the center of the first to the
-    // center of the last, is `3 * circle_gap` and is centered at the vertical
+    // The vertical span of the 5 circles, from the center of the first to the
+    // center of the last, is `4 * circle_gap` and is centered at the vertical
     // center of the map. `y_indentation` is the top of the map, and the 2nd
     // term shifts us to the center of the map and then back up by the half of
     // that span that lies above the map center.
-    let top_circle_center = y_indentation + (map_height - 3.0 * circle_gap) / 2.0;
+    let top_circle_center = y_indentation + (map_height - 4.0 * circle_gap) / 2.0;
     circles::draw_compass(
-        local_state,
+        game_state.compass_yaw,
         x,
         top_circle_center,
         circle_radius,
@@ -126,16 +129,135 @@ pub fn draw(game_state: &Game, assets: &Assets, fps: &FrameRate, estimated_serve
         stat_font_size,
     );
 
+    let ammo_y = top_circle_center + circle_gap * 3.0;
+    if let Some(progress) = game_state.reload_progress() {
+        circles::draw_reload(
+            progress,
+            x,
+            ammo_y,
+            circle_radius,
+            &assets.font,
+            stat_font_size,
+        );
+    } else if let Some((rounds, size)) = game_state.magazine_rounds() {
+        circles::draw_ammo(
+            (size - rounds) as usize,
+            size as usize,
+            x,
+            ammo_y,
+            circle_radius,
+            &assets.font,
+            stat_font_size,
+        );
+    } else {
+        circles::draw_ammo(
+            game_state.local_bullets_in_air(),
+            MAX_BULLETS_PER_PLAYER,
+            x,
+            ammo_y,
+            circle_radius,
+            &assets.font,
+            stat_font_size,
+        );
+    }
+
     circles::draw_timer(
         estimated_server_time,
         game_state.start_time,
         game_state.timer_duration,
         x,
-        top_circle_center + circle_gap * 3.0,
+        top_circle_center + circle_gap * 4.0,
         circle_radius,
         &game_state.timer_markers,
         &game_state.needle_textures,
     );
 
+    if game_state.spectators > 0 {
+        draw_text_ex(
+            &format!("{} watching", game_state.spectators),
+            x_indentation,
+            y_indentation + map_height + stat_font_size as f32,
+            TextParams {
+                font: Some(&assets.font),
+                font_size: stat_font_size,
+                color: BLACK,
+                ..Default::default()
+            },
+        );
+    }
+
+    if game_state.is_link_unhealthy() {
+        draw_text_ex(
+            "Connection unstable",
+            x_indentation,
+            y_indentation + map_height + stat_font_size as f32 * 2.0,
+            TextParams {
+                font: Some(&assets.font),
+                font_size: stat_font_size,
+                color: RED,
+                ..Default::default()
+            },
+        );
+    }
+
+    if game_state.show_stats {
+        let local_stats = stats::PlayerStats {
+            health: local_player.health,
+            max_health: MAX_HEALTH,
+            position: local_state.position,
+            kills: game_state.local_kills(),
+        };
+        stats::draw_panel(
+            &local_stats,
+            x_indentation,
+            y_indentation + map_height + stat_font_size as f32 * 3.0,
+            &assets.font,
+            stat_font_size,
+        );
+    }
+
+    for (i, line) in game_state.kill_feed().enumerate() {
+        let dimensions = measure_text(line, Some(&assets.font), stat_font_size, 1.0);
+        draw_text_ex(
+            line,
+            screen_width() - dimensions.width - x_indentation,
+            y_indentation + stat_font_size as f32 * (i + 1) as f32,
+            TextParams {
+                font: Some(&assets.font),
+                font_size: stat_font_size,
+                color: BLACK,
+                ..Default::default()
+            },
+        );
+    }
+
+    if game_state.paused {
+        draw_paused(assets, stat_font_size);
+    }
+
     pop_camera_state();
 }
+
+fn draw_paused(assets: &Assets, stat_font_size: u16) {
+    let font_size = stat_font_size.saturating_mul(3);
+    let text = "Paused";
+    let dimensions = measure_text(text, Some(&assets.font), font_size, 1.0);
+    draw_rectangle(
+        0.0,
+        0.0,
+        screen_width(),
+        screen_height(),
+        Color::new(0.0, 0.0, 0.0, 0.4),
+    );
+    draw_text_ex(
+        text,
+        (screen_width() - dimensions.width) / 2.0,
+        (screen_height() + dimensions.offset_y) / 2.0,
+        TextParams {
+            font: Some(&assets.font),
+            font_size,
+            color: WHITE,
+            ..Default::default()
+        },
+    );
+}
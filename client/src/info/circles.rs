@@ -4,7 +4,6 @@ use macroquad::prelude::*;
 
 use super::BG_COLOR;
 use crate::frame::FrameRate;
-use common::player::PlayerState;
 
 pub struct TimerMarkers {
     pub render_target: RenderTarget,
@@ -84,6 +83,7 @@ impl TimerMarkers {
 
 const HEALTH_FLASH_START_THRESHOLD: f32 = 0.6;
 const TIMER_FLASH_START_THRESHOLD: f32 = 0.9;
+const AMMO_FLASH_START_THRESHOLD: f32 = 0.75;
 const MIN_FLASH_SPEED: f32 = 4.0;
 const MAX_FLASH_SPEED: f32 = 10.0;
 
@@ -182,16 +182,19 @@ impl NeedleTextures {
     }
 }
 
-pub fn draw_compass(
-    local_state: &PlayerState,
-    x: f32,
-    y: f32,
-    radius: f32,
-    needles: &NeedleTextures,
-) {
+const COMPASS_SMOOTHING: f32 = 0.3;
+
+pub fn smooth_yaw(displayed: f32, target: f32, frame_time: f32) -> f32 {
+    let delta = (target - displayed + PI).rem_euclid(2.0 * PI) - PI;
+    let factor = 1.0 - (1.0 - COMPASS_SMOOTHING).powf(frame_time * 60.0);
+    let yaw = displayed + delta * factor;
+    (yaw + PI).rem_euclid(2.0 * PI) - PI
+}
+
+pub fn draw_compass(yaw: f32, x: f32, y: f32, radius: f32, needles: &NeedleTextures) {
     draw_circle(x, y, radius, BG_COLOR);
 
-    let rotation = local_state.yaw;
+    let rotation = yaw;
     let texture = &needles.compass_render_target.texture;
 
     // Calculate size on screen (reversing the supersampling).
@@ -268,14 +271,72 @@ pub fn draw_health(
         flash_opacity,
     );
 
+    draw_circle_value(&health.to_string(), x, y, radius, font, font_size);
+}
+
+pub fn draw_ammo(
+    bullets_in_air: usize,
+    max_bullets: usize,
+    x: f32,
+    y: f32,
+    radius: f32,
+    font: &Font,
+    font_size: u16,
+) {
+    let severity = ammo_severity(bullets_in_air, max_bullets);
+
+    let (current_speed, should_flash) = get_flash_params(severity, AMMO_FLASH_START_THRESHOLD);
+
+    let phase = get_time() as f32 * current_speed;
+
+    let flash_opacity = calculate_flash_opacity(phase, should_flash);
+
+    let danger_color = Color::new(1.0, 0.0, 0.0, 1.0);
+    let safety_color = Color::new(0.0, 1.0, 0.0, 1.0);
+
+    draw_severity_arcs(
+        x,
+        y,
+        radius,
+        danger_color,
+        safety_color,
+        severity,
+        flash_opacity,
+    );
+
+    let available = max_bullets.saturating_sub(bullets_in_air);
+    draw_circle_value(&available.to_string(), x, y, radius, font, font_size);
+}
+
+pub fn draw_reload(progress: f32, x: f32, y: f32, radius: f32, font: &Font, font_size: u16) {
+    let empty_color = Color::new(0.6, 0.6, 0.6, 1.0);
+    let loaded_color = Color::new(0.0, 1.0, 0.0, 1.0);
+
+    draw_severity_arcs(
+        x,
+        y,
+        radius,
+        empty_color,
+        loaded_color,
+        1.0 - progress.clamp(0.0, 1.0),
+        1.0,
+    );
+
+    draw_circle_value("R", x, y, radius, font, font_size);
+}
+
+fn ammo_severity(bullets_in_air: usize, max_bullets: usize) -> f32 {
+    (bullets_in_air as f32 / max_bullets.max(1) as f32).clamp(0.0, 1.0)
+}
+
+fn draw_circle_value(text: &str, x: f32, y: f32, radius: f32, font: &Font, font_size: u16) {
     let font = Some(font);
-    let text = format!("{}", health);
-    let text_dims = measure_text(&text, font, font_size, 1.0);
+    let text_dims = measure_text(text, font, font_size, 1.0);
     let text_x = x - text_dims.width / 2.0;
     let text_y = y + text_dims.height / 2.0 - radius * 0.055;
 
     draw_text_ex(
-        &text,
+        text,
         text_x,
         text_y,
         TextParams {
@@ -421,3 +482,54 @@ fn draw_severity_arcs(
         danger_color,
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compass_eases_along_the_shorter_arc_across_pi() {
+        let displayed = PI - 0.1;
+        let target = -PI + 0.1;
+
+        let yaw = smooth_yaw(displayed, target, 1.0 / 60.0);
+
+        let expected = PI - 0.1 + 0.2 * COMPASS_SMOOTHING;
+        assert!(
+            (yaw - expected).abs() < 1e-4,
+            "yaw {} expected {}",
+            yaw,
+            expected
+        );
+
+        let mut yaw = displayed;
+        for _ in 0..120 {
+            yaw = smooth_yaw(yaw, target, 1.0 / 60.0);
+            assert!(yaw.abs() > PI - 0.2, "needle swung the long way to {}", yaw);
+        }
+        assert!((yaw - target).abs() < 1e-3);
+    }
+
+    fn ammo_flash(bullets_in_air: usize) -> (f32, bool) {
+        let severity = ammo_severity(bullets_in_air, 24);
+        get_flash_params(severity, AMMO_FLASH_START_THRESHOLD)
+    }
+
+    #[test]
+    fn ammo_flashes_faster_as_it_nears_the_limit() {
+        assert_eq!(ammo_flash(0), (0.0, false));
+        assert_eq!(ammo_flash(17), (0.0, false));
+
+        let (threshold_speed, threshold_flash) = ammo_flash(18);
+        assert!(threshold_flash);
+        assert_eq!(threshold_speed, MIN_FLASH_SPEED);
+
+        let (near_limit_speed, near_limit_flash) = ammo_flash(23);
+        assert!(near_limit_flash);
+        assert!(near_limit_speed > threshold_speed);
+        assert!(near_limit_speed <= MAX_FLASH_SPEED);
+
+        assert_eq!(ammo_flash(24), (0.0, false));
+        assert_eq!(ammo_flash(30), (0.0, false));
+    }
+}
@@ -2,7 +2,7 @@ use macroquad::prelude::*;
 
 use super::{SPACE_SYMBOL, WALL_SYMBOL};
 use crate::info::{BG_COLOR, FONT_SIZE};
-use common::maze::{Maze, RADIUS};
+use common::maze::{Maze, RADIUS, WALL};
 
 pub struct MapOverlay {
     pub render_target: RenderTarget,
@@ -81,8 +81,8 @@ fn create_map_string(grid: &[Vec<u8>]) -> String {
     for row in grid {
         for &cell in row {
             match cell {
-                0 => map_string.push_str(SPACE_SYMBOL),
-                _ => map_string.push_str(WALL_SYMBOL),
+                WALL => map_string.push_str(WALL_SYMBOL),
+                _ => map_string.push_str(SPACE_SYMBOL),
             }
         }
         map_string.push('\n');
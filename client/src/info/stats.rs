@@ -0,0 +1,65 @@
+use macroquad::prelude::*;
+
+use common::maze::CELL_SIZE;
+
+const STATS_COLOR: Color = BLACK;
+
+pub struct PlayerStats {
+    pub health: u8,
+    pub max_health: u8,
+    pub position: Vec3,
+    pub kills: u32,
+}
+
+pub fn panel_lines(stats: &PlayerStats) -> Vec<String> {
+    let cell_x = (stats.position.x / CELL_SIZE).floor() as i32;
+    let cell_z = (stats.position.z / CELL_SIZE).floor() as i32;
+    vec![
+        format!("Health: {}/{}", stats.health, stats.max_health),
+        format!(
+            "Position: {:.0}, {:.0} (cell {}, {})",
+            stats.position.x, stats.position.z, cell_x, cell_z
+        ),
+        format!("Kills: {}", stats.kills),
+    ]
+}
+
+pub fn draw_panel(stats: &PlayerStats, x: f32, y: f32, font: &Font, font_size: u16) {
+    for (i, line) in panel_lines(stats).iter().enumerate() {
+        draw_text_ex(
+            line,
+            x,
+            y + font_size as f32 * i as f32,
+            TextParams {
+                font: Some(font),
+                font_size,
+                color: STATS_COLOR,
+                ..Default::default()
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn panel_lists_health_position_and_kills() {
+        let stats = PlayerStats {
+            health: 4,
+            max_health: 9,
+            position: vec3(100.4, 24.0, 200.6),
+            kills: 2,
+        };
+
+        assert_eq!(
+            panel_lines(&stats),
+            vec![
+                "Health: 4/9".to_string(),
+                "Position: 100, 201 (cell 1, 3)".to_string(),
+                "Kills: 2".to_string(),
+            ]
+        );
+    }
+}
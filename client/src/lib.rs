@@ -4,6 +4,7 @@ pub mod env;
 pub mod fade;
 pub mod frame;
 pub mod game;
+pub mod identity;
 pub mod info;
 pub mod lobby;
 pub mod net;
@@ -23,18 +23,8 @@ pub fn update(
         return LobbyStep::Continue;
     }
 
-    if matches!(session.input_mode(), crate::state::InputMode::Enabled) {
-        let ui_ref: &mut dyn crate::lobby::ui::LobbyUi = ui;
-        match ui_ref.poll_input(common::chat::MAX_CHAT_MESSAGE_BYTES, is_host) {
-            Ok(Some(input)) => session.add_input(input),
-            Err(e @ crate::lobby::ui::UiInputError::Disconnected) => {
-                ui.show_sanitized_error(&format!("No connection: {}.", e));
-                return LobbyStep::Transition(ClientState::Disconnected {
-                    message: e.to_string(),
-                });
-            }
-            Ok(None) => {}
-        }
+    if let Some(next_state) = poll_input(session, ui, is_host) {
+        return LobbyStep::Transition(next_state);
     }
 
     if let Some(next_state) = transition(session, ui, network_handle, assets) {
@@ -60,6 +50,28 @@ pub fn update(
     LobbyStep::Continue
 }
 
+pub fn poll_input(
+    session: &mut ClientSession,
+    ui: &mut dyn crate::lobby::ui::LobbyUi,
+    is_host: bool,
+) -> Option<ClientState> {
+    if !matches!(session.input_mode(), crate::state::InputMode::Enabled) {
+        return None;
+    }
+
+    match ui.poll_input(session.max_chat_bytes, is_host) {
+        Ok(Some(input)) => session.add_input(input),
+        Err(e @ crate::lobby::ui::UiInputError::Disconnected) => {
+            ui.show_sanitized_error(&format!("No connection: {}.", e));
+            return Some(ClientState::Disconnected {
+                message: e.to_string(),
+            });
+        }
+        Ok(None) => {}
+    }
+    None
+}
+
 fn transition(
     session: &mut ClientSession,
     ui: &mut dyn crate::lobby::ui::LobbyUi,
@@ -77,6 +89,12 @@ fn transition(
                 Lobby::Passcode { .. } => {
                     state_handlers::passcode::handle(&mut lobby_state, session, ui)
                 }
+                Lobby::BrowsingRooms { .. } => state_handlers::room_browser::handle(
+                    &mut lobby_state,
+                    session,
+                    ui,
+                    network_handle,
+                ),
                 Lobby::Connecting { .. } => state_handlers::connecting::handle(
                     &mut lobby_state,
                     session,
@@ -92,6 +110,9 @@ fn transition(
                 Lobby::AwaitingUsernameConfirmation => {
                     state_handlers::waiting::handle(&mut lobby_state, session, ui, network_handle)
                 }
+                Lobby::ChoosingColor { .. } => {
+                    state_handlers::color::handle(&mut lobby_state, session, ui, network_handle)
+                }
                 Lobby::Chat { .. } => state_handlers::chat::handle(
                     &mut lobby_state,
                     session,
@@ -1,7 +1,7 @@
 use macroquad::prelude::*;
 
 use crate::{game::world::maze::MazeMeshes, info::map::MapOverlay};
-use common::{auth::Passcode, snapshot::InitialData};
+use common::{auth::Passcode, player::Color, snapshot::InitialData};
 
 pub enum Lobby {
     ServerAddress {
@@ -10,6 +10,10 @@ pub enum Lobby {
     Passcode {
         prompt_printed: bool,
     },
+    BrowsingRooms {
+        list_requested: bool,
+        prompt_printed: bool,
+    },
     Connecting {
         pending_passcode: Option<Passcode>,
     },
@@ -22,6 +26,11 @@ pub enum Lobby {
         prompt_printed: bool,
     },
     AwaitingUsernameConfirmation,
+    ChoosingColor {
+        current: Color,
+        available_colors: Vec<Color>,
+        prompt_printed: bool,
+    },
     Chat {
         awaiting_initial_roster: bool,
         waiting_for_server: bool,
@@ -35,6 +44,7 @@ pub enum Lobby {
     },
     ChoosingDifficulty {
         prompt_printed: bool,
+        selected: Option<u8>,
         choice_sent: bool,
     },
 }
@@ -50,6 +60,14 @@ impl std::fmt::Debug for Lobby {
                 .debug_struct("Passcode")
                 .field("prompt_printed", prompt_printed)
                 .finish(),
+            Lobby::BrowsingRooms {
+                list_requested,
+                prompt_printed,
+            } => f
+                .debug_struct("BrowsingRooms")
+                .field("list_requested", list_requested)
+                .field("prompt_printed", prompt_printed)
+                .finish(),
             Lobby::Connecting { pending_passcode } => f
                 .debug_struct("Connecting")
                 .field("pending_passcode", pending_passcode)
@@ -71,6 +89,16 @@ impl std::fmt::Debug for Lobby {
             Lobby::AwaitingUsernameConfirmation => {
                 write!(f, "AwaitingUsernameConfirmation")
             }
+            Lobby::ChoosingColor {
+                current,
+                available_colors,
+                prompt_printed,
+            } => f
+                .debug_struct("ChoosingColor")
+                .field("current", current)
+                .field("available_colors", available_colors)
+                .field("prompt_printed", prompt_printed)
+                .finish(),
             Lobby::Chat {
                 awaiting_initial_roster,
                 waiting_for_server,
@@ -95,10 +123,12 @@ impl std::fmt::Debug for Lobby {
                 .finish(),
             Lobby::ChoosingDifficulty {
                 prompt_printed,
+                selected,
                 choice_sent,
             } => f
                 .debug_struct("ChoosingDifficulty")
                 .field("prompt_printed", prompt_printed)
+                .field("selected", selected)
                 .field("choice_sent", choice_sent)
                 .finish(),
         }
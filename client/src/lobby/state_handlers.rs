@@ -1,9 +1,11 @@
 pub mod auth;
 pub mod chat;
+pub mod color;
 pub mod connecting;
 pub mod countdown;
 pub mod difficulty;
 pub mod passcode;
+pub mod room_browser;
 pub mod server_address;
 pub mod start_countdown;
 pub mod username;
@@ -14,7 +16,7 @@ use crate::{net::RenetNetworkHandle, run::ClientRunner};
 
 pub fn update(runner: &mut ClientRunner) {
     let mut network_handle = RenetNetworkHandle::new(&mut runner.client, &mut runner.transport);
-    let is_host = runner.session.is_host;
+    let is_host = runner.session.is_host || runner.session.start_vote_open;
 
     match super::flow::update(
         &mut runner.session,
@@ -159,7 +161,7 @@ mod tests {
         assert_eq!(ui_auth.prompts.len(), 1, "expected one prompt to be shown");
         assert_eq!(
             ui_auth.prompts[0],
-            auth::passcode_prompt(MAX_ATTEMPTS - 1),
+            auth::passcode_prompt(MAX_ATTEMPTS - 1, MAX_ATTEMPTS),
             "incorrect prompt shown after receiving server info"
         );
     }
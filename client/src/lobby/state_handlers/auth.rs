@@ -1,7 +1,4 @@
-use bincode::{
-    config::standard,
-    serde::{decode_from_slice, encode_to_vec},
-};
+use bincode::serde::{decode_from_slice, encode_to_vec};
 
 use crate::{
     lobby::ui::{LobbyUi, UiErrorKind},
@@ -10,13 +7,13 @@ use crate::{
     state::{ClientState, Lobby},
 };
 use common::{
-    auth::{MAX_ATTEMPTS, Passcode},
+    auth::Passcode,
     input::sanitize,
     net::AppChannel,
     player::MAX_USERNAME_LENGTH,
     protocol::{
-        AUTH_INCORRECT_PASSCODE_DISCONNECTING_MESSAGE, AUTH_INCORRECT_PASSCODE_TRY_AGAIN_MESSAGE,
-        ClientMessage, GAME_ALREADY_STARTED_MESSAGE, ServerMessage, auth_success_message,
+        AUTH_INCORRECT_PASSCODE_TRY_AGAIN_MESSAGE, ClientMessage, GAME_ALREADY_STARTED_MESSAGE,
+        ServerMessage, auth_success_message, config,
     },
 };
 
@@ -38,7 +35,31 @@ pub fn handle(
     let mut guess_sent_this_frame = false;
 
     while let Some(data) = network.receive_message(AppChannel::ReliableOrdered) {
-        match decode_from_slice::<ServerMessage, _>(&data, standard()) {
+        match decode_from_slice::<ServerMessage, _>(&data, config()) {
+            Ok((
+                ServerMessage::AuthSucceeded {
+                    max_username_length,
+                },
+                _,
+            )) => {
+                session.set_auth_waiting_for_server(false);
+                *waiting_for_server = false;
+                session.max_username_length = max_username_length.clamp(1, MAX_USERNAME_LENGTH);
+                ui.show_message(&format!(
+                    "Server: {}",
+                    auth_success_message(session.max_username_length)
+                ));
+                return Some(ClientState::Lobby(Lobby::ChoosingUsername {
+                    prompt_printed: false,
+                }));
+            }
+            Ok((ServerMessage::AuthRequired { max_attempts }, _)) => {
+                session.max_auth_attempts = max_attempts.max(1);
+                *guesses_left = session.max_auth_attempts;
+            }
+            Ok((ServerMessage::DisconnectReason(reason), _)) => {
+                return Some(session.disconnect_for(reason));
+            }
             Ok((ServerMessage::ServerInfo { message }, _)) => {
                 session.set_auth_waiting_for_server(false);
                 *waiting_for_server = false;
@@ -51,18 +72,13 @@ pub fn handle(
 
                 ui.show_message(&format!("Server: {}", sanitized_message));
 
-                if sanitized_message == auth_success_message(MAX_USERNAME_LENGTH) {
-                    return Some(ClientState::Lobby(Lobby::ChoosingUsername {
-                        prompt_printed: false,
-                    }));
-                } else if sanitized_message == AUTH_INCORRECT_PASSCODE_TRY_AGAIN_MESSAGE {
+                if sanitized_message == AUTH_INCORRECT_PASSCODE_TRY_AGAIN_MESSAGE {
                     *guesses_left = guesses_left.saturating_sub(1);
-                    ui.show_sanitized_prompt(&passcode_prompt(*guesses_left));
+                    ui.show_sanitized_prompt(&passcode_prompt(
+                        *guesses_left,
+                        session.max_auth_attempts,
+                    ));
                     *waiting_for_input = true;
-                } else if sanitized_message == AUTH_INCORRECT_PASSCODE_DISCONNECTING_MESSAGE {
-                    return Some(ClientState::Disconnected {
-                        message: "authentication failed".to_string(),
-                    });
                 }
             }
             Ok((_, _)) => {}
@@ -87,7 +103,7 @@ pub fn handle(
 
                 let message = ClientMessage::SendPasscode(passcode.bytes);
                 let payload =
-                    encode_to_vec(&message, standard()).expect("failed to serialize SendPasscode");
+                    encode_to_vec(&message, config()).expect("failed to serialize SendPasscode");
                 network.send_message(AppChannel::ReliableOrdered, payload);
 
                 should_mark_waiting_for_server = true;
@@ -103,7 +119,10 @@ pub fn handle(
                     ),
                 );
 
-                ui.show_sanitized_prompt(&passcode_prompt(*guesses_left));
+                ui.show_sanitized_prompt(&passcode_prompt(
+                    *guesses_left,
+                    session.max_auth_attempts,
+                ));
             }
         }
         if should_mark_waiting_for_server {
@@ -112,7 +131,7 @@ pub fn handle(
     }
 
     if session.auth_waiting_for_server() && !*waiting_for_input && !guess_sent_this_frame {
-        ui.show_prompt(&passcode_prompt(*guesses_left));
+        ui.show_prompt(&passcode_prompt(*guesses_left, session.max_auth_attempts));
     }
 
     if guess_sent_this_frame {
@@ -131,8 +150,8 @@ pub fn handle(
     None
 }
 
-pub fn passcode_prompt(remaining: u8) -> String {
-    if remaining == MAX_ATTEMPTS {
+pub fn passcode_prompt(remaining: u8, max_attempts: u8) -> String {
+    if remaining == max_attempts {
         format!("Enter passcode ({} guesses): ", remaining)
     } else {
         format!(
@@ -156,3 +175,84 @@ pub fn parse_passcode_input(input: &str) -> Option<Passcode> {
     }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{MockNetwork, MockUi};
+    use common::auth::MAX_ATTEMPTS;
+
+    fn authenticating_session() -> ClientSession {
+        let mut session = ClientSession::new(0);
+        session.transition(ClientState::Lobby(Lobby::Authenticating {
+            waiting_for_input: false,
+            waiting_for_server: true,
+            guesses_left: MAX_ATTEMPTS,
+        }));
+        session
+    }
+
+    fn run(session: &mut ClientSession, network: &mut MockNetwork) -> Option<ClientState> {
+        let mut ui = MockUi::new();
+        let mut temp_state = std::mem::take(&mut session.state);
+        let result = if let ClientState::Lobby(lobby_state) = &mut temp_state {
+            handle(lobby_state, session, &mut ui, network)
+        } else {
+            panic!("expected Lobby state");
+        };
+        session.state = temp_state;
+        result
+    }
+
+    #[test]
+    fn auth_succeeded_moves_to_username_selection_with_the_server_limit() {
+        let mut session = authenticating_session();
+        let mut network = MockNetwork::new();
+        network.queue_server_message(ServerMessage::AuthSucceeded {
+            max_username_length: 8,
+        });
+
+        let next_state = run(&mut session, &mut network);
+
+        assert!(matches!(
+            next_state,
+            Some(ClientState::Lobby(Lobby::ChoosingUsername { .. }))
+        ));
+        assert_eq!(session.max_username_length, 8);
+    }
+
+    #[test]
+    fn guesses_are_counted_from_the_server_limit() {
+        let mut session = authenticating_session();
+        let mut network = MockNetwork::new();
+        network.queue_server_message(ServerMessage::AuthRequired { max_attempts: 5 });
+        network.queue_server_message(ServerMessage::ServerInfo {
+            message: AUTH_INCORRECT_PASSCODE_TRY_AGAIN_MESSAGE.to_string(),
+        });
+
+        run(&mut session, &mut network);
+
+        assert_eq!(session.max_auth_attempts, 5);
+        assert!(matches!(
+            session.state,
+            ClientState::Lobby(Lobby::Authenticating {
+                guesses_left: 4,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn success_text_in_server_info_does_not_authenticate() {
+        let mut session = authenticating_session();
+        let mut network = MockNetwork::new();
+        network.queue_server_message(ServerMessage::ServerInfo {
+            message: auth_success_message(8),
+        });
+
+        let next_state = run(&mut session, &mut network);
+
+        assert!(next_state.is_none());
+        assert_eq!(session.max_username_length, MAX_USERNAME_LENGTH);
+    }
+}
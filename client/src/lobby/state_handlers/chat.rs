@@ -1,7 +1,6 @@
-use bincode::{
-    config::standard,
-    serde::{decode_from_slice, encode_to_vec},
-};
+use std::time::Instant;
+
+use bincode::serde::{decode_from_slice, encode_to_vec};
 
 use super::start_countdown::handle_countdown_started;
 use crate::{
@@ -13,9 +12,13 @@ use crate::{
 };
 use common::{
     net::AppChannel,
-    protocol::{ClientMessage, ServerMessage},
+    protocol::{ClientMessage, PlayerRosterEntry, ServerMessage, config},
+    team::{TEAM_COUNT, team_name},
 };
 
+pub const CLEAR_COMMAND: &str = "/clear";
+const PING_COMMAND: &str = "/ping";
+
 pub fn handle(
     lobby_state: &mut Lobby,
     session: &mut ClientSession,
@@ -34,7 +37,7 @@ pub fn handle(
     while let Some(data) = network.receive_message(AppChannel::ReliableOrdered) {
         *waiting_for_server = false;
 
-        match decode_from_slice::<ServerMessage, _>(&data, standard()) {
+        match decode_from_slice::<ServerMessage, _>(&data, config()) {
             Ok((
                 ServerMessage::CountdownStarted {
                     end_time,
@@ -42,11 +45,18 @@ pub fn handle(
                 },
                 _,
             )) => {
-                return Some(handle_countdown_started(end_time, game_data, assets));
+                return Some(handle_countdown_started(
+                    end_time,
+                    game_data,
+                    assets,
+                    session.floor_theme,
+                    session.shadows,
+                ));
             }
             Ok((ServerMessage::BeginDifficultySelection, _)) => {
                 return Some(ClientState::Lobby(Lobby::ChoosingDifficulty {
                     prompt_printed: false,
+                    selected: None,
                     choice_sent: false,
                 }));
             }
@@ -61,40 +71,84 @@ pub fn handle(
                 },
                 _,
             )) => {
-                if session.awaiting_initial_roster() {
+                if session.awaiting_initial_roster()
+                    || session
+                        .recent_chat
+                        .is_duplicate(&username, &content, Instant::now())
+                {
                     continue;
                 }
                 ui.show_sanitized_message_with_color(&format!("{}: {}", username, content), color);
             }
             Ok((ServerMessage::UserJoined { username }, _)) => {
+                if !session.roster.contains(&username) {
+                    session.roster.push(username.clone());
+                    ui.set_mention_candidates(&session.roster);
+                }
                 if session.awaiting_initial_roster() {
                     continue;
                 }
                 ui.show_sanitized_message(&format!("Server: {} joined the chat.", username));
             }
             Ok((ServerMessage::UserLeft { username }, _)) => {
+                session.roster.retain(|name| *name != username);
+                ui.set_mention_candidates(&session.roster);
                 if session.awaiting_initial_roster() {
                     continue;
                 }
                 ui.show_sanitized_message(&format!("Server: {} left the chat.", username));
             }
-            Ok((ServerMessage::Roster { online }, _)) => {
-                if online.is_empty() {
+            Ok((ServerMessage::PlayerCount(count), _)) => {
+                ui.set_status_line(&format!("Players online: {}", count));
+            }
+            Ok((ServerMessage::Roster { online, connecting }, _)) => {
+                session.roster = online.iter().map(|entry| entry.username.clone()).collect();
+                ui.set_mention_candidates(&session.roster);
+                if online.is_empty() && connecting == 0 {
                     ui.show_sanitized_message("Server: You are the only player online.");
                 } else {
                     ui.show_sanitized_message("Server: Players online:");
-                    for entry in online {
-                        ui.show_sanitized_message_with_color(
-                            &format!(" - {}", entry.username),
-                            entry.color,
-                        );
+                    show_roster_entries(ui, &online);
+                    for _ in 0..connecting {
+                        ui.show_sanitized_message(" - (connecting...)");
                     }
                 }
                 session.mark_initial_roster_received();
             }
+            Ok((ServerMessage::ColorAssigned { color }, _)) => {
+                ui.set_local_player_color(color);
+                ui.show_message_with_color(&format!("Server: Your color is {}.", color), color);
+            }
+            Ok((ServerMessage::DisconnectReason(reason), _)) => {
+                return Some(session.disconnect_for(reason));
+            }
             Ok((ServerMessage::ServerInfo { message }, _)) => {
                 ui.show_sanitized_message(&format!("Server: {}", message));
             }
+            Ok((ServerMessage::ChatCooldown { seconds_remaining }, _)) => {
+                ui.show_warning(&format!(
+                    "Slow down! You can chat again in {:.1} seconds.",
+                    seconds_remaining
+                ));
+            }
+            Ok((
+                ServerMessage::StartVote {
+                    username,
+                    votes,
+                    needed,
+                },
+                _,
+            )) => {
+                session.start_vote_open = true;
+                ui.show_sanitized_message(&format!(
+                    "Server: {} wants to start ({}/{} votes). Press TAB to agree.",
+                    username, votes, needed
+                ));
+            }
+            Ok((ServerMessage::Pong(sent_at), _)) => {
+                let rtt_ms = (common::time::now_as_secs_f64() - sent_at) * 1000.0;
+                ui.show_message(&format!("Pong: round trip {:.0} ms.", rtt_ms.max(0.0)));
+            }
             Ok((ServerMessage::AppointHost, _)) => {
                 session.is_host = true;
                 ui.show_sanitized_message(
@@ -111,10 +165,10 @@ pub fn handle(
 
     while let Some(input) = session.take_input() {
         if input == "\t" {
-            if session.is_host {
+            if session.is_host || session.start_vote_open {
                 let message = ClientMessage::RequestStartGame;
                 let payload =
-                    encode_to_vec(&message, standard()).expect("failed to serialize command");
+                    encode_to_vec(&message, config()).expect("failed to serialize command");
                 network.send_message(AppChannel::ReliableOrdered, payload);
                 *waiting_for_server = true;
             }
@@ -127,9 +181,21 @@ pub fn handle(
             continue;
         }
 
+        if trimmed_input == CLEAR_COMMAND {
+            ui.clear_history();
+            continue;
+        }
+
+        if trimmed_input == PING_COMMAND {
+            let message = ClientMessage::Ping(common::time::now_as_secs_f64());
+            let payload = encode_to_vec(&message, config()).expect("failed to serialize Ping");
+            network.send_message(AppChannel::ReliableOrdered, payload);
+            continue;
+        }
+
         let message = ClientMessage::SendChat(trimmed_input.to_string());
 
-        let payload = encode_to_vec(&message, standard()).expect("failed to serialize chat");
+        let payload = encode_to_vec(&message, config()).expect("failed to serialize chat");
         network.send_message(AppChannel::ReliableOrdered, payload);
 
         *waiting_for_server = true;
@@ -154,11 +220,37 @@ pub fn handle(
     }
 }
 
+fn show_roster_entries(ui: &mut dyn LobbyUi, online: &[PlayerRosterEntry]) {
+    if online.iter().all(|entry| entry.team.is_none()) {
+        for entry in online {
+            ui.show_sanitized_message_with_color(&format!(" - {}", entry.username), entry.color);
+        }
+        return;
+    }
+
+    for team in 0..TEAM_COUNT {
+        let members: Vec<&PlayerRosterEntry> = online
+            .iter()
+            .filter(|entry| entry.team == Some(team))
+            .collect();
+        if members.is_empty() {
+            continue;
+        }
+        ui.show_sanitized_message(&format!(" {}:", team_name(team)));
+        for entry in members {
+            ui.show_sanitized_message_with_color(&format!("   - {}", entry.username), entry.color);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{test_helpers::MockNetwork, test_helpers::MockUi};
-    use common::chat::MAX_CHAT_MESSAGE_BYTES;
+    use common::{
+        chat::MAX_CHAT_MESSAGE_BYTES,
+        protocol::{DisconnectReason, KICKED_MESSAGE},
+    };
 
     #[test]
     fn enforces_max_message_length() {
@@ -208,7 +300,7 @@ mod tests {
         assert_eq!(network.sent_messages.len(), 1);
 
         let (_, payload) = network.sent_messages.pop_front().unwrap();
-        let (message, _) = decode_from_slice::<ClientMessage, _>(&payload, standard()).unwrap();
+        let (message, _) = decode_from_slice::<ClientMessage, _>(&payload, config()).unwrap();
 
         match message {
             ClientMessage::SendChat(content) => {
@@ -218,6 +310,59 @@ mod tests {
         }
     }
 
+    #[test]
+    fn clear_command_clears_history_without_sending() {
+        let mut session = ClientSession::new(0);
+        session.transition(ClientState::Lobby(Lobby::Chat {
+            awaiting_initial_roster: false,
+            waiting_for_server: false,
+        }));
+        session.mark_initial_roster_received();
+        session.add_input(" /clear ".to_string());
+
+        let mut ui = MockUi::new();
+        let mut network = MockNetwork::new();
+        let mut temp_state = std::mem::take(&mut session.state);
+        if let ClientState::Lobby(lobby_state) = &mut temp_state {
+            handle(lobby_state, &mut session, &mut ui, &mut network, None);
+        }
+
+        assert_eq!(ui.history_clears, 1);
+        assert!(network.sent_messages.is_empty());
+        assert!(matches!(
+            temp_state,
+            ClientState::Lobby(Lobby::Chat {
+                waiting_for_server: false,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn player_count_updates_status_line() {
+        let mut session = ClientSession::new(0);
+        session.transition(ClientState::Lobby(Lobby::Chat {
+            awaiting_initial_roster: true,
+            waiting_for_server: false,
+        }));
+        session.mark_initial_roster_received();
+
+        let mut ui = MockUi::new();
+        let mut network = MockNetwork::new();
+        network.queue_server_message(ServerMessage::PlayerCount(2));
+        network.queue_server_message(ServerMessage::PlayerCount(3));
+
+        let mut temp_state = std::mem::take(&mut session.state);
+        if let ClientState::Lobby(lobby_state) = &mut temp_state {
+            handle(lobby_state, &mut session, &mut ui, &mut network, None);
+        } else {
+            panic!("expected Lobby state");
+        }
+
+        assert_eq!(ui.status_line.as_deref(), Some("Players online: 3"));
+        assert!(ui.messages.is_empty());
+    }
+
     #[test]
     fn sanitizes_chat_messages_ansi_and_control_chars() {
         let bell = "\x07";
@@ -258,6 +403,183 @@ mod tests {
         assert_eq!(ui.messages[0], "Hacker: This is Danger!");
     }
 
+    #[test]
+    fn roster_tracks_players_joining_and_leaving() {
+        let mut session = ClientSession::new(0);
+        session.transition(ClientState::Lobby(Lobby::Chat {
+            awaiting_initial_roster: true,
+            waiting_for_server: false,
+        }));
+
+        let mut ui = MockUi::new();
+        let mut network = MockNetwork::new();
+        network.queue_server_message(ServerMessage::Roster {
+            online: vec![PlayerRosterEntry {
+                username: "alice".to_string(),
+                color: common::player::Color::RED,
+                team: None,
+            }],
+            connecting: 0,
+        });
+        network.queue_server_message(ServerMessage::UserJoined {
+            username: "bob".to_string(),
+        });
+        network.queue_server_message(ServerMessage::UserLeft {
+            username: "alice".to_string(),
+        });
+
+        let mut temp_state = std::mem::take(&mut session.state);
+        if let ClientState::Lobby(lobby_state) = &mut temp_state {
+            handle(lobby_state, &mut session, &mut ui, &mut network, None);
+        } else {
+            panic!("expected Lobby state");
+        }
+
+        assert_eq!(session.roster, vec!["bob".to_string()]);
+    }
+
+    #[test]
+    fn replayed_chat_message_is_shown_once() {
+        let mut session = ClientSession::new(0);
+        session.transition(ClientState::Lobby(Lobby::Chat {
+            awaiting_initial_roster: true,
+            waiting_for_server: false,
+        }));
+        session.mark_initial_roster_received();
+
+        let mut ui = MockUi::new();
+        let mut network = MockNetwork::new();
+        for _ in 0..2 {
+            network.queue_server_message(ServerMessage::ChatMessage {
+                username: "alice".to_string(),
+                color: common::player::Color::RED,
+                content: "hello".to_string(),
+            });
+        }
+
+        let mut temp_state = std::mem::take(&mut session.state);
+        if let ClientState::Lobby(lobby_state) = &mut temp_state {
+            handle(lobby_state, &mut session, &mut ui, &mut network, None);
+        } else {
+            panic!("expected Lobby state");
+        }
+
+        assert_eq!(ui.messages, vec!["alice: hello".to_string()]);
+    }
+
+    #[test]
+    fn disconnect_reason_ends_the_session_with_its_message() {
+        let mut session = ClientSession::new(0);
+        session.transition(ClientState::Lobby(Lobby::Chat {
+            awaiting_initial_roster: false,
+            waiting_for_server: false,
+        }));
+
+        let mut ui = MockUi::new();
+        let mut network = MockNetwork::new();
+        network.queue_server_message(ServerMessage::DisconnectReason(DisconnectReason::Kicked));
+
+        let mut temp_state = std::mem::take(&mut session.state);
+        let next_state = if let ClientState::Lobby(lobby_state) = &mut temp_state {
+            handle(lobby_state, &mut session, &mut ui, &mut network, None)
+        } else {
+            panic!("expected Lobby state");
+        };
+
+        assert!(matches!(
+            next_state,
+            Some(ClientState::Disconnected { message }) if message == KICKED_MESSAGE
+        ));
+        assert_eq!(session.disconnect_reason, Some(DisconnectReason::Kicked));
+    }
+
+    #[test]
+    fn shows_slow_down_notice_on_chat_cooldown() {
+        let mut session = ClientSession::new(0);
+        session.transition(ClientState::Lobby(Lobby::Chat {
+            awaiting_initial_roster: true,
+            waiting_for_server: true,
+        }));
+        session.mark_initial_roster_received();
+
+        let mut ui = MockUi::new();
+        let mut network = MockNetwork::new();
+
+        network.queue_server_message(ServerMessage::ChatCooldown {
+            seconds_remaining: 2.5,
+        });
+
+        let next_state = {
+            let mut temp_state = std::mem::take(&mut session.state);
+            let result = if let ClientState::Lobby(lobby_state) = &mut temp_state {
+                handle(lobby_state, &mut session, &mut ui, &mut network, None)
+            } else {
+                panic!("expected Lobby state");
+            };
+            session.state = temp_state;
+            result
+        };
+
+        assert!(next_state.is_none());
+        assert_eq!(
+            ui.warnings,
+            vec!["Slow down! You can chat again in 2.5 seconds.".to_string()]
+        );
+        assert!(ui.messages.is_empty());
+        assert!(matches!(
+            &session.state,
+            ClientState::Lobby(Lobby::Chat {
+                waiting_for_server: false,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn groups_roster_by_team() {
+        let mut session = ClientSession::new(0);
+        session.transition(ClientState::Lobby(Lobby::Chat {
+            awaiting_initial_roster: true,
+            waiting_for_server: false,
+        }));
+
+        let mut ui = MockUi::new();
+        let mut network = MockNetwork::new();
+
+        let entry = |username: &str, team| PlayerRosterEntry {
+            username: username.to_string(),
+            color: common::player::Color::RED,
+            team: Some(team),
+        };
+        network.queue_server_message(ServerMessage::Roster {
+            online: vec![entry("Bob", 1), entry("Carol", 0), entry("Dave", 1)],
+            connecting: 0,
+        });
+
+        let _next_state = {
+            let mut temp_state = std::mem::take(&mut session.state);
+            let result = if let ClientState::Lobby(lobby_state) = &mut temp_state {
+                handle(lobby_state, &mut session, &mut ui, &mut network, None)
+            } else {
+                panic!("expected Lobby state");
+            };
+            session.state = temp_state;
+            result
+        };
+
+        assert_eq!(
+            ui.messages,
+            vec![
+                "Server: Players online:",
+                " Team 1:",
+                "   - Carol",
+                " Team 2:",
+                "   - Bob",
+                "   - Dave",
+            ]
+        );
+    }
+
     #[test]
     fn sends_start_game_request_on_tab_input() {
         let mut session = ClientSession::new(0);
@@ -289,7 +611,7 @@ mod tests {
         let (channel, payload) = network.sent_messages.pop_front().unwrap();
         assert_eq!(channel, AppChannel::ReliableOrdered);
 
-        let (message, _) = decode_from_slice::<ClientMessage, _>(&payload, standard()).unwrap();
+        let (message, _) = decode_from_slice::<ClientMessage, _>(&payload, config()).unwrap();
         assert!(matches!(message, ClientMessage::RequestStartGame));
         assert!(
             matches!(
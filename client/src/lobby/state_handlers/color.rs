@@ -0,0 +1,140 @@
+use bincode::serde::encode_to_vec;
+
+use crate::{
+    lobby::ui::{LobbyUi, UiInputError},
+    net::NetworkHandle,
+    session::ClientSession,
+    state::{ClientState, Lobby},
+};
+use common::{
+    input::UiKey,
+    net::AppChannel,
+    protocol::{ClientMessage, config},
+};
+
+pub fn handle(
+    lobby_state: &mut Lobby,
+    _session: &mut ClientSession,
+    ui: &mut dyn LobbyUi,
+    network: &mut dyn NetworkHandle,
+) -> Option<ClientState> {
+    let Lobby::ChoosingColor {
+        current,
+        available_colors,
+        prompt_printed,
+    } = lobby_state
+    else {
+        unreachable!();
+    };
+
+    if !*prompt_printed {
+        ui.show_message("Server: Choose your color.");
+        ui.show_message(" ");
+        for (i, &color) in available_colors.iter().enumerate() {
+            ui.show_message_with_color(&format!("  {}. {}", i, color), color);
+        }
+        ui.show_message(" ");
+        ui.show_prompt(&format!(
+            "Pick a number, or press ENTER to keep {}.",
+            current
+        ));
+        *prompt_printed = true;
+    }
+
+    let chat = Some(ClientState::Lobby(Lobby::Chat {
+        awaiting_initial_roster: true,
+        waiting_for_server: false,
+    }));
+
+    match ui.poll_single_key() {
+        Ok(Some(UiKey::Enter)) => return chat,
+        Ok(Some(UiKey::Char(c))) => {
+            let choice = c
+                .to_digit(10)
+                .and_then(|digit| available_colors.get(digit as usize));
+            if let Some(&color) = choice {
+                let message = ClientMessage::SetColor(color);
+                let payload =
+                    encode_to_vec(&message, config()).expect("failed to serialize SetColor");
+                network.send_message(AppChannel::ReliableOrdered, payload);
+                return chat;
+            }
+        }
+        Ok(_) => {}
+        Err(UiInputError::Disconnected) => {
+            ui.show_sanitized_error("No connection: disconnected.");
+            return Some(ClientState::Disconnected {
+                message: "disconnected".to_string(),
+            });
+        }
+    }
+
+    if network.is_disconnected() {
+        return Some(ClientState::Disconnected {
+            message: format!(
+                "disconnected while choosing color: {}",
+                network.get_disconnect_reason()
+            ),
+        });
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use bincode::serde::decode_from_slice;
+
+    use super::*;
+    use crate::test_helpers::{MockNetwork, MockUi};
+    use common::player::Color;
+
+    fn choosing_color() -> Lobby {
+        Lobby::ChoosingColor {
+            current: Color::RED,
+            available_colors: vec![Color::RED, Color::BLUE, Color::LIME],
+            prompt_printed: false,
+        }
+    }
+
+    #[test]
+    fn digit_sends_chosen_color_and_enters_chat() {
+        let mut session = ClientSession::new(0);
+        let mut lobby_state = choosing_color();
+        let mut ui = MockUi::new();
+        ui.keys.push_back(Ok(Some(UiKey::Char('1'))));
+        let mut network = MockNetwork::new();
+
+        let next_state = handle(&mut lobby_state, &mut session, &mut ui, &mut network);
+
+        assert!(matches!(
+            next_state,
+            Some(ClientState::Lobby(Lobby::Chat { .. }))
+        ));
+        assert_eq!(ui.messages.len(), 6);
+        let (channel, payload) = network.sent_messages.pop_front().expect("no message sent");
+        assert_eq!(channel, AppChannel::ReliableOrdered);
+        let (message, _) = decode_from_slice::<ClientMessage, _>(&payload, config()).unwrap();
+        assert!(matches!(message, ClientMessage::SetColor(Color::BLUE)));
+    }
+
+    #[test]
+    fn out_of_range_digit_is_ignored_and_enter_keeps_current_color() {
+        let mut session = ClientSession::new(0);
+        let mut lobby_state = choosing_color();
+        let mut ui = MockUi::new();
+        ui.keys.push_back(Ok(Some(UiKey::Char('7'))));
+        ui.keys.push_back(Ok(Some(UiKey::Enter)));
+        let mut network = MockNetwork::new();
+
+        let first = handle(&mut lobby_state, &mut session, &mut ui, &mut network);
+        assert!(first.is_none());
+        let second = handle(&mut lobby_state, &mut session, &mut ui, &mut network);
+
+        assert!(matches!(
+            second,
+            Some(ClientState::Lobby(Lobby::Chat { .. }))
+        ));
+        assert!(network.sent_messages.is_empty());
+    }
+}
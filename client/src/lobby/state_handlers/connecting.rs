@@ -1,4 +1,4 @@
-use bincode::{config::standard, serde::decode_from_slice, serde::encode_to_vec};
+use bincode::{serde::decode_from_slice, serde::encode_to_vec};
 
 use crate::{
     lobby::ui::{LobbyUi, UiErrorKind},
@@ -7,14 +7,13 @@ use crate::{
     state::{ClientState, Lobby},
 };
 use common::{
-    auth::MAX_ATTEMPTS,
     net::AppChannel,
-    protocol::{ClientMessage, ServerMessage},
+    protocol::{ClientMessage, ServerMessage, config},
 };
 
 pub fn handle(
     lobby_state: &mut Lobby,
-    _session: &mut ClientSession,
+    session: &mut ClientSession,
     ui: &mut dyn LobbyUi,
     network: &mut dyn NetworkHandle,
 ) -> Option<ClientState> {
@@ -23,10 +22,13 @@ pub fn handle(
     };
 
     while let Some(data) = network.receive_message(AppChannel::ReliableOrdered) {
-        match decode_from_slice::<ServerMessage, _>(&data, standard()) {
+        match decode_from_slice::<ServerMessage, _>(&data, config()) {
             Ok((ServerMessage::ServerInfo { message }, _)) => {
                 return Some(ClientState::Disconnected { message });
             }
+            Ok((ServerMessage::DisconnectReason(reason), _)) => {
+                return Some(session.disconnect_for(reason));
+            }
             Ok((_, _)) => {}
             Err(e) => {
                 ui.show_typed_error(
@@ -38,6 +40,11 @@ pub fn handle(
     }
 
     if network.is_connected() {
+        let room_code = session.room_code.clone().unwrap_or_default();
+        let message = ClientMessage::JoinRoom(room_code);
+        let payload = encode_to_vec(&message, config()).expect("failed to serialize JoinRoom");
+        network.send_message(AppChannel::ReliableOrdered, payload);
+
         let passcode = pending_passcode.take();
 
         if let Some(passcode) = passcode {
@@ -48,19 +55,19 @@ pub fn handle(
 
             let message = ClientMessage::SendPasscode(passcode.bytes);
             let payload =
-                encode_to_vec(&message, standard()).expect("failed to serialize SendPasscode");
+                encode_to_vec(&message, config()).expect("failed to serialize SendPasscode");
             network.send_message(AppChannel::ReliableOrdered, payload);
 
             Some(ClientState::Lobby(Lobby::Authenticating {
                 waiting_for_input: false,
                 waiting_for_server: true,
-                guesses_left: MAX_ATTEMPTS,
+                guesses_left: session.max_auth_attempts,
             }))
         } else {
             Some(ClientState::Lobby(Lobby::Authenticating {
                 waiting_for_input: true,
                 waiting_for_server: false,
-                guesses_left: MAX_ATTEMPTS,
+                guesses_left: session.max_auth_attempts,
             }))
         }
     } else if network.is_disconnected() {
@@ -85,7 +92,7 @@ mod tests {
         net::DisconnectKind,
         test_helpers::{MockNetwork, MockUi},
     };
-    use common::protocol::{GAME_ALREADY_STARTED_MESSAGE, ServerMessage};
+    use common::protocol::{DisconnectReason, GAME_ALREADY_STARTED_MESSAGE, ServerMessage};
 
     mod guards {
         use super::*;
@@ -149,6 +156,63 @@ mod tests {
         );
     }
 
+    #[test]
+    fn disconnect_reason_disconnects_during_connecting() {
+        let mut session = ClientSession::new(0);
+        session.transition(ClientState::Lobby(Lobby::Connecting {
+            pending_passcode: None,
+        }));
+        let mut ui = MockUi::default();
+        let mut network = MockNetwork::new();
+        network.queue_server_message(ServerMessage::DisconnectReason(
+            DisconnectReason::ProtocolError,
+        ));
+
+        let mut temp_state = std::mem::take(&mut session.state);
+        let next_state = if let ClientState::Lobby(lobby_state) = &mut temp_state {
+            handle(lobby_state, &mut session, &mut ui, &mut network)
+        } else {
+            panic!("expected Lobby state");
+        };
+
+        assert!(matches!(
+            next_state,
+            Some(ClientState::Disconnected { message, .. })
+                if message == DisconnectReason::ProtocolError.message()
+        ));
+        assert_eq!(
+            session.disconnect_reason,
+            Some(DisconnectReason::ProtocolError)
+        );
+    }
+
+    #[test]
+    fn joins_room_before_sending_passcode() {
+        let mut session = ClientSession::new(0);
+        session.room_code = Some("ABCD".to_string());
+        session.transition(ClientState::Lobby(Lobby::Connecting {
+            pending_passcode: Some(
+                common::auth::Passcode::from_string("123456").expect("failed to create passcode"),
+            ),
+        }));
+        let mut ui = MockUi::default();
+        let mut network = MockNetwork::new();
+        network.set_connected(true);
+
+        let mut temp_state = std::mem::take(&mut session.state);
+        if let ClientState::Lobby(lobby_state) = &mut temp_state {
+            handle(lobby_state, &mut session, &mut ui, &mut network);
+        }
+
+        let sent: Vec<ClientMessage> = network
+            .sent_messages
+            .iter()
+            .map(|(_, data)| decode_from_slice(data, config()).unwrap().0)
+            .collect();
+        assert!(matches!(&sent[0], ClientMessage::JoinRoom(code) if code == "ABCD"));
+        assert!(matches!(sent[1], ClientMessage::SendPasscode(_)));
+    }
+
     #[test]
     fn disconnect_reason_mapping_game_already_started_on_disconnect() {
         let mut session = ClientSession::new(0);
@@ -1,4 +1,4 @@
-use bincode::{config::standard, serde::decode_from_slice};
+use bincode::serde::decode_from_slice;
 
 use crate::{
     assets::Assets,
@@ -7,7 +7,10 @@ use crate::{
     session::ClientSession,
     state::{ClientState, Lobby},
 };
-use common::{net::AppChannel, protocol::ServerMessage};
+use common::{
+    net::AppChannel,
+    protocol::{ServerMessage, config},
+};
 
 pub fn handle(
     lobby_state: &mut Lobby,
@@ -62,10 +65,11 @@ pub fn handle(
     }
 
     while let Some(data) = network.receive_message(AppChannel::ReliableOrdered) {
-        match decode_from_slice::<ServerMessage, _>(&data, standard()) {
-            Ok((msg, _)) => {
-                let _ = msg;
+        match decode_from_slice::<ServerMessage, _>(&data, config()) {
+            Ok((ServerMessage::DisconnectReason(reason), _)) => {
+                return Some(session.disconnect_for(reason));
             }
+            Ok((_, _)) => {}
             Err(e) => ui.show_typed_error(
                 UiErrorKind::Deserialization,
                 &format!("[DESERIALIZATION ERROR: {}]", e),
@@ -1,7 +1,4 @@
-use bincode::{
-    config::standard,
-    serde::{decode_from_slice, encode_to_vec},
-};
+use bincode::serde::{decode_from_slice, encode_to_vec};
 
 use super::start_countdown::handle_countdown_started;
 use crate::{
@@ -15,10 +12,18 @@ use common::{
     input::UiKey,
     net::AppChannel,
     player::Color,
-    protocol::{ClientMessage, ServerMessage},
+    protocol::{ClientMessage, ServerMessage, config},
 };
 
 const INVALID_CHOICE_MESSAGE: &str = "Invalid choice. Please press 0 through 9.";
+const CONFIRM_INPUT: &str = "\n";
+
+fn selected_message(level: u8) -> String {
+    format!(
+        "Maze {} selected. Press Enter to confirm, or another number to change it.",
+        level
+    )
+}
 
 fn enqueue_difficulty_input(
     session: &mut ClientSession,
@@ -37,6 +42,7 @@ fn enqueue_difficulty_input(
             Some(UiKey::Char(c)) if matches!(c, '0'..='9') => {
                 session.add_input(c.to_string());
             }
+            Some(UiKey::Enter) => session.add_input(CONFIRM_INPUT.to_string()),
             _ => {}
         },
         Err(UiInputError::Disconnected) => {
@@ -59,6 +65,7 @@ pub fn handle(
 ) -> Option<ClientState> {
     let Lobby::ChoosingDifficulty {
         prompt_printed,
+        selected,
         choice_sent,
     } = lobby_state
     else {
@@ -87,12 +94,12 @@ pub fn handle(
         ui.show_message_with_color("  9. Wilson (next level)", Color::RED);
         ui.show_message(" ");
 
-        ui.show_prompt("Pick a number.");
+        ui.show_prompt("Pick a number, then press Enter to confirm.");
         *prompt_printed = true;
     }
 
     while let Some(data) = network.receive_message(AppChannel::ReliableOrdered) {
-        match decode_from_slice::<ServerMessage, _>(&data, standard()) {
+        match decode_from_slice::<ServerMessage, _>(&data, config()) {
             Ok((
                 ServerMessage::CountdownStarted {
                     end_time,
@@ -100,12 +107,22 @@ pub fn handle(
                 },
                 _,
             )) => {
-                return Some(handle_countdown_started(end_time, game_data, assets));
+                return Some(handle_countdown_started(
+                    end_time,
+                    game_data,
+                    assets,
+                    session.floor_theme,
+                    session.shadows,
+                ));
+            }
+            Ok((ServerMessage::DisconnectReason(reason), _)) => {
+                return Some(session.disconnect_for(reason));
             }
             Ok((ServerMessage::ServerInfo { message }, _)) => {
                 ui.show_sanitized_message(&format!("Server: {}", message));
                 return Some(ClientState::Lobby(Lobby::ChoosingDifficulty {
                     prompt_printed: false,
+                    selected: None,
                     choice_sent: false,
                 }));
             }
@@ -122,6 +139,22 @@ pub fn handle(
 
     if !choice_already_sent {
         if let Some(input) = session.take_input() {
+            if input == CONFIRM_INPUT {
+                if selected.is_none() {
+                    return None;
+                }
+                let msg = ClientMessage::ConfirmDifficulty;
+                let payload =
+                    encode_to_vec(&msg, config()).expect("failed to serialize ConfirmDifficulty");
+                network.send_message(AppChannel::ReliableOrdered, payload);
+
+                return Some(ClientState::Lobby(Lobby::ChoosingDifficulty {
+                    prompt_printed: *prompt_printed,
+                    selected: *selected,
+                    choice_sent: true,
+                }));
+            }
+
             let trimmed = input.trim();
             let level = match trimmed {
                 "0" => Some(0),
@@ -146,12 +179,14 @@ pub fn handle(
             if let Some(level) = level {
                 let msg = ClientMessage::SetDifficulty(level);
                 let payload =
-                    encode_to_vec(&msg, standard()).expect("failed to serialize SetDifficulty");
+                    encode_to_vec(&msg, config()).expect("failed to serialize SetDifficulty");
                 network.send_message(AppChannel::ReliableOrdered, payload);
+                ui.show_message(&selected_message(level));
 
                 return Some(ClientState::Lobby(Lobby::ChoosingDifficulty {
                     prompt_printed: *prompt_printed,
-                    choice_sent: true,
+                    selected: Some(level),
+                    choice_sent: false,
                 }));
             }
         } else {
@@ -191,6 +226,7 @@ mod tests {
         let mut session = ClientSession::new(0);
         session.transition(ClientState::Lobby(Lobby::ChoosingDifficulty {
             prompt_printed: false,
+            selected: None,
             choice_sent: false,
         }));
         let mut ui = MockUi::default();
@@ -216,6 +252,7 @@ mod tests {
         let mut session = ClientSession::new(0);
         session.transition(ClientState::Lobby(Lobby::ChoosingDifficulty {
             prompt_printed: true,
+            selected: Some(3),
             choice_sent: true,
         }));
 
@@ -241,6 +278,7 @@ mod tests {
                 _next_state,
                 Some(ClientState::Lobby(Lobby::ChoosingDifficulty {
                     prompt_printed: false,
+                    selected: None,
                     choice_sent: false
                 }))
             ),
@@ -259,6 +297,7 @@ mod tests {
         let mut session = ClientSession::new(0);
         session.transition(ClientState::Lobby(Lobby::ChoosingDifficulty {
             prompt_printed: true,
+            selected: None,
             choice_sent: false,
         }));
 
@@ -281,11 +320,12 @@ mod tests {
             matches!(
                 _next_state,
                 Some(ClientState::Lobby(Lobby::ChoosingDifficulty {
-                    choice_sent: true,
+                    selected: Some(2),
+                    choice_sent: false,
                     ..
                 }))
             ),
-            "choice should be marked as sent after pressing a key"
+            "choice should be selected but not confirmed after pressing a key"
         );
 
         let (channel, payload) = network
@@ -294,15 +334,56 @@ mod tests {
             .expect("expected difficulty choice to be sent");
         assert_eq!(channel, AppChannel::ReliableOrdered);
         let (msg, _) =
-            decode_from_slice::<ClientMessage, _>(&payload, standard()).expect("decode message");
+            decode_from_slice::<ClientMessage, _>(&payload, config()).expect("decode message");
         assert_eq!(msg, ClientMessage::SetDifficulty(2));
     }
 
+    #[test]
+    fn enter_confirms_only_a_selected_choice() {
+        let mut session = ClientSession::new(0);
+        let mut ui = MockUi::default();
+        let mut network = MockNetwork::new();
+
+        let mut lobby_state = Lobby::ChoosingDifficulty {
+            prompt_printed: true,
+            selected: None,
+            choice_sent: false,
+        };
+        ui.keys.push_back(Ok(Some(UiKey::Enter)));
+        let next_state = handle(&mut lobby_state, &mut session, &mut ui, &mut network, None);
+        assert!(next_state.is_none());
+        assert!(network.sent_messages.is_empty());
+
+        let mut lobby_state = Lobby::ChoosingDifficulty {
+            prompt_printed: true,
+            selected: Some(5),
+            choice_sent: false,
+        };
+        ui.keys.push_back(Ok(Some(UiKey::Enter)));
+        let next_state = handle(&mut lobby_state, &mut session, &mut ui, &mut network, None);
+        assert!(matches!(
+            next_state,
+            Some(ClientState::Lobby(Lobby::ChoosingDifficulty {
+                selected: Some(5),
+                choice_sent: true,
+                ..
+            }))
+        ));
+        let (_, payload) = network
+            .sent_messages
+            .pop_front()
+            .expect("expected confirmation to be sent");
+        let (msg, _) =
+            decode_from_slice::<ClientMessage, _>(&payload, config()).expect("decode message");
+        assert_eq!(msg, ClientMessage::ConfirmDifficulty);
+    }
+
     #[test]
     fn returns_disconnect_on_input_source_drop() {
         let mut session = ClientSession::new(0);
         session.transition(ClientState::Lobby(Lobby::ChoosingDifficulty {
             prompt_printed: true,
+            selected: None,
             choice_sent: false,
         }));
 
@@ -4,7 +4,6 @@ use crate::{
     session::ClientSession,
     state::{ClientState, Lobby},
 };
-use common::auth::MAX_ATTEMPTS;
 
 pub fn handle(
     lobby_state: &mut Lobby,
@@ -30,7 +29,10 @@ pub fn handle(
                     ),
                 );
 
-                ui.show_sanitized_prompt(&passcode_prompt(MAX_ATTEMPTS));
+                ui.show_sanitized_prompt(&passcode_prompt(
+                    session.max_auth_attempts,
+                    session.max_auth_attempts,
+                ));
 
                 *prompt_printed = true;
                 return None;
@@ -39,7 +41,10 @@ pub fn handle(
     }
 
     if !*prompt_printed {
-        ui.show_prompt(&passcode_prompt(MAX_ATTEMPTS));
+        ui.show_prompt(&passcode_prompt(
+            session.max_auth_attempts,
+            session.max_auth_attempts,
+        ));
         *prompt_printed = true;
         return None;
     }
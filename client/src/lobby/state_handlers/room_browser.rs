@@ -0,0 +1,197 @@
+use bincode::{serde::decode_from_slice, serde::encode_to_vec};
+
+use crate::{
+    lobby::ui::{LobbyUi, UiErrorKind},
+    net::NetworkHandle,
+    session::ClientSession,
+    state::{ClientState, Lobby},
+};
+use common::{
+    net::AppChannel,
+    protocol::{ClientMessage, RoomSummary, ServerMessage, config},
+};
+
+const ROOM_PROMPT: &str = "Enter a room code to join, or press Enter to refresh: ";
+
+pub fn handle(
+    lobby_state: &mut Lobby,
+    session: &mut ClientSession,
+    ui: &mut dyn LobbyUi,
+    network: &mut dyn NetworkHandle,
+) -> Option<ClientState> {
+    let Lobby::BrowsingRooms {
+        list_requested,
+        prompt_printed,
+    } = lobby_state
+    else {
+        unreachable!();
+    };
+
+    if network.is_disconnected() {
+        return Some(ClientState::Disconnected {
+            message: format!("connection failed: {}", network.get_disconnect_reason()),
+        });
+    }
+
+    if !*list_requested {
+        if network.is_connected() {
+            request_room_list(network);
+            *list_requested = true;
+        }
+        return None;
+    }
+
+    while let Some(data) = network.receive_message(AppChannel::ReliableOrdered) {
+        match decode_from_slice::<ServerMessage, _>(&data, config()) {
+            Ok((ServerMessage::RoomList { rooms }, _)) => {
+                show_room_list(ui, &rooms);
+                ui.show_prompt(ROOM_PROMPT);
+                *prompt_printed = true;
+            }
+            Ok((ServerMessage::ServerInfo { message }, _)) => {
+                return Some(ClientState::Disconnected { message });
+            }
+            Ok((ServerMessage::DisconnectReason(reason), _)) => {
+                return Some(session.disconnect_for(reason));
+            }
+            Ok((_, _)) => {}
+            Err(e) => {
+                ui.show_typed_error(
+                    UiErrorKind::Deserialization,
+                    &format!("[DESERIALIZATION ERROR: {}]", e),
+                );
+            }
+        }
+    }
+
+    if *prompt_printed && let Some(input_string) = session.take_input() {
+        let code = input_string.trim().to_uppercase();
+        if code.is_empty() {
+            request_room_list(network);
+            *prompt_printed = false;
+            return None;
+        }
+
+        session.room_code = Some(code);
+        return Some(ClientState::Lobby(Lobby::Passcode {
+            prompt_printed: false,
+        }));
+    }
+
+    None
+}
+
+fn request_room_list(network: &mut dyn NetworkHandle) {
+    let payload =
+        encode_to_vec(&ClientMessage::ListRooms, config()).expect("failed to serialize ListRooms");
+    network.send_message(AppChannel::ReliableOrdered, payload);
+}
+
+fn show_room_list(ui: &mut dyn LobbyUi, rooms: &[RoomSummary]) {
+    if rooms.is_empty() {
+        ui.show_message("No rooms are open on this server.");
+        return;
+    }
+
+    ui.show_message("Rooms:");
+    for room in rooms {
+        let lock = if room.passcode_protected {
+            ", passcode"
+        } else {
+            ""
+        };
+        ui.show_sanitized_message(&format!(
+            "  {}  {} player(s), {}{}",
+            room.code, room.players, room.state, lock
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{MockNetwork, MockUi};
+    use common::protocol::{DisconnectReason, SERVER_SHUTTING_DOWN_MESSAGE};
+
+    fn run(
+        session: &mut ClientSession,
+        ui: &mut MockUi,
+        network: &mut MockNetwork,
+    ) -> Option<ClientState> {
+        let mut temp_state = std::mem::take(&mut session.state);
+        let result = if let ClientState::Lobby(lobby_state) = &mut temp_state {
+            handle(lobby_state, session, ui, network)
+        } else {
+            panic!("expected Lobby state");
+        };
+        session.state = temp_state;
+        result
+    }
+
+    #[test]
+    fn lists_rooms_then_joins_chosen_code() {
+        let mut session = ClientSession::new(0);
+        session.transition(ClientState::Lobby(Lobby::BrowsingRooms {
+            list_requested: false,
+            prompt_printed: false,
+        }));
+        let mut ui = MockUi::default();
+        let mut network = MockNetwork::new();
+        network.set_connected(true);
+
+        assert!(run(&mut session, &mut ui, &mut network).is_none());
+        let (_, request) = network.sent_messages.pop_front().expect("expected request");
+        assert!(matches!(
+            decode_from_slice::<ClientMessage, _>(&request, config())
+                .unwrap()
+                .0,
+            ClientMessage::ListRooms
+        ));
+
+        network.queue_server_message(ServerMessage::RoomList {
+            rooms: vec![RoomSummary {
+                code: "ABCD".to_string(),
+                players: 2,
+                state: "Lobby".to_string(),
+                passcode_protected: true,
+            }],
+        });
+        assert!(run(&mut session, &mut ui, &mut network).is_none());
+        assert!(ui.messages.iter().any(|line| line.contains("ABCD")));
+        assert_eq!(ui.prompts, vec![ROOM_PROMPT.to_string()]);
+
+        session.add_input("abcd".to_string());
+        let next_state = run(&mut session, &mut ui, &mut network);
+        assert!(matches!(
+            next_state,
+            Some(ClientState::Lobby(Lobby::Passcode { .. }))
+        ));
+        assert_eq!(session.room_code.as_deref(), Some("ABCD"));
+    }
+
+    #[test]
+    fn shutdown_notice_disconnects_while_browsing() {
+        let mut session = ClientSession::new(0);
+        session.transition(ClientState::Lobby(Lobby::BrowsingRooms {
+            list_requested: true,
+            prompt_printed: true,
+        }));
+        let mut ui = MockUi::default();
+        let mut network = MockNetwork::new();
+        network.set_connected(true);
+        network.queue_server_message(ServerMessage::DisconnectReason(
+            DisconnectReason::ShuttingDown,
+        ));
+
+        let next_state = run(&mut session, &mut ui, &mut network);
+
+        assert!(matches!(
+            next_state,
+            Some(ClientState::Disconnected { message }) if message == SERVER_SHUTTING_DOWN_MESSAGE
+        ));
+        assert_eq!(
+            session.disconnect_reason,
+            Some(DisconnectReason::ShuttingDown)
+        );
+    }
+}
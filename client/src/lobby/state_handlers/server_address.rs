@@ -1,4 +1,4 @@
-use std::net::{IpAddr, SocketAddr};
+use std::net::SocketAddr;
 
 use crate::{
     lobby::ui::LobbyUi,
@@ -6,6 +6,8 @@ use crate::{
     state::{ClientState, Lobby},
 };
 
+const BROWSE_ROOMS: &str = "?";
+
 pub fn handle(
     lobby_state: &mut Lobby,
     session: &mut ClientSession,
@@ -15,7 +17,7 @@ pub fn handle(
         unreachable!();
     };
 
-    let default_server_connectable_addr = crate::env::default_server_address();
+    let default_server_connectable_addr = session.default_server_addr;
 
     if let Ok(Some(common::input::UiKey::Tab)) = ui.poll_single_key() {
         let localhost_addr = SocketAddr::new(
@@ -30,10 +32,18 @@ pub fn handle(
     }
 
     if let Some(input_string) = session.take_input() {
-        match parse_server_address(&input_string, default_server_connectable_addr) {
+        let (address, room_code) = split_room_code(&input_string);
+        match parse_server_address(address, default_server_connectable_addr) {
             Ok(parsed_server_addr) => {
                 session.input_queue.clear();
                 session.server_addr = Some(parsed_server_addr);
+                if room_code.as_deref() == Some(BROWSE_ROOMS) {
+                    return Some(ClientState::Lobby(Lobby::BrowsingRooms {
+                        list_requested: false,
+                        prompt_printed: false,
+                    }));
+                }
+                session.room_code = room_code;
                 return Some(ClientState::Lobby(Lobby::Passcode {
                     prompt_printed: false,
                 }));
@@ -54,9 +64,14 @@ pub fn handle(
         ui.show_warning("  WASD to move.");
         ui.show_warning("  Arrow keys to turn.");
         ui.show_warning("  Space to fire.");
+        ui.show_warning("  E to jump.");
+        ui.show_warning("  C to crouch or stand.");
+        ui.show_warning("  When spectating, E and C to fly up and down.");
+        ui.show_warning("  When spectating, [ and ] to follow other players.");
         ui.show_warning("  Left shift for sniper mode.");
         ui.show_message(" ");
         ui.show_warning("  Escape to quit/exit.");
+        ui.show_warning("  Ctrl + and Ctrl - to resize chat text.");
         ui.show_message(" ");
         ui.show_prompt(&server_address_prompt());
         *prompt_printed = true;
@@ -68,10 +83,20 @@ pub fn handle(
 
 fn server_address_prompt() -> String {
     format!(
-        "Press Enter to connect to the default server (recommended),\nor Tab for localhost (testing),\nor pick another server (ip[:port]): ",
+        "Press Enter to connect to the default server (recommended),\nor Tab for localhost (testing),\nor pick another server (ip[:port][/room]),\nor add /? to browse its rooms: ",
     )
 }
 
+fn split_room_code(input: &str) -> (&str, Option<String>) {
+    match input.trim().split_once('/') {
+        Some((address, code)) if !code.trim().is_empty() => {
+            (address, Some(code.trim().to_uppercase()))
+        }
+        Some((address, _)) => (address, None),
+        None => (input, None),
+    }
+}
+
 fn parse_server_address(
     input: &str,
     default_server_connectable_addr: SocketAddr,
@@ -81,21 +106,15 @@ fn parse_server_address(
         return Ok(default_server_connectable_addr);
     }
 
-    if let Ok(addr) = trimmed.parse::<SocketAddr>() {
-        return Ok(addr);
-    }
-
-    if let Ok(ip) = trimmed.parse::<IpAddr>() {
-        return Ok(SocketAddr::new(ip, default_server_connectable_addr.port()));
-    }
-
-    Err(format!(
-        "Invalid address. Press Enter, or Tab, or choose an IP like 192.168.0.10:5000.",
-    ))
+    common::net::parse_socket_addr(trimmed, default_server_connectable_addr.port()).map_err(|_| {
+        "Invalid address. Press Enter, or Tab, or choose an IP like 192.168.0.10:5000.".to_string()
+    })
 }
 
 #[cfg(test)]
 mod tests {
+    use std::net::IpAddr;
+
     use super::*;
     use crate::test_helpers::MockUi;
 
@@ -132,6 +151,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn splits_room_code_from_address() {
+        assert_eq!(
+            split_room_code("192.168.1.50:6000/abcd"),
+            ("192.168.1.50:6000", Some("ABCD".to_string()))
+        );
+        assert_eq!(split_room_code("/WXYZ"), ("", Some("WXYZ".to_string())));
+        assert_eq!(split_room_code("192.168.1.50"), ("192.168.1.50", None));
+    }
+
     #[test]
     fn invalid_input_reprompts() {
         let mut session = ClientSession::new(0);
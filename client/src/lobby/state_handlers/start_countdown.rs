@@ -1,6 +1,6 @@
 use crate::{
     assets::Assets,
-    game::world::maze,
+    game::world::maze::{self, FloorTheme},
     game::world::sky,
     info,
     state::{ClientState, Lobby},
@@ -11,6 +11,8 @@ pub fn handle_countdown_started(
     end_time: f64,
     game_data: InitialData,
     assets: Option<&Assets>,
+    floor_theme: FloorTheme,
+    shadows: bool,
 ) -> ClientState {
     let assets = assets.expect("assets required for countdown but none provided");
     let (wall_texture, sky_texture) = match game_data.difficulty {
@@ -74,7 +76,13 @@ pub fn handle_countdown_started(
     let sky_colors = sky::sky_colors(game_data.difficulty);
     let sky_mesh = sky::generate_sky(sky_texture, sky_colors);
 
-    let maze_meshes = maze::build_maze_meshes(&game_data.maze, wall_texture, game_data.difficulty);
+    let maze_meshes = maze::build_maze_meshes(
+        &game_data.maze,
+        wall_texture,
+        game_data.difficulty,
+        floor_theme,
+        shadows,
+    );
     let map_overlay = info::map::initialize_map(&game_data.maze, &assets.map_font);
 
     ClientState::Lobby(Lobby::Countdown {
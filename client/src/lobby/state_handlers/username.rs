@@ -1,7 +1,4 @@
-use bincode::{
-    config::standard,
-    serde::{decode_from_slice, encode_to_vec},
-};
+use bincode::serde::{decode_from_slice, encode_to_vec};
 
 use crate::{
     lobby::ui::{LobbyUi, UiErrorKind},
@@ -11,7 +8,7 @@ use crate::{
 };
 use common::{
     net::AppChannel,
-    protocol::{ClientMessage, ServerMessage},
+    protocol::{ClientMessage, ServerMessage, config},
 };
 
 pub fn handle(
@@ -25,7 +22,7 @@ pub fn handle(
     };
 
     while let Some(data) = network.receive_message(AppChannel::ReliableOrdered) {
-        match decode_from_slice::<ServerMessage, _>(&data, standard()) {
+        match decode_from_slice::<ServerMessage, _>(&data, config()) {
             Ok((msg, _)) => {
                 if let Some(next) = handle_server_message(session, ui, &msg) {
                     return Some(next);
@@ -53,12 +50,12 @@ pub fn handle(
             }));
         }
 
-        let validation = validate_username_input(&input);
+        let validation = validate_username_input(&input, session.max_username_length);
         match validation {
             Ok(username) => {
                 let message = ClientMessage::SetUsername(username);
                 let payload =
-                    encode_to_vec(&message, standard()).expect("failed to serialize SetUsername");
+                    encode_to_vec(&message, config()).expect("failed to serialize SetUsername");
 
                 network.send_message(AppChannel::ReliableOrdered, payload);
 
@@ -187,6 +184,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn enforces_server_username_limit() {
+        let mut session = ClientSession::new(0);
+        set_choosing_username(&mut session);
+        session.max_username_length = 8;
+        session.add_input("ninechars".to_string());
+
+        let mut ui = MockUi::default();
+        let mut network = MockNetwork::new();
+
+        let mut temp_state = std::mem::take(&mut session.state);
+        if let ClientState::Lobby(lobby_state) = &mut temp_state {
+            handle(lobby_state, &mut session, &mut ui, &mut network);
+        }
+        session.state = temp_state;
+
+        assert!(network.sent_messages.is_empty());
+        assert_eq!(
+            ui.error_kinds,
+            vec![UiErrorKind::UsernameValidation(UsernameError::TooLong)]
+        );
+    }
+
     #[test]
     fn handles_local_validation_error() {
         let mut session = ClientSession::new(0);
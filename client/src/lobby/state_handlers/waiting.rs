@@ -1,4 +1,4 @@
-use bincode::{config::standard, serde::decode_from_slice};
+use bincode::serde::decode_from_slice;
 
 use crate::{
     lobby::ui::{LobbyUi, UiErrorKind},
@@ -7,21 +7,43 @@ use crate::{
     state::{ClientState, Lobby},
 };
 use common::{
+    chat::MAX_CHAT_MESSAGE_BYTES,
     net::AppChannel,
-    protocol::{GAME_ALREADY_STARTED_MESSAGE, ServerMessage},
+    protocol::{GAME_ALREADY_STARTED_MESSAGE, ServerMessage, config},
+    team::team_name,
 };
 
 pub fn handle(
     _lobby_state: &mut Lobby,
-    _session: &mut ClientSession,
+    session: &mut ClientSession,
     ui: &mut dyn LobbyUi,
     network: &mut dyn NetworkHandle,
 ) -> Option<ClientState> {
     while let Some(data) = network.receive_message(AppChannel::ReliableOrdered) {
-        match decode_from_slice::<ServerMessage, _>(&data, standard()) {
-            Ok((ServerMessage::Welcome { username, color }, _)) => {
+        match decode_from_slice::<ServerMessage, _>(&data, config()) {
+            Ok((
+                ServerMessage::Welcome {
+                    username,
+                    color,
+                    team,
+                    available_colors,
+                    max_chat_bytes,
+                },
+                _,
+            )) => {
+                session.max_chat_bytes = max_chat_bytes.min(MAX_CHAT_MESSAGE_BYTES);
                 ui.set_local_player_color(color);
                 ui.show_sanitized_message(&format!("Server: Welcome, {}!", username));
+                if let Some(team) = team {
+                    ui.show_sanitized_message(&format!("Server: You are on {}.", team_name(team)));
+                }
+                if available_colors.len() > 1 {
+                    return Some(ClientState::Lobby(Lobby::ChoosingColor {
+                        current: color,
+                        available_colors,
+                        prompt_printed: false,
+                    }));
+                }
                 return Some(ClientState::Lobby(Lobby::Chat {
                     awaiting_initial_roster: true,
                     waiting_for_server: false,
@@ -39,6 +61,9 @@ pub fn handle(
                     prompt_printed: false,
                 }));
             }
+            Ok((ServerMessage::DisconnectReason(reason), _)) => {
+                return Some(session.disconnect_for(reason));
+            }
             Ok((ServerMessage::ServerInfo { message }, _)) => {
                 if message != GAME_ALREADY_STARTED_MESSAGE {
                     ui.show_sanitized_message(&format!("Server: {}", message));
@@ -70,9 +95,12 @@ pub fn handle(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::test_helpers::{MockNetwork, MockUi};
+    use crate::{
+        lobby::{flow, state_handlers::chat},
+        test_helpers::{MockNetwork, MockUi},
+    };
     use common::player::Color;
-    use common::protocol::{GAME_ALREADY_STARTED_MESSAGE, ServerMessage};
+    use common::protocol::{ClientMessage, GAME_ALREADY_STARTED_MESSAGE, ServerMessage};
 
     fn set_awaiting_state(session: &mut ClientSession) {
         session.transition(ClientState::Lobby(Lobby::AwaitingUsernameConfirmation));
@@ -88,6 +116,9 @@ mod tests {
         network.queue_server_message(ServerMessage::Welcome {
             username: "TestUser".to_string(),
             color: Color::RED,
+            team: None,
+            available_colors: vec![Color::RED],
+            max_chat_bytes: MAX_CHAT_MESSAGE_BYTES,
         });
 
         let _next_state = {
@@ -112,6 +143,42 @@ mod tests {
         assert_eq!(ui.messages[0], "Server: Welcome, TestUser!");
     }
 
+    #[test]
+    fn chat_input_respects_a_smaller_server_limit() {
+        let mut session = ClientSession::new(0);
+        set_awaiting_state(&mut session);
+
+        let mut ui = MockUi::default();
+        let mut network = MockNetwork::new();
+        network.queue_server_message(ServerMessage::Welcome {
+            username: "TestUser".to_string(),
+            color: Color::RED,
+            team: None,
+            available_colors: vec![Color::RED],
+            max_chat_bytes: 10,
+        });
+
+        let mut temp_state = std::mem::take(&mut session.state);
+        let next_state = if let ClientState::Lobby(lobby_state) = &mut temp_state {
+            handle(lobby_state, &mut session, &mut ui, &mut network)
+        } else {
+            panic!("expected Lobby state");
+        };
+        session.transition(next_state.expect("expected to move on to chat"));
+
+        ui.inputs
+            .push_back(Ok(Some("a".repeat(MAX_CHAT_MESSAGE_BYTES))));
+        assert!(flow::poll_input(&mut session, &mut ui, false).is_none());
+        let mut temp_state = std::mem::take(&mut session.state);
+        if let ClientState::Lobby(lobby_state) = &mut temp_state {
+            chat::handle(lobby_state, &mut session, &mut ui, &mut network, None);
+        }
+
+        let (_, payload) = network.sent_messages.pop_front().unwrap();
+        let (message, _) = decode_from_slice::<ClientMessage, _>(&payload, config()).unwrap();
+        assert!(matches!(message, ClientMessage::SendChat(content) if content == "a".repeat(10)));
+    }
+
     #[test]
     fn handles_username_error() {
         let mut session = ClientSession::new(0);
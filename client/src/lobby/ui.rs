@@ -1,4 +1,6 @@
 pub mod gui;
+pub mod mentions;
+pub mod shortcodes;
 
 use std::{fmt, net::SocketAddr};
 
@@ -9,6 +11,7 @@ use common::{
     player::{Color, UsernameError},
 };
 pub use gui::Gui;
+use shortcodes::expand_shortcodes;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum UiErrorKind {
@@ -52,7 +55,7 @@ pub trait LobbyUi {
     }
 
     fn show_sanitized_message(&mut self, message: &str) {
-        self.show_message(&sanitize(message));
+        self.show_message(&expand_shortcodes(&sanitize(message)));
     }
 
     fn show_sanitized_error(&mut self, message: &str) {
@@ -72,10 +75,13 @@ pub trait LobbyUi {
     }
 
     fn show_sanitized_message_with_color(&mut self, message: &str, color: Color) {
-        self.show_message_with_color(&sanitize(message), color);
+        self.show_message_with_color(&expand_shortcodes(&sanitize(message)), color);
     }
 
     fn set_local_player_color(&mut self, _color: Color) {}
+    fn set_status_line(&mut self, _status: &str) {}
+    fn set_mention_candidates(&mut self, _usernames: &[String]) {}
+    fn clear_history(&mut self) {}
 
     fn show_typed_error(&mut self, _kind: UiErrorKind, message: &str) {
         self.show_sanitized_error(message);
@@ -5,13 +5,17 @@ use std::{
 
 use macroquad::prelude::*;
 
-use crate::lobby::ui::{LobbyUi, UiInputError};
+use crate::lobby::ui::{LobbyUi, UiInputError, mentions::MentionCompleter};
 use common::{input::UiKey, player::Color as PlayerColor};
 
 const PROMPT: &str = "> ";
-const FONT_SIZE: f32 = 24.0;
+pub const DEFAULT_FONT_SIZE: f32 = 24.0;
+const MIN_FONT_SIZE: f32 = 12.0;
+const MAX_FONT_SIZE: f32 = 48.0;
+const FONT_SIZE_STEP: f32 = 2.0;
 const SIDE_PAD: f32 = 20.0;
 const BOTTOM_PAD: f32 = 40.0;
+const TOP_PAD: f32 = 30.0;
 
 const TEXT_COLOR: Color = WHITE;
 const WARNING_COLOR: Color = YELLOW;
@@ -19,9 +23,23 @@ const ERROR_COLOR: Color = RED;
 const PROMPT_COLOR: Color = LIGHTGRAY;
 const INPUT_COLOR: Color = LIGHTGRAY;
 const BANNER_COLOR: Color = YELLOW;
+const STATUS_COLOR: Color = GRAY;
 const BACKGROUND_COLOR: Color = BLACK;
 const BANNER_COLUMN_GAP: f32 = 12.0;
 
+pub fn font_size_from_args(args: impl IntoIterator<Item = String>) -> f32 {
+    let mut font_size = DEFAULT_FONT_SIZE;
+    for arg in args {
+        if let Some(value) = arg.strip_prefix("--font-size=") {
+            match value.parse::<f32>() {
+                Ok(parsed) if parsed.is_finite() => font_size = parsed,
+                _ => eprintln!("invalid font size '{}'; ignoring", value),
+            }
+        }
+    }
+    font_size
+}
+
 fn player_color_to_text_color(color: PlayerColor) -> Color {
     match color {
         PlayerColor::RED => RED,
@@ -48,9 +66,13 @@ pub struct Gui {
     left_arrow_last_pressed: Option<Instant>,
     backspace_last_pressed: Option<Instant>,
     local_player_color: Option<PlayerColor>,
+    status_line: Option<String>,
     scroll_offset: usize,
     up_arrow_last_pressed: Option<Instant>,
     down_arrow_last_pressed: Option<Instant>,
+    mention_candidates: Vec<String>,
+    mentions: MentionCompleter,
+    font_size: f32,
 }
 
 impl Gui {
@@ -64,12 +86,26 @@ impl Gui {
             left_arrow_last_pressed: None,
             backspace_last_pressed: None,
             local_player_color: None,
+            status_line: None,
             scroll_offset: 0,
             up_arrow_last_pressed: None,
             down_arrow_last_pressed: None,
+            mention_candidates: Vec::new(),
+            mentions: MentionCompleter::default(),
+            font_size: DEFAULT_FONT_SIZE,
         }
     }
 
+    pub fn with_font_size(mut self, font_size: f32) -> Self {
+        self.font_size = font_size.clamp(MIN_FONT_SIZE, MAX_FONT_SIZE);
+        self
+    }
+
+    fn adjust_font_size(&mut self, steps: f32) {
+        self.font_size =
+            (self.font_size + steps * FONT_SIZE_STEP).clamp(MIN_FONT_SIZE, MAX_FONT_SIZE);
+    }
+
     fn add_history(&mut self, message: &str, color: Color) {
         self.message_history.push((message.to_string(), color));
 
@@ -87,7 +123,7 @@ impl Gui {
 
         clear_background(BACKGROUND_COLOR);
 
-        let line_height = FONT_SIZE * 1.2;
+        let line_height = self.font_size * 1.2;
         let max_width = screen_width() - 2.0 * SIDE_PAD; // of a line of text.
 
         // Start at the bottom,
@@ -103,10 +139,34 @@ impl Gui {
             );
         } // and move the current_baseline to the line above the input.
         self.draw_chat_history(current_baseline, line_height, max_width, font);
+        self.draw_status_line(font);
 
         pop_camera_state();
     }
 
+    fn draw_status_line(&self, font: Option<&Font>) {
+        let Some(status) = &self.status_line else {
+            return;
+        };
+        let width = self.measure_text_strict(status, font);
+        let x = screen_width() - SIDE_PAD - width;
+        if let Some(font) = font {
+            draw_text_ex(
+                status,
+                x,
+                TOP_PAD,
+                TextParams {
+                    font: Some(font),
+                    font_size: self.font_size as u16,
+                    color: STATUS_COLOR,
+                    ..Default::default()
+                },
+            );
+        } else {
+            draw_text(status, x, TOP_PAD, self.font_size, STATUS_COLOR);
+        }
+    }
+
     fn draw_input(
         &self,
         current_baseline: &mut f32,
@@ -133,13 +193,13 @@ impl Gui {
                     draw_y,
                     TextParams {
                         font: Some(font),
-                        font_size: FONT_SIZE as u16,
+                        font_size: self.font_size as u16,
                         color: input_color,
                         ..Default::default()
                     },
                 );
             } else {
-                draw_text(line, SIDE_PAD, draw_y, FONT_SIZE, input_color);
+                draw_text(line, SIDE_PAD, draw_y, self.font_size, input_color);
             }
 
             draw_y += line_height;
@@ -207,9 +267,9 @@ impl Gui {
 
         draw_rectangle(
             cursor_x,
-            cursor_y - FONT_SIZE + 5.0,
+            cursor_y - self.font_size + 5.0,
             2.0,
-            FONT_SIZE,
+            self.font_size,
             input_color,
         );
     }
@@ -252,13 +312,13 @@ impl Gui {
                                 current_baseline,
                                 TextParams {
                                     font: Some(font),
-                                    font_size: FONT_SIZE as u16,
+                                    font_size: self.font_size as u16,
                                     color: *color,
                                     ..Default::default()
                                 },
                             );
                         } else {
-                            draw_text(label, SIDE_PAD, current_baseline, FONT_SIZE, *color);
+                            draw_text(label, SIDE_PAD, current_baseline, self.font_size, *color);
                         }
                     }
 
@@ -274,13 +334,13 @@ impl Gui {
                             current_baseline,
                             TextParams {
                                 font: Some(font),
-                                font_size: FONT_SIZE as u16,
+                                font_size: self.font_size as u16,
                                 color: *color,
                                 ..Default::default()
                             },
                         );
                     } else {
-                        draw_text(line, value_x, current_baseline, FONT_SIZE, *color);
+                        draw_text(line, value_x, current_baseline, self.font_size, *color);
                     }
                     current_baseline -= line_height;
                 }
@@ -299,13 +359,13 @@ impl Gui {
                         current_baseline,
                         TextParams {
                             font: Some(font),
-                            font_size: FONT_SIZE as u16,
+                            font_size: self.font_size as u16,
                             color: *color,
                             ..Default::default()
                         },
                     );
                 } else {
-                    draw_text(line, SIDE_PAD, current_baseline, FONT_SIZE, *color);
+                    draw_text(line, SIDE_PAD, current_baseline, self.font_size, *color);
                 }
                 current_baseline -= line_height;
             }
@@ -314,7 +374,7 @@ impl Gui {
 
     // Measure text width, forcing the inclusion of trailing spaces.
     fn measure_text_strict(&self, text: &str, font: Option<&Font>) -> f32 {
-        let font_size = FONT_SIZE as u16;
+        let font_size = self.font_size as u16;
         let line_spacing = 1.0;
 
         if text.ends_with(' ') {
@@ -330,93 +390,97 @@ impl Gui {
     }
 
     fn wrap_text(&self, text: &str, max_width: f32, font: Option<&Font>) -> Vec<String> {
-        let mut wrapped_lines = Vec::new();
+        wrap_text(text, max_width, |text| self.measure_text_strict(text, font))
+    }
 
-        for line in text.lines() {
-            if line.is_empty() {
-                wrapped_lines.push(String::new());
-                continue;
-            }
+    fn delete_previous_char(&mut self) {
+        let byte_index = self
+            .input_buffer
+            .char_indices()
+            .map(|(i, _)| i)
+            .nth(self.cursor_pos)
+            .unwrap_or(self.input_buffer.len());
 
-            // This will become a row of text as it appears on screen.
-            let mut current_line = String::new();
+        self.input_buffer.remove(byte_index);
+    }
+}
 
-            let parts: Vec<&str> = line.split(' ').collect();
+fn wrap_text(text: &str, max_width: f32, measure: impl Fn(&str) -> f32) -> Vec<String> {
+    let mut wrapped_lines = Vec::new();
 
-            for (i, part) in parts.iter().enumerate() {
-                // Define 'word' (the chunk we are trying to fit).
-                // If i > 0, this part was preceded by a space, so we include it.
-                let word = if i == 0 {
-                    part.to_string()
-                } else {
-                    format!(" {}", part)
-                };
+    for line in text.lines() {
+        if line.is_empty() {
+            wrapped_lines.push(String::new());
+            continue;
+        }
 
-                let line_with_word = format!("{}{}", current_line, word);
-                let line_with_word_width = self.measure_text_strict(&line_with_word, font);
+        // This will become a row of text as it appears on screen.
+        let mut current_line = String::new();
 
-                // Case 1: Word fits on the current line.
-                if line_with_word_width <= max_width {
-                    current_line = line_with_word;
-                    continue;
-                }
+        let parts: Vec<&str> = line.split(' ').collect();
 
-                // Word doesn't fit: need to handle wrapping.
-                let word_width = self.measure_text_strict(&word, font);
-                let is_at_prompt_only = current_line.trim() == ">";
-                let word_fits_on_new_line = word_width <= max_width;
+        for (i, part) in parts.iter().enumerate() {
+            // Define 'word' (the chunk we are trying to fit).
+            // If i > 0, this part was preceded by a space, so we include it.
+            let word = if i == 0 {
+                part.to_string()
+            } else {
+                format!(" {}", part)
+            };
 
-                // Case 2: Standard wrap: word fits on a new line and is not
-                // the prompt, >, so add it to the current line.
-                if word_fits_on_new_line && !is_at_prompt_only {
-                    wrapped_lines.push(current_line);
-                    current_line = word.to_string();
-                }
-                // Case 3: Force-split: either word is too wide OR we're at the prompt.
-                else {
-                    // We enter this case when EITHER:
-                    // a) The word is wider than the entire screen width, OR
-                    // b) current_line is just the prompt (">") and we want to keep
-                    //    the next word attached to it rather than wrapping the word
-                    //    to a new line (which would leave ">" stranded alone).
-
-                    // We append characters to whatever is already in
-                    // current_line (which might be ">"), so the prompt stays
-                    // attached.
-                    for character in word.chars() {
-                        let line_with_char = format!("{}{}", current_line, character);
-                        let line_with_char_width = self.measure_text_strict(&line_with_char, font);
-
-                        if line_with_char_width > max_width {
-                            // Current line is now full, so push it and start fresh.
-                            wrapped_lines.push(current_line);
-                            current_line = character.to_string();
-                        } else {
-                            // Character fits, so keep building on `current_line`.
-                            current_line = line_with_char;
-                        }
-                    }
-                }
+            let line_with_word = format!("{}{}", current_line, word);
+            let line_with_word_width = measure(&line_with_word);
+
+            // Case 1: Word fits on the current line.
+            if line_with_word_width <= max_width {
+                current_line = line_with_word;
+                continue;
             }
 
-            if !current_line.is_empty() {
+            // Word doesn't fit: need to handle wrapping.
+            let word_width = measure(&word);
+            let is_at_prompt_only = current_line.trim() == ">";
+            let word_fits_on_new_line = word_width <= max_width;
+
+            // Case 2: Standard wrap: word fits on a new line and is not
+            // the prompt, >, so add it to the current line.
+            if word_fits_on_new_line && !is_at_prompt_only {
                 wrapped_lines.push(current_line);
+                current_line = word.to_string();
+            }
+            // Case 3: Force-split: either word is too wide OR we're at the prompt.
+            else {
+                // We enter this case when EITHER:
+                // a) The word is wider than the entire screen width, OR
+                // b) current_line is just the prompt (">") and we want to keep
+                //    the next word attached to it rather than wrapping the word
+                //    to a new line (which would leave ">" stranded alone).
+
+                // We append characters to whatever is already in
+                // current_line (which might be ">"), so the prompt stays
+                // attached.
+                for character in word.chars() {
+                    let line_with_char = format!("{}{}", current_line, character);
+                    let line_with_char_width = measure(&line_with_char);
+
+                    if line_with_char_width > max_width {
+                        // Current line is now full, so push it and start fresh.
+                        wrapped_lines.push(current_line);
+                        current_line = character.to_string();
+                    } else {
+                        // Character fits, so keep building on `current_line`.
+                        current_line = line_with_char;
+                    }
+                }
             }
         }
 
-        wrapped_lines
+        if !current_line.is_empty() {
+            wrapped_lines.push(current_line);
+        }
     }
 
-    fn delete_previous_char(&mut self) {
-        let byte_index = self
-            .input_buffer
-            .char_indices()
-            .map(|(i, _)| i)
-            .nth(self.cursor_pos)
-            .unwrap_or(self.input_buffer.len());
-
-        self.input_buffer.remove(byte_index);
-    }
+    wrapped_lines
 }
 
 impl LobbyUi for Gui {
@@ -433,6 +497,19 @@ impl LobbyUi for Gui {
         self.local_player_color = Some(color);
     }
 
+    fn set_status_line(&mut self, status: &str) {
+        self.status_line = Some(status.to_string());
+    }
+
+    fn set_mention_candidates(&mut self, usernames: &[String]) {
+        self.mention_candidates = usernames.to_vec();
+    }
+
+    fn clear_history(&mut self) {
+        self.message_history.clear();
+        self.scroll_offset = 0;
+    }
+
     fn show_error(&mut self, message: &str) {
         self.add_history(
             &format!("[ERROR] {}.", message.trim_end_matches('.')),
@@ -482,7 +559,30 @@ impl LobbyUi for Gui {
     }
 
     fn poll_input(&mut self, limit: usize, is_host: bool) -> Result<Option<String>, UiInputError> {
+        if is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl) {
+            let steps = if is_key_pressed(KeyCode::Equal) || is_key_pressed(KeyCode::KpAdd) {
+                1.0
+            } else if is_key_pressed(KeyCode::Minus) || is_key_pressed(KeyCode::KpSubtract) {
+                -1.0
+            } else {
+                0.0
+            };
+            if steps != 0.0 {
+                self.adjust_font_size(steps);
+                while get_char_pressed().is_some() {}
+                return Ok(None);
+            }
+        }
+
         if is_key_pressed(KeyCode::Tab) {
+            if let Some(completed) = self
+                .mentions
+                .complete(&self.input_buffer, &self.mention_candidates)
+            {
+                self.cursor_pos = completed.chars().count();
+                self.input_buffer = completed;
+                return Ok(None);
+            }
             if is_host {
                 self.input_buffer.clear();
                 self.cursor_pos = 0;
@@ -691,3 +791,49 @@ impl LobbyUi for Gui {
         pop_camera_state();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn measure_at(font_size: f32) -> impl Fn(&str) -> f32 {
+        move |text| text.chars().count() as f32 * font_size * 0.5
+    }
+
+    #[test]
+    fn wrapping_width_scales_with_font_size() {
+        let text = "the quick brown fox jumps over the lazy dog";
+        let max_width = 240.0;
+
+        let small = wrap_text(text, max_width, measure_at(DEFAULT_FONT_SIZE));
+        let large = wrap_text(text, max_width, measure_at(DEFAULT_FONT_SIZE * 2.0));
+
+        assert_eq!(
+            small,
+            vec!["the quick brown fox", " jumps over the lazy", " dog"]
+        );
+        assert!(large.len() > small.len());
+        for line in &large {
+            assert!(measure_at(DEFAULT_FONT_SIZE * 2.0)(line) <= max_width);
+        }
+    }
+
+    #[test]
+    fn font_size_is_adjusted_within_bounds() {
+        let mut gui = Gui::new();
+        gui.adjust_font_size(1.0);
+        assert_eq!(gui.font_size, DEFAULT_FONT_SIZE + FONT_SIZE_STEP);
+
+        gui.adjust_font_size(-100.0);
+        assert_eq!(gui.font_size, MIN_FONT_SIZE);
+        assert_eq!(Gui::new().with_font_size(100.0).font_size, MAX_FONT_SIZE);
+    }
+
+    #[test]
+    fn font_size_is_parsed_from_args() {
+        let args = ["--practice", "--font-size=32"].map(String::from);
+        assert_eq!(font_size_from_args(args), 32.0);
+        let args = ["--font-size=huge".to_string()];
+        assert_eq!(font_size_from_args(args), DEFAULT_FONT_SIZE);
+    }
+}
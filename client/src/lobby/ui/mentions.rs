@@ -0,0 +1,103 @@
+#[derive(Debug, Default)]
+pub struct MentionCompleter {
+    cycle: Option<Cycle>,
+}
+
+#[derive(Debug)]
+struct Cycle {
+    stem: String,
+    matches: Vec<String>,
+    index: usize,
+    completed: String,
+}
+
+impl MentionCompleter {
+    pub fn complete(&mut self, input: &str, roster: &[String]) -> Option<String> {
+        if let Some(cycle) = &mut self.cycle
+            && cycle.completed == input
+        {
+            cycle.index = (cycle.index + 1) % cycle.matches.len();
+            cycle.completed = format!("{}@{}", cycle.stem, cycle.matches[cycle.index]);
+            return Some(cycle.completed.clone());
+        }
+        self.cycle = None;
+
+        let at = input.rfind('@')?;
+        let partial = &input[at + 1..];
+        if partial.chars().any(char::is_whitespace) {
+            return None;
+        }
+
+        let partial = partial.to_lowercase();
+        let mut matches: Vec<String> = roster
+            .iter()
+            .filter(|name| name.to_lowercase().starts_with(&partial))
+            .cloned()
+            .collect();
+        if matches.is_empty() {
+            return Some(input.to_string());
+        }
+        matches.sort();
+        matches.dedup();
+
+        let stem = input[..at].to_string();
+        let completed = format!("{}@{}", stem, matches[0]);
+        self.cycle = Some(Cycle {
+            stem,
+            matches,
+            index: 0,
+            completed: completed.clone(),
+        });
+        Some(completed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roster() -> Vec<String> {
+        ["alice", "bob", "albert"]
+            .into_iter()
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn unique_prefix_completes_to_the_full_username() {
+        let mut completer = MentionCompleter::default();
+
+        assert_eq!(
+            completer.complete("hi @bo", &roster()),
+            Some("hi @bob".to_string())
+        );
+        assert_eq!(
+            completer.complete("hi @bob", &roster()),
+            Some("hi @bob".to_string())
+        );
+    }
+
+    #[test]
+    fn repeated_completion_cycles_through_matches() {
+        let mut completer = MentionCompleter::default();
+
+        let first = completer.complete("@Al", &roster());
+        assert_eq!(first, Some("@albert".to_string()));
+        let second = completer.complete("@albert", &roster());
+        assert_eq!(second, Some("@alice".to_string()));
+        let third = completer.complete("@alice", &roster());
+        assert_eq!(third, Some("@albert".to_string()));
+    }
+
+    #[test]
+    fn input_without_a_partial_mention_is_left_to_other_uses_of_tab() {
+        let mut completer = MentionCompleter::default();
+
+        assert_eq!(completer.complete("hello", &roster()), None);
+        assert_eq!(completer.complete("@bob thanks", &roster()), None);
+        assert_eq!(
+            completer.complete("@zed", &roster()),
+            Some("@zed".to_string())
+        );
+    }
+}
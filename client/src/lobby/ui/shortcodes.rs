@@ -0,0 +1,43 @@
+const SHORTCODES: [(&str, &str); 8] = [
+    (":heart:", "♥"),
+    (":star:", "★"),
+    (":skull:", "☠"),
+    (":sun:", "☀"),
+    (":note:", "♪"),
+    (":check:", "✓"),
+    (":)", "☺"),
+    (":(", "☹"),
+];
+
+pub fn expand_shortcodes(message: &str) -> String {
+    SHORTCODES
+        .iter()
+        .fold(message.to_string(), |text, (code, glyph)| {
+            text.replace(code, glyph)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lobby::ui::LobbyUi, test_helpers::MockUi};
+
+    #[test]
+    fn known_shortcodes_expand_to_glyphs() {
+        assert_eq!(expand_shortcodes("hi :) :heart:"), "hi ☺ ♥");
+        assert_eq!(expand_shortcodes(":star::star:"), "★★");
+    }
+
+    #[test]
+    fn unknown_shortcodes_are_left_intact() {
+        assert_eq!(expand_shortcodes("a :foo: b"), "a :foo: b");
+        assert_eq!(expand_shortcodes("heart: :heart"), "heart: :heart");
+    }
+
+    #[test]
+    fn expansion_keeps_control_characters_stripped() {
+        let mut ui = MockUi::new();
+        ui.show_sanitized_message("\x1b[31m:heart:\x07 hi");
+        assert_eq!(ui.messages, vec!["♥ hi".to_string()]);
+    }
+}
@@ -2,7 +2,13 @@ use macroquad::prelude::Conf;
 
 use client::{
     self,
-    lobby::ui::Gui,
+    assets::Assets,
+    env,
+    game::{
+        input, practice,
+        world::maze::{self, FloorTheme},
+    },
+    lobby::ui::{Gui, gui},
     run::{self, WINDOW_HEIGHT, WINDOW_WIDTH},
 };
 use common;
@@ -22,11 +28,36 @@ fn window_conf() -> Conf {
 
 #[macroquad::main(window_conf)]
 async fn main() {
-    let ui = Gui::new();
+    let floor_theme = FloorTheme::from_args(std::env::args());
+    let shadows = maze::shadows_from_args(std::env::args());
+    let server_addr =
+        env::server_address_from_args(std::env::args(), env::default_server_address());
+    let rotation_sensitivity = input::rotation_sensitivity_from_args(std::env::args());
+    let auto_center = input::auto_center_from_args(std::env::args());
+    if std::env::args().any(|arg| arg == "--practice") {
+        let assets = Assets::load().await;
+        practice::run(
+            &assets,
+            floor_theme,
+            shadows,
+            rotation_sensitivity,
+            auto_center,
+        )
+        .await;
+        return;
+    }
+
+    let ui = Gui::new().with_font_size(gui::font_size_from_args(std::env::args()));
     let private_key = common::auth::private_key();
 
-    run::run_client_loop(private_key, ui).await;
+    run::run_client_loop(
+        private_key,
+        ui,
+        floor_theme,
+        shadows,
+        rotation_sensitivity,
+        auto_center,
+        server_addr,
+    )
+    .await;
 }
-
-
-
@@ -1,5 +1,6 @@
 use std::{
-    net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket},
+    thread,
     time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
@@ -10,22 +11,24 @@ use renet_netcode::{ClientAuthentication, NetcodeClientTransport};
 use crate::{
     after_game_chat,
     assets::Assets,
-    game,
-    game::world::sky,
-    info,
+    frame, game,
+    game::world::{maze::FloorTheme, sky},
+    identity, info,
     lobby::{
         self,
-        ui::{Gui, LobbyUi},
+        ui::{Gui, LobbyUi, UiErrorKind},
     },
     net::{self, DisconnectKind, RenetNetworkHandle},
     session::{ClientSession, Clock},
     state::{ClientState, InputMode, Lobby},
 };
-use common::{self, constants::TICK_SECS};
+use common::{self, constants::TICK_SECS, player::RotationSensitivity, protocol::DisconnectReason};
 
 pub const WINDOW_WIDTH: f32 = 1280.0;
 pub const WINDOW_HEIGHT: f32 = 720.0;
 
+const NETWORK_PUMP_INTERVAL: Duration = Duration::from_millis(4);
+
 pub struct ClientRunner {
     pub session: ClientSession,
     pub client: RenetClient,
@@ -98,7 +101,7 @@ impl ClientRunner {
 
         let now = Instant::now();
         let dt = now - self.last_updated;
-        self.frame_dt = dt;
+        self.frame_dt += dt;
         self.last_updated = now;
 
         let mut result: Result<(), String> = Ok(());
@@ -128,12 +131,24 @@ impl ClientRunner {
                         self.client.disconnect_reason(),
                         self.transport.disconnect_reason(),
                     ),
+                    self.session.disconnect_reason,
                 );
                 self.session.set_pending_disconnect(message);
             }
         }
     }
 
+    fn wait_for_frame_cap(&mut self, target_fps: u32, frame_start: Instant) {
+        loop {
+            let remaining = frame::frame_sleep_duration(target_fps, frame_start.elapsed());
+            if remaining.is_zero() {
+                break;
+            }
+            thread::sleep(remaining.min(NETWORK_PUMP_INTERVAL));
+            self.pump_network();
+        }
+    }
+
     fn display_disconnect_message(&mut self, disconnect_message: &str) {
         if !self.session.disconnected_notified {
             let separator = if disconnect_message
@@ -175,32 +190,49 @@ impl ClientRunner {
             return;
         }
 
+        let is_playing = matches!(&self.session.state, ClientState::Game(_));
         match &mut self.session.state {
-            ClientState::Game(game_state) => {
+            ClientState::Game(game_state) | ClientState::Spectating(game_state) => {
                 Self::update_sim_clock(&mut self.session.clock, self.frame_dt);
 
                 let mut network = RenetNetworkHandle::new(&mut self.client, &mut self.transport);
 
+                if self.session.is_host && is_key_pressed(KeyCode::P) {
+                    game_state.request_toggle_pause(&mut network);
+                }
+
                 let next_state = game_state.update_with_network(
                     &mut self.session.clock,
                     &mut network,
                     &self.assets,
                 );
+                if let Some(warning) = game_state.take_link_warning() {
+                    self.ui
+                        .show_typed_error(UiErrorKind::Deserialization, &warning);
+                }
                 match next_state {
                     Some(ClientState::AfterGameChat(chat_state)) => {
                         self.ui.flush_input();
                         let old =
                             std::mem::replace(&mut self.session.state, ClientState::default());
-                        if let ClientState::Game(game) = old {
+                        if let ClientState::Game(game) | ClientState::Spectating(game) = old {
                             let full_chat = game.consume_for_after_game(chat_state);
                             self.session.state = ClientState::AfterGameChat(full_chat);
                         } else {
-                            unreachable!("transition to AfterGameChat only happens from Game");
+                            unreachable!(
+                                "transition to AfterGameChat only happens from Game or Spectating"
+                            );
                         }
                     }
                     Some(other) => {
                         self.session.transition(other);
                     }
+                    None if is_playing && game_state.is_spectating() => {
+                        let old = std::mem::take(&mut self.session.state);
+                        if let ClientState::Game(game) = old {
+                            self.session.state = ClientState::Spectating(game);
+                        }
+                    }
                     None => {
                         let tick_fraction = (self.session.clock.accumulated_time / TICK_SECS)
                             .clamp(0.0, 1.0) as f32;
@@ -290,18 +322,20 @@ impl ClientRunner {
         let needle_textures = info::circles::NeedleTextures::new(info::BASE_CIRCLE_RADIUS);
 
         self.session.local_player_index = Some(local_player_index);
-        self.session
-            .transition(ClientState::Game(game::state::Game::new(
-                local_player_index,
-                initial_data,
-                maze_meshes,
-                map_overlay,
-                sky_mesh,
-                sim_tick,
-                timer_markers,
-                self.session.clock.estimated_server_time,
-                needle_textures,
-            )));
+        let mut game = game::state::Game::new(
+            local_player_index,
+            initial_data,
+            maze_meshes,
+            map_overlay,
+            sky_mesh,
+            sim_tick,
+            timer_markers,
+            self.session.clock.estimated_server_time,
+            needle_textures,
+        );
+        game.rotation_sensitivity = self.session.rotation_sensitivity;
+        game.auto_center = self.session.auto_center;
+        self.session.transition(ClientState::Game(game));
 
         Ok(())
     }
@@ -321,9 +355,22 @@ impl ClientRunner {
     }
 }
 
-pub async fn run_client_loop(private_key: [u8; 32], mut ui: Gui) {
-    let client_id = ::rand::random::<u64>();
+pub async fn run_client_loop(
+    private_key: [u8; 32],
+    mut ui: Gui,
+    floor_theme: FloorTheme,
+    shadows: bool,
+    rotation_sensitivity: RotationSensitivity,
+    auto_center: bool,
+    default_server_addr: SocketAddr,
+) {
+    let client_id = identity::client_id_from_args(std::env::args());
     let mut session = ClientSession::new(client_id);
+    session.floor_theme = floor_theme;
+    session.shadows = shadows;
+    session.default_server_addr = default_server_addr;
+    session.rotation_sensitivity = rotation_sensitivity;
+    session.auto_center = auto_center;
     let assets = Assets::load().await;
     let Some(server_addr) =
         prompt_for_server_address(&mut session, &mut ui, Some(&assets.font)).await
@@ -335,8 +382,8 @@ pub async fn run_client_loop(private_key: [u8; 32], mut ui: Gui) {
 
     #[cfg(target_os = "windows")]
     let socket_addr = {
-        if server_addr.ip().is_loopback() {
-            SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0)
+        if server_addr.ip().is_loopback() || server_addr.is_ipv6() {
+            SocketAddr::new(unspecified_ip_for(server_addr), 0)
         } else {
             let local_ip = get_best_local_binding_ip();
             SocketAddr::new(local_ip, 0)
@@ -344,7 +391,7 @@ pub async fn run_client_loop(private_key: [u8; 32], mut ui: Gui) {
     };
 
     #[cfg(not(target_os = "windows"))]
-    let socket_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0);
+    let socket_addr = SocketAddr::new(unspecified_ip_for(server_addr), 0);
     let socket = match UdpSocket::bind(socket_addr) {
         Ok(socket) => socket,
         Err(e) => {
@@ -366,20 +413,39 @@ pub async fn run_client_loop(private_key: [u8; 32], mut ui: Gui) {
         .ui
         .print_client_banner(common::protocol::version(), server_addr);
 
+    let fps_cap = frame::fps_cap_from_env();
+    if let Some(target_fps) = fps_cap {
+        println!("Frame rate capped at {} FPS.", target_fps);
+    }
+
     loop {
         if should_quit() {
             break;
         }
 
+        let frame_start = Instant::now();
         runner.session.clock.fps.update();
         // println!("{}", runner.session.clock.fps.rate);
         runner.pump_network();
         runner.update_client_state();
+        runner.frame_dt = Duration::ZERO;
+
+        if let Some(target_fps) = fps_cap {
+            runner.wait_for_frame_cap(target_fps, frame_start);
+        }
 
         next_frame().await;
     }
 }
 
+fn unspecified_ip_for(server_addr: SocketAddr) -> IpAddr {
+    if server_addr.is_ipv4() {
+        IpAddr::V4(Ipv4Addr::UNSPECIFIED)
+    } else {
+        IpAddr::V6(Ipv6Addr::UNSPECIFIED)
+    }
+}
+
 fn should_quit() -> bool {
     is_quit_requested() || is_key_pressed(KeyCode::Escape)
 }
@@ -462,7 +528,16 @@ async fn prompt_for_server_address(
     }
 }
 
-fn disconnect_message(state: &ClientState, error: &str, kind: DisconnectKind) -> String {
+fn disconnect_message(
+    state: &ClientState,
+    error: &str,
+    kind: DisconnectKind,
+    reason: Option<DisconnectReason>,
+) -> String {
+    if let Some(reason) = reason {
+        return reason.message().to_string();
+    }
+
     match state {
         ClientState::Lobby(lobby_state) => match lobby_state {
             Lobby::Connecting { .. }
@@ -520,6 +595,42 @@ fn disconnect_message(state: &ClientState, error: &str, kind: DisconnectKind) ->
 mod tests {
     use super::*;
     use crate::lobby::state::Lobby;
+    use common::protocol::PROTOCOL_ERROR_MESSAGE;
+
+    #[test]
+    fn disconnect_reason_from_the_server_takes_precedence_over_guessing() {
+        let state = ClientState::Lobby(Lobby::Chat {
+            awaiting_initial_roster: false,
+            waiting_for_server: false,
+        });
+        for (reason, expected) in [
+            (
+                DisconnectReason::ServerFull,
+                "The server is full. Please try again later.",
+            ),
+            (
+                DisconnectReason::Kicked,
+                "You were removed from the server.",
+            ),
+            (
+                DisconnectReason::FailedAuth,
+                "Incorrect passcode. Disconnecting.",
+            ),
+            (
+                DisconnectReason::ShuttingDown,
+                "The server is shutting down.",
+            ),
+            (DisconnectReason::ProtocolError, PROTOCOL_ERROR_MESSAGE),
+        ] {
+            let msg = disconnect_message(
+                &state,
+                "connection terminated by server",
+                DisconnectKind::DisconnectedByServer,
+                Some(reason),
+            );
+            assert_eq!(msg, expected);
+        }
+    }
 
     #[test]
     fn disconnect_message_for_connecting_when_server_terminates() {
@@ -530,6 +641,7 @@ mod tests {
             &state,
             "connection terminated by server",
             DisconnectKind::DisconnectedByServer,
+            None,
         );
         assert_eq!(
             msg,
@@ -546,6 +658,7 @@ mod tests {
             &state,
             "DisconnectedByServer",
             DisconnectKind::DisconnectedByServer,
+            None,
         );
         assert_eq!(
             msg,
@@ -564,6 +677,7 @@ mod tests {
             &state,
             "connection terminated by server",
             DisconnectKind::DisconnectedByServer,
+            None,
         );
         assert_eq!(
             msg,
@@ -578,6 +692,7 @@ mod tests {
             &state,
             "timeout",
             DisconnectKind::Other("timeout".to_string()),
+            None,
         );
         assert_eq!(
             msg,
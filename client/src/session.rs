@@ -1,24 +1,30 @@
-use std::{collections::VecDeque, net::SocketAddr, time::Instant};
+use std::{
+    collections::VecDeque,
+    hash::{DefaultHasher, Hash, Hasher},
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
 
 use crate::{
     after_game_chat::AfterGameChat,
     frame::FrameRate,
+    game::world::maze::FloorTheme,
     lobby::state::Lobby,
     state::{ClientState, InputMode},
+    time::OffsetFilter,
+};
+use common::{
+    auth::MAX_ATTEMPTS,
+    chat::MAX_CHAT_MESSAGE_BYTES,
+    player::{MAX_USERNAME_LENGTH, RotationSensitivity, UsernameError, sanitize_username},
+    protocol::DisconnectReason,
 };
-use common::player::{MAX_USERNAME_LENGTH, UsernameError, sanitize_username};
-
-#[derive(Debug)]
-pub struct ClockSample {
-    pub server_time: f64,
-    pub client_receive_time: f64,
-    pub rtt: f64,
-}
 
 #[derive(Debug)]
 pub struct ClientSession {
     pub client_id: u64,
     pub is_host: bool,
+    pub start_vote_open: bool,
     pub state: ClientState,
     pub clock: Clock,
     pub input_queue: Vec<String>,
@@ -26,6 +32,18 @@ pub struct ClientSession {
     pub disconnected_notified: bool,
     pub pending_disconnect: Option<String>,
     pub server_addr: Option<SocketAddr>,
+    pub default_server_addr: SocketAddr,
+    pub room_code: Option<String>,
+    pub floor_theme: FloorTheme,
+    pub shadows: bool,
+    pub rotation_sensitivity: RotationSensitivity,
+    pub auto_center: bool,
+    pub max_chat_bytes: usize,
+    pub max_username_length: usize,
+    pub max_auth_attempts: u8,
+    pub recent_chat: RecentChat,
+    pub roster: Vec<String>,
+    pub disconnect_reason: Option<DisconnectReason>,
     waiting_since: Option<Instant>,
     waiting_message_shown: bool,
 }
@@ -35,6 +53,7 @@ impl ClientSession {
         Self {
             client_id,
             is_host: false,
+            start_vote_open: false,
             state: ClientState::Lobby(Lobby::ServerAddress {
                 prompt_printed: false,
             }),
@@ -44,6 +63,18 @@ impl ClientSession {
             disconnected_notified: false,
             pending_disconnect: None,
             server_addr: None,
+            default_server_addr: crate::env::default_server_address(),
+            room_code: None,
+            floor_theme: FloorTheme::default(),
+            shadows: true,
+            rotation_sensitivity: RotationSensitivity::default(),
+            auto_center: false,
+            max_chat_bytes: MAX_CHAT_MESSAGE_BYTES,
+            max_username_length: MAX_USERNAME_LENGTH,
+            max_auth_attempts: MAX_ATTEMPTS,
+            recent_chat: RecentChat::default(),
+            roster: Vec::new(),
+            disconnect_reason: None,
             waiting_since: None,
             waiting_message_shown: false,
         }
@@ -60,6 +91,9 @@ impl ClientSession {
         if matches!(new_state, ClientState::AfterGameChat { .. }) {
             self.input_queue.clear();
         }
+        if !matches!(new_state, ClientState::Lobby(Lobby::Chat { .. })) {
+            self.start_vote_open = false;
+        }
         self.state = new_state;
     }
 
@@ -190,6 +224,13 @@ impl ClientSession {
         match &self.state {
             ClientState::Lobby(Lobby::ServerAddress { .. }) => InputMode::Enabled,
             ClientState::Lobby(Lobby::Passcode { .. }) => InputMode::Enabled,
+            ClientState::Lobby(Lobby::BrowsingRooms { prompt_printed, .. }) => {
+                if *prompt_printed {
+                    InputMode::Enabled
+                } else {
+                    InputMode::DisabledWaiting
+                }
+            }
             ClientState::Lobby(Lobby::Connecting { .. }) => InputMode::Hidden,
             ClientState::Lobby(Lobby::Authenticating {
                 waiting_for_input,
@@ -206,6 +247,7 @@ impl ClientSession {
             }
             ClientState::Lobby(Lobby::ChoosingUsername { .. }) => InputMode::Enabled,
             ClientState::Lobby(Lobby::AwaitingUsernameConfirmation) => InputMode::DisabledWaiting,
+            ClientState::Lobby(Lobby::ChoosingColor { .. }) => InputMode::SingleKey,
             ClientState::Lobby(Lobby::Chat {
                 waiting_for_server, ..
             }) => {
@@ -239,7 +281,7 @@ impl ClientSession {
             ClientState::Disconnected { .. }
             | ClientState::EndAfterLeaderboard
             | ClientState::Transitioning => InputMode::Hidden,
-            ClientState::Game(_) => InputMode::SingleKey,
+            ClientState::Game(_) | ClientState::Spectating(_) => InputMode::SingleKey,
         }
     }
 
@@ -283,6 +325,13 @@ impl ClientSession {
     pub fn take_pending_disconnect(&mut self) -> Option<String> {
         self.pending_disconnect.take()
     }
+
+    pub fn disconnect_for(&mut self, reason: DisconnectReason) -> ClientState {
+        self.disconnect_reason = Some(reason);
+        ClientState::Disconnected {
+            message: reason.message().to_string(),
+        }
+    }
 }
 
 impl Default for ClientState {
@@ -299,7 +348,7 @@ pub struct InputUiState {
 #[derive(Debug)]
 pub struct Clock {
     pub estimated_server_time: f64,
-    pub samples: VecDeque<ClockSample>,
+    pub offset_filter: OffsetFilter,
     pub smoothed_rtt: f64,
     pub accumulated_time: f64,
     pub continuous_sim_time: f64,
@@ -311,7 +360,7 @@ impl Clock {
     pub fn new() -> Self {
         Self {
             estimated_server_time: 0.0, // Seconds.
-            samples: VecDeque::new(),
+            offset_filter: OffsetFilter::default(),
             smoothed_rtt: 0.0,
             accumulated_time: 0.0,
             continuous_sim_time: 0.0,
@@ -321,15 +370,47 @@ impl Clock {
     }
 }
 
-pub fn username_prompt() -> String {
+const DUPLICATE_CHAT_WINDOW: Duration = Duration::from_secs(2);
+const RECENT_CHAT_CAPACITY: usize = 16;
+
+#[derive(Debug, Default)]
+pub struct RecentChat {
+    fingerprints: VecDeque<(u64, Instant)>,
+}
+
+impl RecentChat {
+    pub fn is_duplicate(&mut self, username: &str, content: &str, now: Instant) -> bool {
+        self.fingerprints
+            .retain(|&(_, seen)| now.duration_since(seen) < DUPLICATE_CHAT_WINDOW);
+
+        let mut hasher = DefaultHasher::new();
+        (username, content).hash(&mut hasher);
+        let fingerprint = hasher.finish();
+
+        if self
+            .fingerprints
+            .iter()
+            .any(|&(seen, _)| seen == fingerprint)
+        {
+            return true;
+        }
+        if self.fingerprints.len() == RECENT_CHAT_CAPACITY {
+            self.fingerprints.pop_front();
+        }
+        self.fingerprints.push_back((fingerprint, now));
+        false
+    }
+}
+
+pub fn username_prompt(max_length: usize) -> String {
     format!(
         "Choose a username (1-{} characters, letters/numbers/_/- only): ",
-        MAX_USERNAME_LENGTH
+        max_length
     )
 }
 
-pub fn validate_username_input(input: &str) -> Result<String, UsernameError> {
-    sanitize_username(input)
+pub fn validate_username_input(input: &str, max_length: usize) -> Result<String, UsernameError> {
+    sanitize_username(input, max_length)
 }
 
 #[cfg(test)]
@@ -370,19 +451,58 @@ mod tests {
         }
     }
 
+    #[test]
+    fn leaving_the_lobby_chat_closes_the_start_vote() {
+        let mut session = ClientSession::new(0);
+        let chat = || {
+            ClientState::Lobby(Lobby::Chat {
+                awaiting_initial_roster: false,
+                waiting_for_server: false,
+            })
+        };
+        session.transition(chat());
+        session.start_vote_open = true;
+        session.transition(chat());
+        assert!(session.start_vote_open);
+
+        session.transition(ClientState::Disconnected {
+            message: "done".to_string(),
+        });
+        assert!(!session.start_vote_open);
+    }
+
+    #[test]
+    fn immediate_duplicate_chat_is_suppressed_but_a_later_repeat_is_shown() {
+        let mut recent = RecentChat::default();
+        let start = Instant::now();
+
+        assert!(!recent.is_duplicate("alice", "hi", start));
+        assert!(recent.is_duplicate("alice", "hi", start + Duration::from_millis(100)));
+        assert!(!recent.is_duplicate("bob", "hi", start + Duration::from_millis(100)));
+        assert!(!recent.is_duplicate("alice", "hello", start + Duration::from_millis(100)));
+        assert!(!recent.is_duplicate("alice", "hi", start + DUPLICATE_CHAT_WINDOW));
+    }
+
     #[test]
     fn username_validation_rejects_invalid_values() {
-        assert_eq!(validate_username_input(""), Err(UsernameError::Empty));
-        assert_eq!(validate_username_input("    "), Err(UsernameError::Empty));
         assert_eq!(
-            validate_username_input("user!"),
+            validate_username_input("", MAX_USERNAME_LENGTH),
+            Err(UsernameError::Empty)
+        );
+        assert_eq!(
+            validate_username_input("    ", MAX_USERNAME_LENGTH),
+            Err(UsernameError::Empty)
+        );
+        assert_eq!(
+            validate_username_input("user!", MAX_USERNAME_LENGTH),
             Err(UsernameError::InvalidCharacter('!'))
         );
     }
 
     #[test]
     fn username_validation_accepts_trimmed_valid_value() {
-        let validated = validate_username_input("  Player-1  ").expect("valid username expected");
+        let validated = validate_username_input("  Player-1  ", MAX_USERNAME_LENGTH)
+            .expect("valid username expected");
         assert_eq!(validated, "player-1");
     }
 
@@ -390,9 +510,18 @@ mod tests {
     fn username_validation_respects_length_limit() {
         let too_long = "abcdefghijklmnopq";
         assert_eq!(
-            validate_username_input(too_long),
+            validate_username_input(too_long, MAX_USERNAME_LENGTH),
+            Err(UsernameError::TooLong)
+        );
+    }
+
+    #[test]
+    fn username_validation_respects_a_server_limit() {
+        assert_eq!(
+            validate_username_input("abcdefghi", 8),
             Err(UsernameError::TooLong)
         );
+        assert!(username_prompt(8).contains("1-8 characters"));
     }
 
     #[test]
@@ -4,6 +4,7 @@ pub use crate::{after_game_chat::AfterGameChat, game::state::Game, lobby::state:
 pub enum ClientState {
     Lobby(Lobby),
     Game(Game),
+    Spectating(Game),
     AfterGameChat(AfterGameChat),
     Disconnected { message: String },
     EndAfterLeaderboard,
@@ -1,13 +1,17 @@
 use std::{collections::VecDeque, net::SocketAddr};
 
-use bincode::{config::standard, serde::encode_to_vec};
+use bincode::serde::encode_to_vec;
 use macroquad::prelude::Font;
 
 use crate::{
     lobby::ui::{LobbyUi, UiErrorKind, UiInputError},
     net::{DisconnectKind, NetworkHandle},
 };
-use common::{input::UiKey, net::AppChannel, protocol::ServerMessage};
+use common::{
+    input::UiKey,
+    net::AppChannel,
+    protocol::{ServerMessage, config},
+};
 
 #[derive(Default)]
 pub struct MockUi {
@@ -20,6 +24,8 @@ pub struct MockUi {
     pub keys: VecDeque<Result<Option<UiKey>, UiInputError>>,
     pub countdown_draws: Vec<String>,
     pub local_player_color: Option<common::player::Color>,
+    pub status_line: Option<String>,
+    pub history_clears: usize,
 }
 
 impl MockUi {
@@ -46,6 +52,8 @@ impl MockUi {
             keys: VecDeque::new(),
             countdown_draws: Vec::new(),
             local_player_color: None,
+            status_line: None,
+            history_clears: 0,
         }
     }
 }
@@ -79,6 +87,14 @@ impl LobbyUi for MockUi {
         self.local_player_color = Some(color);
     }
 
+    fn set_status_line(&mut self, status: &str) {
+        self.status_line = Some(status.to_string());
+    }
+
+    fn clear_history(&mut self) {
+        self.history_clears += 1;
+    }
+
     fn poll_input(&mut self, limit: usize, _is_host: bool) -> Result<Option<String>, UiInputError> {
         self.inputs.pop_front().unwrap_or(Ok(None)).map(|opt| {
             opt.map(|mut s| {
@@ -125,8 +141,7 @@ impl MockNetwork {
         Self::default()
     }
 
-    #[allow(dead_code)]
-    fn set_connected(&mut self, connected: bool) {
+    pub fn set_connected(&mut self, connected: bool) {
         self.is_connected_val = connected;
     }
 
@@ -141,7 +156,7 @@ impl MockNetwork {
     }
 
     pub fn queue_server_message(&mut self, message: ServerMessage) {
-        let data = encode_to_vec(&message, standard()).expect("failed to serialize test message");
+        let data = encode_to_vec(&message, config()).expect("failed to serialize test message");
         self.messages_to_receive.push_back(data);
     }
 }
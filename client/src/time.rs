@@ -3,15 +3,16 @@ use std::{
     time::{Duration, Instant},
 };
 
-use bincode::config::standard;
-
 use crate::{
     net::{NetworkHandle, RenetNetworkHandle},
-    session::{ClientSession, ClockSample},
+    session::ClientSession,
+};
+use common::{
+    constants::TICK_SECS,
+    net::AppChannel,
+    protocol::{ServerMessage, config},
 };
-use common::{constants::TICK_SECS, net::AppChannel, protocol::ServerMessage};
 
-const SAMPLE_WINDOW_SIZE: usize = 10;
 const HARD_SNAP_THRESHOLD: f64 = 1.0;
 const ALPHA_SPEED_UP: f64 = 0.1;
 const ALPHA_SLOW_DOWN: f64 = 0.1;
@@ -25,10 +26,51 @@ const NUDGE_CLAMP: f64 = 0.01;
 // late on the server, consider increasing it, e.g. to 4 ticks.
 const JITTER_SAFETY_MARGIN: f64 = 0.05;
 
-// 1 second of sample age is weighted as equivalent to 20ms of extra RTT. This
-// ensures we prefer a slightly higher RTT from *now* over a perfect RTT from 1
-// second ago.
-const AGE_PENALTY_FACTOR: f64 = 0.02;
+const OFFSET_ALPHA_MIN: f64 = 0.1;
+const OFFSET_WARMUP_SAMPLES: u32 = 3;
+const RTT_OUTLIER_FACTOR: f64 = 2.0;
+const RTT_OUTLIER_MARGIN: f64 = 0.02;
+const MAX_CONSECUTIVE_OUTLIERS: u32 = 5;
+
+#[derive(Debug, Default)]
+pub struct OffsetFilter {
+    offset: Option<f64>,
+    rtt: f64,
+    accepted: u32,
+    consecutive_outliers: u32,
+}
+
+impl OffsetFilter {
+    pub fn offset(&self) -> Option<f64> {
+        self.offset
+    }
+
+    pub fn add_sample(&mut self, offset: f64, rtt: f64) -> bool {
+        if !offset.is_finite() || !rtt.is_finite() {
+            return false;
+        }
+
+        if self.accepted >= OFFSET_WARMUP_SAMPLES
+            && rtt > self.rtt * RTT_OUTLIER_FACTOR + RTT_OUTLIER_MARGIN
+        {
+            self.consecutive_outliers += 1;
+            if self.consecutive_outliers <= MAX_CONSECUTIVE_OUTLIERS {
+                return false;
+            }
+            self.accepted = 0;
+        }
+        self.consecutive_outliers = 0;
+
+        self.accepted = self.accepted.saturating_add(1);
+        let alpha = (1.0 / self.accepted as f64).max(OFFSET_ALPHA_MIN);
+        self.offset = Some(match self.offset {
+            Some(current) => current + (offset - current) * alpha,
+            None => offset,
+        });
+        self.rtt += (rtt - self.rtt) * alpha;
+        true
+    }
+}
 
 pub fn estimate_server_clock(
     session: &mut ClientSession,
@@ -42,61 +84,23 @@ pub fn estimate_server_clock(
     let now_seconds = get_monotonic_seconds();
     let mut latest_rtt = None;
 
-    // Drain pending messages, append samples, then trim the window.
     while let Some(message) = network.receive_message(AppChannel::ServerTime) {
         if let Ok((ServerMessage::ServerTime(server_sent_time), _)) =
-            bincode::serde::decode_from_slice(&message, standard())
+            bincode::serde::decode_from_slice(&message, config())
         {
             let rtt = network.rtt();
-
-            // Only add valid samples.
-            if rtt.is_finite() {
-                session.clock.samples.push_back(ClockSample {
-                    server_time: server_sent_time,
-                    client_receive_time: now_seconds,
-                    rtt,
-                });
+            let offset = server_sent_time + rtt / 2.0 - now_seconds;
+            if session.clock.offset_filter.add_sample(offset, rtt) {
                 latest_rtt = Some(rtt);
-
-                // Maintain window size.
-                if session.clock.samples.len() > SAMPLE_WINDOW_SIZE {
-                    session.clock.samples.pop_front();
-                }
             }
         }
     }
 
-    if session.clock.samples.is_empty() {
+    let Some(offset) = session.clock.offset_filter.offset() else {
         return;
-    }
-
-    // We select the best sample by balancing RTT against how old the data is.
-    // Minimizing the score (RTT + Age Penalty) prevents the clock from getting
-    // stuck on an old "lucky" packet with low RTT while the server time drifts.
-    let best_sample = match session
-        .clock
-        .samples
-        .iter()
-        .filter(|&s| s.rtt.is_finite())
-        .min_by(|a, b| {
-            let age_a = now_seconds - a.client_receive_time;
-            let age_b = now_seconds - b.client_receive_time;
-
-            let score_a = a.rtt + (age_a * AGE_PENALTY_FACTOR);
-            let score_b = b.rtt + (age_b * AGE_PENALTY_FACTOR);
-
-            score_a.partial_cmp(&score_b).unwrap()
-        }) {
-        Some(sample) => sample,
-        None => {
-            println!("No usable samples, e.g. all samples are NaN?");
-            return;
-        }
     };
 
-    let age_of_sample = now_seconds - best_sample.client_receive_time;
-    let latency_estimate = best_sample.rtt / 2.0;
-    let target_server_time = best_sample.server_time + latency_estimate + age_of_sample;
+    let target_server_time = now_seconds + offset;
     let error = target_server_time - session.clock.estimated_server_time;
 
     // Hard snap (teleport if wildly off).
@@ -140,6 +144,10 @@ pub fn calculate_target_time(smoothed_rtt: f64, estimated_server_time: f64) -> f
     estimated_server_time + travel_time + JITTER_SAFETY_MARGIN
 }
 
+pub fn interpolation_delay_secs(ticks_per_snapshot: u64) -> f64 {
+    INTERPOLATION_DELAY_SECS.max(2.0 * ticks_per_snapshot as f64 * TICK_SECS)
+}
+
 pub fn tick_from_time(time: f64) -> u64 {
     (time / TICK_SECS).floor() as u64
 }
@@ -168,3 +176,65 @@ fn get_monotonic_seconds() -> f64 {
     let start = START_TIME.get_or_init(Instant::now);
     start.elapsed().as_secs_f64()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRUE_OFFSET: f64 = 100.0;
+    const BASE_RTT: f64 = 0.05;
+
+    #[test]
+    fn interpolation_delay_covers_two_snapshot_intervals() {
+        assert_eq!(interpolation_delay_secs(1), INTERPOLATION_DELAY_SECS);
+        assert_eq!(interpolation_delay_secs(3), INTERPOLATION_DELAY_SECS);
+        assert!((interpolation_delay_secs(6) - 0.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn first_sample_sets_offset_immediately() {
+        let mut filter = OffsetFilter::default();
+        assert!(filter.add_sample(TRUE_OFFSET + 0.01, BASE_RTT));
+        assert_eq!(filter.offset(), Some(TRUE_OFFSET + 0.01));
+    }
+
+    #[test]
+    fn noisy_offsets_stay_near_the_true_offset() {
+        let mut filter = OffsetFilter::default();
+
+        for i in 0..60 {
+            let jitter = ((i * 7) % 11) as f64 / 11.0 * 0.02 - 0.01;
+            let (offset, rtt) = if i % 9 == 8 {
+                (TRUE_OFFSET + 0.4, BASE_RTT + 0.8)
+            } else {
+                (TRUE_OFFSET + jitter, BASE_RTT + jitter.abs())
+            };
+            filter.add_sample(offset, rtt);
+
+            if i >= 5 {
+                let estimate = filter.offset().expect("expected an offset");
+                assert!(
+                    (estimate - TRUE_OFFSET).abs() < 0.01,
+                    "sample {}: estimate {} drifted too far",
+                    i,
+                    estimate
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn persistent_rtt_change_is_eventually_accepted() {
+        let mut filter = OffsetFilter::default();
+        for _ in 0..10 {
+            filter.add_sample(TRUE_OFFSET, BASE_RTT);
+        }
+
+        let accepted = (0..MAX_CONSECUTIVE_OUTLIERS + 1)
+            .filter(|_| filter.add_sample(TRUE_OFFSET + 0.2, 0.5))
+            .count();
+
+        assert_eq!(accepted, 1);
+        assert_eq!(filter.offset(), Some(TRUE_OFFSET + 0.2));
+    }
+}
@@ -1,17 +1,20 @@
+use std::str::FromStr;
+
 use glam::{Vec3, vec3};
+use serde::{Deserialize, Serialize};
 
 use crate::{
     constants::{TICK_SECS, TICK_SECS_F32},
-    maze::{CELL_SIZE, Maze},
+    maze::{CELL_SIZE, Maze, MazeGrid, WALL_HEIGHT},
     player,
 };
 
 pub const MAX_BULLETS_PER_PLAYER: usize = 24;
 pub const FIRE_COOLDOWN_SECS: f64 = 0.1;
+pub const RELOAD_SECS: f64 = 1.5;
 pub const SPEED: f32 = 720.0;
 pub const LIFESPAN_SECS: f64 = 2.5;
 pub const MAX_BOUNCES: u8 = 5;
-pub const BULLET_SPAWN_OFFSET: f32 = player::RADIUS + BULLET_CORE_RADIUS + 0.1;
 
 // The bullet's shell radius is used for display and for collisions with walls
 // and floor. It's core radius is for collisions with players. This is to let
@@ -21,7 +24,168 @@ pub const BULLET_SPAWN_OFFSET: f32 = player::RADIUS + BULLET_CORE_RADIUS + 0.1;
 // any potential feeling the shooter might have that its periphery should be
 // doing more damage.
 pub const BULLET_SHELL_RADIUS: f32 = 4.0;
-const BULLET_CORE_RADIUS: f32 = 0.1;
+pub const BULLET_CORE_RADIUS: f32 = 0.1;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct BulletRadii {
+    shell: f32,
+    core: f32,
+}
+
+impl Default for BulletRadii {
+    fn default() -> Self {
+        Self {
+            shell: BULLET_SHELL_RADIUS,
+            core: BULLET_CORE_RADIUS,
+        }
+    }
+}
+
+impl FromStr for BulletRadii {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |value: Option<&str>| value.and_then(|value| value.trim().parse::<f32>().ok());
+        let mut parts = s.split(',');
+        match (parse(parts.next()), parse(parts.next()), parts.next()) {
+            (Some(shell), Some(core), None) => Self::new(shell, core),
+            _ => Err(format!(
+                "invalid bullet radii '{}' (expected SHELL,CORE)",
+                s
+            )),
+        }
+    }
+}
+
+impl BulletRadii {
+    pub fn new(shell: f32, core: f32) -> Result<Self, String> {
+        if !(core > 0.0 && shell.is_finite()) {
+            return Err(format!(
+                "bullet radii must be positive and finite, got shell {} and core {}",
+                shell, core
+            ));
+        }
+        if core > shell {
+            return Err(format!(
+                "bullet core radius {} must not exceed shell radius {}",
+                core, shell
+            ));
+        }
+        Ok(Self { shell, core })
+    }
+
+    pub fn shell(&self) -> f32 {
+        self.shell
+    }
+
+    pub fn core(&self) -> f32 {
+        self.core
+    }
+
+    pub fn spawn_offset(&self) -> f32 {
+        player::RADIUS + self.core + 0.1
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct BulletTuning {
+    pub speed: f32,
+    pub lifespan_secs: f64,
+    pub gravity: f32,
+    pub magazine: Option<Magazine>,
+    pub radii: BulletRadii,
+}
+
+impl Default for BulletTuning {
+    fn default() -> Self {
+        Self {
+            speed: SPEED,
+            lifespan_secs: LIFESPAN_SECS,
+            gravity: 0.0,
+            magazine: None,
+            radii: BulletRadii::default(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Magazine {
+    pub size: u8,
+    pub reload_secs: f64,
+}
+
+impl Magazine {
+    pub fn for_difficulty(size: u8, level: u8) -> Self {
+        let steps = level.saturating_sub(1);
+        Self {
+            size: size.max(1),
+            reload_secs: (RELOAD_SECS - 0.1 * steps as f64).max(0.5),
+        }
+    }
+
+    pub fn reload_ticks(&self) -> u64 {
+        (self.reload_secs / TICK_SECS).ceil() as u64
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ammo {
+    pub rounds: u8,
+    pub reloading_until_tick: Option<u64>,
+}
+
+impl Ammo {
+    pub fn full(magazine: &Magazine) -> Self {
+        Self {
+            rounds: magazine.size,
+            reloading_until_tick: None,
+        }
+    }
+
+    pub fn refill_if_reloaded(&mut self, magazine: &Magazine, current_tick: u64) {
+        if self
+            .reloading_until_tick
+            .is_some_and(|until| current_tick >= until)
+        {
+            *self = Self::full(magazine);
+        }
+    }
+
+    pub fn ready(&mut self, magazine: &Magazine, current_tick: u64) -> bool {
+        self.refill_if_reloaded(magazine, current_tick);
+        self.reloading_until_tick.is_none() && self.rounds > 0
+    }
+
+    pub fn spend(&mut self, magazine: &Magazine, current_tick: u64) {
+        self.rounds = self.rounds.saturating_sub(1);
+        if self.rounds == 0 {
+            self.reloading_until_tick = Some(current_tick + magazine.reload_ticks());
+        }
+    }
+
+    pub fn reload_progress(&self, magazine: &Magazine, current_tick: u64) -> Option<f32> {
+        let until = self.reloading_until_tick?;
+        let remaining = until.saturating_sub(current_tick) as f32;
+        Some((1.0 - remaining / magazine.reload_ticks().max(1) as f32).clamp(0.0, 1.0))
+    }
+}
+
+impl BulletTuning {
+    pub fn for_difficulty(level: u8) -> Self {
+        let steps = level.saturating_sub(1);
+        Self {
+            speed: SPEED + 40.0 * steps as f32,
+            lifespan_secs: LIFESPAN_SECS + 0.1 * steps as f64,
+            gravity: 0.0,
+            magazine: None,
+            radii: BulletRadii::default(),
+        }
+    }
+
+    pub fn lifespan_ticks(&self) -> u64 {
+        (self.lifespan_secs / TICK_SECS).ceil() as u64
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Bullet {
@@ -56,9 +220,13 @@ impl Bullet {
         self.position += self.velocity * delta;
     }
 
-    pub fn is_expired(&self, current_tick: u64) -> bool {
+    pub fn fall(&mut self, tuning: &BulletTuning) {
+        fall(&mut self.velocity, tuning);
+    }
+
+    pub fn is_expired(&self, current_tick: u64, tuning: &BulletTuning) -> bool {
         let age = (current_tick - self.spawn_tick) as f64 * TICK_SECS;
-        age > LIFESPAN_SECS
+        age > tuning.lifespan_secs
     }
 
     pub fn has_bounced_enough(&self) -> bool {
@@ -77,14 +245,23 @@ pub enum WallBounce {
     Stuck,
 }
 
-pub fn bounce_off_ground(position: &mut Vec3, velocity: &mut Vec3, bounces: &mut u8) -> bool {
-    if position.y > BULLET_SHELL_RADIUS || velocity.y >= 0.0 {
+pub fn fall(velocity: &mut Vec3, tuning: &BulletTuning) {
+    velocity.y -= tuning.gravity * TICK_SECS_F32;
+}
+
+pub fn bounce_off_ground(
+    position: &mut Vec3,
+    velocity: &mut Vec3,
+    bounces: &mut u8,
+    radii: &BulletRadii,
+) -> bool {
+    if position.y > radii.shell || velocity.y >= 0.0 {
         return false;
     }
 
     // Time to impact: negative value, represents time in the past. Due to the
     // early returns, we can be sure that `t` <= 0.0.
-    let t = (BULLET_SHELL_RADIUS - position.y) / velocity.y;
+    let t = (radii.shell - position.y) / velocity.y;
 
     // Rewind to impact point.
     *position += *velocity * t;
@@ -150,13 +327,55 @@ fn find_intersection_with_box(
     Some(t_min)
 }
 
+fn closest_wall_hit(
+    ray_origin: Vec3,
+    direction: Vec3,
+    trace_distance: f32,
+    cells: &[(usize, usize)],
+) -> Option<(f32, Vec3)> {
+    let mut closest_hit: Option<(f32, Vec3)> = None;
+
+    for &(check_x, check_z) in cells {
+        // Diagonal corners of this wall cell with minimum and maximum world coordinates.
+        let box_min = vec3(check_x as f32 * CELL_SIZE, 0.0, check_z as f32 * CELL_SIZE);
+        let box_max = box_min + vec3(CELL_SIZE, WALL_HEIGHT, CELL_SIZE);
+
+        if let Some(t) = find_intersection_with_box(ray_origin, direction, box_min, box_max) {
+            if t > 0.0 && t < trace_distance {
+                let hit_point = ray_origin + direction * t;
+
+                // Calculate normal based on which face was hit.
+                let normal = if (hit_point.x - box_min.x).abs() < 0.1 {
+                    Vec3::new(-1.0, 0.0, 0.0)
+                } else if (hit_point.x - box_max.x).abs() < 0.1 {
+                    Vec3::new(1.0, 0.0, 0.0)
+                } else if (hit_point.z - box_min.z).abs() < 0.1 {
+                    Vec3::new(0.0, 0.0, -1.0)
+                } else if (hit_point.z - box_max.z).abs() < 0.1 {
+                    Vec3::new(0.0, 0.0, 1.0)
+                } else {
+                    -direction // Fallback.
+                };
+
+                // Update if we have a new closest hit.
+                if closest_hit.is_none() || t < closest_hit.unwrap().0 {
+                    closest_hit = Some((t, normal));
+                }
+            }
+        }
+    }
+
+    closest_hit
+}
+
 pub fn bounce_off_wall(
     position: &mut Vec3,
     velocity: &mut Vec3,
     bounces: &mut u8,
     maze: &Maze,
+    radii: &BulletRadii,
 ) -> WallBounce {
-    let is_bullet_above_wall_height = position.y - BULLET_SHELL_RADIUS > CELL_SIZE;
+    let is_bullet_above_wall_height = position.y - radii.shell > WALL_HEIGHT;
     if is_bullet_above_wall_height {
         return WallBounce::None;
     }
@@ -169,65 +388,13 @@ pub fn bounce_off_wall(
     let trace_distance = velocity.length() * TICK_SECS_F32;
     let ray_origin = *position - direction * trace_distance;
 
-    let mut closest_hit: Option<(f32, Vec3)> = None;
-
-    let end_pos = *position;
-
-    // Get the min and max grid coordinates that the ray spans.
-    let min_x = ray_origin.x.min(end_pos.x) / CELL_SIZE;
-    let max_x = ray_origin.x.max(end_pos.x) / CELL_SIZE;
-    let min_z = ray_origin.z.min(end_pos.z) / CELL_SIZE;
-    let max_z = ray_origin.z.max(end_pos.z) / CELL_SIZE;
-
-    // Check all grid cells along the path.
-    for check_z in (min_z.floor() as isize - 1)..=(max_z.ceil() as isize + 1) {
-        for check_x in (min_x.floor() as isize - 1)..=(max_x.ceil() as isize + 1) {
-            if check_x < 0
-                || check_z < 0
-                || check_x >= maze.grid[0].len() as isize
-                || check_z >= maze.grid.len() as isize
-            {
-                continue;
-            }
-
-            if maze.grid[check_z as usize][check_x as usize] == 0 {
-                continue; // Empty cell.
-            }
-
-            // Diagonal corners of this wall cell with minimum and maximum world coordinates.
-            let box_min = vec3(check_x as f32 * CELL_SIZE, 0.0, check_z as f32 * CELL_SIZE);
-            let box_max = box_min + vec3(CELL_SIZE, CELL_SIZE, CELL_SIZE);
-
-            if let Some(t) = find_intersection_with_box(ray_origin, direction, box_min, box_max) {
-                if t > 0.0 && t < trace_distance {
-                    let hit_point = ray_origin + direction * t;
-
-                    // Calculate normal based on which face was hit.
-                    let normal = if (hit_point.x - box_min.x).abs() < 0.1 {
-                        Vec3::new(-1.0, 0.0, 0.0)
-                    } else if (hit_point.x - box_max.x).abs() < 0.1 {
-                        Vec3::new(1.0, 0.0, 0.0)
-                    } else if (hit_point.z - box_min.z).abs() < 0.1 {
-                        Vec3::new(0.0, 0.0, -1.0)
-                    } else if (hit_point.z - box_max.z).abs() < 0.1 {
-                        Vec3::new(0.0, 0.0, 1.0)
-                    } else {
-                        -direction // Fallback.
-                    };
-
-                    // Update if we have a new closest hit.
-                    if closest_hit.is_none() || t < closest_hit.unwrap().0 {
-                        closest_hit = Some((t, normal));
-                    }
-                }
-            }
-        }
-    }
+    let cells = MazeGrid::new(maze).wall_cells_along(ray_origin, *position);
+    let closest_hit = closest_wall_hit(ray_origin, direction, trace_distance, &cells);
 
     if let Some((t, normal)) = closest_hit {
         // Move bullet to hit point and bounce.
         let hit_point = ray_origin + direction * t;
-        *position = hit_point - direction * BULLET_SHELL_RADIUS;
+        *position = hit_point - direction * radii.shell;
         redirect(velocity, bounces, normal);
         return WallBounce::Bounce;
     }
@@ -235,8 +402,20 @@ pub fn bounce_off_wall(
     WallBounce::None
 }
 
-pub fn is_bullet_colliding_with_player(bullet_position: Vec3, player_position: Vec3) -> bool {
-    bullet_position.distance(player_position) < BULLET_CORE_RADIUS + player::RADIUS
+pub fn is_bullet_colliding_with_player(
+    bullet_position: Vec3,
+    player_position: Vec3,
+    radii: &BulletRadii,
+) -> bool {
+    bullet_position.distance(player_position) < radii.core + player::RADIUS
+}
+
+pub fn is_bullet_colliding_with_bullet(
+    position: Vec3,
+    other_position: Vec3,
+    radii: &BulletRadii,
+) -> bool {
+    position.distance(other_position) < 2.0 * radii.shell
 }
 
 pub fn direction_from_yaw_pitch(yaw: f32, pitch: f32) -> Vec3 {
@@ -248,8 +427,12 @@ pub fn direction_from_yaw_pitch(yaw: f32, pitch: f32) -> Vec3 {
     direction.normalize_or_zero()
 }
 
-pub fn spawn_position(player_position: Vec3, direction: Vec3) -> Vec3 {
-    player_position + direction * BULLET_SPAWN_OFFSET
+pub fn spawn_position(player_position: Vec3, direction: Vec3, radii: &BulletRadii) -> Vec3 {
+    player_position + direction * radii.spawn_offset()
+}
+
+pub fn spawn_velocity(direction: Vec3, tuning: &BulletTuning) -> Vec3 {
+    direction * tuning.speed
 }
 
 pub fn cooldown_ticks() -> u64 {
@@ -260,12 +443,14 @@ pub fn update_bullet_position(
     bullet: &mut Bullet,
     maze: &Maze,
     current_tick: u64,
+    tuning: &BulletTuning,
 ) -> BulletUpdateResult {
     let mut result = BulletUpdateResult::default();
 
+    bullet.fall(tuning);
     bullet.advance(1);
 
-    if bullet.is_expired(current_tick) || bullet.has_bounced_enough() {
+    if bullet.is_expired(current_tick, tuning) || bullet.has_bounced_enough() {
         result.should_remove = true;
         result.event_type = BulletEventType::Expire;
     } else {
@@ -273,8 +458,9 @@ pub fn update_bullet_position(
             &mut bullet.position,
             &mut bullet.velocity,
             &mut bullet.bounces,
+            &tuning.radii,
         ) {
-            result.hit_inanimate = true;
+            result.impact = Some(ImpactSurface::Ground);
         }
 
         match bounce_off_wall(
@@ -282,9 +468,10 @@ pub fn update_bullet_position(
             &mut bullet.velocity,
             &mut bullet.bounces,
             maze,
+            &tuning.radii,
         ) {
             WallBounce::Bounce => {
-                result.hit_inanimate = true;
+                result.impact = Some(ImpactSurface::Wall);
             }
             WallBounce::Stuck => {
                 result.should_remove = true;
@@ -300,10 +487,17 @@ pub fn update_bullet_position(
 #[derive(Debug, Default)]
 pub struct BulletUpdateResult {
     pub should_remove: bool,
-    pub hit_inanimate: bool,
+    pub impact: Option<ImpactSurface>,
     pub event_type: BulletEventType,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImpactSurface {
+    Wall,
+    Ground,
+    Player,
+}
+
 #[derive(Debug, Default, PartialEq)]
 pub enum BulletEventType {
     #[default]
@@ -311,12 +505,23 @@ pub enum BulletEventType {
     Expire,
 }
 
+pub fn is_same_team(shooter_team: Option<u8>, target_team: Option<u8>) -> bool {
+    matches!((shooter_team, target_team), (Some(a), Some(b)) if a == b)
+}
+
 pub fn check_player_collision(
     bullet: &mut Bullet,
     player_position: Vec3,
     player_health: u8,
+    shooter_team: Option<u8>,
+    target_team: Option<u8>,
+    friendly_fire: bool,
+    radii: &BulletRadii,
 ) -> PlayerCollisionResult {
-    if !is_bullet_colliding_with_player(bullet.position, player_position) {
+    if !friendly_fire && is_same_team(shooter_team, target_team) {
+        return PlayerCollisionResult::default();
+    }
+    if !is_bullet_colliding_with_player(bullet.position, player_position, radii) {
         return PlayerCollisionResult::default();
     }
 
@@ -328,7 +533,7 @@ pub fn check_player_collision(
 
         if distance > 0.001 {
             let bullet_direction = bullet.velocity.normalize_or_zero();
-            let collision_radius = player::RADIUS + BULLET_CORE_RADIUS;
+            let collision_radius = player::RADIUS + radii.core;
 
             // Calculate intersection between the ray and the `collision_sphere`
             // intersection to find entry point.
@@ -359,7 +564,7 @@ pub fn check_player_collision(
             }
         } else {
             let normal = bullet.velocity.normalize_or_zero();
-            bullet.position = player_position + normal * (player::RADIUS + BULLET_CORE_RADIUS);
+            bullet.position = player_position + normal * (player::RADIUS + radii.core);
             bullet.redirect(normal);
         }
 
@@ -392,3 +597,362 @@ fn redirect(velocity: &mut Vec3, bounces: &mut u8, normal: Vec3) {
 fn reflect(direction: Vec3, normal: Vec3) -> Vec3 {
     direction - 2.0 * direction.dot(normal) * normal
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::{SPACE, WALL};
+
+    fn bullet_at(position: Vec3) -> Bullet {
+        Bullet::new(0, 0, position, vec3(SPEED, 0.0, 0.0), 0)
+    }
+
+    fn maze_with_wall_at_origin() -> Maze {
+        Maze {
+            grid: vec![vec![WALL, SPACE]],
+            spaces: vec![(0, 1)],
+            teleporters: Vec::new(),
+        }
+    }
+
+    fn patterned_maze() -> Maze {
+        let grid = (0..12)
+            .map(|z| {
+                (0..12)
+                    .map(|x| {
+                        if x == 0 || z == 0 || x == 11 || z == 11 || (x * 7 + z * 3) % 5 == 0 {
+                            WALL
+                        } else {
+                            SPACE
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        Maze {
+            grid,
+            spaces: Vec::new(),
+            teleporters: Vec::new(),
+        }
+    }
+
+    fn bounding_box_wall_cells(start: Vec3, end: Vec3, maze: &Maze) -> Vec<(usize, usize)> {
+        let min_x = start.x.min(end.x) / CELL_SIZE;
+        let max_x = start.x.max(end.x) / CELL_SIZE;
+        let min_z = start.z.min(end.z) / CELL_SIZE;
+        let max_z = start.z.max(end.z) / CELL_SIZE;
+
+        let mut cells = Vec::new();
+        for check_z in (min_z.floor() as isize - 1)..=(max_z.ceil() as isize + 1) {
+            for check_x in (min_x.floor() as isize - 1)..=(max_x.ceil() as isize + 1) {
+                if check_x < 0
+                    || check_z < 0
+                    || check_x >= maze.grid[0].len() as isize
+                    || check_z >= maze.grid.len() as isize
+                {
+                    continue;
+                }
+                if maze.grid[check_z as usize][check_x as usize] == WALL {
+                    cells.push((check_x as usize, check_z as usize));
+                }
+            }
+        }
+        cells
+    }
+
+    #[test]
+    fn grid_traversal_finds_the_same_wall_hits_as_bounding_box_scan() {
+        let maze = patterned_maze();
+        let rays = [
+            (vec3(100.0, 10.0, 100.0), vec3(650.0, 10.0, 420.0)),
+            (vec3(650.0, 10.0, 420.0), vec3(100.0, 10.0, 100.0)),
+            (vec3(96.0, 10.0, 96.0), vec3(544.0, 10.0, 544.0)),
+            (vec3(128.0, 10.0, 70.0), vec3(128.0, 10.0, 600.0)),
+            (vec3(70.0, 10.0, 300.0), vec3(700.0, 10.0, 300.0)),
+            (vec3(200.0, 10.0, 700.0), vec3(210.0, 10.0, 80.0)),
+            (vec3(300.0, 10.0, 300.0), vec3(310.0, 10.0, 305.0)),
+            (vec3(-50.0, 10.0, 100.0), vec3(900.0, 10.0, 130.0)),
+        ];
+
+        for (start, end) in rays {
+            let direction = (end - start).normalize();
+            let trace_distance = start.distance(end);
+            let dda = MazeGrid::new(&maze).wall_cells_along(start, end);
+            let scan = bounding_box_wall_cells(start, end, &maze);
+
+            assert!(dda.iter().all(|cell| scan.contains(cell)));
+            assert!(dda.len() < scan.len());
+            assert_eq!(
+                closest_wall_hit(start, direction, trace_distance, &dda),
+                closest_wall_hit(start, direction, trace_distance, &scan),
+                "ray from {:?} to {:?}",
+                start,
+                end
+            );
+        }
+    }
+
+    fn bounce_moving_into_wall(height: f32) -> WallBounce {
+        let maze = maze_with_wall_at_origin();
+        let mut position = vec3(CELL_SIZE - 1.0, height, CELL_SIZE / 2.0);
+        let mut velocity = vec3(-SPEED, 0.0, 0.0);
+        let mut bounces = 0;
+        bounce_off_wall(
+            &mut position,
+            &mut velocity,
+            &mut bounces,
+            &maze,
+            &BulletRadii::default(),
+        )
+    }
+
+    #[test]
+    fn bullet_above_wall_height_flies_over() {
+        let height = WALL_HEIGHT + BULLET_SHELL_RADIUS + 1.0;
+        assert_eq!(bounce_moving_into_wall(height), WallBounce::None);
+    }
+
+    #[test]
+    fn bullet_below_wall_height_bounces() {
+        let height = WALL_HEIGHT - BULLET_SHELL_RADIUS - 1.0;
+        assert_eq!(bounce_moving_into_wall(height), WallBounce::Bounce);
+    }
+
+    fn impact_after_one_tick(position: Vec3, velocity: Vec3) -> Option<ImpactSurface> {
+        let maze = maze_with_wall_at_origin();
+        let mut bullet = Bullet::new(0, 0, position, velocity, 0);
+        update_bullet_position(&mut bullet, &maze, 0, &BulletTuning::for_difficulty(1)).impact
+    }
+
+    #[test]
+    fn impacts_are_labelled_with_the_surface_hit() {
+        let open_air = vec3(CELL_SIZE * 1.5, WALL_HEIGHT / 2.0, CELL_SIZE / 2.0);
+        assert_eq!(impact_after_one_tick(open_air, vec3(0.0, 0.0, SPEED)), None);
+
+        let near_floor = vec3(CELL_SIZE * 1.5, BULLET_SHELL_RADIUS + 1.0, CELL_SIZE / 2.0);
+        assert_eq!(
+            impact_after_one_tick(near_floor, vec3(0.0, -SPEED, 0.0)),
+            Some(ImpactSurface::Ground)
+        );
+
+        let near_wall = vec3(CELL_SIZE + 1.0, WALL_HEIGHT / 2.0, CELL_SIZE / 2.0);
+        assert_eq!(
+            impact_after_one_tick(near_wall, vec3(-SPEED, 0.0, 0.0)),
+            Some(ImpactSurface::Wall)
+        );
+    }
+
+    #[test]
+    fn same_team_takes_no_damage_without_friendly_fire() {
+        let target = vec3(100.0, 0.0, 100.0);
+        let mut bullet = bullet_at(target);
+
+        let result = check_player_collision(
+            &mut bullet,
+            target,
+            3,
+            Some(1),
+            Some(1),
+            false,
+            &BulletRadii::default(),
+        );
+
+        assert!(!result.hit_player);
+        assert_eq!(bullet.position, target);
+    }
+
+    #[test]
+    fn same_team_takes_damage_with_friendly_fire() {
+        let target = vec3(100.0, 0.0, 100.0);
+        let mut bullet = bullet_at(target);
+
+        let result = check_player_collision(
+            &mut bullet,
+            target,
+            3,
+            Some(1),
+            Some(1),
+            true,
+            &BulletRadii::default(),
+        );
+
+        assert!(result.hit_player);
+        assert_eq!(result.new_health, 2);
+    }
+
+    #[test]
+    fn cross_team_takes_damage_without_friendly_fire() {
+        let target = vec3(100.0, 0.0, 100.0);
+        let mut bullet = bullet_at(target);
+
+        let result = check_player_collision(
+            &mut bullet,
+            target,
+            3,
+            Some(1),
+            Some(2),
+            false,
+            &BulletRadii::default(),
+        );
+
+        assert!(result.hit_player);
+        assert_eq!(result.new_health, 2);
+    }
+
+    #[test]
+    fn free_for_all_takes_damage_without_friendly_fire() {
+        let target = vec3(100.0, 0.0, 100.0);
+        let mut bullet = bullet_at(target);
+
+        let result = check_player_collision(
+            &mut bullet,
+            target,
+            1,
+            None,
+            None,
+            false,
+            &BulletRadii::default(),
+        );
+
+        assert!(result.hit_player);
+        assert_eq!(result.new_health, 0);
+        assert!(result.should_remove_bullet);
+    }
+
+    #[test]
+    fn larger_core_radius_hits_players_from_farther_away() {
+        let target = vec3(100.0, 0.0, 100.0);
+        let distance = player::RADIUS + 2.0;
+        let hits = |radii: BulletRadii| {
+            let mut bullet = bullet_at(target + vec3(distance, 0.0, 0.0));
+            bullet.velocity = vec3(-SPEED, 0.0, 0.0);
+            check_player_collision(&mut bullet, target, 3, None, None, true, &radii).hit_player
+        };
+
+        assert!(!hits(BulletRadii::default()));
+        assert!(hits(BulletRadii::new(BULLET_SHELL_RADIUS, 3.0).unwrap()));
+    }
+
+    #[test]
+    fn core_radius_may_not_exceed_shell_radius() {
+        assert!(BulletRadii::new(4.0, 4.0).is_ok());
+        assert!(BulletRadii::new(4.0, 4.5).is_err());
+        assert!(BulletRadii::new(4.0, 0.0).is_err());
+        assert!(BulletRadii::new(f32::INFINITY, 1.0).is_err());
+        assert_eq!("6, 0.5".parse(), BulletRadii::new(6.0, 0.5));
+        assert!("0.5,6".parse::<BulletRadii>().is_err());
+        assert!("6".parse::<BulletRadii>().is_err());
+    }
+
+    #[test]
+    fn expiry_follows_lifespan() {
+        let bullet = bullet_at(vec3(100.0, 20.0, 100.0));
+        for lifespan_secs in [1.0, 2.5, 4.0] {
+            let tuning = BulletTuning {
+                lifespan_secs,
+                ..Default::default()
+            };
+            let lifespan_ticks = (lifespan_secs / TICK_SECS).round() as u64;
+
+            assert!(!bullet.is_expired(lifespan_ticks - 1, &tuning));
+            assert!(bullet.is_expired(lifespan_ticks + 1, &tuning));
+        }
+    }
+
+    fn ticks_until_first_ground_bounce(gravity: f32) -> u64 {
+        let maze = Maze::new(crate::maze::Algorithm::Backtrack);
+        let tuning = BulletTuning {
+            gravity,
+            lifespan_secs: 60.0,
+            ..Default::default()
+        };
+        let (z, x) = maze.spaces[0];
+        let position = maze
+            .position_from_grid_coordinates(player::HEIGHT, z, x)
+            .expect("maze should have open cells");
+        let mut bullet = Bullet::new(0, 0, position, Vec3::ZERO, 0);
+        (1..=600)
+            .find(|&tick| {
+                let result = update_bullet_position(&mut bullet, &maze, tick, &tuning);
+                matches!(result.impact, Some(ImpactSurface::Ground))
+            })
+            .expect("falling bullet should reach the ground within ten seconds")
+    }
+
+    #[test]
+    fn lower_gravity_delays_a_bullet_reaching_the_ground() {
+        let normal = ticks_until_first_ground_bounce(player::GRAVITY);
+        let low = ticks_until_first_ground_bounce(player::LOW_GRAVITY);
+
+        assert!(low > normal);
+    }
+
+    #[test]
+    fn harder_difficulties_have_faster_longer_lived_bullets() {
+        let easy = BulletTuning::for_difficulty(1);
+        let hard = BulletTuning::for_difficulty(9);
+
+        assert_eq!(easy, BulletTuning::default());
+        assert!(hard.speed > easy.speed);
+        assert!(hard.lifespan_ticks() > easy.lifespan_ticks());
+
+        let bullet = bullet_at(vec3(100.0, 20.0, 100.0));
+        let tick = easy.lifespan_ticks() + 1;
+        assert!(bullet.is_expired(tick, &easy));
+        assert!(!bullet.is_expired(tick, &hard));
+    }
+
+    fn settled_position(maze: &Maze, crouch: bool) -> Vec3 {
+        let (z, x) = maze.spaces[0];
+        let position = maze
+            .position_from_grid_coordinates(player::HEIGHT, z, x)
+            .expect("maze should have open cells");
+        let mut state = player::PlayerState::new(position);
+        let input = player::PlayerInput {
+            crouch,
+            ..Default::default()
+        };
+        state.update(
+            maze,
+            &input,
+            &player::MovementProfile::default(),
+            0,
+            &Vec::new(),
+            0.0,
+        );
+        state.position
+    }
+
+    #[test]
+    fn crouching_lowers_bullet_spawn_height() {
+        let maze = Maze::new(crate::maze::Algorithm::Backtrack);
+        let direction = direction_from_yaw_pitch(0.0, 0.1);
+        let radii = BulletRadii::default();
+        let standing = spawn_position(settled_position(&maze, false), direction, &radii);
+        let crouching = spawn_position(settled_position(&maze, true), direction, &radii);
+
+        assert_eq!(
+            standing.y - crouching.y,
+            player::HEIGHT - player::CROUCH_HEIGHT
+        );
+        assert_eq!(
+            crouching.y,
+            player::CROUCH_HEIGHT + direction.y * radii.spawn_offset()
+        );
+    }
+
+    #[test]
+    fn bullet_at_standing_eye_level_passes_over_crouching_player() {
+        let maze = Maze::new(crate::maze::Algorithm::Backtrack);
+        let crouched = settled_position(&maze, true);
+        let overhead = vec3(crouched.x, player::HEIGHT, crouched.z);
+
+        let radii = BulletRadii::default();
+        assert!(!is_bullet_colliding_with_player(overhead, crouched, &radii));
+        assert!(is_bullet_colliding_with_player(
+            overhead,
+            settled_position(&maze, false),
+            &radii
+        ));
+    }
+}
@@ -5,8 +5,11 @@ pub mod constants;
 pub mod input;
 pub mod maze;
 pub mod net;
+pub mod pickups;
 pub mod player;
 pub mod protocol;
 pub mod ring;
 pub mod snapshot;
+pub mod spawn;
+pub mod team;
 pub mod time;
@@ -1,28 +1,55 @@
+pub mod grid;
 pub mod maker;
+pub mod symmetry;
 
 use std::fmt;
 
 use glam::{Vec3, vec3};
-use rand;
+use rand::{Rng, SeedableRng, rngs::StdRng};
 use serde::{Deserialize, Serialize};
 
+pub use grid::MazeGrid;
 pub use maker::Algorithm;
 use maker::MazeMaker;
+pub use symmetry::Symmetry;
 
 pub const CELL_SIZE: f32 = 64.0;
+pub const WALL_HEIGHT: f32 = CELL_SIZE;
+pub const SPACE: u8 = 0;
+pub const WALL: u8 = 1;
+pub const TELEPORTER: u8 = 2;
+pub const TELEPORTER_PAIRS: usize = 2;
 pub const RADIUS: usize = 16; // Double and add one to get the width of the maze in grid cells, including edge walls. The reason for this calculation is to ensure an odd number of chars for the width. This lets us draw a nice map with equally thick edges, no matter the value of this parameter used to set its width.
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Maze {
     // TODO: Consider making `grid` an array of arrays since its size is known and fixed.
-    pub grid: Vec<Vec<u8>>, // 0 is a space, 1 is a wall.
+    pub grid: Vec<Vec<u8>>, // 0 is a space, 1 is a wall, 2 is a teleporter pad.
     pub spaces: Vec<(usize, usize)>,
+    pub teleporters: Vec<((usize, usize), (usize, usize))>,
 }
 
 impl Maze {
     pub fn new(generator: Algorithm) -> Self {
-        let maker = MazeMaker::new(RADIUS, RADIUS, generator);
-        let grid = maker.grid;
+        Self::generate(generator, None, &mut rand::rng())
+    }
+
+    pub fn new_symmetric(generator: Algorithm, symmetry: Symmetry) -> Self {
+        Self::generate(generator, Some(symmetry), &mut rand::rng())
+    }
+
+    pub fn generate(generator: Algorithm, symmetry: Option<Symmetry>, rng: &mut impl Rng) -> Self {
+        let maker_rng = StdRng::from_rng(rng);
+        let mut grid = MazeMaker::new(RADIUS, RADIUS, generator, maker_rng).grid;
+        if let Some(symmetry) = symmetry {
+            symmetry::apply(&mut grid, symmetry);
+        }
+        let mut maze = Self::from_grid(grid);
+        maze.place_teleporters(symmetry, rng);
+        maze
+    }
+
+    fn from_grid(grid: Vec<Vec<u8>>) -> Self {
         let mut spaces = Vec::new();
 
         for (i, row) in grid.iter().enumerate() {
@@ -33,7 +60,50 @@ impl Maze {
             }
         }
 
-        Self { grid, spaces }
+        Self {
+            grid,
+            spaces,
+            teleporters: Vec::new(),
+        }
+    }
+
+    fn place_teleporters(&mut self, symmetry: Option<Symmetry>, rng: &mut impl Rng) {
+        let height = self.grid.len();
+        let width = self.grid.first().map_or(0, Vec::len);
+
+        for _ in 0..TELEPORTER_PAIRS {
+            if self.spaces.len() < 2 {
+                return;
+            }
+            let from = self.spaces[rng.random_range(0..self.spaces.len())];
+            let to = match symmetry {
+                Some(symmetry) => symmetry.counterpart(height, width, from.0, from.1),
+                None => self.spaces[rng.random_range(0..self.spaces.len())],
+            };
+            if from == to || self.grid[to.0][to.1] != SPACE {
+                continue;
+            }
+            self.add_teleporter_pair(from, to);
+        }
+    }
+
+    pub fn add_teleporter_pair(&mut self, from: (usize, usize), to: (usize, usize)) {
+        self.grid[from.0][from.1] = TELEPORTER;
+        self.grid[to.0][to.1] = TELEPORTER;
+        self.spaces.retain(|&cell| cell != from && cell != to);
+        self.teleporters.push((from, to));
+    }
+
+    pub fn teleporter_destination(&self, z: usize, x: usize) -> Option<(usize, usize)> {
+        self.teleporters.iter().find_map(|&(from, to)| {
+            if from == (z, x) {
+                Some(to)
+            } else if to == (z, x) {
+                Some(from)
+            } else {
+                None
+            }
+        })
     }
 
     pub fn is_outside(&self, x: f32, z: f32) -> bool {
@@ -80,7 +150,46 @@ impl Maze {
             .iter()
             .map(|row| {
                 row.iter()
-                    .map(|&cell| if cell == 0 { "  " } else { "██" })
+                    .map(|&cell| match cell {
+                        SPACE => "  ",
+                        TELEPORTER => "()",
+                        _ => "██",
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    pub fn to_ascii(&self) -> String {
+        self.render_ascii(|_, _, cell| match cell {
+            WALL => '#',
+            _ => ' ',
+        })
+    }
+
+    pub fn to_ascii_with_markers(&self, players: &[(usize, usize)]) -> String {
+        self.render_ascii(|z, x, cell| {
+            if players.contains(&(z, x)) {
+                '@'
+            } else {
+                match cell {
+                    WALL => '#',
+                    TELEPORTER => 'T',
+                    _ => ' ',
+                }
+            }
+        })
+    }
+
+    fn render_ascii(&self, symbol: impl Fn(usize, usize, u8) -> char) -> String {
+        self.grid
+            .iter()
+            .enumerate()
+            .map(|(z, row)| {
+                row.iter()
+                    .enumerate()
+                    .map(|(x, &cell)| symbol(z, x, cell))
                     .collect::<String>()
             })
             .collect::<Vec<String>>()
@@ -96,7 +205,7 @@ impl Maze {
         let outside_maze =
             end.x < 0.0 || end.z < 0.0 || end_x >= grid[0].len() || end_z >= grid.len();
 
-        outside_maze || grid[end_z][end_x] == 0
+        outside_maze || grid[end_z][end_x] != WALL
     }
 
     pub fn is_sphere_clear(&self, center: &Vec3, radius: f32) -> bool {
@@ -115,7 +224,7 @@ impl Maze {
                     continue;
                 }
 
-                if grid[z as usize][x as usize] == 0 {
+                if grid[z as usize][x as usize] != WALL {
                     continue;
                 }
 
@@ -169,7 +278,11 @@ impl Maze {
         }
     }
 
-    pub fn make_exit(&mut self, solo_player_grid_coords: (usize, usize)) -> (usize, usize) {
+    pub fn make_exit(
+        &mut self,
+        solo_player_grid_coords: (usize, usize),
+        rng: &mut impl Rng,
+    ) -> (usize, usize) {
         let grid = &self.grid;
         let height = grid.len();
         let width = if height > 0 { grid[0].len() } else { 0 };
@@ -177,10 +290,10 @@ impl Maze {
         let mut candidates = Vec::new();
 
         let has_space_neighbor = |z: usize, x: usize| {
-            (z > 0 && grid[z - 1][x] == 0)
-                || (z + 1 < height && grid[z + 1][x] == 0)
-                || (x > 0 && grid[z][x - 1] == 0)
-                || (x + 1 < width && grid[z][x + 1] == 0)
+            (z > 0 && grid[z - 1][x] != WALL)
+                || (z + 1 < height && grid[z + 1][x] != WALL)
+                || (x > 0 && grid[z][x - 1] != WALL)
+                || (x + 1 < width && grid[z][x + 1] != WALL)
         };
 
         let mut check_if_valid = |z, x| {
@@ -208,7 +321,7 @@ impl Maze {
         if candidates.is_empty() {
             self.punch_path_to_nearest_space_bfs(fallback)
         } else {
-            let i = rand::random_range(0..candidates.len());
+            let i = rng.random_range(0..candidates.len());
             let exit = candidates[i];
             self.grid[exit.0][exit.1] = 0;
             self.spaces.push(exit);
@@ -353,8 +466,8 @@ impl Maze {
 
         loop {
             let (z, x) = current;
-            if self.grid[z][x] != 0 {
-                self.grid[z][x] = 0;
+            if self.grid[z][x] == WALL {
+                self.grid[z][x] = SPACE;
                 self.spaces.push((z, x));
             }
             if current == exit_coords {
@@ -391,8 +504,33 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn ascii_export_renders_walls_spaces_and_markers() {
+        let maze = Maze {
+            grid: vec![
+                vec![WALL, WALL, WALL, WALL, WALL],
+                vec![WALL, SPACE, SPACE, TELEPORTER, WALL],
+                vec![WALL, WALL, WALL, WALL, WALL],
+            ],
+            spaces: vec![(1, 1), (1, 2)],
+            teleporters: Vec::new(),
+        };
+
+        assert_eq!(maze.to_ascii(), "#####\n#   #\n#####");
+        assert_eq!(maze.to_ascii_with_markers(&[(1, 1)]), "#####\n#@ T#\n#####");
+
+        let ascii = Maze::new(Algorithm::Backtrack).to_ascii();
+        let rows: Vec<&str> = ascii.lines().collect();
+        assert_eq!(rows.len(), 2 * RADIUS + 1);
+        assert!(rows.len() % 2 == 1);
+        assert!(rows.iter().all(|row| row.len() == rows.len()));
+    }
+
     fn random_algorithm() -> Algorithm {
-        let i = rand::random_range(0..10);
+        algorithm_at(rand::random_range(0..10))
+    }
+
+    fn algorithm_at(i: usize) -> Algorithm {
         match i {
             0 => Algorithm::RecursiveDivision,
             1 => Algorithm::Backtrack,
@@ -407,6 +545,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn seeded_generation_is_reproducible_for_every_algorithm() {
+        for i in 0..10 {
+            let generate = |seed| {
+                let mut rng = StdRng::seed_from_u64(seed);
+                Maze::generate(algorithm_at(i), None, &mut rng)
+            };
+            let first = generate(7);
+            let second = generate(7);
+            assert_eq!(first.grid, second.grid, "algorithm {} differs", i);
+            assert_eq!(first.teleporters, second.teleporters);
+        }
+    }
+
     #[test]
     fn test_backtrack_all_spaces_are_connected() {
         for _ in 0..64 {
@@ -479,6 +631,39 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_symmetric_mazes_match_their_counterparts_and_stay_connected() {
+        for symmetry in [Symmetry::Mirror, Symmetry::Rotational] {
+            for _ in 0..16 {
+                let maze = Maze::new_symmetric(random_algorithm(), symmetry);
+                let height = maze.grid.len();
+                let width = maze.grid[0].len();
+
+                for z in 0..height {
+                    for x in 0..width {
+                        let (cz, cx) = symmetry.counterpart(height, width, z, x);
+                        assert_eq!(
+                            maze.grid[z][x], maze.grid[cz][cx],
+                            "{} symmetry broken at ({}, {}):\n{}",
+                            symmetry, z, x, maze
+                        );
+                    }
+                }
+
+                for x in 0..width {
+                    assert_eq!(maze.grid[0][x], 1);
+                    assert_eq!(maze.grid[height - 1][x], 1);
+                }
+                for z in 0..height {
+                    assert_eq!(maze.grid[z][0], 1);
+                    assert_eq!(maze.grid[z][width - 1], 1);
+                }
+
+                assert_all_spaces_are_connected(&maze);
+            }
+        }
+    }
+
     #[test]
     fn test_voronoi_queue_all_spaces_are_connected() {
         for _ in 0..64 {
@@ -500,7 +685,7 @@ mod tests {
 
         for r in 0..height {
             for c in 0..width {
-                if grid[r][c] == 0 {
+                if grid[r][c] != WALL {
                     total_spaces += 1;
                     if start_pos.is_none() {
                         start_pos = Some((r, c));
@@ -513,7 +698,7 @@ mod tests {
         assert!(total_spaces > 1, "there should be more than one space");
 
         assert!(
-            total_spaces == maze.spaces.len(),
+            total_spaces == maze.spaces.len() + maze.teleporters.len() * 2,
             "total spaces should equal `maze.spaces.len()`, got {} and {}",
             total_spaces,
             maze.spaces.len()
@@ -539,7 +724,7 @@ mod tests {
                     let nr_u = nr as usize;
                     let nc_u = nc as usize;
 
-                    if grid[nr_u][nc_u] == 0 && !visited[nr_u][nc_u] {
+                    if grid[nr_u][nc_u] != WALL && !visited[nr_u][nc_u] {
                         visited[nr_u][nc_u] = true;
                         queue.push_back((nr_u, nc_u));
                     }
@@ -575,10 +760,14 @@ mod tests {
             }
         }
 
-        let mut maze = Maze { grid, spaces };
+        let mut maze = Maze {
+            grid,
+            spaces,
+            teleporters: Vec::new(),
+        };
 
         let solo_player_grid_coords = (1, 3);
-        let exit = maze.make_exit(solo_player_grid_coords);
+        let exit = maze.make_exit(solo_player_grid_coords, &mut rand::rng());
 
         assert_eq!(exit, (6, 3));
         assert_eq!(maze.grid[6][3], 0);
@@ -607,10 +796,14 @@ mod tests {
             }
         }
 
-        let mut maze = Maze { grid, spaces };
+        let mut maze = Maze {
+            grid,
+            spaces,
+            teleporters: Vec::new(),
+        };
 
         let solo_player_grid_coords = (1, 3);
-        let exit = maze.make_exit(solo_player_grid_coords);
+        let exit = maze.make_exit(solo_player_grid_coords, &mut rand::rng());
 
         assert_eq!(exit, (6, 3));
         assert_eq!(maze.grid[6][3], 0);
@@ -632,7 +825,7 @@ mod tests {
             let player_index = rand::random_range(0..maze.spaces.len());
             let solo_player_grid_coords = maze.spaces[player_index];
 
-            let exit = maze.make_exit(solo_player_grid_coords);
+            let exit = maze.make_exit(solo_player_grid_coords, &mut rand::rng());
 
             let height = maze.grid.len();
             assert!(height != 0, "maze should have some rows");
@@ -692,7 +885,7 @@ mod tests {
 
             let solo_player_grid_coords = (player_z + 1, player_x + 1);
 
-            let exit = maze.make_exit(solo_player_grid_coords);
+            let exit = maze.make_exit(solo_player_grid_coords, &mut rand::rng());
 
             let height = maze.grid.len();
             assert!(height != 0, "maze should have some rows");
@@ -722,7 +915,11 @@ mod tests {
                 }
             }
         }
-        let maze = Maze { grid, spaces };
+        let maze = Maze {
+            grid,
+            spaces,
+            teleporters: Vec::new(),
+        };
 
         let center = vec3(1.5 * CELL_SIZE, 0.0, 1.5 * CELL_SIZE);
         assert!(maze.is_sphere_clear(&center, 1.0));
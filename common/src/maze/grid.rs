@@ -0,0 +1,105 @@
+use glam::Vec3;
+
+use crate::maze::{CELL_SIZE, Maze, WALL};
+
+pub struct MazeGrid<'a> {
+    grid: &'a [Vec<u8>],
+}
+
+impl<'a> MazeGrid<'a> {
+    pub fn new(maze: &'a Maze) -> Self {
+        Self { grid: &maze.grid }
+    }
+
+    pub fn wall_cells_along(&self, start: Vec3, end: Vec3) -> Vec<(usize, usize)> {
+        let mut cells = Vec::new();
+
+        let mut x = (start.x / CELL_SIZE).floor() as isize;
+        let mut z = (start.z / CELL_SIZE).floor() as isize;
+        let end_x = (end.x / CELL_SIZE).floor() as isize;
+        let end_z = (end.z / CELL_SIZE).floor() as isize;
+
+        let (step_x, mut t_max_x, t_delta_x) = axis_setup(start.x, end.x, x);
+        let (step_z, mut t_max_z, t_delta_z) = axis_setup(start.z, end.z, z);
+
+        if step_x == 0 && start.x == x as f32 * CELL_SIZE {
+            self.push_segment_column(&mut cells, x - 1, z, end_z);
+        }
+        if step_z == 0 && start.z == z as f32 * CELL_SIZE {
+            self.push_segment_row(&mut cells, z - 1, x, end_x);
+        }
+
+        let steps = (end_x - x).abs() + (end_z - z).abs();
+        self.push_if_wall(&mut cells, x, z);
+        for _ in 0..steps {
+            if (x, z) == (end_x, end_z) || (t_max_x > 1.0 && t_max_z > 1.0) {
+                break;
+            }
+            if t_max_x < t_max_z {
+                x += step_x;
+                t_max_x += t_delta_x;
+            } else if t_max_z < t_max_x {
+                z += step_z;
+                t_max_z += t_delta_z;
+            } else {
+                self.push_if_wall(&mut cells, x + step_x, z);
+                self.push_if_wall(&mut cells, x, z + step_z);
+                x += step_x;
+                z += step_z;
+                t_max_x += t_delta_x;
+                t_max_z += t_delta_z;
+            }
+            self.push_if_wall(&mut cells, x, z);
+        }
+
+        cells
+    }
+
+    fn push_segment_column(
+        &self,
+        cells: &mut Vec<(usize, usize)>,
+        x: isize,
+        z: isize,
+        end_z: isize,
+    ) {
+        for check_z in z.min(end_z)..=z.max(end_z) {
+            self.push_if_wall(cells, x, check_z);
+        }
+    }
+
+    fn push_segment_row(&self, cells: &mut Vec<(usize, usize)>, z: isize, x: isize, end_x: isize) {
+        for check_x in x.min(end_x)..=x.max(end_x) {
+            self.push_if_wall(cells, check_x, z);
+        }
+    }
+
+    fn push_if_wall(&self, cells: &mut Vec<(usize, usize)>, x: isize, z: isize) {
+        if x < 0 || z < 0 {
+            return;
+        }
+        let (x, z) = (x as usize, z as usize);
+        let is_wall = self
+            .grid
+            .get(z)
+            .and_then(|row| row.get(x))
+            .is_some_and(|&cell| cell == WALL);
+        if is_wall && !cells.contains(&(x, z)) {
+            cells.push((x, z));
+        }
+    }
+}
+
+fn axis_setup(start: f32, end: f32, cell: isize) -> (isize, f32, f32) {
+    let delta = end - start;
+    if delta.abs() < 1e-6 {
+        return (0, f32::INFINITY, f32::INFINITY);
+    }
+    let t_delta = CELL_SIZE / delta.abs();
+    if delta > 0.0 {
+        let boundary = (cell + 1) as f32 * CELL_SIZE;
+        (1, (boundary - start) / delta, t_delta)
+    } else {
+        let boundary = cell as f32 * CELL_SIZE;
+        (-1, (boundary - start) / delta, t_delta)
+    }
+}
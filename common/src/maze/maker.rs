@@ -2,7 +2,10 @@ pub mod algorithms;
 
 use std::collections::HashMap;
 
-use rand::prelude::{IndexedRandom, Rng, ThreadRng};
+use rand::{
+    prelude::{IndexedRandom, Rng},
+    rngs::StdRng,
+};
 
 use algorithms::{
     backtrack::Backtrack, binary_tree::BinaryTree, blobby::Blobby, division::RecursiveDivision,
@@ -43,13 +46,13 @@ impl Cell {
     }
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
 pub enum Orientation {
     Horizontal,
     Vertical,
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
 pub struct Wall {
     pub x: usize,
     pub z: usize,
@@ -78,18 +81,22 @@ impl Wall {
 
 pub struct MazeMaker {
     pub grid: Vec<Vec<u8>>,
-    pub rng: ThreadRng,
+    pub rng: StdRng,
     width: usize,
     height: usize,
 }
 
 impl MazeMaker {
-    pub fn new(horizontal_radius: usize, vertical_radius: usize, generator: Algorithm) -> Self {
+    pub fn new(
+        horizontal_radius: usize,
+        vertical_radius: usize,
+        generator: Algorithm,
+        rng: StdRng,
+    ) -> Self {
         let width = 2 * horizontal_radius + 1;
         let height = 2 * vertical_radius + 1;
 
         let grid = vec![vec![1; width]; height];
-        let rng = rand::rng();
         let mut maze = MazeMaker {
             grid,
             width,
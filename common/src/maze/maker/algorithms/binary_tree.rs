@@ -1,4 +1,4 @@
-use rand::random_range;
+use rand::Rng;
 
 use super::super::MazeMaker;
 
@@ -42,7 +42,7 @@ impl BinaryTree for MazeMaker {
                 }
 
                 if !directions.is_empty() {
-                    let r = random_range(0..directions.len());
+                    let r = self.rng.random_range(0..directions.len());
                     let (dy, dx) = directions[r];
                     let wall_z = (z as isize + dy) as usize;
                     let wall_x = (x as isize + dx) as usize;
@@ -17,7 +17,7 @@ impl Kruskal for MazeMaker {
 
         let mut rooms = DisjointSetVec::from(rooms);
 
-        walls.shuffle(&mut rand::rng());
+        walls.shuffle(&mut self.rng);
         for wall in walls {
             let (room_1, room_2) = self.get_flanking_cells(wall);
             let i = room_to_index.get(&[room_1.x, room_1.z]).expect(&format!(
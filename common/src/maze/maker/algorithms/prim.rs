@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::BTreeSet;
 
 use rand::prelude::IteratorRandom;
 
@@ -13,7 +13,7 @@ impl Prim for MazeMaker {
         let initial_cell = self.pick_cell();
         self.visit_cell(initial_cell);
 
-        let mut frontier = HashSet::new();
+        let mut frontier = BTreeSet::new();
         add_walls(self, initial_cell, &mut frontier);
 
         while let Some(wall) = pick_wall(self, &frontier) {
@@ -46,13 +46,13 @@ fn is_wall_clear(maze: &MazeMaker, wall: Wall) -> bool {
 fn visit_new_cell_and_add_its_walls(
     maze: &mut MazeMaker,
     cell: Cell,
-    frontier: &mut HashSet<Wall>,
+    frontier: &mut BTreeSet<Wall>,
 ) {
     maze.visit_cell(cell);
     add_walls(maze, cell, frontier);
 }
 
-fn add_walls(maze: &mut MazeMaker, cell: Cell, frontier: &mut HashSet<Wall>) {
+fn add_walls(maze: &mut MazeMaker, cell: Cell, frontier: &mut BTreeSet<Wall>) {
     let neighbors = maze.get_neighbors(cell, false, false);
     for neighbor in neighbors {
         let (is_there_a_wall, wall) = is_there_a_wall_between(maze, cell, neighbor);
@@ -62,6 +62,6 @@ fn add_walls(maze: &mut MazeMaker, cell: Cell, frontier: &mut HashSet<Wall>) {
     }
 }
 
-fn pick_wall(maze: &mut MazeMaker, frontier: &HashSet<Wall>) -> Option<Wall> {
+fn pick_wall(maze: &mut MazeMaker, frontier: &BTreeSet<Wall>) -> Option<Wall> {
     frontier.iter().choose(&mut maze.rng).copied()
 }
@@ -1,4 +1,4 @@
-use std::collections::{HashSet, VecDeque};
+use std::collections::{BTreeSet, HashSet, VecDeque};
 
 use rand::Rng;
 
@@ -40,8 +40,8 @@ impl MazeMaker {
         }
 
         let rng = &mut self.rng;
-        let mut team_a_cells = HashSet::new();
-        let mut team_b_cells = HashSet::new();
+        let mut team_a_cells = BTreeSet::new();
+        let mut team_b_cells = BTreeSet::new();
         let mut frontier_a = VecDeque::new();
         let mut frontier_b = VecDeque::new();
 
@@ -130,7 +130,7 @@ impl MazeMaker {
     }
 
     fn find_enclaves(&self, cells: Vec<(usize, usize)>) -> Vec<Vec<(usize, usize)>> {
-        let mut unvisited: HashSet<(usize, usize)> = cells.into_iter().collect();
+        let mut unvisited: BTreeSet<(usize, usize)> = cells.into_iter().collect();
         let mut enclaves = Vec::new();
 
         while !unvisited.is_empty() {
@@ -0,0 +1,155 @@
+use std::{collections::VecDeque, fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Symmetry {
+    Mirror,
+    Rotational,
+}
+
+impl fmt::Display for Symmetry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Symmetry::Mirror => "mirror",
+            Symmetry::Rotational => "rotational",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Symmetry {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "mirror" => Ok(Symmetry::Mirror),
+            "rotational" | "rotate" => Ok(Symmetry::Rotational),
+            _ => Err(format!("unknown maze symmetry '{}'", s)),
+        }
+    }
+}
+
+impl Symmetry {
+    pub fn counterpart(self, height: usize, width: usize, z: usize, x: usize) -> (usize, usize) {
+        match self {
+            Symmetry::Mirror => (z, width - 1 - x),
+            Symmetry::Rotational => (height - 1 - z, width - 1 - x),
+        }
+    }
+}
+
+pub fn apply(grid: &mut [Vec<u8>], symmetry: Symmetry) {
+    let height = grid.len();
+    let width = grid.first().map_or(0, Vec::len);
+
+    for z in 0..height {
+        for x in 0..width {
+            let (source_z, source_x) = symmetry.counterpart(height, width, z, x);
+            if (source_z, source_x) < (z, x) {
+                grid[z][x] = grid[source_z][source_x];
+            }
+        }
+    }
+
+    while let Some(path) = path_to_nearest_component(grid) {
+        for (z, x) in path {
+            let (mirror_z, mirror_x) = symmetry.counterpart(height, width, z, x);
+            grid[z][x] = 0;
+            grid[mirror_z][mirror_x] = 0;
+        }
+    }
+}
+
+fn label_components(grid: &[Vec<u8>]) -> (Vec<Vec<Option<usize>>>, usize) {
+    let height = grid.len();
+    let width = grid.first().map_or(0, Vec::len);
+    let mut labels = vec![vec![None; width]; height];
+    let mut count = 0;
+
+    for z in 0..height {
+        for x in 0..width {
+            if grid[z][x] != 0 || labels[z][x].is_some() {
+                continue;
+            }
+            let mut queue = VecDeque::from([(z, x)]);
+            labels[z][x] = Some(count);
+            while let Some((cz, cx)) = queue.pop_front() {
+                for (nz, nx) in neighbors(height, width, cz, cx) {
+                    if grid[nz][nx] == 0 && labels[nz][nx].is_none() {
+                        labels[nz][nx] = Some(count);
+                        queue.push_back((nz, nx));
+                    }
+                }
+            }
+            count += 1;
+        }
+    }
+
+    (labels, count)
+}
+
+fn path_to_nearest_component(grid: &[Vec<u8>]) -> Option<Vec<(usize, usize)>> {
+    let (labels, count) = label_components(grid);
+    if count < 2 {
+        return None;
+    }
+
+    let height = grid.len();
+    let width = grid[0].len();
+    let mut previous: Vec<Vec<Option<(usize, usize)>>> = vec![vec![None; width]; height];
+    let mut visited = vec![vec![false; width]; height];
+    let mut queue = VecDeque::new();
+
+    for z in 0..height {
+        for x in 0..width {
+            if labels[z][x] == Some(0) {
+                visited[z][x] = true;
+                queue.push_back((z, x));
+            }
+        }
+    }
+
+    while let Some((z, x)) = queue.pop_front() {
+        for (nz, nx) in neighbors(height, width, z, x) {
+            let is_border = nz == 0 || nx == 0 || nz == height - 1 || nx == width - 1;
+            if visited[nz][nx] || is_border {
+                continue;
+            }
+            visited[nz][nx] = true;
+            previous[nz][nx] = Some((z, x));
+
+            if matches!(labels[nz][nx], Some(label) if label != 0) {
+                let mut path = Vec::new();
+                let mut cell = previous[nz][nx];
+                while let Some((pz, px)) = cell {
+                    if labels[pz][px].is_some() {
+                        break;
+                    }
+                    path.push((pz, px));
+                    cell = previous[pz][px];
+                }
+                return Some(path);
+            }
+
+            queue.push_back((nz, nx));
+        }
+    }
+
+    None
+}
+
+fn neighbors(
+    height: usize,
+    width: usize,
+    z: usize,
+    x: usize,
+) -> impl Iterator<Item = (usize, usize)> {
+    [(0isize, 1isize), (0, -1), (1, 0), (-1, 0)]
+        .into_iter()
+        .filter_map(move |(dz, dx)| {
+            let nz = z.checked_add_signed(dz)?;
+            let nx = x.checked_add_signed(dx)?;
+            (nz < height && nx < width).then_some((nz, nx))
+        })
+}
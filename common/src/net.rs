@@ -1,6 +1,6 @@
 use std::{
     io,
-    net::{SocketAddr, UdpSocket},
+    net::{IpAddr, SocketAddr, UdpSocket},
     time::Duration,
 };
 
@@ -66,3 +66,56 @@ pub fn bind_socket(addr: SocketAddr) -> io::Result<UdpSocket> {
     socket.bind(&addr.into())?;
     Ok(socket.into())
 }
+
+pub fn parse_socket_addr(input: &str, default_port: u16) -> Result<SocketAddr, String> {
+    let trimmed = input.trim();
+    if let Ok(addr) = trimmed.parse::<SocketAddr>() {
+        return Ok(addr);
+    }
+    if let Ok(ip) = trimmed.parse::<IpAddr>() {
+        return Ok(SocketAddr::new(ip, default_port));
+    }
+    Err(format!(
+        "invalid address '{}': expected an IP address with an optional port",
+        trimmed
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    use super::*;
+
+    #[test]
+    fn ipv4_addresses_parse_with_and_without_a_port() {
+        let ip = IpAddr::V4(Ipv4Addr::new(192, 168, 1, 50));
+        assert_eq!(
+            parse_socket_addr("192.168.1.50:6000", 5000),
+            Ok(SocketAddr::new(ip, 6000))
+        );
+        assert_eq!(
+            parse_socket_addr(" 192.168.1.50 ", 5000),
+            Ok(SocketAddr::new(ip, 5000))
+        );
+    }
+
+    #[test]
+    fn ipv6_addresses_parse_with_and_without_a_port() {
+        let ip = IpAddr::V6(Ipv6Addr::LOCALHOST);
+        assert_eq!(
+            parse_socket_addr("[::1]:6000", 5000),
+            Ok(SocketAddr::new(ip, 6000))
+        );
+        assert_eq!(
+            parse_socket_addr("::1", 5000),
+            Ok(SocketAddr::new(ip, 5000))
+        );
+    }
+
+    #[test]
+    fn invalid_addresses_are_rejected() {
+        assert!(parse_socket_addr("example", 5000).is_err());
+        assert!(parse_socket_addr("192.168.1.50:port", 5000).is_err());
+    }
+}
@@ -0,0 +1,115 @@
+use glam::Vec3;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    constants::TICK_SECS,
+    maze::Maze,
+    player::{self, MAX_HEALTH},
+};
+
+pub const RADIUS: f32 = 6.0;
+pub const HEALTH_RESTORED: u8 = 3;
+pub const RESPAWN_SECS: f64 = 20.0;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PickupKind {
+    Health,
+    Ammo,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Pickup {
+    pub id: u32,
+    pub kind: PickupKind,
+    pub position: Vec3,
+}
+
+impl Pickup {
+    pub fn is_touching(&self, player_position: Vec3) -> bool {
+        let offset = player_position - self.position;
+        offset.x.hypot(offset.z) <= player::RADIUS + RADIUS
+    }
+}
+
+pub fn restored_health(health: u8) -> Option<u8> {
+    (health > 0 && health < MAX_HEALTH)
+        .then(|| health.saturating_add(HEALTH_RESTORED).min(MAX_HEALTH))
+}
+
+pub fn respawn_ticks() -> u64 {
+    (RESPAWN_SECS / TICK_SECS).ceil() as u64
+}
+
+pub fn place_pickups(
+    maze: &Maze,
+    kinds: &[PickupKind],
+    taken: &[(usize, usize)],
+    rng: &mut impl Rng,
+) -> Vec<Pickup> {
+    let mut free: Vec<(usize, usize)> = maze
+        .spaces
+        .iter()
+        .copied()
+        .filter(|cell| !taken.contains(cell))
+        .collect();
+    kinds
+        .iter()
+        .zip(0..)
+        .map_while(|(&kind, id)| {
+            if free.is_empty() {
+                return None;
+            }
+            let (z, x) = free.swap_remove(rng.random_range(0..free.len()));
+            let position = maze.position_from_grid_coordinates(RADIUS, z, x)?;
+            Some(Pickup { id, kind, position })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+    use crate::maze::CELL_SIZE;
+
+    fn corridor() -> Maze {
+        Maze {
+            grid: vec![
+                vec![1, 1, 1, 1, 1],
+                vec![1, 0, 0, 0, 1],
+                vec![1, 1, 1, 1, 1],
+            ],
+            spaces: vec![(1, 1), (1, 2), (1, 3)],
+            teleporters: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn pickups_avoid_taken_cells_and_each_other() {
+        let maze = corridor();
+        let mut rng = StdRng::seed_from_u64(7);
+        let kinds = [PickupKind::Health, PickupKind::Ammo, PickupKind::Health];
+
+        let pickups = place_pickups(&maze, &kinds, &[(1, 2)], &mut rng);
+
+        assert_eq!(pickups.len(), 2);
+        let mut cells: Vec<usize> = pickups
+            .iter()
+            .map(|pickup| (pickup.position.x / CELL_SIZE) as usize)
+            .collect();
+        cells.sort_unstable();
+        assert_eq!(cells, vec![1, 3]);
+        assert_eq!(pickups[0].kind, PickupKind::Health);
+        assert_eq!(pickups[1].kind, PickupKind::Ammo);
+    }
+
+    #[test]
+    fn health_is_restored_up_to_the_maximum_only_when_needed() {
+        assert_eq!(restored_health(4), Some(4 + HEALTH_RESTORED));
+        assert_eq!(restored_health(MAX_HEALTH - 1), Some(MAX_HEALTH));
+        assert_eq!(restored_health(MAX_HEALTH), None);
+        assert_eq!(restored_health(0), None);
+    }
+}
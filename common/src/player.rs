@@ -1,6 +1,7 @@
 use std::{
     f32::consts::{FRAC_PI_2, PI},
     fmt,
+    str::FromStr,
 };
 
 use glam::{Vec2, Vec3, vec2, vec3};
@@ -15,6 +16,7 @@ pub const MAX_USERNAME_LENGTH: usize = 16;
 // send and to render.
 
 pub const HEIGHT: f32 = 24.0; // Height of the player's eye level from the ground.
+pub const CROUCH_HEIGHT: f32 = 12.0; // Eye level while crouching.
 pub const RADIUS: f32 = 8.0;
 pub const MAX_SPEED: f32 = 240.0; // Units per second.
 pub const ACCELERATION: f32 = 1200.0; // Reaches max in 0.2 seconds.
@@ -22,7 +24,51 @@ pub const FRICTION: f32 = 5.0;
 pub const MAX_ROTATION_SPEED: f32 = 4.0 * PI; // 2 turns per second.
 pub const ROTATION_ACCELERATION: f32 = (MAX_ROTATION_SPEED / 0.4) * PI; // Max in 0.4 seconds.
 pub const ROTATION_FRICTION: f32 = 10.0; // Stop in ~0.2 seconds when key is released.
+pub const MIN_SENSITIVITY_PERCENT: u16 = 25;
+pub const MAX_SENSITIVITY_PERCENT: u16 = 400;
 pub const MAX_HEALTH: u8 = 9;
+pub const REST_PITCH: f32 = 0.1;
+const AUTO_CENTER_TOLERANCE: f32 = 0.02;
+pub const JUMP_SPEED: f32 = 250.0; // Units per second.
+pub const GRAVITY: f32 = 960.0; // Units per second squared.
+pub const LOW_GRAVITY: f32 = GRAVITY / 4.0;
+const MIN_CORRECTION: f32 = 0.01;
+const MAX_SMOOTHED_CORRECTION: f32 = 32.0;
+const CORRECTION_DECAY: f32 = 0.85;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct MovementProfile {
+    pub max_speed: f32,
+    pub acceleration: f32,
+    pub friction: f32,
+    pub gravity: f32,
+}
+
+impl Default for MovementProfile {
+    fn default() -> Self {
+        Self {
+            max_speed: MAX_SPEED,
+            acceleration: ACCELERATION,
+            friction: FRICTION,
+            gravity: GRAVITY,
+        }
+    }
+}
+
+impl MovementProfile {
+    pub fn arcade() -> Self {
+        Self {
+            max_speed: 320.0,
+            acceleration: 3200.0,
+            friction: FRICTION,
+            gravity: GRAVITY,
+        }
+    }
+
+    pub fn with_gravity(self, gravity: f32) -> Self {
+        Self { gravity, ..self }
+    }
+}
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct Player {
@@ -34,6 +80,7 @@ pub struct Player {
     pub disconnected: bool,
     pub current_tick: u64,
     pub health: u8,
+    pub team: Option<u8>,
 }
 
 impl Player {
@@ -47,6 +94,7 @@ impl Player {
             disconnected: false,
             current_tick: 0,
             health: MAX_HEALTH,
+            team: None,
         }
     }
 
@@ -59,6 +107,7 @@ impl Player {
 pub struct PlayerState {
     pub position: Vec3,
     pub velocity: Vec3,
+    pub vertical_velocity: f32,
 
     pub yaw: f32,
     pub pitch: f32,
@@ -67,6 +116,7 @@ pub struct PlayerState {
     pub pitch_velocity: f32,
 
     pub is_zoomed: bool,
+    pub is_crouching: bool,
 }
 
 impl PlayerState {
@@ -74,11 +124,13 @@ impl PlayerState {
         Self {
             position,
             velocity: vec3(0.0, 0.0, 0.0),
-            pitch: 0.1,
+            vertical_velocity: 0.0,
+            pitch: REST_PITCH,
             yaw: 0.0,
             pitch_velocity: 0.0,
             yaw_velocity: 0.0,
             is_zoomed: false,
+            is_crouching: false,
         }
     }
 
@@ -86,17 +138,70 @@ impl PlayerState {
         &mut self,
         maze: &Maze,
         input: &PlayerInput,
+        movement: &MovementProfile,
         own_index: usize,
         player_positions: &Vec<(usize, Vec3)>,
         repulsion_strength: f32,
     ) {
+        let previous_cell = maze.grid_coordinates_from_position(&self.position);
         let forward = self.apply_rotation(input);
-        self.apply_translation(input, forward);
+        self.apply_translation(input, forward, movement);
         self.resolve_collision_with_walls(maze);
+        self.apply_teleport(maze, previous_cell);
         self.resolve_collision_with_other_players(own_index, player_positions, repulsion_strength);
+        self.apply_vertical_movement(input, movement.gravity);
         self.is_zoomed = input.is_zoomed;
     }
 
+    pub fn eye_height(&self) -> f32 {
+        if self.is_crouching {
+            CROUCH_HEIGHT
+        } else {
+            HEIGHT
+        }
+    }
+
+    pub fn correct_to(&mut self, authoritative: &WirePlayerLocal) {
+        self.position = vec3(
+            authoritative.position.x,
+            authoritative.height,
+            authoritative.position.y,
+        );
+        self.velocity = vec3(authoritative.velocity.x, 0.0, authoritative.velocity.y);
+        self.vertical_velocity = authoritative.vertical_velocity;
+        self.yaw = authoritative.yaw;
+        self.pitch = authoritative.pitch;
+        self.yaw_velocity = authoritative.yaw_velocity;
+        self.pitch_velocity = authoritative.pitch_velocity;
+        self.is_zoomed = authoritative.is_zoomed;
+        self.is_crouching = authoritative.is_crouching;
+    }
+
+    pub fn is_grounded(&self) -> bool {
+        self.position.y <= self.eye_height()
+    }
+    fn apply_vertical_movement(&mut self, input: &PlayerInput, gravity: f32) {
+        let was_grounded = self.is_grounded();
+        self.is_crouching = input.crouch;
+        let ground = self.eye_height();
+
+        if was_grounded {
+            self.position.y = ground;
+            if !input.jump {
+                return;
+            }
+            self.vertical_velocity = JUMP_SPEED;
+        }
+
+        self.vertical_velocity -= gravity * TICK_SECS_F32;
+        self.position.y += self.vertical_velocity * TICK_SECS_F32;
+
+        if self.position.y <= ground {
+            self.position.y = ground;
+            self.vertical_velocity = 0.0;
+        }
+    }
+
     fn apply_rotation(&mut self, input: &PlayerInput) -> Vec3 {
         let mut yaw_wish = 0.0;
         if input.yaw_left {
@@ -114,16 +219,19 @@ impl PlayerState {
             pitch_wish -= 1.0;
         }
 
+        let sensitivity = input.rotation_sensitivity.multiplier();
         Self::apply_axis_rotation(
             &mut self.yaw,
             &mut self.yaw_velocity,
-            yaw_wish,
+            yaw_wish * sensitivity,
+            MAX_ROTATION_SPEED * sensitivity,
             self.is_zoomed,
         );
         Self::apply_axis_rotation(
             &mut self.pitch,
             &mut self.pitch_velocity,
-            pitch_wish,
+            pitch_wish * sensitivity,
+            MAX_ROTATION_SPEED * sensitivity,
             self.is_zoomed,
         );
 
@@ -134,7 +242,12 @@ impl PlayerState {
         forward
     }
 
-    fn apply_translation(&mut self, input: &PlayerInput, forward: Vec3) {
+    fn apply_translation(
+        &mut self,
+        input: &PlayerInput,
+        forward: Vec3,
+        movement: &MovementProfile,
+    ) {
         let mut move_wish = Vec3::ZERO;
         let right = vec3(-forward.z, 0.0, forward.x);
         if input.forward {
@@ -154,15 +267,15 @@ impl PlayerState {
             move_wish = move_wish.normalize();
         }
 
-        self.velocity += move_wish * ACCELERATION * TICK_SECS_F32;
+        self.velocity += move_wish * movement.acceleration * TICK_SECS_F32;
 
         let current_speed = self.velocity.length();
         if current_speed > 0.0 {
-            let drop = current_speed * FRICTION * TICK_SECS_F32;
+            let drop = current_speed * movement.friction * TICK_SECS_F32;
             let new_speed = (current_speed - drop).max(0.0);
 
-            if current_speed > MAX_SPEED {
-                self.velocity = self.velocity.normalize() * MAX_SPEED;
+            if current_speed > movement.max_speed {
+                self.velocity = self.velocity.normalize() * movement.max_speed;
             } else {
                 self.velocity *= new_speed / current_speed;
             }
@@ -196,6 +309,22 @@ impl PlayerState {
         }
     }
 
+    fn apply_teleport(&mut self, maze: &Maze, previous_cell: Option<(u8, u8)>) {
+        let Some((x, z)) = maze.grid_coordinates_from_position(&self.position) else {
+            return;
+        };
+        if previous_cell == Some((x, z)) {
+            return;
+        }
+        let Some((to_z, to_x)) = maze.teleporter_destination(z as usize, x as usize) else {
+            return;
+        };
+        if let Some(destination) = maze.position_from_grid_coordinates(self.position.y, to_z, to_x)
+        {
+            self.position = destination;
+        }
+    }
+
     fn resolve_collision_with_other_players(
         &mut self,
         own_index: usize,
@@ -228,13 +357,19 @@ impl PlayerState {
         }
     }
 
-    fn apply_axis_rotation(angle: &mut f32, velocity: &mut f32, wish: f32, is_zoomed: bool) {
+    fn apply_axis_rotation(
+        angle: &mut f32,
+        velocity: &mut f32,
+        wish: f32,
+        max_speed: f32,
+        is_zoomed: bool,
+    ) {
         let is_driving =
             wish.abs() > 0.0 && (velocity.abs() < 0.001 || wish.signum() == velocity.signum());
 
         match is_driving {
             true => {
-                let current_ratio = velocity.abs() / MAX_ROTATION_SPEED;
+                let current_ratio = velocity.abs() / max_speed;
 
                 // "Initial responsiveness" (proportion of maximum acceleration
                 // available initially) + "the rest of the acceleration" * "the
@@ -247,8 +382,8 @@ impl PlayerState {
 
                 *velocity += wish * (ROTATION_ACCELERATION * ramp_multiplier) * TICK_SECS_F32;
 
-                if velocity.abs() > MAX_ROTATION_SPEED {
-                    *velocity = velocity.signum() * MAX_ROTATION_SPEED;
+                if velocity.abs() > max_speed {
+                    *velocity = velocity.signum() * max_speed;
                 }
             }
             false => {
@@ -271,9 +406,31 @@ impl PlayerState {
     }
 }
 
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CorrectionOffset(pub Vec3);
+
+impl CorrectionOffset {
+    pub fn absorb(&mut self, predicted: Vec3, corrected: Vec3) {
+        let error = predicted + self.0 - corrected;
+        self.0 = if (MIN_CORRECTION..=MAX_SMOOTHED_CORRECTION).contains(&error.length()) {
+            error
+        } else {
+            Vec3::ZERO
+        };
+    }
+
+    pub fn decay(&mut self) {
+        self.0 *= CORRECTION_DECAY;
+        if self.0.length() < MIN_CORRECTION {
+            self.0 = Vec3::ZERO;
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
 pub struct WirePlayerRemote {
     pub position: Vec2,
+    pub height: f32,
     pub yaw: f32,
     pub pitch: f32,
 }
@@ -282,6 +439,7 @@ impl From<PlayerState> for WirePlayerRemote {
     fn from(player_state: PlayerState) -> Self {
         Self {
             position: vec2(player_state.position.x, player_state.position.z),
+            height: player_state.position.y,
             yaw: player_state.yaw,
             pitch: player_state.pitch,
         }
@@ -292,6 +450,8 @@ impl From<PlayerState> for WirePlayerRemote {
 pub struct WirePlayerLocal {
     pub position: Vec2,
     pub velocity: Vec2,
+    pub height: f32,
+    pub vertical_velocity: f32,
 
     pub yaw: f32,
     pub pitch: f32,
@@ -300,6 +460,7 @@ pub struct WirePlayerLocal {
     pub pitch_velocity: f32,
 
     pub is_zoomed: bool,
+    pub is_crouching: bool,
 }
 
 impl From<PlayerState> for WirePlayerLocal {
@@ -307,6 +468,8 @@ impl From<PlayerState> for WirePlayerLocal {
         Self {
             position: vec2(player_state.position.x, player_state.position.z),
             velocity: vec2(player_state.velocity.x, player_state.velocity.z),
+            height: player_state.position.y,
+            vertical_velocity: player_state.vertical_velocity,
 
             yaw: player_state.yaw,
             pitch: player_state.pitch,
@@ -315,6 +478,7 @@ impl From<PlayerState> for WirePlayerLocal {
             pitch_velocity: player_state.pitch_velocity,
 
             is_zoomed: player_state.is_zoomed,
+            is_crouching: player_state.is_crouching,
         }
     }
 }
@@ -333,6 +497,82 @@ pub struct PlayerInput {
     pub fire: bool,
     pub fire_nonce: Option<u32>,
     pub is_zoomed: bool,
+    pub jump: bool,
+    pub crouch: bool,
+    pub rotation_sensitivity: RotationSensitivity,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
+pub struct RotationSensitivity(u16);
+
+impl RotationSensitivity {
+    pub fn from_percent(percent: u16) -> Self {
+        Self(percent.clamp(MIN_SENSITIVITY_PERCENT, MAX_SENSITIVITY_PERCENT))
+    }
+
+    pub fn multiplier(self) -> f32 {
+        self.0
+            .clamp(MIN_SENSITIVITY_PERCENT, MAX_SENSITIVITY_PERCENT) as f32
+            / 100.0
+    }
+}
+
+impl Default for RotationSensitivity {
+    fn default() -> Self {
+        Self(100)
+    }
+}
+
+impl FromStr for RotationSensitivity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().parse::<f32>() {
+            Ok(multiplier) if multiplier.is_finite() && multiplier > 0.0 => {
+                Ok(Self::from_percent((multiplier * 100.0).round() as u16))
+            }
+            _ => Err(format!("invalid rotation sensitivity '{}'", s)),
+        }
+    }
+}
+
+impl PlayerInput {
+    pub fn is_idle(&self) -> bool {
+        !(self.forward
+            || self.backward
+            || self.left
+            || self.right
+            || self.yaw_left
+            || self.yaw_right
+            || self.pitch_up
+            || self.pitch_down
+            || self.fire
+            || self.jump
+            || self.crouch)
+    }
+
+    pub fn auto_center_pitch(&mut self, state: &PlayerState) {
+        if self.pitch_up || self.pitch_down {
+            return;
+        }
+        let offset_at_stop = |input: &PlayerInput| {
+            let mut state = *state;
+            state.apply_rotation(input);
+            REST_PITCH - (state.pitch + state.pitch_velocity / ROTATION_FRICTION)
+        };
+        let offset = offset_at_stop(self);
+        if offset.abs() <= AUTO_CENTER_TOLERANCE {
+            return;
+        }
+        let pressed = PlayerInput {
+            pitch_up: offset > 0.0,
+            pitch_down: offset < 0.0,
+            ..*self
+        };
+        if offset_at_stop(&pressed).abs() < offset.abs() {
+            *self = pressed;
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, Display, IntoStaticStr, PartialEq, Eq)]
@@ -365,6 +605,10 @@ pub const COLORS: [Color; 10] = [
     Color::RED,
 ];
 
+pub fn next_available_color(taken: &[Color]) -> Option<Color> {
+    COLORS.into_iter().find(|color| !taken.contains(color))
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum UsernameError {
     Empty,
@@ -388,14 +632,14 @@ impl fmt::Display for UsernameError {
 
 impl std::error::Error for UsernameError {}
 
-pub fn sanitize_username(input: &str) -> Result<String, UsernameError> {
+pub fn sanitize_username(input: &str, max_length: usize) -> Result<String, UsernameError> {
     let trimmed = input.trim();
 
     if trimmed.is_empty() {
         return Err(UsernameError::Empty);
     }
 
-    if trimmed.chars().count() > MAX_USERNAME_LENGTH {
+    if trimmed.chars().count() > max_length {
         return Err(UsernameError::TooLong);
     }
 
@@ -422,22 +666,56 @@ pub fn sanitize_username(input: &str) -> Result<String, UsernameError> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{
+        maze::{CELL_SIZE, SPACE, WALL},
+        ring::Ring,
+    };
+
+    #[test]
+    fn next_available_color_skips_taken_colors_in_palette_order() {
+        assert_eq!(next_available_color(&[]), Some(COLORS[0]));
+        assert_eq!(
+            next_available_color(&[COLORS[0], COLORS[2]]),
+            Some(COLORS[1])
+        );
+        assert_eq!(next_available_color(&[COLORS[1]]), Some(COLORS[0]));
+    }
+
+    #[test]
+    fn next_available_color_is_none_once_the_palette_is_exhausted() {
+        assert_eq!(next_available_color(&COLORS), None);
+    }
 
     #[test]
     fn sanitize_rejects_empty_usernames() {
-        assert_eq!(sanitize_username("   "), Err(UsernameError::Empty));
+        assert_eq!(
+            sanitize_username("   ", MAX_USERNAME_LENGTH),
+            Err(UsernameError::Empty)
+        );
     }
 
     #[test]
     fn sanitize_rejects_usernames_that_are_too_long() {
         let long_name = "abcdefghijklmnopq"; // 17 characters, one more than permitted.
-        assert_eq!(sanitize_username(long_name), Err(UsernameError::TooLong));
+        assert_eq!(
+            sanitize_username(long_name, MAX_USERNAME_LENGTH),
+            Err(UsernameError::TooLong)
+        );
+    }
+
+    #[test]
+    fn sanitize_applies_a_shorter_length_limit() {
+        assert_eq!(sanitize_username("abcdefgh", 8), Ok("abcdefgh".to_string()));
+        assert_eq!(
+            sanitize_username("abcdefghi", 8),
+            Err(UsernameError::TooLong)
+        );
     }
 
     #[test]
     fn sanitize_rejects_usernames_with_invalid_characters() {
         assert_eq!(
-            sanitize_username("user!"),
+            sanitize_username("user!", MAX_USERNAME_LENGTH),
             Err(UsernameError::InvalidCharacter('!'))
         );
     }
@@ -445,12 +723,380 @@ mod tests {
     #[test]
     fn sanitize_accepts_valid_usernames() {
         let name = "Player_1";
-        assert_eq!(sanitize_username(name), Ok("player_1".to_string()));
+        assert_eq!(
+            sanitize_username(name, MAX_USERNAME_LENGTH),
+            Ok("player_1".to_string())
+        );
+    }
+
+    fn yaw_after_holding_left(sensitivity: RotationSensitivity, ticks: usize) -> PlayerState {
+        let maze = Maze::new(crate::maze::Algorithm::Backtrack);
+        let mut state = grounded_state(&maze);
+        let input = PlayerInput {
+            yaw_left: true,
+            rotation_sensitivity: sensitivity,
+            ..Default::default()
+        };
+        for _ in 0..ticks {
+            state.update(
+                &maze,
+                &input,
+                &MovementProfile::default(),
+                0,
+                &Vec::new(),
+                0.0,
+            );
+        }
+        state
+    }
+
+    #[test]
+    fn sensitivity_scales_rotation_speed_and_cap() {
+        let normal = yaw_after_holding_left(RotationSensitivity::default(), 10);
+        let fast = yaw_after_holding_left(RotationSensitivity::from_percent(200), 10);
+        let slow = yaw_after_holding_left(RotationSensitivity::from_percent(50), 10);
+        assert!(fast.yaw > normal.yaw);
+        assert!(slow.yaw < normal.yaw);
+
+        let fast = yaw_after_holding_left(RotationSensitivity::from_percent(200), 120);
+        let slow = yaw_after_holding_left(RotationSensitivity::from_percent(50), 120);
+        assert!((fast.yaw_velocity - 2.0 * MAX_ROTATION_SPEED).abs() < 1e-3);
+        assert!((slow.yaw_velocity - 0.5 * MAX_ROTATION_SPEED).abs() < 1e-3);
+    }
+
+    #[test]
+    fn sensitivity_parses_multipliers_and_clamps_to_range() {
+        assert_eq!("1.5".parse(), Ok(RotationSensitivity::from_percent(150)));
+        assert_eq!(
+            "100".parse::<RotationSensitivity>().unwrap().multiplier(),
+            MAX_SENSITIVITY_PERCENT as f32 / 100.0
+        );
+        assert_eq!(
+            "0.01".parse::<RotationSensitivity>().unwrap().multiplier(),
+            MIN_SENSITIVITY_PERCENT as f32 / 100.0
+        );
+        assert!("-1".parse::<RotationSensitivity>().is_err());
+        assert!("fast".parse::<RotationSensitivity>().is_err());
     }
 
     #[test]
     fn sanitize_trims_whitespace() {
         let name = "  Player-2  ";
-        assert_eq!(sanitize_username(name), Ok("player-2".to_string()));
+        assert_eq!(
+            sanitize_username(name, MAX_USERNAME_LENGTH),
+            Ok("player-2".to_string())
+        );
+    }
+
+    fn ticks_to_reach_max_speed(movement: &MovementProfile) -> usize {
+        let mut state = PlayerState::new(Vec3::ZERO);
+        let input = PlayerInput {
+            forward: true,
+            ..Default::default()
+        };
+        (1..=600)
+            .find(|_| {
+                state.apply_translation(&input, vec3(0.0, 0.0, -1.0), movement);
+                state.velocity.length() >= 0.9 * movement.max_speed
+            })
+            .expect("should approach max speed within ten seconds")
+    }
+
+    #[test]
+    fn higher_acceleration_reaches_max_speed_in_fewer_ticks() {
+        let default = MovementProfile::default();
+        let snappy = MovementProfile {
+            acceleration: default.acceleration * 4.0,
+            ..default
+        };
+
+        assert!(ticks_to_reach_max_speed(&snappy) < ticks_to_reach_max_speed(&default));
+        assert!(
+            ticks_to_reach_max_speed(&MovementProfile::arcade())
+                < ticks_to_reach_max_speed(&default)
+        );
+    }
+
+    #[test]
+    fn replaying_inputs_after_a_correction_converges_on_the_server_state() {
+        let maze = Maze::new(crate::maze::Algorithm::Backtrack);
+        let movement = MovementProfile::default();
+        let mut history: Ring<PlayerInput, 16> = Ring::new();
+        for tick in 0..12 {
+            history.insert(
+                tick,
+                PlayerInput {
+                    sim_tick: tick,
+                    forward: true,
+                    yaw_left: tick % 3 == 0,
+                    ..Default::default()
+                },
+            );
+        }
+        let step = |state: &mut PlayerState, tick: u64| {
+            let input = history.get(tick).expect("input should be buffered");
+            state.update(&maze, input, &movement, 0, &Vec::new(), 0.0);
+        };
+
+        let mut server = grounded_state(&maze);
+        let mut predicted = server;
+        predicted.position.x += 3.0;
+        let mut acknowledged = None;
+        for tick in 0..12 {
+            step(&mut server, tick);
+            step(&mut predicted, tick);
+            if tick == 5 {
+                acknowledged = Some(WirePlayerLocal::from(server));
+            }
+        }
+        assert_ne!(predicted.position, server.position);
+
+        predicted.correct_to(&acknowledged.expect("snapshot should be taken"));
+        for tick in 6..12 {
+            step(&mut predicted, tick);
+        }
+
+        assert!(predicted.position.distance(server.position) < 1e-3);
+        assert!(predicted.velocity.distance(server.velocity) < 1e-3);
+        assert_eq!(predicted.yaw, server.yaw);
+    }
+
+    #[test]
+    fn small_corrections_are_smoothed_and_large_ones_snap() {
+        let mut offset = CorrectionOffset::default();
+        offset.absorb(Vec3::new(2.0, 0.0, 0.0), Vec3::ZERO);
+        assert_eq!(offset.0, Vec3::new(2.0, 0.0, 0.0));
+
+        offset.decay();
+        assert!(offset.0.x > 0.0 && offset.0.x < 2.0);
+        for _ in 0..100 {
+            offset.decay();
+        }
+        assert_eq!(offset.0, Vec3::ZERO);
+
+        offset.absorb(Vec3::ZERO, Vec3::new(0.0, 0.0, 500.0));
+        assert_eq!(offset.0, Vec3::ZERO);
+    }
+
+    fn grounded_state(maze: &Maze) -> PlayerState {
+        let (z, x) = maze.spaces[0];
+        let position = maze
+            .position_from_grid_coordinates(HEIGHT, z, x)
+            .expect("maze should have open cells");
+        PlayerState::new(position)
+    }
+
+    fn ticks_airborne_after_jump(movement: &MovementProfile) -> usize {
+        let maze = Maze::new(crate::maze::Algorithm::Backtrack);
+        let mut state = grounded_state(&maze);
+        let jump = PlayerInput {
+            jump: true,
+            ..Default::default()
+        };
+        state.update(&maze, &jump, movement, 0, &Vec::new(), 0.0);
+        (1..=600)
+            .find(|_| {
+                state.update(
+                    &maze,
+                    &PlayerInput::default(),
+                    movement,
+                    0,
+                    &Vec::new(),
+                    0.0,
+                );
+                state.is_grounded()
+            })
+            .expect("should land within ten seconds")
+    }
+
+    #[test]
+    fn lower_gravity_keeps_a_jumping_player_airborne_longer() {
+        let default = MovementProfile::default();
+        let low = default.with_gravity(LOW_GRAVITY);
+
+        assert!(ticks_airborne_after_jump(&low) > ticks_airborne_after_jump(&default));
+    }
+
+    #[test]
+    fn jump_rises_then_lands_after_expected_ticks() {
+        let maze = Maze::new(crate::maze::Algorithm::Backtrack);
+        let mut state = grounded_state(&maze);
+        let start = state.position;
+        let jump = PlayerInput {
+            jump: true,
+            ..Default::default()
+        };
+        let idle = PlayerInput::default();
+
+        state.update(
+            &maze,
+            &jump,
+            &MovementProfile::default(),
+            0,
+            &Vec::new(),
+            0.0,
+        );
+        assert!(state.position.y > HEIGHT);
+
+        let mut peak = state.position.y;
+        for _ in 1..30 {
+            state.update(
+                &maze,
+                &idle,
+                &MovementProfile::default(),
+                0,
+                &Vec::new(),
+                0.0,
+            );
+            assert!(!state.is_grounded());
+            peak = peak.max(state.position.y);
+        }
+        assert!(peak > HEIGHT + 30.0);
+
+        state.update(
+            &maze,
+            &idle,
+            &MovementProfile::default(),
+            0,
+            &Vec::new(),
+            0.0,
+        );
+        assert!(state.is_grounded());
+        assert_eq!(state.position.y, HEIGHT);
+        assert_eq!(state.vertical_velocity, 0.0);
+        assert_eq!((state.position.x, state.position.z), (start.x, start.z));
+    }
+
+    #[test]
+    fn holding_jump_in_the_air_does_not_boost() {
+        let maze = Maze::new(crate::maze::Algorithm::Backtrack);
+        let jump = PlayerInput {
+            jump: true,
+            ..Default::default()
+        };
+        let mut held = grounded_state(&maze);
+        let mut tapped = grounded_state(&maze);
+
+        held.update(
+            &maze,
+            &jump,
+            &MovementProfile::default(),
+            0,
+            &Vec::new(),
+            0.0,
+        );
+        tapped.update(
+            &maze,
+            &jump,
+            &MovementProfile::default(),
+            0,
+            &Vec::new(),
+            0.0,
+        );
+        for _ in 0..10 {
+            held.update(
+                &maze,
+                &jump,
+                &MovementProfile::default(),
+                0,
+                &Vec::new(),
+                0.0,
+            );
+            tapped.update(
+                &maze,
+                &PlayerInput::default(),
+                &MovementProfile::default(),
+                0,
+                &Vec::new(),
+                0.0,
+            );
+        }
+
+        assert_eq!(held.position.y, tapped.position.y);
+    }
+
+    fn corridor_with_teleporters() -> Maze {
+        let mut grid = vec![vec![WALL; 5]; 5];
+        for row in [1, 3] {
+            grid[row][1..4].fill(SPACE);
+        }
+        let spaces = (1..4).flat_map(|x| [(1, x), (3, x)]).collect();
+        let mut maze = Maze {
+            grid,
+            spaces,
+            teleporters: Vec::new(),
+        };
+        maze.add_teleporter_pair((1, 2), (3, 2));
+        maze
+    }
+
+    #[test]
+    fn stepping_onto_a_teleporter_moves_player_to_its_partner() {
+        let maze = corridor_with_teleporters();
+        let mut state = PlayerState::new(vec3(2.0 * CELL_SIZE - 1.0, HEIGHT, 1.5 * CELL_SIZE));
+        state.velocity = vec3(MAX_SPEED, 0.0, 0.0);
+
+        state.update(
+            &maze,
+            &PlayerInput::default(),
+            &MovementProfile::default(),
+            0,
+            &Vec::new(),
+            0.0,
+        );
+
+        assert_eq!(
+            state.position,
+            vec3(2.5 * CELL_SIZE, HEIGHT, 3.5 * CELL_SIZE)
+        );
+    }
+
+    #[test]
+    fn standing_on_a_teleporter_does_not_bounce_back() {
+        let maze = corridor_with_teleporters();
+        let arrival = vec3(2.5 * CELL_SIZE, HEIGHT, 3.5 * CELL_SIZE);
+        let mut state = PlayerState::new(arrival);
+
+        for _ in 0..10 {
+            state.update(
+                &maze,
+                &PlayerInput::default(),
+                &MovementProfile::default(),
+                0,
+                &Vec::new(),
+                0.0,
+            );
+        }
+
+        assert_eq!(state.position, arrival);
+    }
+
+    #[test]
+    fn auto_centering_presses_pitch_keys_until_the_view_settles_at_rest() {
+        let mut state = PlayerState::new(Vec3::ZERO);
+        state.pitch = 1.0;
+        let mut pitches = Vec::new();
+        let mut presses = 0;
+
+        for _ in 0..120 {
+            let mut input = PlayerInput::default();
+            input.auto_center_pitch(&state);
+            assert!(!input.pitch_up);
+            presses += input.pitch_down as usize;
+            state.apply_rotation(&input);
+            pitches.push(state.pitch);
+        }
+
+        assert!(presses > 0);
+        assert!(pitches.windows(2).all(|pair| pair[1] <= pair[0]));
+        let settled = *pitches.last().unwrap();
+        assert!((settled - REST_PITCH).abs() <= AUTO_CENTER_TOLERANCE);
+
+        let mut held = PlayerInput {
+            pitch_up: true,
+            ..Default::default()
+        };
+        held.auto_center_pitch(&state);
+        assert!(held.pitch_up && !held.pitch_down);
     }
 }
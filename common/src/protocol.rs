@@ -1,18 +1,30 @@
+use bincode::config::{Configuration, standard};
 use glam::Vec3;
 use serde::{Deserialize, Serialize};
 use strum::Display;
 
 use crate::{
+    bullets::ImpactSurface,
+    pickups::Pickup,
     player::{Color, PlayerInput},
     ring::WireItem,
     snapshot::{InitialData, Snapshot},
 };
 
-pub const AUTH_INCORRECT_PASSCODE_DISCONNECTING_MESSAGE: &str =
-    "Incorrect passcode. Disconnecting.";
 pub const AUTH_INCORRECT_PASSCODE_TRY_AGAIN_MESSAGE: &str = "Incorrect passcode. Try again.";
 pub const GAME_ALREADY_STARTED_MESSAGE: &str =
     "The game is already in progress. Please try again after this match.";
+pub const SERVER_SHUTTING_DOWN_MESSAGE: &str = "The server is shutting down.";
+pub const SERVER_FULL_MESSAGE: &str = "The server is full. Please try again later.";
+pub const KICKED_MESSAGE: &str = "You were removed from the server.";
+pub const AUTH_FAILED_MESSAGE: &str = "Incorrect passcode. Disconnecting.";
+pub const PROTOCOL_ERROR_MESSAGE: &str =
+    "The server did not understand your client. Please check that you have the latest version.";
+pub const DUPLICATE_CONNECTION_MESSAGE: &str = "You are already connected to this server.";
+
+pub fn config() -> Configuration {
+    standard()
+}
 
 pub fn auth_success_message(max_username_length: usize) -> String {
     format!(
@@ -21,6 +33,29 @@ pub fn auth_success_message(max_username_length: usize) -> String {
     )
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisconnectReason {
+    ServerFull,
+    Kicked,
+    FailedAuth,
+    ShuttingDown,
+    ProtocolError,
+    DuplicateConnection,
+}
+
+impl DisconnectReason {
+    pub fn message(self) -> &'static str {
+        match self {
+            Self::ServerFull => SERVER_FULL_MESSAGE,
+            Self::Kicked => KICKED_MESSAGE,
+            Self::FailedAuth => AUTH_FAILED_MESSAGE,
+            Self::ShuttingDown => SERVER_SHUTTING_DOWN_MESSAGE,
+            Self::ProtocolError => PROTOCOL_ERROR_MESSAGE,
+            Self::DuplicateConnection => DUPLICATE_CONNECTION_MESSAGE,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Display)]
 #[strum(serialize_all = "lowercase")]
 pub enum AfterGameExitReason {
@@ -37,18 +72,54 @@ pub struct AfterGameLeaderboardEntry {
     pub color: Color,
     pub ticks_survived: u64,
     pub exit_reason: AfterGameExitReason,
+    pub kills: u32,
+    pub deaths: u32,
+    pub self_kills: u32,
+    pub shots_fired: u32,
+    pub hits_landed: u32,
+    pub damage_dealt: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AfterGameAward {
+    pub title: String,
+    pub recipients: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RoundStanding {
+    pub username: String,
+    pub points: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RoundSummary {
+    pub round: u8,
+    pub total_rounds: u8,
+    pub standings: Vec<RoundStanding>,
+    pub next_round_follows: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RoomSummary {
+    pub code: String,
+    pub players: usize,
+    pub state: String,
+    pub passcode_protected: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PlayerRosterEntry {
     pub username: String,
     pub color: Color,
+    pub team: Option<u8>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub enum ServerMessage {
     Snapshot(WireItem<Snapshot>),
     BulletEvent(BulletEvent),
+    PickupEvent(PickupEvent),
     ServerTime(f64),
     CountdownStarted {
         end_time: f64,
@@ -57,6 +128,12 @@ pub enum ServerMessage {
     Welcome {
         username: String,
         color: Color,
+        team: Option<u8>,
+        available_colors: Vec<Color>,
+        max_chat_bytes: usize,
+    },
+    ColorAssigned {
+        color: Color,
     },
     UsernameError {
         message: String,
@@ -64,6 +141,7 @@ pub enum ServerMessage {
     AppointHost,
     Roster {
         online: Vec<PlayerRosterEntry>,
+        connecting: usize,
     },
     UserJoined {
         username: String,
@@ -71,6 +149,7 @@ pub enum ServerMessage {
     UserLeft {
         username: String,
     },
+    PlayerCount(usize),
     ChatMessage {
         username: String,
         color: Color,
@@ -81,15 +160,51 @@ pub enum ServerMessage {
     },
     AfterGameLeaderboard {
         entries: Vec<AfterGameLeaderboardEntry>,
+        awards: Vec<AfterGameAward>,
+        rounds: Option<RoundSummary>,
     },
     ServerInfo {
         message: String,
     },
+    AuthRequired {
+        max_attempts: u8,
+    },
+    AuthSucceeded {
+        max_username_length: usize,
+    },
+    ChatCooldown {
+        seconds_remaining: f32,
+    },
+    SpectatorCount {
+        watching: usize,
+    },
+    GamePaused {
+        paused: bool,
+        timer_shift_secs: f64,
+    },
+    PlayerKilled {
+        victim: usize,
+        killer: usize,
+    },
+    PlayerAfk {
+        index: usize,
+        afk: bool,
+    },
     BeginDifficultySelection, // Allow host to move to phase where they choose a difficulty.
     DenyDifficultySelection,  // Refuse non-host client who asks to choose a difficulty level.
-    Victory {
-        winner_index: usize,
+    GameOver {
+        winner: Option<String>,
     },
+    RoomList {
+        rooms: Vec<RoomSummary>,
+    },
+    StartVote {
+        username: String,
+        votes: usize,
+        needed: usize,
+    },
+    DisconnectReason(DisconnectReason),
+    Pong(f64),
 }
 
 impl ServerMessage {
@@ -97,21 +212,35 @@ impl ServerMessage {
         match self {
             Self::Snapshot(_) => "Snapshot",
             Self::BulletEvent(_) => "BulletEvent",
+            Self::PickupEvent(_) => "PickupEvent",
             Self::ServerTime(_) => "ServerTime",
             Self::CountdownStarted { .. } => "CountdownStarted",
             Self::Welcome { .. } => "Welcome",
+            Self::ColorAssigned { .. } => "ColorAssigned",
             Self::UsernameError { .. } => "UsernameError",
             Self::AppointHost => "AppointHost",
             Self::Roster { .. } => "Roster",
             Self::UserJoined { .. } => "UserJoined",
             Self::UserLeft { .. } => "UserLeft",
+            Self::PlayerCount(_) => "PlayerCount",
             Self::ChatMessage { .. } => "ChatMessage",
             Self::AfterGameRoster { .. } => "AfterGameRoster",
             Self::AfterGameLeaderboard { .. } => "AfterGameLeaderboard",
             Self::ServerInfo { .. } => "ServerInfo",
+            Self::AuthRequired { .. } => "AuthRequired",
+            Self::AuthSucceeded { .. } => "AuthSucceeded",
+            Self::ChatCooldown { .. } => "ChatCooldown",
+            Self::SpectatorCount { .. } => "SpectatorCount",
+            Self::GamePaused { .. } => "GamePaused",
+            Self::PlayerKilled { .. } => "PlayerKilled",
+            Self::PlayerAfk { .. } => "PlayerAfk",
             Self::BeginDifficultySelection => "BeginDifficultySelection",
             Self::DenyDifficultySelection => "DenyDifficultySelection",
-            Self::Victory { .. } => "Victory",
+            Self::GameOver { .. } => "GameOver",
+            Self::RoomList { .. } => "RoomList",
+            Self::StartVote { .. } => "StartVote",
+            Self::DisconnectReason(_) => "DisconnectReason",
+            Self::Pong(_) => "Pong",
         }
     }
 }
@@ -131,6 +260,7 @@ pub enum BulletEvent {
         tick: u64,
         position: Vec3,
         velocity: Vec3,
+        surface: ImpactSurface,
     },
     HitPlayer {
         bullet_id: u32,
@@ -146,17 +276,46 @@ pub enum BulletEvent {
         position: Vec3,
         velocity: Vec3,
     },
+    HitBullet {
+        bullet_id: u32,
+        other_bullet_id: u32,
+        tick: u64,
+        position: Vec3,
+        velocity: Vec3,
+    },
+}
+
+impl BulletEvent {
+    pub fn impact_surface(&self) -> Option<ImpactSurface> {
+        match self {
+            Self::HitInanimate { surface, .. } => Some(*surface),
+            Self::HitPlayer { .. } => Some(ImpactSurface::Player),
+            Self::Spawn { .. } | Self::Expire { .. } | Self::HitBullet { .. } => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum PickupEvent {
+    Spawned(Pickup),
+    Collected { pickup_id: u32, player_index: usize },
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
 pub enum ClientMessage {
     SendPasscode(Vec<u8>),
     SetUsername(String),
+    SetColor(Color),
     SendChat(String),
     RequestStartGame,
     SetDifficulty(u8),
+    ConfirmDifficulty,
     EnterAfterGameChat,
     Input(WireItem<PlayerInput>),
+    TogglePause,
+    JoinRoom(String),
+    ListRooms,
+    Ping(f64),
 }
 
 pub fn version() -> u64 {
@@ -167,3 +326,78 @@ pub fn version() -> u64 {
         .parse()
         .expect("failed to parse major version")
 }
+
+#[cfg(test)]
+mod tests {
+    use bincode::serde::{decode_from_slice, encode_to_vec};
+
+    use super::*;
+
+    #[test]
+    fn each_disconnect_reason_has_its_own_message() {
+        assert_eq!(
+            DisconnectReason::ServerFull.message(),
+            "The server is full. Please try again later."
+        );
+        assert_eq!(
+            DisconnectReason::Kicked.message(),
+            "You were removed from the server."
+        );
+        assert_eq!(
+            DisconnectReason::FailedAuth.message(),
+            "Incorrect passcode. Disconnecting."
+        );
+        assert_eq!(
+            DisconnectReason::ShuttingDown.message(),
+            "The server is shutting down."
+        );
+        assert_eq!(
+            DisconnectReason::ProtocolError.message(),
+            "The server did not understand your client. Please check that you have the latest version."
+        );
+        assert_eq!(
+            DisconnectReason::DuplicateConnection.message(),
+            "You are already connected to this server."
+        );
+    }
+
+    #[test]
+    fn shared_config_round_trips_messages() {
+        let message = ClientMessage::SendChat("hello".to_string());
+        let payload = encode_to_vec(&message, config()).expect("failed to encode");
+        let (decoded, read) =
+            decode_from_slice::<ClientMessage, _>(&payload, config()).expect("failed to decode");
+
+        assert_eq!(read, payload.len());
+        assert!(matches!(decoded, ClientMessage::SendChat(text) if text == "hello"));
+    }
+
+    #[test]
+    fn bullet_events_report_their_impact_surface() {
+        let hit_player = BulletEvent::HitPlayer {
+            bullet_id: 0,
+            tick: 0,
+            position: Vec3::ZERO,
+            velocity: Vec3::ZERO,
+            target_index: 1,
+            target_health: 2,
+        };
+        let hit_wall = BulletEvent::HitInanimate {
+            bullet_id: 0,
+            tick: 0,
+            position: Vec3::ZERO,
+            velocity: Vec3::ZERO,
+            surface: ImpactSurface::Wall,
+        };
+        let expire = BulletEvent::Expire {
+            bullet_id: 0,
+            tick: 0,
+            position: Vec3::ZERO,
+            velocity: Vec3::ZERO,
+        };
+
+        assert_eq!(hit_player.impact_surface(), Some(ImpactSurface::Player));
+        assert_eq!(hit_wall.impact_surface(), Some(ImpactSurface::Wall));
+        assert_eq!(expire.impact_surface(), None);
+    }
+}
@@ -1,18 +1,22 @@
 use std::collections::HashMap;
 
-use rand;
+use rand::{SeedableRng, rngs::StdRng};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    constants::{BATTLE_TIMER_DURATION, SOLO_TIMER_DURATION},
-    maze::{self, Maze, maker::Algorithm},
-    player::{self, Color, Player, WirePlayerLocal, WirePlayerRemote},
+    bullets::{Ammo, BulletRadii, BulletTuning, Magazine},
+    constants::{BATTLE_TIMER_DURATION, SOLO_TIMER_DURATION, TICKS_PER_BROADCAST},
+    maze::{self, Maze, Symmetry, maker::Algorithm},
+    pickups::{self, Pickup, PickupKind},
+    player::{self, Color, MovementProfile, Player, WirePlayerLocal, WirePlayerRemote},
+    spawn::{self, SpawnLayout},
 };
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
 pub struct Snapshot {
     pub remote: Vec<WirePlayerRemote>,
     pub local: WirePlayerLocal,
+    pub ammo: Option<Ammo>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -22,6 +26,10 @@ pub struct InitialData {
     pub difficulty: u8,
     pub exit_coords: Option<(usize, usize)>,
     pub timer_duration: f32,
+    pub bullet_tuning: BulletTuning,
+    pub movement_profile: MovementProfile,
+    pub ticks_per_snapshot: u64,
+    pub pickups: Vec<Pickup>,
 }
 
 impl Default for InitialData {
@@ -32,12 +40,52 @@ impl Default for InitialData {
             difficulty: 1,
             exit_coords: None,
             timer_duration: 360.0,
+            bullet_tuning: BulletTuning::default(),
+            movement_profile: MovementProfile::default(),
+            ticks_per_snapshot: TICKS_PER_BROADCAST,
+            pickups: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MapOptions {
+    pub spawn_layout: SpawnLayout,
+    pub symmetry: Option<Symmetry>,
+    pub movement_profile: MovementProfile,
+    pub gravity: Option<f32>,
+    pub ticks_per_snapshot: u64,
+    pub seed: Option<u64>,
+    pub magazine_size: Option<u8>,
+    pub bullet_radii: Option<BulletRadii>,
+}
+
+impl Default for MapOptions {
+    fn default() -> Self {
+        Self {
+            spawn_layout: SpawnLayout::default(),
+            symmetry: None,
+            movement_profile: MovementProfile::default(),
+            gravity: None,
+            ticks_per_snapshot: TICKS_PER_BROADCAST,
+            seed: None,
+            magazine_size: None,
+            bullet_radii: None,
         }
     }
 }
 
 impl InitialData {
     pub fn new(usernames: &HashMap<u64, String>, colors: &HashMap<u64, Color>, level: u8) -> Self {
+        Self::with_options(usernames, colors, level, MapOptions::default())
+    }
+
+    pub fn with_options(
+        usernames: &HashMap<u64, String>,
+        colors: &HashMap<u64, Color>,
+        level: u8,
+        options: MapOptions,
+    ) -> Self {
         let generator = match level {
             0 => Algorithm::BinaryTree,
             1 => Algorithm::RecursiveDivision,
@@ -51,26 +99,39 @@ impl InitialData {
             9 => Algorithm::Wilson,
             _ => Algorithm::Backtrack,
         };
-        let mut maze = maze::Maze::new(generator);
+        let mut rng = match options.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_rng(&mut rand::rng()),
+        };
+        let mut maze = maze::Maze::generate(generator, options.symmetry, &mut rng);
 
         let mut solo_player_grid_coords = (0, 0);
 
-        let mut spaces_remaining = maze.spaces.clone();
+        let spawn_points =
+            spawn::spawn_points(&maze, options.spawn_layout, usernames.len(), &mut rng);
+        spawn::open_enclosed_spawns(&mut maze, &spawn_points);
+        let spawn_cells = spawn_points.clone();
+        let mut spawn_points = spawn_points.into_iter();
         let mut player_count: usize = 0;
+        let mut taken: Vec<Color> = colors.values().copied().collect();
+        let mut usernames: Vec<(u64, String)> = usernames.clone().into_iter().collect();
+        usernames.sort_by_key(|&(client_id, _)| client_id);
         let players: Vec<Player> = usernames
-            .clone()
             .into_iter()
             .map(|(client_id, username)| {
-                let space_index = rand::random_range(0..spaces_remaining.len());
-                let (z, x) = spaces_remaining.remove(space_index);
+                let (z, x) = spawn_points
+                    .next()
+                    .expect("maze has fewer open cells than players");
                 solo_player_grid_coords = (z, x);
                 let start_position = maze
                     .position_from_grid_coordinates(player::HEIGHT, z, x)
                     .expect("failed to get start position from maze");
-                let color = colors
-                    .get(&client_id)
-                    .copied()
-                    .unwrap_or(player::COLORS[player_count % player::COLORS.len()]);
+                let color = colors.get(&client_id).copied().unwrap_or_else(|| {
+                    let color = player::next_available_color(&taken)
+                        .unwrap_or(player::COLORS[player_count % player::COLORS.len()]);
+                    taken.push(color);
+                    color
+                });
                 let player = Player::new(
                     player_count,
                     client_id,
@@ -88,19 +149,47 @@ impl InitialData {
 
         let is_solo = player_count == 1;
         if is_solo {
-            exit_coords = Some(maze.make_exit(solo_player_grid_coords));
+            exit_coords = Some(maze.make_exit(solo_player_grid_coords, &mut rng));
             timer_duration = SOLO_TIMER_DURATION
         } else {
             exit_coords = None;
             timer_duration = BATTLE_TIMER_DURATION;
         }
 
+        let bullet_tuning = BulletTuning {
+            magazine: options
+                .magazine_size
+                .map(|size| Magazine::for_difficulty(size, level)),
+            radii: options.bullet_radii.unwrap_or_default(),
+            gravity: options.gravity.unwrap_or_default(),
+            ..BulletTuning::for_difficulty(level)
+        };
+
+        let mut pickup_kinds = vec![PickupKind::Health; player_count];
+        if bullet_tuning.magazine.is_some() {
+            pickup_kinds.extend(vec![PickupKind::Ammo; player_count]);
+        }
+        let pickups = pickups::place_pickups(&maze, &pickup_kinds, &spawn_cells, &mut rng);
+
         Self {
             maze,
             players,
             difficulty: level,
             exit_coords,
             timer_duration,
+            bullet_tuning,
+            movement_profile: match options.gravity {
+                Some(gravity) => options.movement_profile.with_gravity(gravity),
+                None => options.movement_profile,
+            },
+            ticks_per_snapshot: options.ticks_per_snapshot,
+            pickups,
+        }
+    }
+
+    pub fn assign_teams(&mut self, teams: &HashMap<u64, u8>) {
+        for player in &mut self.players {
+            player.team = teams.get(&player.client_id).copied();
         }
     }
 }
@@ -0,0 +1,383 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque, hash_map::Entry},
+    fmt,
+    str::FromStr,
+};
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::maze::{Maze, SPACE, WALL};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SpawnLayout {
+    Corners,
+    Perimeter,
+    RandomOpen,
+    CenterCluster,
+    #[default]
+    Spread,
+}
+
+impl fmt::Display for SpawnLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SpawnLayout::Corners => "corners",
+            SpawnLayout::Perimeter => "perimeter",
+            SpawnLayout::RandomOpen => "random",
+            SpawnLayout::CenterCluster => "center",
+            SpawnLayout::Spread => "spread",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for SpawnLayout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "corners" => Ok(SpawnLayout::Corners),
+            "perimeter" => Ok(SpawnLayout::Perimeter),
+            "random" | "random-open" => Ok(SpawnLayout::RandomOpen),
+            "center" | "center-cluster" => Ok(SpawnLayout::CenterCluster),
+            "spread" | "farthest" => Ok(SpawnLayout::Spread),
+            _ => Err(format!("unknown spawn layout '{}'", s)),
+        }
+    }
+}
+
+pub fn spawn_points(
+    maze: &Maze,
+    layout: SpawnLayout,
+    count: usize,
+    rng: &mut impl Rng,
+) -> Vec<(usize, usize)> {
+    let count = count.min(maze.spaces.len());
+    if count == 0 {
+        return Vec::new();
+    }
+
+    match layout {
+        SpawnLayout::Corners => {
+            let targets = corner_targets(maze);
+            nearest_to_targets(maze, (0..count).map(|i| targets[i % targets.len()]))
+        }
+        SpawnLayout::Perimeter => nearest_to_targets(maze, perimeter_targets(maze, count)),
+        SpawnLayout::RandomOpen => {
+            let mut remaining = maze.spaces.clone();
+            (0..count)
+                .map(|_| remaining.swap_remove(rng.random_range(0..remaining.len())))
+                .collect()
+        }
+        SpawnLayout::CenterCluster => {
+            let center = grid_center(maze);
+            let mut spaces = maze.spaces.clone();
+            spaces.sort_by(|&a, &b| {
+                distance_squared(a, center).total_cmp(&distance_squared(b, center))
+            });
+            spaces.truncate(count);
+            spaces
+        }
+        SpawnLayout::Spread => farthest_points(maze, count),
+    }
+}
+
+pub fn open_enclosed_spawns(maze: &mut Maze, points: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    let mut carved = Vec::new();
+    for &point in points {
+        if interior_neighbors(maze, point).any(|(z, x)| maze.grid[z][x] != WALL) {
+            continue;
+        }
+        for (z, x) in path_to_nearest_open_cell(maze, point) {
+            if maze.grid[z][x] == WALL {
+                maze.grid[z][x] = SPACE;
+                maze.spaces.push((z, x));
+                carved.push((z, x));
+            }
+        }
+    }
+    carved
+}
+
+fn path_to_nearest_open_cell(maze: &Maze, start: (usize, usize)) -> Vec<(usize, usize)> {
+    let mut previous = HashMap::from([(start, start)]);
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(cell) = queue.pop_front() {
+        if cell != start && maze.grid[cell.0][cell.1] != WALL {
+            let mut path = vec![cell];
+            let mut current = cell;
+            while current != start {
+                current = previous[&current];
+                path.push(current);
+            }
+            return path;
+        }
+        for neighbor in interior_neighbors(maze, cell) {
+            if let Entry::Vacant(entry) = previous.entry(neighbor) {
+                entry.insert(cell);
+                queue.push_back(neighbor);
+            }
+        }
+    }
+
+    Vec::new()
+}
+
+fn interior_neighbors(maze: &Maze, (z, x): (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
+    let height = maze.grid.len();
+    let width = maze.grid.first().map_or(0, Vec::len);
+    [
+        (z.wrapping_sub(1), x),
+        (z + 1, x),
+        (z, x.wrapping_sub(1)),
+        (z, x + 1),
+    ]
+    .into_iter()
+    .filter(move |&(z, x)| {
+        (1..height.saturating_sub(1)).contains(&z) && (1..width.saturating_sub(1)).contains(&x)
+    })
+}
+
+fn farthest_points(maze: &Maze, count: usize) -> Vec<(usize, usize)> {
+    let center = grid_center(maze);
+    let first = maze
+        .spaces
+        .iter()
+        .copied()
+        .max_by(|&a, &b| distance_squared(a, center).total_cmp(&distance_squared(b, center)))
+        .expect("maze should have open cells");
+
+    let mut points = vec![first];
+    let mut nearest: Vec<f32> = maze
+        .spaces
+        .iter()
+        .map(|&cell| cell_distance_squared(cell, first))
+        .collect();
+
+    while points.len() < count {
+        let (index, _) = nearest
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .expect("maze should have open cells");
+        let next = maze.spaces[index];
+        points.push(next);
+        for (distance, &cell) in nearest.iter_mut().zip(&maze.spaces) {
+            *distance = distance.min(cell_distance_squared(cell, next));
+        }
+    }
+
+    points
+}
+
+fn cell_distance_squared(a: (usize, usize), (z, x): (usize, usize)) -> f32 {
+    distance_squared(a, (z as f32, x as f32))
+}
+
+fn grid_size(maze: &Maze) -> (f32, f32) {
+    let height = maze.grid.len() as f32;
+    let width = maze.grid.first().map_or(0, Vec::len) as f32;
+    (height, width)
+}
+
+fn grid_center(maze: &Maze) -> (f32, f32) {
+    let (height, width) = grid_size(maze);
+    ((height - 1.0) / 2.0, (width - 1.0) / 2.0)
+}
+
+fn corner_targets(maze: &Maze) -> [(f32, f32); 4] {
+    let (height, width) = grid_size(maze);
+    let (bottom, right) = (height - 1.0, width - 1.0);
+    [(0.0, 0.0), (bottom, right), (0.0, right), (bottom, 0.0)]
+}
+
+fn perimeter_targets(maze: &Maze, count: usize) -> Vec<(f32, f32)> {
+    let (height, width) = grid_size(maze);
+    let (bottom, right) = (height - 1.0, width - 1.0);
+    let perimeter = 2.0 * (bottom + right);
+
+    (0..count)
+        .map(|i| {
+            let mut distance = perimeter * i as f32 / count as f32;
+            if distance < right {
+                return (0.0, distance);
+            }
+            distance -= right;
+            if distance < bottom {
+                return (distance, right);
+            }
+            distance -= bottom;
+            if distance < right {
+                return (bottom, right - distance);
+            }
+            distance -= right;
+            (bottom - distance, 0.0)
+        })
+        .collect()
+}
+
+fn nearest_to_targets(
+    maze: &Maze,
+    targets: impl IntoIterator<Item = (f32, f32)>,
+) -> Vec<(usize, usize)> {
+    let mut used = HashSet::new();
+    let mut points = Vec::new();
+
+    for target in targets {
+        let nearest = maze
+            .spaces
+            .iter()
+            .copied()
+            .filter(|cell| !used.contains(cell))
+            .min_by(|&a, &b| distance_squared(a, target).total_cmp(&distance_squared(b, target)));
+        if let Some(cell) = nearest {
+            used.insert(cell);
+            points.push(cell);
+        }
+    }
+
+    points
+}
+
+fn distance_squared((z, x): (usize, usize), (target_z, target_x): (f32, f32)) -> f32 {
+    let dz = z as f32 - target_z;
+    let dx = x as f32 - target_x;
+    dz * dz + dx * dx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::Algorithm;
+
+    const LAYOUTS: [SpawnLayout; 5] = [
+        SpawnLayout::Corners,
+        SpawnLayout::Perimeter,
+        SpawnLayout::RandomOpen,
+        SpawnLayout::CenterCluster,
+        SpawnLayout::Spread,
+    ];
+
+    fn assert_well_formed(maze: &Maze, points: &[(usize, usize)]) {
+        let unique: HashSet<_> = points.iter().collect();
+        assert_eq!(unique.len(), points.len(), "spawn points overlap");
+        for &(z, x) in points {
+            assert_eq!(maze.grid[z][x], 0, "spawn point ({}, {}) is not open", z, x);
+        }
+    }
+
+    #[test]
+    fn every_layout_returns_requested_open_distinct_points() {
+        let maze = Maze::new(Algorithm::Backtrack);
+        for layout in LAYOUTS {
+            for count in [1, 2, 4, 7] {
+                let points = spawn_points(&maze, layout, count, &mut rand::rng());
+                assert_eq!(points.len(), count, "{} with {} players", layout, count);
+                assert_well_formed(&maze, &points);
+            }
+        }
+    }
+
+    #[test]
+    fn count_is_capped_by_open_cells() {
+        let maze = Maze::new(Algorithm::Backtrack);
+        let too_many = maze.spaces.len() + 5;
+        for layout in LAYOUTS {
+            let points = spawn_points(&maze, layout, too_many, &mut rand::rng());
+            assert_eq!(points.len(), maze.spaces.len());
+            assert_well_formed(&maze, &points);
+        }
+    }
+
+    #[test]
+    fn corners_are_spread_across_quadrants() {
+        let maze = Maze::new(Algorithm::Backtrack);
+        let (cz, cx) = grid_center(&maze);
+        let points = spawn_points(&maze, SpawnLayout::Corners, 4, &mut rand::rng());
+        let quadrants: HashSet<_> = points
+            .iter()
+            .map(|&(z, x)| ((z as f32) < cz, (x as f32) < cx))
+            .collect();
+        assert_eq!(quadrants.len(), 4);
+    }
+
+    #[test]
+    fn center_cluster_is_nearer_the_center_than_corners() {
+        let maze = Maze::new(Algorithm::Backtrack);
+        let center = grid_center(&maze);
+        let spread = |layout| {
+            spawn_points(&maze, layout, 4, &mut rand::rng())
+                .into_iter()
+                .map(|cell| distance_squared(cell, center))
+                .sum::<f32>()
+        };
+        assert!(spread(SpawnLayout::CenterCluster) < spread(SpawnLayout::Corners));
+    }
+
+    #[test]
+    fn spread_points_are_far_apart_and_deterministic() {
+        let maze = Maze::new(Algorithm::Backtrack);
+        let points = spawn_points(&maze, SpawnLayout::Spread, 4, &mut rand::rng());
+        assert_well_formed(&maze, &points);
+        assert_eq!(
+            points,
+            spawn_points(&maze, SpawnLayout::Spread, 4, &mut rand::rng())
+        );
+
+        let min_distance = crate::maze::RADIUS as f32;
+        for (i, &a) in points.iter().enumerate() {
+            for &b in &points[i + 1..] {
+                assert!(
+                    cell_distance_squared(a, b) > min_distance * min_distance,
+                    "spawn points {:?} and {:?} are too close",
+                    a,
+                    b
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn enclosed_spawn_is_carved_out_to_the_nearest_open_cell() {
+        let grid = vec![
+            vec![1, 1, 1, 1, 1, 1, 1],
+            vec![1, 0, 1, 1, 1, 1, 1],
+            vec![1, 1, 1, 1, 1, 1, 1],
+            vec![1, 1, 1, 1, 0, 0, 1],
+            vec![1, 1, 1, 1, 1, 1, 1],
+        ];
+        let mut maze = Maze {
+            grid,
+            spaces: vec![(1, 1), (3, 4), (3, 5)],
+            teleporters: Vec::new(),
+        };
+
+        let carved = open_enclosed_spawns(&mut maze, &[(1, 1), (3, 4)]);
+
+        assert_eq!(carved.len(), 4, "carved {:?}", carved);
+        for &(z, x) in &carved {
+            assert_eq!(maze.grid[z][x], SPACE);
+            assert!(maze.spaces.contains(&(z, x)));
+            assert!(
+                z > 0 && z < 4 && x > 0 && x < 6,
+                "edge cell ({}, {}) carved",
+                z,
+                x
+            );
+        }
+        assert!(interior_neighbors(&maze, (1, 1)).any(|(z, x)| maze.grid[z][x] == SPACE));
+        assert!(open_enclosed_spawns(&mut maze, &[(1, 1)]).is_empty());
+    }
+
+    #[test]
+    fn parses_layout_names() {
+        assert_eq!("corners".parse(), Ok(SpawnLayout::Corners));
+        assert_eq!("Perimeter".parse(), Ok(SpawnLayout::Perimeter));
+        assert_eq!("random-open".parse(), Ok(SpawnLayout::RandomOpen));
+        assert_eq!("center".parse(), Ok(SpawnLayout::CenterCluster));
+        assert_eq!("spread".parse(), Ok(SpawnLayout::Spread));
+        assert!("spiral".parse::<SpawnLayout>().is_err());
+    }
+}
@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+
+pub const TEAM_COUNT: u8 = 2;
+
+pub fn team_name(team: u8) -> String {
+    format!("Team {}", team + 1)
+}
+
+pub fn smallest_team(teams: impl IntoIterator<Item = u8>) -> u8 {
+    let mut sizes = [0usize; TEAM_COUNT as usize];
+    for team in teams {
+        if let Some(size) = sizes.get_mut(team as usize) {
+            *size += 1;
+        }
+    }
+
+    (0..TEAM_COUNT)
+        .min_by_key(|&team| sizes[team as usize])
+        .expect("expected at least one team")
+}
+
+pub fn balance(assignments: &mut HashMap<u64, u8>) {
+    let mut client_ids: Vec<u64> = assignments.keys().copied().collect();
+    client_ids.sort_unstable();
+
+    loop {
+        let mut sizes = [0usize; TEAM_COUNT as usize];
+        for &team in assignments.values() {
+            sizes[team as usize] += 1;
+        }
+
+        let (largest, &largest_size) = sizes
+            .iter()
+            .enumerate()
+            .max_by_key(|&(_, size)| size)
+            .expect("expected at least one team");
+        let (smallest, &smallest_size) = sizes
+            .iter()
+            .enumerate()
+            .min_by_key(|&(_, size)| size)
+            .expect("expected at least one team");
+
+        if largest_size - smallest_size <= 1 {
+            return;
+        }
+
+        let mover = client_ids
+            .iter()
+            .rev()
+            .find(|id| assignments[id] as usize == largest)
+            .copied()
+            .expect("expected a member of the largest team");
+        assignments.insert(mover, smallest as u8);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn join_in_order(count: u64) -> HashMap<u64, u8> {
+        let mut assignments = HashMap::new();
+        for client_id in 0..count {
+            let team = smallest_team(assignments.values().copied());
+            assignments.insert(client_id, team);
+        }
+        assignments
+    }
+
+    fn team_sizes(assignments: &HashMap<u64, u8>) -> Vec<usize> {
+        (0..TEAM_COUNT)
+            .map(|team| assignments.values().filter(|&&t| t == team).count())
+            .collect()
+    }
+
+    #[test]
+    fn even_player_count_splits_evenly() {
+        let assignments = join_in_order(6);
+        assert_eq!(team_sizes(&assignments), vec![3, 3]);
+    }
+
+    #[test]
+    fn odd_player_count_differs_by_at_most_one() {
+        let assignments = join_in_order(5);
+        assert_eq!(team_sizes(&assignments), vec![3, 2]);
+    }
+
+    #[test]
+    fn late_joiner_goes_to_smaller_team() {
+        let mut assignments = HashMap::from([(1, 0), (2, 0), (3, 1)]);
+        let team = smallest_team(assignments.values().copied());
+        assert_eq!(team, 1);
+
+        assignments.insert(4, team);
+        assert_eq!(team_sizes(&assignments), vec![2, 2]);
+    }
+
+    #[test]
+    fn balance_restores_even_split_after_departures() {
+        let mut assignments = HashMap::from([(1, 0), (2, 0), (3, 0), (4, 0)]);
+        balance(&mut assignments);
+        assert_eq!(team_sizes(&assignments), vec![2, 2]);
+    }
+
+    #[test]
+    fn balance_leaves_odd_split_within_one() {
+        let mut assignments = HashMap::from([(1, 1), (2, 1), (3, 1), (4, 1), (5, 0)]);
+        balance(&mut assignments);
+
+        let sizes = team_sizes(&assignments);
+        assert_eq!(sizes.iter().sum::<usize>(), 5);
+        assert!(sizes[0].abs_diff(sizes[1]) <= 1);
+    }
+}
@@ -0,0 +1,127 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HitRecord {
+    pub shooter: usize,
+    pub victim: usize,
+    pub distance: f32,
+    pub bounces: u8,
+    pub lethal: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Award {
+    pub title: &'static str,
+    pub recipients: Vec<usize>,
+}
+
+pub const LONGEST_RANGE_HIT: &str = "Longest-range hit";
+pub const MOST_BOUNCED_KILL: &str = "Most bounces before a kill";
+pub const SURVIVOR: &str = "Survivor";
+
+pub fn compute_awards(hits: &[HitRecord], ticks_survived: &[u64]) -> Vec<Award> {
+    [
+        (LONGEST_RANGE_HIT, longest_range_hit(hits)),
+        (MOST_BOUNCED_KILL, most_bounced_kill(hits)),
+        (SURVIVOR, survivors(ticks_survived)),
+    ]
+    .into_iter()
+    .filter(|(_, recipients)| !recipients.is_empty())
+    .map(|(title, recipients)| Award { title, recipients })
+    .collect()
+}
+
+pub fn longest_range_hit(hits: &[HitRecord]) -> Vec<usize> {
+    let Some(best) = hits.iter().map(|hit| hit.distance).reduce(f32::max) else {
+        return Vec::new();
+    };
+    recipients(hits.iter().filter(|hit| hit.distance == best))
+}
+
+pub fn most_bounced_kill(hits: &[HitRecord]) -> Vec<usize> {
+    let kills = || hits.iter().filter(|hit| hit.lethal && hit.bounces > 0);
+    let Some(best) = kills().map(|hit| hit.bounces).max() else {
+        return Vec::new();
+    };
+    recipients(kills().filter(|hit| hit.bounces == best))
+}
+
+pub fn survivors(ticks_survived: &[u64]) -> Vec<usize> {
+    let Some(&best) = ticks_survived.iter().max() else {
+        return Vec::new();
+    };
+    ticks_survived
+        .iter()
+        .enumerate()
+        .filter(|&(_, &ticks)| ticks == best)
+        .map(|(index, _)| index)
+        .collect()
+}
+
+fn recipients<'a>(hits: impl Iterator<Item = &'a HitRecord>) -> Vec<usize> {
+    let mut shooters: Vec<usize> = hits.map(|hit| hit.shooter).collect();
+    shooters.sort_unstable();
+    shooters.dedup();
+    shooters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hit(shooter: usize, distance: f32, bounces: u8, lethal: bool) -> HitRecord {
+        HitRecord {
+            shooter,
+            victim: 9,
+            distance,
+            bounces,
+            lethal,
+        }
+    }
+
+    #[test]
+    fn awards_go_to_the_best_recipients_and_are_shared_on_ties() {
+        let hits = [
+            hit(0, 12.0, 0, false),
+            hit(1, 30.0, 2, false),
+            hit(2, 30.0, 1, true),
+            hit(1, 5.0, 3, true),
+            hit(0, 8.0, 3, true),
+            hit(0, 9.0, 4, true),
+        ];
+
+        let awards = compute_awards(&hits[..5], &[100, 250, 250]);
+
+        assert_eq!(
+            awards,
+            vec![
+                Award {
+                    title: LONGEST_RANGE_HIT,
+                    recipients: vec![1, 2],
+                },
+                Award {
+                    title: MOST_BOUNCED_KILL,
+                    recipients: vec![0, 1],
+                },
+                Award {
+                    title: SURVIVOR,
+                    recipients: vec![1, 2],
+                },
+            ]
+        );
+        assert_eq!(most_bounced_kill(&hits), vec![0]);
+    }
+
+    #[test]
+    fn awards_without_qualifying_events_are_omitted() {
+        let hits = [hit(0, 12.0, 3, false), hit(1, 4.0, 0, true)];
+
+        let awards = compute_awards(&hits, &[]);
+
+        assert_eq!(
+            awards,
+            vec![Award {
+                title: LONGEST_RANGE_HIT,
+                recipients: vec![0],
+            }]
+        );
+    }
+}
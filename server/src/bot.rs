@@ -0,0 +1,474 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    f32::consts::{PI, TAU},
+};
+
+use glam::Vec3;
+
+use common::{
+    bullets::cooldown_ticks,
+    maze::{CELL_SIZE, Maze, WALL},
+    player::{PlayerInput, PlayerState},
+};
+
+pub const BOT_CLIENT_ID_BASE: u64 = u64::MAX - 255;
+pub const BOT_NAMES: [&str; 4] = ["asterion", "daedalus", "icarus", "pasiphae"];
+
+const TURN_DEADBAND: f32 = 0.05;
+const STEER_TOLERANCE: f32 = 0.5;
+const PITCH_TOLERANCE: f32 = 0.05;
+const WAYPOINT_RADIUS: f32 = CELL_SIZE * 0.25;
+const LINE_OF_SIGHT_STEP: f32 = CELL_SIZE / 8.0;
+
+pub fn is_bot(client_id: u64) -> bool {
+    client_id >= BOT_CLIENT_ID_BASE
+}
+
+pub fn add_bots(usernames: &mut HashMap<u64, String>, count: usize) {
+    for (i, name) in BOT_NAMES.iter().take(count).enumerate() {
+        usernames.insert(BOT_CLIENT_ID_BASE + i as u64, name.to_string());
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BotSkill {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl BotSkill {
+    pub fn for_difficulty(level: u8) -> Self {
+        match level {
+            0..=2 => BotSkill::Easy,
+            3..=6 => BotSkill::Normal,
+            _ => BotSkill::Hard,
+        }
+    }
+
+    pub fn aim_spread(self) -> f32 {
+        match self {
+            BotSkill::Easy => 0.3,
+            BotSkill::Normal => 0.05,
+            BotSkill::Hard => 0.0,
+        }
+    }
+
+    pub fn sample_aim_offset(self) -> f32 {
+        let spread = self.aim_spread();
+        if spread == 0.0 {
+            0.0
+        } else {
+            rand::random_range(-spread..=spread)
+        }
+    }
+
+    fn fire_chance(self) -> f64 {
+        match self {
+            BotSkill::Easy => 0.03,
+            BotSkill::Normal => 0.2,
+            BotSkill::Hard => 1.0,
+        }
+    }
+
+    fn aim_tolerance(self) -> f32 {
+        match self {
+            BotSkill::Easy => 0.3,
+            BotSkill::Normal => 0.1,
+            BotSkill::Hard => 0.06,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Target {
+    pub position: Vec3,
+    pub velocity: Vec3,
+}
+
+#[derive(Debug, Clone)]
+pub struct Bot {
+    skill: BotSkill,
+    bullet_speed: f32,
+    aim_offset: f32,
+    last_fire_tick: Option<u64>,
+    path: VecDeque<(usize, usize)>,
+}
+
+impl Bot {
+    pub fn new(skill: BotSkill, bullet_speed: f32) -> Self {
+        Self {
+            skill,
+            bullet_speed,
+            aim_offset: skill.sample_aim_offset(),
+            last_fire_tick: None,
+            path: VecDeque::new(),
+        }
+    }
+
+    pub fn skill(&self) -> BotSkill {
+        self.skill
+    }
+
+    pub fn next_input(
+        &mut self,
+        maze: &Maze,
+        state: &PlayerState,
+        targets: &[Target],
+        sim_tick: u64,
+    ) -> PlayerInput {
+        let mut input = PlayerInput {
+            sim_tick,
+            ..Default::default()
+        };
+
+        if let Some(target) = nearest_visible_target(maze, state.position, targets) {
+            let direction = aim_direction(self.skill, state.position, &target, self.bullet_speed);
+            pitch_toward(&mut input, state.pitch, direction.y.asin());
+            let desired = yaw_of(direction) + self.aim_offset;
+            let error = turn_toward(&mut input, state.yaw, desired);
+            let cooled_down = self
+                .last_fire_tick
+                .is_none_or(|tick| sim_tick.saturating_sub(tick) >= cooldown_ticks());
+            if cooled_down
+                && error.abs() < self.skill.aim_tolerance()
+                && rand::random_bool(self.skill.fire_chance())
+            {
+                input.fire = true;
+                input.fire_nonce = Some(sim_tick as u32);
+                self.last_fire_tick = Some(sim_tick);
+                self.aim_offset = self.skill.sample_aim_offset();
+            }
+            return input;
+        }
+        pitch_toward(&mut input, state.pitch, 0.0);
+
+        let Some(current) = cell_of(maze, state.position) else {
+            return input;
+        };
+        if self.path.is_empty() {
+            let destination = maze.spaces[rand::random_range(0..maze.spaces.len())];
+            self.path = path_between(maze, current, destination).unwrap_or_default();
+        }
+
+        while let Some(&(z, x)) = self.path.front() {
+            let Some(waypoint) = maze.position_from_grid_coordinates(state.position.y, z, x) else {
+                self.path.clear();
+                break;
+            };
+            let offset = waypoint - state.position;
+            if offset.x.hypot(offset.z) < WAYPOINT_RADIUS {
+                self.path.pop_front();
+                continue;
+            }
+
+            let error = turn_toward(&mut input, state.yaw, yaw_of(waypoint - state.position));
+            input.forward = error.abs() < STEER_TOLERANCE;
+            break;
+        }
+
+        input
+    }
+}
+
+pub fn has_line_of_sight(maze: &Maze, from: Vec3, to: Vec3) -> bool {
+    let distance = from.distance(to);
+    let steps = (distance / LINE_OF_SIGHT_STEP).ceil() as usize;
+    (0..=steps).all(|i| {
+        let point = from.lerp(to, i as f32 / steps.max(1) as f32);
+        cell_of(maze, point).is_some_and(|(z, x)| maze.grid[z][x] != WALL)
+    })
+}
+
+pub fn lead_direction(
+    shooter: Vec3,
+    target: Vec3,
+    target_velocity: Vec3,
+    bullet_speed: f32,
+) -> Option<Vec3> {
+    let offset = target - shooter;
+    let a = target_velocity.length_squared() - bullet_speed * bullet_speed;
+    let b = 2.0 * offset.dot(target_velocity);
+    let c = offset.length_squared();
+
+    let time = if a.abs() < f32::EPSILON {
+        (b.abs() > f32::EPSILON).then(|| -c / b)?
+    } else {
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant < 0.0 {
+            return None;
+        }
+        let root = discriminant.sqrt();
+        [(-b - root) / (2.0 * a), (-b + root) / (2.0 * a)]
+            .into_iter()
+            .filter(|&t| t > 0.0)
+            .min_by(f32::total_cmp)?
+    };
+    if time <= 0.0 {
+        return None;
+    }
+
+    Some((offset + target_velocity * time).normalize_or_zero())
+}
+
+pub fn aim_direction(skill: BotSkill, shooter: Vec3, target: &Target, bullet_speed: f32) -> Vec3 {
+    let direct = (target.position - shooter).normalize_or_zero();
+    if skill == BotSkill::Hard {
+        lead_direction(shooter, target.position, target.velocity, bullet_speed).unwrap_or(direct)
+    } else {
+        direct
+    }
+}
+
+fn nearest_visible_target(maze: &Maze, position: Vec3, targets: &[Target]) -> Option<Target> {
+    targets
+        .iter()
+        .copied()
+        .filter(|target| has_line_of_sight(maze, position, target.position))
+        .min_by(|a, b| {
+            position
+                .distance_squared(a.position)
+                .total_cmp(&position.distance_squared(b.position))
+        })
+}
+
+fn cell_of(maze: &Maze, position: Vec3) -> Option<(usize, usize)> {
+    maze.grid_coordinates_from_position(&position)
+        .map(|(x, z)| (z as usize, x as usize))
+}
+
+fn yaw_of(direction: Vec3) -> f32 {
+    (-direction.x).atan2(-direction.z)
+}
+
+fn turn_toward(input: &mut PlayerInput, yaw: f32, desired: f32) -> f32 {
+    let error = (desired - yaw + PI).rem_euclid(TAU) - PI;
+    if error > TURN_DEADBAND {
+        input.yaw_left = true;
+    } else if error < -TURN_DEADBAND {
+        input.yaw_right = true;
+    }
+    error
+}
+
+fn pitch_toward(input: &mut PlayerInput, pitch: f32, desired: f32) {
+    if pitch > desired + PITCH_TOLERANCE {
+        input.pitch_down = true;
+    } else if pitch < desired - PITCH_TOLERANCE {
+        input.pitch_up = true;
+    }
+}
+
+fn path_between(
+    maze: &Maze,
+    start: (usize, usize),
+    goal: (usize, usize),
+) -> Option<VecDeque<(usize, usize)>> {
+    let height = maze.grid.len();
+    let width = maze.grid.first().map_or(0, Vec::len);
+    let mut previous = vec![vec![None; width]; height];
+    let mut queue = VecDeque::from([start]);
+    previous[start.0][start.1] = Some(start);
+
+    while let Some((z, x)) = queue.pop_front() {
+        if (z, x) == goal {
+            let mut path = VecDeque::new();
+            let mut cell = goal;
+            while cell != start {
+                path.push_front(cell);
+                cell = previous[cell.0][cell.1]?;
+            }
+            return Some(path);
+        }
+
+        let neighbors = [
+            (z.wrapping_sub(1), x),
+            (z + 1, x),
+            (z, x.wrapping_sub(1)),
+            (z, x + 1),
+        ];
+        for (nz, nx) in neighbors {
+            if nz < height && nx < width && maze.grid[nz][nx] != WALL && previous[nz][nx].is_none()
+            {
+                previous[nz][nx] = Some((z, x));
+                queue.push_back((nz, nx));
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::vec3;
+
+    use super::*;
+    use common::{
+        bullets::{
+            self, Bullet, BulletTuning, SPEED, check_player_collision, update_bullet_position,
+        },
+        constants::TICK_SECS_F32,
+        maze::{Algorithm, SPACE},
+        player::{HEIGHT, MAX_SPEED, MovementProfile},
+    };
+
+    fn open_room(width: usize, height: usize) -> Maze {
+        let mut grid = vec![vec![WALL; width]; height];
+        let mut spaces = Vec::new();
+        for (z, row) in grid.iter_mut().enumerate().take(height - 1).skip(1) {
+            for (x, cell) in row.iter_mut().enumerate().take(width - 1).skip(1) {
+                *cell = SPACE;
+                spaces.push((z, x));
+            }
+        }
+        Maze {
+            grid,
+            spaces,
+            teleporters: Vec::new(),
+        }
+    }
+
+    fn centre(z: usize, x: usize) -> Vec3 {
+        vec3(
+            (x as f32 + 0.5) * CELL_SIZE,
+            HEIGHT,
+            (z as f32 + 0.5) * CELL_SIZE,
+        )
+    }
+
+    fn still(position: Vec3) -> Target {
+        Target {
+            position,
+            velocity: Vec3::ZERO,
+        }
+    }
+
+    fn fires_within(maze: &Maze, from: Vec3, target: Vec3, ticks: u64) -> bool {
+        let mut bot = Bot::new(BotSkill::Normal, SPEED);
+        let mut state = PlayerState::new(from);
+        (0..ticks).any(|tick| {
+            let input = bot.next_input(maze, &state, &[still(target)], tick);
+            if input.fire {
+                assert!(has_line_of_sight(maze, state.position, target));
+            }
+            state.update(
+                maze,
+                &input,
+                &MovementProfile::default(),
+                0,
+                &Vec::new(),
+                0.0,
+            );
+            input.fire
+        })
+    }
+
+    fn shot_hits(maze: &Maze, shooter: Vec3, direction: Vec3, mut target: Target) -> bool {
+        let tuning = BulletTuning::default();
+        let mut bullet = Bullet::new(
+            0,
+            0,
+            bullets::spawn_position(shooter, direction, &tuning.radii),
+            bullets::spawn_velocity(direction, &tuning),
+            0,
+        );
+        (1..120).any(|tick| {
+            target.position += target.velocity * TICK_SECS_F32;
+            if update_bullet_position(&mut bullet, maze, tick, &tuning).should_remove {
+                return false;
+            }
+            check_player_collision(
+                &mut bullet,
+                target.position,
+                9,
+                None,
+                None,
+                true,
+                &tuning.radii,
+            )
+            .hit_player
+        })
+    }
+
+    #[test]
+    fn wandering_bot_stays_on_open_cells() {
+        let maze = Maze::new(Algorithm::Backtrack);
+        let (z, x) = maze.spaces[0];
+        let start = maze.position_from_grid_coordinates(HEIGHT, z, x).unwrap();
+        let mut state = PlayerState::new(start);
+        let mut bot = Bot::new(BotSkill::Normal, SPEED);
+
+        for tick in 0..1200 {
+            let input = bot.next_input(&maze, &state, &[], tick);
+            assert!(!input.fire);
+            state.update(
+                &maze,
+                &input,
+                &MovementProfile::default(),
+                0,
+                &Vec::new(),
+                0.0,
+            );
+            let (z, x) = cell_of(&maze, state.position).expect("bot left the maze");
+            assert_ne!(maze.grid[z][x], WALL);
+        }
+        assert_ne!(state.position, start);
+    }
+
+    #[test]
+    fn bot_fires_only_with_line_of_sight() {
+        let mut maze = open_room(7, 5);
+
+        let visible = centre(1, 5);
+        assert!(has_line_of_sight(&maze, centre(1, 1), visible));
+        assert!(fires_within(&maze, centre(1, 1), visible, 600));
+
+        for row in &mut maze.grid {
+            row[3] = WALL;
+        }
+        maze.spaces.retain(|&(_, x)| x != 3);
+        let hidden = centre(2, 4);
+        assert!(!has_line_of_sight(&maze, centre(2, 1), hidden));
+        assert!(!fires_within(&maze, centre(2, 1), hidden, 600));
+    }
+
+    #[test]
+    fn hard_bot_leads_a_moving_target_into_the_bullet() {
+        let maze = open_room(13, 13);
+        let shooter = centre(6, 1);
+        let target = Target {
+            position: centre(2, 8),
+            velocity: vec3(0.0, 0.0, MAX_SPEED),
+        };
+
+        let direct = aim_direction(BotSkill::Normal, shooter, &target, SPEED);
+        let lead = aim_direction(BotSkill::Hard, shooter, &target, SPEED);
+
+        assert_ne!(direct, lead);
+        assert!(!shot_hits(&maze, shooter, direct, target));
+        assert!(shot_hits(&maze, shooter, lead, target));
+    }
+
+    #[test]
+    fn lead_is_impossible_against_a_target_faster_than_the_bullet() {
+        let shooter = centre(1, 1);
+        let away = vec3(SPEED * 2.0, 0.0, 0.0);
+        assert_eq!(lead_direction(shooter, centre(1, 3), away, SPEED), None);
+    }
+
+    #[test]
+    fn easy_bot_spreads_its_aim_and_hard_bot_does_not() {
+        let offsets: Vec<f32> = (0..200)
+            .map(|_| BotSkill::Easy.sample_aim_offset())
+            .collect();
+        let spread = BotSkill::Easy.aim_spread();
+        assert!(offsets.iter().all(|offset| offset.abs() <= spread));
+        let min = offsets.iter().copied().fold(f32::INFINITY, f32::min);
+        let max = offsets.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        assert!(max - min > spread);
+
+        assert!((0..200).all(|_| BotSkill::Hard.sample_aim_offset() == 0.0));
+    }
+}
@@ -0,0 +1,230 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, VecDeque},
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use bincode::serde::encode_to_vec;
+
+use crate::net::ServerNetworkHandle;
+use common::{
+    net::AppChannel,
+    protocol::{ServerMessage, config},
+};
+
+pub const CHAT_BURST_LIMIT: usize = 5;
+pub const CHAT_BURST_WINDOW: Duration = Duration::from_secs(5);
+pub const DUPLICATE_CHAT_WINDOW: Duration = Duration::from_millis(200);
+pub const CHAT_LOG_PATH: &str = "chat.log";
+pub const CHAT_LOG_MAX_BYTES: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, Default)]
+pub struct ChatRateLimiter {
+    recent_messages: HashMap<u64, VecDeque<Instant>>,
+}
+
+impl ChatRateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn check(&mut self, client_id: u64, now: Instant) -> Option<Duration> {
+        let recent = self.recent_messages.entry(client_id).or_default();
+        while recent
+            .front()
+            .is_some_and(|&sent| now.duration_since(sent) >= CHAT_BURST_WINDOW)
+        {
+            recent.pop_front();
+        }
+
+        if recent.len() >= CHAT_BURST_LIMIT {
+            let oldest = *recent.front().expect("expected a recent chat message");
+            return Some(CHAT_BURST_WINDOW - now.duration_since(oldest));
+        }
+
+        recent.push_back(now);
+        None
+    }
+
+    pub fn remove_client(&mut self, client_id: u64) {
+        self.recent_messages.remove(&client_id);
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ChatDeduplicator {
+    last_messages: HashMap<u64, (String, Instant)>,
+}
+
+impl ChatDeduplicator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_duplicate(&mut self, client_id: u64, content: &str, now: Instant) -> bool {
+        if let Some((last_content, sent)) = self.last_messages.get(&client_id)
+            && last_content == content
+            && now.duration_since(*sent) < DUPLICATE_CHAT_WINDOW
+        {
+            return true;
+        }
+
+        self.last_messages
+            .insert(client_id, (content.to_string(), now));
+        false
+    }
+
+    pub fn remove_client(&mut self, client_id: u64) {
+        self.last_messages.remove(&client_id);
+    }
+}
+
+pub type SharedChatLog = Rc<RefCell<ChatLog>>;
+
+#[derive(Debug)]
+pub struct ChatLog {
+    path: PathBuf,
+    max_bytes: u64,
+    file: File,
+    size: u64,
+}
+
+impl ChatLog {
+    pub fn open(path: PathBuf, max_bytes: u64) -> io::Result<Self> {
+        let file = open_for_append(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path,
+            max_bytes,
+            file,
+            size,
+        })
+    }
+
+    pub fn open_shared(path: &Path) -> Option<SharedChatLog> {
+        match Self::open(path.to_path_buf(), CHAT_LOG_MAX_BYTES) {
+            Ok(log) => {
+                println!("Logging chat to {}.", path.display());
+                Some(Rc::new(RefCell::new(log)))
+            }
+            Err(e) => {
+                eprintln!("failed to open chat log {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    pub fn rotated_path(&self) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(".1");
+        PathBuf::from(path)
+    }
+
+    pub fn append(&mut self, username: &str, content: &str, timestamp: f64) -> io::Result<()> {
+        let line = format!("{:.3}\t{}\t{}\n", timestamp, username, content);
+        if self.size > 0 && self.size + line.len() as u64 > self.max_bytes {
+            fs::rename(&self.path, self.rotated_path())?;
+            self.file = open_for_append(&self.path)?;
+            self.size = 0;
+        }
+
+        self.file.write_all(line.as_bytes())?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+}
+
+fn open_for_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+pub fn log_chat(log: Option<&SharedChatLog>, username: &str, content: &str) {
+    let Some(log) = log else {
+        return;
+    };
+
+    let timestamp = common::time::now_as_secs_f64();
+    if let Err(e) = log.borrow_mut().append(username, content, timestamp) {
+        eprintln!("failed to write chat log: {}", e);
+    }
+}
+
+pub fn send_chat_cooldown(
+    network: &mut dyn ServerNetworkHandle,
+    client_id: u64,
+    remaining: Duration,
+) {
+    let message = ServerMessage::ChatCooldown {
+        seconds_remaining: remaining.as_secs_f32(),
+    };
+    let payload = encode_to_vec(&message, config()).expect("failed to serialize ChatCooldown");
+    network.send_message(client_id, AppChannel::ReliableOrdered, payload);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allows_burst_then_reports_cooldown() {
+        let mut limiter = ChatRateLimiter::new();
+        let start = Instant::now();
+
+        for _ in 0..CHAT_BURST_LIMIT {
+            assert!(limiter.check(1, start).is_none());
+        }
+
+        let later = start + Duration::from_secs(1);
+        assert_eq!(
+            limiter.check(1, later),
+            Some(CHAT_BURST_WINDOW - Duration::from_secs(1))
+        );
+        assert!(limiter.check(2, later).is_none());
+    }
+
+    #[test]
+    fn suppresses_identical_chat_within_window() {
+        let mut deduplicator = ChatDeduplicator::new();
+        let start = Instant::now();
+
+        assert!(!deduplicator.is_duplicate(1, "hi", start));
+        assert!(deduplicator.is_duplicate(1, "hi", start));
+        assert!(!deduplicator.is_duplicate(2, "hi", start));
+        assert!(!deduplicator.is_duplicate(1, "hi", start + DUPLICATE_CHAT_WINDOW));
+    }
+
+    #[test]
+    fn chat_log_rotates_when_threshold_is_exceeded() {
+        let path = std::env::temp_dir().join(format!("chat-log-test-{}.log", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut log = ChatLog::open(path.clone(), 40).unwrap();
+        let _ = fs::remove_file(log.rotated_path());
+
+        log.append("alice", "first message", 1.0).unwrap();
+        log.append("bob", "second message", 2.0).unwrap();
+
+        let rotated = fs::read_to_string(log.rotated_path()).unwrap();
+        let current = fs::read_to_string(&path).unwrap();
+        assert_eq!(rotated, "1.000\talice\tfirst message\n");
+        assert_eq!(current, "2.000\tbob\tsecond message\n");
+
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(log.rotated_path()).unwrap();
+    }
+
+    #[test]
+    fn cooldown_expires_after_window() {
+        let mut limiter = ChatRateLimiter::new();
+        let start = Instant::now();
+
+        for _ in 0..CHAT_BURST_LIMIT {
+            limiter.check(1, start);
+        }
+
+        assert!(limiter.check(1, start + CHAT_BURST_WINDOW).is_none());
+    }
+}
@@ -0,0 +1,332 @@
+use std::{
+    io::{self, BufRead},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+    },
+    thread,
+};
+
+use bincode::serde::encode_to_vec;
+
+use crate::{
+    net::{ServerNetworkHandle, disconnect_with_reason},
+    rooms::Rooms,
+};
+use common::{
+    net::AppChannel,
+    protocol::{DisconnectReason, ServerMessage, config},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConsoleCommand {
+    Passcode,
+    RotatePasscode,
+    NewRoom,
+    Status,
+    Say(String),
+    Kick(u64),
+    Shutdown,
+}
+
+impl ConsoleCommand {
+    pub fn parse(line: &str) -> Option<Self> {
+        let line = line.trim();
+        let (name, argument) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let argument = argument.trim();
+        match (name.to_lowercase().as_str(), argument.is_empty()) {
+            ("passcode", true) => Some(Self::Passcode),
+            ("rotate-passcode" | "new-passcode", true) => Some(Self::RotatePasscode),
+            ("room" | "new-room", true) => Some(Self::NewRoom),
+            ("status", true) => Some(Self::Status),
+            ("say", false) => Some(Self::Say(argument.to_string())),
+            ("kick", false) => argument.parse().ok().map(Self::Kick),
+            ("shutdown" | "quit", true) => Some(Self::Shutdown),
+            _ => None,
+        }
+    }
+}
+
+pub fn spawn_stdin_reader() -> Receiver<String> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    receiver
+}
+
+pub fn handle_command(
+    line: &str,
+    rooms: &mut Rooms,
+    network: &mut dyn ServerNetworkHandle,
+    shutdown: &AtomicBool,
+) -> Option<String> {
+    if line.trim().is_empty() {
+        return None;
+    }
+
+    match ConsoleCommand::parse(line) {
+        Some(ConsoleCommand::Passcode) => Some(
+            rooms
+                .iter()
+                .map(|room| format!("{}: {}", room.code, room.passcode.string))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ),
+        Some(ConsoleCommand::RotatePasscode) => Some(
+            rooms
+                .iter_mut()
+                .map(|room| {
+                    let passcode = room.rotate_passcode().string.clone();
+                    format!("Room code: {}, passcode: {}", room.code, passcode)
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ),
+        Some(ConsoleCommand::NewRoom) => {
+            let room = rooms.create_room();
+            Some(format!(
+                "Room code: {}, passcode: {}",
+                room.code, room.passcode.string
+            ))
+        }
+        Some(ConsoleCommand::Status) => Some(status(rooms, network)),
+        Some(ConsoleCommand::Say(text)) => {
+            let message = ServerMessage::ServerInfo {
+                message: text.clone(),
+            };
+            let payload =
+                encode_to_vec(&message, config()).expect("failed to serialize ServerInfo");
+            for room in rooms.iter() {
+                for client_id in room.state.server_info_recipients() {
+                    network.send_message(client_id, AppChannel::ReliableOrdered, payload.clone());
+                }
+            }
+            Some(format!("Said: {}", text))
+        }
+        Some(ConsoleCommand::Kick(client_id)) => {
+            if !network.clients_id().contains(&client_id) {
+                return Some(format!("No client with ID {}.", client_id));
+            }
+            disconnect_with_reason(network, client_id, DisconnectReason::Kicked);
+            Some(format!("Kicked client {}.", client_id))
+        }
+        Some(ConsoleCommand::Shutdown) => {
+            shutdown.store(true, Ordering::Relaxed);
+            None
+        }
+        None => Some(format!("Unknown command '{}'.", line.trim())),
+    }
+}
+
+fn status(rooms: &Rooms, network: &dyn ServerNetworkHandle) -> String {
+    let mut client_ids = network.clients_id();
+    client_ids.sort_unstable();
+    let mut lines = vec![format!("{} client(s) connected.", client_ids.len())];
+    for room in rooms.iter() {
+        lines.push(format!(
+            "Room {} ({}): {} member(s)",
+            room.code,
+            room.state.name(),
+            room.members().len()
+        ));
+    }
+    for client_id in client_ids {
+        match rooms.room_of(client_id) {
+            Some(code) => lines.push(format!("  Client {} in room {}", client_id, code)),
+            None => lines.push(format!("  Client {} not in a room", client_id)),
+        }
+    }
+    lines.join("\n")
+}
+
+pub fn process_console_input(
+    console: &Receiver<String>,
+    rooms: &mut Rooms,
+    network: &mut dyn ServerNetworkHandle,
+    shutdown: &AtomicBool,
+) {
+    while let Ok(line) = console.try_recv() {
+        if let Some(output) = handle_command(&line, rooms, network, shutdown) {
+            println!("{}", output);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use bincode::serde::decode_from_slice;
+
+    use super::*;
+    use crate::{
+        run::update_server_state,
+        settings::Settings,
+        state::{Lobby, ServerState},
+        test_helpers::MockServerNetwork,
+    };
+    use common::auth::Passcode;
+
+    fn rooms_with_passcode(passcode: &str) -> (Rooms, String) {
+        let mut rooms = Rooms::new(Settings::default());
+        let code = rooms.create_room().code.clone();
+        rooms.room_mut(&code).unwrap().passcode =
+            Passcode::from_string(passcode).expect("failed to create passcode");
+        (rooms, code)
+    }
+
+    fn run(line: &str, rooms: &mut Rooms) -> Option<String> {
+        handle_command(
+            line,
+            rooms,
+            &mut MockServerNetwork::new(),
+            &AtomicBool::new(false),
+        )
+    }
+
+    #[test]
+    fn parses_commands_with_arguments() {
+        assert_eq!(
+            ConsoleCommand::parse("status"),
+            Some(ConsoleCommand::Status)
+        );
+        assert_eq!(
+            ConsoleCommand::parse("  say  Back in five  "),
+            Some(ConsoleCommand::Say("Back in five".to_string()))
+        );
+        assert_eq!(
+            ConsoleCommand::parse("KICK 42"),
+            Some(ConsoleCommand::Kick(42))
+        );
+        assert_eq!(
+            ConsoleCommand::parse("shutdown"),
+            Some(ConsoleCommand::Shutdown)
+        );
+        assert_eq!(ConsoleCommand::parse("say"), None);
+        assert_eq!(ConsoleCommand::parse("kick"), None);
+        assert_eq!(ConsoleCommand::parse("kick alice"), None);
+        assert_eq!(ConsoleCommand::parse("status now"), None);
+    }
+
+    #[test]
+    fn say_kick_and_shutdown_act_on_the_server() {
+        let (mut rooms, code) = rooms_with_passcode("314159");
+        let mut network = MockServerNetwork::new();
+        network.add_client(1);
+        network.add_client(3);
+        let ServerState::Lobby(lobby) = &mut rooms.room_mut(&code).unwrap().state else {
+            panic!("expected a lobby");
+        };
+        lobby.register_connection(1);
+        lobby.mark_authenticated(1);
+        lobby.register_username(1, "alice");
+        lobby.register_connection(3);
+        let shutdown = AtomicBool::new(false);
+
+        handle_command("say hello", &mut rooms, &mut network, &shutdown);
+        assert!(network.get_broadcast_messages_data().is_empty());
+        assert!(network.get_sent_messages_data(3).is_empty());
+        let sent = network.get_sent_messages_data(1);
+        assert_eq!(sent.len(), 1);
+        let msg = decode_from_slice::<ServerMessage, _>(&sent[0], config())
+            .unwrap()
+            .0;
+        assert!(
+            matches!(msg, ServerMessage::ServerInfo { ref message } if message == "hello"),
+            "expected announcement, got {:?}",
+            msg
+        );
+
+        assert_eq!(
+            handle_command("kick 2", &mut rooms, &mut network, &shutdown),
+            Some("No client with ID 2.".to_string())
+        );
+        assert_eq!(
+            handle_command("kick 1", &mut rooms, &mut network, &shutdown),
+            Some("Kicked client 1.".to_string())
+        );
+        assert!(!network.clients_id().contains(&1));
+
+        assert!(!shutdown.load(Ordering::Relaxed));
+        handle_command("shutdown", &mut rooms, &mut network, &shutdown);
+        assert!(shutdown.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn passcode_command_returns_current_passcode() {
+        let (mut rooms, code) = rooms_with_passcode("314159");
+
+        assert_eq!(
+            run("passcode", &mut rooms),
+            Some(format!("{}: 314159", code))
+        );
+        assert_eq!(
+            run("  PASSCODE \n", &mut rooms),
+            Some(format!("{}: 314159", code))
+        );
+    }
+
+    #[test]
+    fn passcode_command_is_not_sent_over_the_network() {
+        let (mut rooms, _) = rooms_with_passcode("314159");
+        let passcode = Passcode::from_string("314159").expect("failed to create passcode");
+        let mut network = MockServerNetwork::new();
+        network.add_client(1);
+        let mut lobby = Lobby::new();
+        lobby.register_connection(1);
+        let mut state = ServerState::Lobby(lobby);
+
+        let (sender, receiver) = mpsc::channel();
+        sender.send("passcode".to_string()).unwrap();
+        process_console_input(&receiver, &mut rooms, &mut network, &AtomicBool::new(false));
+        update_server_state(&mut network, &mut state, &passcode, &mut Instant::now());
+
+        assert!(network.get_sent_messages_data(1).is_empty());
+        assert!(network.get_broadcast_messages_data().is_empty());
+    }
+
+    #[test]
+    fn rotate_passcode_command_replaces_and_reports_the_passcode() {
+        let (mut rooms, code) = rooms_with_passcode("314159");
+
+        let output = run("rotate-passcode", &mut rooms).expect("expected output");
+
+        let room = rooms.room(&code).unwrap();
+        assert_ne!(room.passcode.string, "314159");
+        assert_eq!(
+            output,
+            format!("Room code: {}, passcode: {}", code, room.passcode.string)
+        );
+    }
+
+    #[test]
+    fn new_room_command_adds_a_room() {
+        let (mut rooms, _) = rooms_with_passcode("314159");
+
+        let output = run("new-room", &mut rooms).expect("expected output");
+
+        assert_eq!(rooms.iter().count(), 2);
+        let created = rooms.iter().last().unwrap();
+        assert!(output.contains(&created.code));
+        assert!(output.contains(&created.passcode.string));
+    }
+
+    #[test]
+    fn unknown_and_empty_commands() {
+        let (mut rooms, _) = rooms_with_passcode("314159");
+
+        assert_eq!(run("   ", &mut rooms), None);
+        assert_eq!(
+            run("launch", &mut rooms),
+            Some("Unknown command 'launch'.".to_string())
+        );
+    }
+}
@@ -0,0 +1,450 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use common::{constants::TICK_SECS, protocol::AfterGameExitReason};
+
+const EVENT_LOG_DIR: &str = "match-logs";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MatchEvent {
+    Joined { player: String },
+    Left { player: String },
+    Shot { shooter: String },
+    Hit { victim: String, health: u8 },
+    Eliminated { victim: String, killer: String },
+    Idle { player: String },
+    Won { winner: String },
+    Escaped { player: String },
+    Draw,
+    TimeUp,
+}
+
+impl fmt::Display for MatchEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatchEvent::Joined { player } => write!(f, "join\t{}", player),
+            MatchEvent::Left { player } => write!(f, "leave\t{}", player),
+            MatchEvent::Shot { shooter } => write!(f, "shot\t{}", shooter),
+            MatchEvent::Hit { victim, health } => write!(f, "hit\t{}\t{}", victim, health),
+            MatchEvent::Eliminated { victim, killer } => {
+                write!(f, "eliminated\t{}\t{}", victim, killer)
+            }
+            MatchEvent::Idle { player } => write!(f, "idle\t{}", player),
+            MatchEvent::Won { winner } => write!(f, "result\twinner\t{}", winner),
+            MatchEvent::Escaped { player } => write!(f, "result\tescaped\t{}", player),
+            MatchEvent::Draw => write!(f, "result\tdraw"),
+            MatchEvent::TimeUp => write!(f, "result\ttime up"),
+        }
+    }
+}
+
+impl FromStr for MatchEvent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.split('\t').collect();
+        let name = |i: usize| fields.get(i).map(|field| field.to_string());
+        let event = match fields.as_slice() {
+            ["join", _] => name(1).map(|player| MatchEvent::Joined { player }),
+            ["leave", _] => name(1).map(|player| MatchEvent::Left { player }),
+            ["shot", _] => name(1).map(|shooter| MatchEvent::Shot { shooter }),
+            ["hit", _, health] => health
+                .parse()
+                .ok()
+                .and_then(|health| name(1).map(|victim| MatchEvent::Hit { victim, health })),
+            ["eliminated", _, _] => name(1)
+                .zip(name(2))
+                .map(|(victim, killer)| MatchEvent::Eliminated { victim, killer }),
+            ["idle", _] => name(1).map(|player| MatchEvent::Idle { player }),
+            ["result", "winner", _] => name(2).map(|winner| MatchEvent::Won { winner }),
+            ["result", "escaped", _] => name(2).map(|player| MatchEvent::Escaped { player }),
+            ["result", "draw"] => Some(MatchEvent::Draw),
+            ["result", "time up"] => Some(MatchEvent::TimeUp),
+            _ => None,
+        };
+        event.ok_or_else(|| format!("unrecognized event '{}'", s))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeaderboardRow {
+    pub rank: usize,
+    pub username: String,
+    pub ticks_survived: u64,
+    pub exit_reason: AfterGameExitReason,
+    pub shots: u32,
+    pub hits_taken: u32,
+    pub kills: u32,
+}
+
+impl fmt::Display for LeaderboardRow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}. {} ({}) survived {:.1}s, shots: {}, hits taken: {}, kills: {}",
+            self.rank,
+            self.username,
+            self.exit_reason,
+            self.ticks_survived as f64 * TICK_SECS,
+            self.shots,
+            self.hits_taken,
+            self.kills
+        )
+    }
+}
+
+pub fn parse(contents: &str) -> Result<Vec<(u64, MatchEvent)>, String> {
+    contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let (tick, event) = line
+                .split_once('\t')
+                .ok_or_else(|| format!("malformed event log line '{}'", line))?;
+            let tick = tick
+                .parse()
+                .map_err(|_| format!("invalid tick in event log line '{}'", line))?;
+            Ok((tick, event.parse()?))
+        })
+        .collect()
+}
+
+pub fn reconstruct_leaderboard(events: &[(u64, MatchEvent)]) -> Vec<LeaderboardRow> {
+    let start_tick = events.first().map_or(0, |&(tick, _)| tick);
+    let end_tick = events.last().map_or(0, |&(tick, _)| tick);
+    let mut rows: Vec<LeaderboardRow> = Vec::new();
+    let mut exits: HashMap<String, u64> = HashMap::new();
+
+    for (tick, event) in events {
+        match event {
+            MatchEvent::Joined { player } => rows.push(LeaderboardRow {
+                rank: 0,
+                username: player.clone(),
+                ticks_survived: 0,
+                exit_reason: AfterGameExitReason::Shot,
+                shots: 0,
+                hits_taken: 0,
+                kills: 0,
+            }),
+            MatchEvent::Draw => {}
+            MatchEvent::Left { player } => record_exit(
+                &mut rows,
+                &mut exits,
+                player,
+                *tick,
+                AfterGameExitReason::Disconnected,
+            ),
+            MatchEvent::Shot { shooter } => {
+                if let Some(row) = rows.iter_mut().find(|row| &row.username == shooter) {
+                    row.shots += 1;
+                }
+            }
+            MatchEvent::Hit { victim, .. } => {
+                if let Some(row) = rows.iter_mut().find(|row| &row.username == victim) {
+                    row.hits_taken += 1;
+                }
+            }
+            MatchEvent::Eliminated { victim, killer } => {
+                if victim != killer
+                    && let Some(row) = rows.iter_mut().find(|row| &row.username == killer)
+                {
+                    row.kills += 1;
+                }
+                if let Some(row) = rows.iter_mut().find(|row| &row.username == victim) {
+                    row.hits_taken += 1;
+                }
+                record_exit(
+                    &mut rows,
+                    &mut exits,
+                    victim,
+                    *tick,
+                    AfterGameExitReason::Shot,
+                );
+            }
+            MatchEvent::Idle { player } => record_exit(
+                &mut rows,
+                &mut exits,
+                player,
+                *tick,
+                AfterGameExitReason::Shot,
+            ),
+            MatchEvent::Won { winner } => record_exit(
+                &mut rows,
+                &mut exits,
+                winner,
+                *tick,
+                AfterGameExitReason::Winner,
+            ),
+            MatchEvent::Escaped { player } => record_exit(
+                &mut rows,
+                &mut exits,
+                player,
+                *tick,
+                AfterGameExitReason::Escaped,
+            ),
+            MatchEvent::TimeUp => {
+                let survivors: Vec<String> = rows
+                    .iter()
+                    .map(|row| row.username.clone())
+                    .filter(|name| !exits.contains_key(name))
+                    .collect();
+                for name in survivors {
+                    record_exit(
+                        &mut rows,
+                        &mut exits,
+                        &name,
+                        *tick,
+                        AfterGameExitReason::Minotaured,
+                    );
+                }
+            }
+        }
+    }
+
+    for row in &mut rows {
+        let exit_tick = exits.get(&row.username).copied().unwrap_or(end_tick);
+        row.ticks_survived = exit_tick.saturating_sub(start_tick);
+    }
+
+    let is_winner = |row: &LeaderboardRow| row.exit_reason == AfterGameExitReason::Winner;
+    rows.sort_by(|a, b| {
+        is_winner(b)
+            .cmp(&is_winner(a))
+            .then(b.ticks_survived.cmp(&a.ticks_survived))
+    });
+    for i in 0..rows.len() {
+        let tied = i > 0
+            && is_winner(&rows[i]) == is_winner(&rows[i - 1])
+            && rows[i].ticks_survived == rows[i - 1].ticks_survived;
+        rows[i].rank = if tied { rows[i - 1].rank } else { i + 1 };
+    }
+    rows
+}
+
+fn record_exit(
+    rows: &mut [LeaderboardRow],
+    exits: &mut HashMap<String, u64>,
+    name: &str,
+    tick: u64,
+    reason: AfterGameExitReason,
+) {
+    if exits.contains_key(name) {
+        return;
+    }
+    exits.insert(name.to_string(), tick);
+    if let Some(row) = rows.iter_mut().find(|row| row.username == name) {
+        row.exit_reason = reason;
+    }
+}
+
+pub fn replay_path_from_args(args: impl IntoIterator<Item = String>) -> Option<PathBuf> {
+    args.into_iter()
+        .filter_map(|arg| arg.strip_prefix("--replay=").map(PathBuf::from))
+        .last()
+}
+
+pub fn load_leaderboard(path: &Path) -> io::Result<Vec<LeaderboardRow>> {
+    let contents = fs::read_to_string(path)?;
+    let events = parse(&contents).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok(reconstruct_leaderboard(&events))
+}
+
+#[derive(Debug, Clone)]
+pub struct EventLog {
+    path: PathBuf,
+}
+
+impl EventLog {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn record(&self, tick: u64, event: &MatchEvent) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}\t{}", tick, event)
+    }
+}
+
+pub fn default_path(start_tick: u64) -> PathBuf {
+    Path::new(EVENT_LOG_DIR).join(format!("match-{}.log", start_tick))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scripted_events_are_appended_one_line_each_in_order() {
+        let path = std::env::temp_dir().join(format!("match-events-{}.log", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let log = EventLog::new(path.clone());
+
+        let script = [
+            (
+                10,
+                MatchEvent::Joined {
+                    player: "alice".to_string(),
+                },
+            ),
+            (
+                10,
+                MatchEvent::Joined {
+                    player: "bob".to_string(),
+                },
+            ),
+            (
+                42,
+                MatchEvent::Shot {
+                    shooter: "alice".to_string(),
+                },
+            ),
+            (
+                45,
+                MatchEvent::Hit {
+                    victim: "bob".to_string(),
+                    health: 8,
+                },
+            ),
+            (
+                90,
+                MatchEvent::Eliminated {
+                    victim: "bob".to_string(),
+                    killer: "alice".to_string(),
+                },
+            ),
+            (
+                90,
+                MatchEvent::Won {
+                    winner: "alice".to_string(),
+                },
+            ),
+            (
+                120,
+                MatchEvent::Left {
+                    player: "bob".to_string(),
+                },
+            ),
+        ];
+        for (tick, event) in &script {
+            log.record(*tick, event).unwrap();
+        }
+
+        let contents = fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            contents.lines().collect::<Vec<_>>(),
+            vec![
+                "10\tjoin\talice",
+                "10\tjoin\tbob",
+                "42\tshot\talice",
+                "45\thit\tbob\t8",
+                "90\teliminated\tbob\talice",
+                "90\tresult\twinner\talice",
+                "120\tleave\tbob",
+            ]
+        );
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    fn row(
+        rank: usize,
+        username: &str,
+        ticks_survived: u64,
+        exit_reason: AfterGameExitReason,
+        (shots, hits_taken, kills): (u32, u32, u32),
+    ) -> LeaderboardRow {
+        LeaderboardRow {
+            rank,
+            username: username.to_string(),
+            ticks_survived,
+            exit_reason,
+            shots,
+            hits_taken,
+            kills,
+        }
+    }
+
+    #[test]
+    fn known_log_reconstructs_leaderboard_with_shared_ranks_for_ties() {
+        let log = "\
+100\tjoin\talice
+100\tjoin\tbob
+100\tjoin\tcarol
+100\tjoin\tdave
+150\tshot\talice
+152\tshot\tcarol
+160\thit\tcarol\t8
+400\tleave\tbob
+650\tshot\talice
+650\tshot\talice
+700\teliminated\tcarol\talice
+700\teliminated\tdave\talice
+700\tresult\twinner\talice
+";
+        let events = parse(log).unwrap();
+        assert_eq!(events.len(), 13);
+
+        assert_eq!(
+            reconstruct_leaderboard(&events),
+            vec![
+                row(1, "alice", 600, AfterGameExitReason::Winner, (3, 0, 2)),
+                row(2, "carol", 600, AfterGameExitReason::Shot, (1, 2, 0)),
+                row(2, "dave", 600, AfterGameExitReason::Shot, (0, 1, 0)),
+                row(4, "bob", 300, AfterGameExitReason::Disconnected, (0, 0, 0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn timer_results_and_malformed_lines_are_handled() {
+        let events = parse("0\tjoin\tsolo\n900\tresult\tescaped\tsolo\n").unwrap();
+        let board = reconstruct_leaderboard(&events);
+        assert_eq!(board[0].exit_reason, AfterGameExitReason::Escaped);
+
+        let events = parse("0\tjoin\ta\n0\tjoin\tb\n900\tresult\ttime up\n").unwrap();
+        let board = reconstruct_leaderboard(&events);
+        assert!(
+            board
+                .iter()
+                .all(|row| row.rank == 1 && row.ticks_survived == 900)
+        );
+        assert!(
+            board
+                .iter()
+                .all(|row| row.exit_reason == AfterGameExitReason::Minotaured)
+        );
+
+        assert!(parse("12\tteleport\talice").is_err());
+        assert!(parse("soon\tjoin\talice").is_err());
+    }
+
+    #[test]
+    fn afk_elimination_ends_survival_without_a_kill() {
+        let events =
+            parse("0\tjoin\ta\n0\tjoin\tb\n300\tidle\tb\n900\tresult\twinner\ta\n").unwrap();
+        let board = reconstruct_leaderboard(&events);
+        assert_eq!(
+            board,
+            vec![
+                row(1, "a", 900, AfterGameExitReason::Winner, (0, 0, 0)),
+                row(2, "b", 300, AfterGameExitReason::Shot, (0, 0, 0)),
+            ]
+        );
+    }
+}
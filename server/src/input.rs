@@ -3,11 +3,19 @@ use std::{
     time::{Duration, Instant},
 };
 
-use bincode::{config::standard, serde::decode_from_slice};
+use bincode::serde::decode_from_slice;
 use rand::{rng, seq::SliceRandom};
 
-use crate::{net::ServerNetworkHandle, player::ServerPlayer, state::Game};
-use common::{net::AppChannel, protocol::ClientMessage};
+use crate::{
+    net::{ServerNetworkHandle, disconnect_with_reason},
+    player::ServerPlayer,
+    state::Game,
+};
+use common::{
+    net::AppChannel,
+    player::PlayerInput,
+    protocol::{ClientMessage, DisconnectReason, config},
+};
 
 const NETWORK_TIME_BUDGET: Duration = Duration::from_millis(2);
 const MAX_MESSAGES_PER_CLIENT_PER_TICK: u32 = 128;
@@ -80,7 +88,7 @@ pub fn receive_inputs(network: &mut dyn ServerNetworkHandle, state: &mut Game) {
                         continue;
                     }
                     InputCapAction::Disconnect => {
-                        network.disconnect(client_id);
+                        disconnect_with_reason(network, client_id, DisconnectReason::ProtocolError);
                         break;
                     }
                 }
@@ -89,14 +97,14 @@ pub fn receive_inputs(network: &mut dyn ServerNetworkHandle, state: &mut Game) {
                     Ok(message) => message,
                     Err(error) => {
                         println!("{}", error.message(client_id, &player.name));
-                        network.disconnect(client_id);
+                        disconnect_with_reason(network, client_id, DisconnectReason::ProtocolError);
                         break;
                     }
                 };
 
                 if let Err(error) = handle_message(player, message) {
                     println!("{}", error.message(client_id, &player.name));
-                    network.disconnect(client_id);
+                    disconnect_with_reason(network, client_id, DisconnectReason::ProtocolError);
                     break;
                 }
             }
@@ -184,14 +192,15 @@ impl fmt::Display for InputError {
 impl std::error::Error for InputError {}
 
 fn decode_message(data: &[u8]) -> Result<ClientMessage, InputError> {
-    decode_from_slice::<ClientMessage, _>(data, standard())
+    decode_from_slice::<ClientMessage, _>(data, config())
         .map(|(message, _)| message)
         .map_err(|_| InputError::Malformed)
 }
 
 fn handle_message(player: &mut ServerPlayer, message: ClientMessage) -> Result<(), InputError> {
     match message {
-        ClientMessage::Input(input) => {
+        ClientMessage::Input(mut input) => {
+            input.data = sanitize_input(input.data);
             player.input_buffer.insert(input);
             Ok(())
         }
@@ -200,6 +209,13 @@ fn handle_message(player: &mut ServerPlayer, message: ClientMessage) -> Result<(
     }
 }
 
+fn sanitize_input(mut input: PlayerInput) -> PlayerInput {
+    if !input.fire {
+        input.fire_nonce = None;
+    }
+    input
+}
+
 fn apply_input_cap(player: &mut ServerPlayer, messages_received: &mut u32) -> InputCapOutcome {
     if *messages_received >= MAX_MESSAGES_PER_CLIENT_PER_TICK {
         let mut event = None;
@@ -234,3 +250,94 @@ fn apply_input_cap(player: &mut ServerPlayer, messages_received: &mut u32) -> In
         event: None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use bincode::serde::encode_to_vec;
+
+    use super::*;
+    use crate::{state_handlers::game::step_simulation, test_helpers::MockServerNetwork};
+    use common::{ring::WireItem, snapshot::InitialData};
+
+    fn game_with_player(network: &mut MockServerNetwork) -> Game {
+        network.add_client(1);
+        let usernames = HashMap::from([(1, "Alice".to_string())]);
+        Game::new(InitialData::new(&usernames, &HashMap::new(), 1))
+    }
+
+    fn queue_input(network: &mut MockServerNetwork, tick: u64, input: PlayerInput) {
+        let message = ClientMessage::Input(WireItem {
+            id: tick as u16,
+            data: input,
+        });
+        network.queue_raw_message(1, encode_to_vec(&message, config()).unwrap());
+    }
+
+    #[test]
+    fn input_message_updates_player_through_shared_simulation() {
+        let mut network = MockServerNetwork::new();
+        let mut game = game_with_player(&mut network);
+        let index = game.client_id_to_index[&1];
+
+        let tick = game.current_tick + 1;
+        let input = PlayerInput {
+            sim_tick: tick,
+            forward: true,
+            yaw_left: true,
+            ..Default::default()
+        };
+        queue_input(&mut network, tick, input);
+
+        receive_inputs(&mut network, &mut game);
+        let player = &mut game.players[index];
+        assert_eq!(player.input_buffer.get(tick), Some(&input));
+
+        let start = player.state.position;
+        let mut expected = player.state;
+        let positions = vec![(index, start)];
+        expected.update(
+            &game.maze,
+            &input,
+            &game.movement_profile,
+            index,
+            &positions,
+            1.0,
+        );
+
+        player.last_input = input;
+        game.current_tick = tick;
+        step_simulation(&mut game);
+
+        assert_eq!(game.players[index].state, expected);
+        assert_ne!(game.players[index].state.position, start);
+    }
+
+    #[test]
+    fn fire_nonce_without_fire_is_dropped_and_stays_dropped() {
+        let mut network = MockServerNetwork::new();
+        let mut game = game_with_player(&mut network);
+        let index = game.client_id_to_index[&1];
+
+        let tick = game.current_tick + 1;
+        let input = PlayerInput {
+            sim_tick: tick,
+            fire: false,
+            fire_nonce: Some(7),
+            ..Default::default()
+        };
+        queue_input(&mut network, tick, input);
+        receive_inputs(&mut network, &mut game);
+
+        let stored = *game.players[index].input_buffer.get(tick).unwrap();
+        assert_eq!(stored.fire_nonce, None);
+        assert_eq!(sanitize_input(stored), stored);
+
+        let bullets_before = game.next_bullet_id;
+        game.players[index].last_input = stored;
+        game.current_tick = tick;
+        step_simulation(&mut game);
+        assert_eq!(game.next_bullet_id, bullets_before);
+    }
+}
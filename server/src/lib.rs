@@ -1,7 +1,16 @@
+pub mod awards;
+pub mod bot;
+pub mod chat;
+pub mod console;
+pub mod event_log;
 pub mod input;
 pub mod net;
 pub mod player;
+pub mod replay;
+pub mod rooms;
+pub mod rounds;
 pub mod run;
+pub mod settings;
 pub mod state;
 pub mod state_handlers;
 #[cfg(test)]
@@ -1,8 +1,12 @@
 use std::{
     env,
     io::{self, stdout},
-    net::SocketAddr,
+    net::IpAddr,
     process,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
 };
 
 use crossterm::{
@@ -13,7 +17,7 @@ use crossterm::{
 };
 
 use common;
-use server::{self, net::BINDING_ADDRESS};
+use server::{self, event_log, net, settings::Settings};
 
 pub struct Defer;
 
@@ -44,23 +48,47 @@ fn clean_up() {
 fn main() {
     let _defer = Defer::new();
 
+    let shutdown = Arc::new(AtomicBool::new(false));
+    let handler_shutdown = Arc::clone(&shutdown);
     ctrlc::set_handler(move || {
-        clean_up();
-        println!("Server forced to shut down.");
-        std::process::exit(0);
+        if handler_shutdown.swap(true, Ordering::Relaxed) {
+            clean_up();
+            println!("Server forced to shut down.");
+            std::process::exit(0);
+        }
     })
     .ok();
 
+    if let Some(path) = event_log::replay_path_from_args(env::args()) {
+        match event_log::load_leaderboard(&path) {
+            Ok(rows) => rows.iter().for_each(|row| println!("{}", row)),
+            Err(e) => {
+                eprintln!("error: failed to replay {}: {}", path.display(), e);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
     let private_key = common::auth::private_key();
-    let public_host = env::var("IP").unwrap_or_else(|_| "127.0.0.1".to_string());
-    let public_ip: std::net::IpAddr = public_host
-        .parse()
-        .expect("`IP` is not a valid IP address.");
-    let connectable_addr = SocketAddr::new(public_ip, 5000);
+    let (bind_args, settings_args): (Vec<String>, Vec<String>) = env::args()
+        .skip(1)
+        .partition(|arg| arg.starts_with("--bind="));
+    let binding_address = match net::bind_address_from_args(&bind_args) {
+        Ok(addr) => addr,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        }
+    };
+    let public_ip: Option<IpAddr> = env::var("IP")
+        .ok()
+        .map(|host| host.parse().expect("`IP` is not a valid IP address."));
+    let connectable_addr = net::connectable_address(binding_address, public_ip);
 
-    let socket = match common::net::bind_socket(BINDING_ADDRESS) {
+    let socket = match common::net::bind_socket(binding_address) {
         Ok(socket) => {
-            println!("Server listening on {}.", BINDING_ADDRESS);
+            println!("Server listening on {}.", binding_address);
             socket
         }
         Err(e) => {
@@ -73,5 +101,6 @@ fn main() {
         }
     };
 
-    server::run::run_server(socket, connectable_addr, private_key);
+    let settings = Settings::from_args(settings_args);
+    server::run::run_server(socket, connectable_addr, private_key, settings, &shutdown);
 }
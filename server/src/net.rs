@@ -1,15 +1,31 @@
 use std::{
-    net::{IpAddr, Ipv4Addr, SocketAddr},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
     time::Duration,
 };
 
+use bincode::serde::encode_to_vec;
 use renet::{RenetServer, ServerEvent};
 use renet_netcode::{ServerAuthentication, ServerConfig};
 
-use common::{self, constants::MAX_PLAYERS, net::AppChannel};
+use common::{
+    self,
+    constants::MAX_PLAYERS,
+    net::AppChannel,
+    protocol::{DisconnectReason, ServerMessage, config},
+};
 
 pub const BINDING_ADDRESS: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 5000);
 
+pub const MAX_RELIABLE_MESSAGE_BYTES: usize = 16 * 1024;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SendPriority {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
 pub enum ServerNetworkEvent {
     ClientConnected { client_id: u64 },
     ClientDisconnected { client_id: u64, reason: String },
@@ -23,10 +39,81 @@ pub trait ServerNetworkHandle {
     fn broadcast_message(&mut self, channel: AppChannel, message: Vec<u8>);
     fn disconnect(&mut self, client_id: u64);
     fn broadcast_message_except(&mut self, client_id: u64, channel: AppChannel, message: Vec<u8>);
+    fn send_message_with_priority(
+        &mut self,
+        client_id: u64,
+        channel: AppChannel,
+        message: Vec<u8>,
+        priority: SendPriority,
+    );
+    fn broadcast_message_with_priority(
+        &mut self,
+        channel: AppChannel,
+        message: Vec<u8>,
+        priority: SendPriority,
+    );
+    fn flush_prioritized(&mut self);
+}
+
+pub struct PrioritizedMessage {
+    pub priority: SendPriority,
+    pub client_id: Option<u64>,
+    pub channel: AppChannel,
+    pub message: Vec<u8>,
+}
+
+#[derive(Default)]
+pub struct PriorityOutbox {
+    pending: Vec<PrioritizedMessage>,
+}
+
+impl PriorityOutbox {
+    pub fn push(&mut self, message: PrioritizedMessage) {
+        self.pending.push(message);
+    }
+
+    pub fn drain(&mut self) -> Vec<PrioritizedMessage> {
+        let mut messages = std::mem::take(&mut self.pending);
+        messages.sort_by_key(|entry| std::cmp::Reverse(entry.priority));
+        messages
+    }
+}
+
+pub fn is_oversized(client_id: u64, data: &[u8]) -> bool {
+    if data.len() <= MAX_RELIABLE_MESSAGE_BYTES {
+        return false;
+    }
+    eprintln!(
+        "client {} sent an oversized message ({} bytes); dropping it",
+        client_id,
+        data.len()
+    );
+    true
+}
+
+pub fn disconnect_with_reason(
+    network: &mut dyn ServerNetworkHandle,
+    client_id: u64,
+    reason: DisconnectReason,
+) {
+    let message = ServerMessage::DisconnectReason(reason);
+    let payload = encode_to_vec(&message, config()).expect("failed to serialize DisconnectReason");
+    network.send_message(client_id, AppChannel::ReliableOrdered, payload);
+    network.disconnect(client_id);
 }
 
 pub struct RenetServerNetworkHandle<'a> {
     pub server: &'a mut RenetServer,
+    outbox: PriorityOutbox,
+}
+
+impl<'a> RenetServerNetworkHandle<'a> {
+    pub fn new(server: &'a mut RenetServer) -> Self {
+        Self {
+            server,
+            outbox: PriorityOutbox::default(),
+        }
+    }
 }
 
 impl ServerNetworkHandle for RenetServerNetworkHandle<'_> {
@@ -70,6 +157,68 @@ impl ServerNetworkHandle for RenetServerNetworkHandle<'_> {
     fn disconnect(&mut self, client_id: u64) {
         self.server.disconnect(client_id);
     }
+
+    fn send_message_with_priority(
+        &mut self,
+        client_id: u64,
+        channel: AppChannel,
+        message: Vec<u8>,
+        priority: SendPriority,
+    ) {
+        self.outbox.push(PrioritizedMessage {
+            priority,
+            client_id: Some(client_id),
+            channel,
+            message,
+        });
+    }
+
+    fn broadcast_message_with_priority(
+        &mut self,
+        channel: AppChannel,
+        message: Vec<u8>,
+        priority: SendPriority,
+    ) {
+        self.outbox.push(PrioritizedMessage {
+            priority,
+            client_id: None,
+            channel,
+            message,
+        });
+    }
+
+    fn flush_prioritized(&mut self) {
+        for entry in self.outbox.drain() {
+            match entry.client_id {
+                Some(client_id) => {
+                    self.server
+                        .send_message(client_id, entry.channel, entry.message)
+                }
+                None => self.server.broadcast_message(entry.channel, entry.message),
+            }
+        }
+    }
+}
+
+pub fn bind_address_from_args<'a>(
+    args: impl IntoIterator<Item = &'a String>,
+) -> Result<SocketAddr, String> {
+    let mut addr = BINDING_ADDRESS;
+    for arg in args {
+        if let Some(value) = arg.strip_prefix("--bind=") {
+            addr = common::net::parse_socket_addr(value, BINDING_ADDRESS.port())?;
+        }
+    }
+    Ok(addr)
+}
+
+pub fn connectable_address(bind_addr: SocketAddr, public_ip: Option<IpAddr>) -> SocketAddr {
+    let ip = public_ip.unwrap_or(match bind_addr.ip() {
+        IpAddr::V4(ip) if ip.is_unspecified() => IpAddr::V4(Ipv4Addr::LOCALHOST),
+        IpAddr::V6(ip) if ip.is_unspecified() => IpAddr::V6(Ipv6Addr::LOCALHOST),
+        ip => ip,
+    });
+    SocketAddr::new(ip, bind_addr.port())
 }
 
 pub fn build_server_config(
@@ -86,3 +235,120 @@ pub fn build_server_config(
         authentication: ServerAuthentication::Secure { private_key },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::MockServerNetwork;
+
+    #[test]
+    fn higher_priority_messages_are_sent_first_when_queued_together() {
+        let mut network = MockServerNetwork::new();
+        network.add_client(1);
+
+        network.send_message_with_priority(
+            1,
+            AppChannel::ReliableOrdered,
+            b"chat".to_vec(),
+            SendPriority::Low,
+        );
+        network.send_message_with_priority(
+            1,
+            AppChannel::ReliableOrdered,
+            b"kill feed".to_vec(),
+            SendPriority::Normal,
+        );
+        network.send_message_with_priority(
+            1,
+            AppChannel::ReliableOrdered,
+            b"victory".to_vec(),
+            SendPriority::High,
+        );
+        network.send_message_with_priority(
+            1,
+            AppChannel::ReliableOrdered,
+            b"more chat".to_vec(),
+            SendPriority::Low,
+        );
+
+        assert!(network.get_sent_messages_data(1).is_empty());
+
+        network.flush_prioritized();
+
+        assert_eq!(
+            network.get_sent_messages_data(1),
+            vec![
+                b"victory".to_vec(),
+                b"kill feed".to_vec(),
+                b"chat".to_vec(),
+                b"more chat".to_vec(),
+            ]
+        );
+    }
+
+    #[test]
+    fn prioritized_broadcasts_are_ordered_by_priority() {
+        let mut network = MockServerNetwork::new();
+
+        network.broadcast_message_with_priority(
+            AppChannel::ReliableOrdered,
+            b"low".to_vec(),
+            SendPriority::Low,
+        );
+        network.broadcast_message_with_priority(
+            AppChannel::ReliableOrdered,
+            b"high".to_vec(),
+            SendPriority::High,
+        );
+        network.flush_prioritized();
+
+        assert_eq!(
+            network.get_broadcast_messages_data(),
+            vec![b"high".to_vec(), b"low".to_vec()]
+        );
+    }
+
+    fn public_addresses(args: &[&str], public_ip: Option<IpAddr>) -> Vec<SocketAddr> {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        let bind_addr = bind_address_from_args(&args).expect("expected a valid bind address");
+        let connectable_addr = connectable_address(bind_addr, public_ip);
+        build_server_config(Duration::ZERO, 1, connectable_addr, [0; 32]).public_addresses
+    }
+
+    #[test]
+    fn public_address_follows_an_ipv4_bind_address() {
+        assert_eq!(
+            public_addresses(&[], None),
+            vec!["127.0.0.1:5000".parse().unwrap()]
+        );
+        assert_eq!(
+            public_addresses(&["--bind=192.168.1.50:6000"], None),
+            vec!["192.168.1.50:6000".parse().unwrap()]
+        );
+        assert_eq!(
+            public_addresses(
+                &["--bind=0.0.0.0:6000"],
+                Some("203.0.113.42".parse().unwrap())
+            ),
+            vec!["203.0.113.42:6000".parse().unwrap()]
+        );
+    }
+
+    #[test]
+    fn public_address_follows_an_ipv6_bind_address() {
+        assert_eq!(
+            public_addresses(&["--bind=[::]:6000"], None),
+            vec!["[::1]:6000".parse().unwrap()]
+        );
+        assert_eq!(
+            public_addresses(&["--bind=2001:db8::7"], None),
+            vec!["[2001:db8::7]:5000".parse().unwrap()]
+        );
+    }
+
+    #[test]
+    fn invalid_bind_address_is_an_error() {
+        let args = vec!["--bind=localhost:5000".to_string()];
+        assert!(bind_address_from_args(&args).is_err());
+    }
+}
@@ -1,10 +1,20 @@
+use glam::{Vec3, vec2};
+
+use crate::bot::Bot;
 use common::{
-    player::{Color, Player, PlayerInput, PlayerState},
+    bullets::Ammo,
+    constants::TICK_SECS_F32,
+    maze::Maze,
+    player::{self, Color, MovementProfile, Player, PlayerInput, PlayerState},
     ring::NetworkBuffer,
 };
 
 pub const INPUT_BUFFER_LENGTH: usize = 128;
 
+pub fn max_step_per_tick(movement: &MovementProfile) -> f32 {
+    movement.max_speed * TICK_SECS_F32 + 2.0 * player::RADIUS
+}
+
 pub struct ServerPlayer {
     pub last_processed_tick: u64,
     pub last_input: PlayerInput,
@@ -18,9 +28,22 @@ pub struct ServerPlayer {
     pub over_cap_strikes: u8,
     pub health: u8,
     pub last_fire_tick: Option<u64>,
+    pub fire_cooldown_violations: u32,
+    pub speed_violations: u32,
+    pub last_active_tick: u64,
+    pub afk: bool,
     pub bullets_in_air: usize,
+    pub ammo: Option<Ammo>,
     pub exit_tick: Option<u64>,
     pub is_zoomed: bool,
+    pub team: Option<u8>,
+    pub kills: u32,
+    pub deaths: u32,
+    pub self_kills: u32,
+    pub shots_fired: u32,
+    pub hits_landed: u32,
+    pub damage_dealt: u32,
+    pub bot: Option<Bot>,
 }
 
 impl ServerPlayer {
@@ -46,11 +69,51 @@ impl ServerPlayer {
             over_cap_strikes: 0,
             health: player.health,
             last_fire_tick: None,
+            fire_cooldown_violations: 0,
+            speed_violations: 0,
+            last_active_tick: current_tick,
+            afk: false,
             bullets_in_air: 0,
+            ammo: None,
             exit_tick: None,
             is_zoomed: false,
+            team: player.team,
+            kills: 0,
+            deaths: 0,
+            self_kills: 0,
+            shots_fired: 0,
+            hits_landed: 0,
+            damage_dealt: 0,
+            bot: None,
         }
     }
+
+    pub fn check_step(&mut self, maze: &Maze, previous: Vec3, max_step: f32) -> bool {
+        let offset = self.state.position - previous;
+        let distance = vec2(offset.x, offset.z).length();
+        if distance <= max_step || self.has_teleported(maze, previous) {
+            return true;
+        }
+
+        self.speed_violations = self.speed_violations.saturating_add(1);
+        eprintln!(
+            "client {} ({}) moved {distance:.1} units in one tick (max {max_step:.1}); ignoring the step",
+            self.client_id, self.name
+        );
+        self.state.position = previous;
+        self.state.velocity = Vec3::ZERO;
+        false
+    }
+
+    fn has_teleported(&self, maze: &Maze, previous: Vec3) -> bool {
+        let Some((x, z)) = maze.grid_coordinates_from_position(&self.state.position) else {
+            return false;
+        };
+        maze.grid_coordinates_from_position(&previous) != Some((x, z))
+            && maze
+                .teleporter_destination(z as usize, x as usize)
+                .is_some()
+    }
 }
 
 #[repr(u8)]
@@ -59,3 +122,62 @@ pub enum Status {
     Dead,
     Disconnected,
 }
+
+#[cfg(test)]
+mod tests {
+    use glam::vec3;
+
+    use super::*;
+    use common::maze::{CELL_SIZE, SPACE, WALL};
+
+    fn corridor_with_teleporters() -> Maze {
+        let mut grid = vec![vec![WALL; 5]; 5];
+        for row in [1, 3] {
+            grid[row][1..4].fill(SPACE);
+        }
+        let spaces = (1..4).flat_map(|x| [(1, x), (3, x)]).collect();
+        let mut maze = Maze {
+            grid,
+            spaces,
+            teleporters: Vec::new(),
+        };
+        maze.add_teleporter_pair((1, 2), (3, 2));
+        maze
+    }
+
+    fn player_at(position: Vec3) -> ServerPlayer {
+        let player = Player::new(0, 1, "Alice".to_string(), position, Color::RED);
+        ServerPlayer::new(player, 0)
+    }
+
+    #[test]
+    fn normal_step_is_accepted_and_impossible_jump_is_rejected() {
+        let maze = corridor_with_teleporters();
+        let max_step = max_step_per_tick(&MovementProfile::default());
+        let start = vec3(1.5 * CELL_SIZE, player::HEIGHT, 1.5 * CELL_SIZE);
+        let mut player = player_at(start);
+
+        let step = start + vec3(max_step * 0.9, 0.0, 0.0);
+        player.state.position = step;
+        assert!(player.check_step(&maze, start, max_step));
+        assert_eq!(player.state.position, step);
+
+        player.state.position = step + vec3(0.0, 0.0, max_step * 2.0);
+        assert!(!player.check_step(&maze, step, max_step));
+        assert_eq!(player.state.position, step);
+        assert_eq!(player.speed_violations, 1);
+    }
+
+    #[test]
+    fn teleporting_is_not_a_speed_violation() {
+        let maze = corridor_with_teleporters();
+        let max_step = max_step_per_tick(&MovementProfile::default());
+        let start = vec3(2.0 * CELL_SIZE - 1.0, player::HEIGHT, 1.5 * CELL_SIZE);
+        let destination = vec3(2.5 * CELL_SIZE, player::HEIGHT, 3.5 * CELL_SIZE);
+        let mut player = player_at(destination);
+
+        assert!(player.check_step(&maze, start, max_step));
+        assert_eq!(player.state.position, destination);
+        assert_eq!(player.speed_violations, 0);
+    }
+}
@@ -0,0 +1,227 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    fs,
+    hash::{Hash, Hasher},
+    io,
+    path::{Path, PathBuf},
+};
+
+use bincode::serde::{decode_from_slice, encode_to_vec};
+use serde::{Deserialize, Serialize};
+
+use crate::{player::Status, state::Game, state_handlers::game::step_simulation};
+use common::{player::PlayerInput, protocol::config, snapshot::InitialData};
+
+const REPLAY_DIR: &str = "replays";
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ReplayTick {
+    pub tick: u64,
+    pub inputs: Vec<Option<PlayerInput>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Replay {
+    pub initial_data: InitialData,
+    pub friendly_fire: bool,
+    pub start_tick: u64,
+    pub ticks: Vec<ReplayTick>,
+    pub checksum: u64,
+}
+
+pub struct ReplayRecorder {
+    path: PathBuf,
+    replay: Replay,
+}
+
+impl ReplayRecorder {
+    pub fn new(
+        path: PathBuf,
+        initial_data: &InitialData,
+        start_tick: u64,
+        friendly_fire: bool,
+    ) -> Self {
+        Self {
+            path,
+            replay: Replay {
+                initial_data: initial_data.clone(),
+                friendly_fire,
+                start_tick,
+                ticks: Vec::new(),
+                checksum: 0,
+            },
+        }
+    }
+
+    pub fn record_tick(&mut self, tick: u64, inputs: Vec<Option<PlayerInput>>) {
+        self.replay.ticks.push(ReplayTick { tick, inputs });
+    }
+
+    pub fn finish(mut self, checksum: u64) -> io::Result<PathBuf> {
+        self.replay.checksum = checksum;
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let bytes = encode_to_vec(&self.replay, config())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(&self.path, bytes)?;
+        Ok(self.path)
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+}
+
+pub fn default_path(start_tick: u64) -> PathBuf {
+    Path::new(REPLAY_DIR).join(format!("replay-{}.bin", start_tick))
+}
+
+pub fn load(path: &Path) -> io::Result<Replay> {
+    let bytes = fs::read(path)?;
+    let (replay, _) = decode_from_slice(&bytes, config())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok(replay)
+}
+
+pub fn checksum(game: &Game) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for player in &game.players {
+        let state = &player.state;
+        for value in [
+            state.position.x,
+            state.position.y,
+            state.position.z,
+            state.yaw,
+            state.pitch,
+        ] {
+            value.to_bits().hash(&mut hasher);
+        }
+        if matches!(player.status, Status::Alive) {
+            player.health.hash(&mut hasher);
+        }
+    }
+    for bullet in &game.bullets {
+        bullet.id.hash(&mut hasher);
+        for value in [bullet.position.x, bullet.position.y, bullet.position.z] {
+            value.to_bits().hash(&mut hasher);
+        }
+    }
+    hasher.finish()
+}
+
+pub fn replay(replay: &Replay) -> Result<Game, String> {
+    let mut game = Game::new(replay.initial_data.clone());
+    game.friendly_fire = replay.friendly_fire;
+    game.current_tick = replay.start_tick;
+    game.game_start_tick = replay.start_tick;
+
+    for recorded in &replay.ticks {
+        if recorded.tick < game.current_tick {
+            return Err(format!(
+                "replay tick {} precedes current tick {}",
+                recorded.tick, game.current_tick
+            ));
+        }
+        while game.current_tick < recorded.tick {
+            game.hold_paused_tick();
+            game.current_tick += 1;
+        }
+
+        if recorded.inputs.len() != game.players.len() {
+            return Err(format!(
+                "replay tick {} has {} inputs for {} players",
+                recorded.tick,
+                recorded.inputs.len(),
+                game.players.len()
+            ));
+        }
+
+        for (player, input) in game.players.iter_mut().zip(&recorded.inputs) {
+            match input {
+                Some(input) => player.last_input = *input,
+                None => {
+                    if matches!(player.status, Status::Alive) {
+                        player.status = Status::Dead;
+                    }
+                }
+            }
+        }
+
+        step_simulation(&mut game);
+        game.current_tick += 1;
+    }
+
+    let actual = checksum(&game);
+    if actual != replay.checksum {
+        return Err(format!(
+            "replay checksum mismatch: expected {:016x}, got {:016x}",
+            replay.checksum, actual
+        ));
+    }
+
+    Ok(game)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn record_game(path: PathBuf) -> (Game, ReplayRecorder) {
+        let usernames = HashMap::from([(1, "Alice".to_string()), (2, "Bob".to_string())]);
+        let initial_data = InitialData::new(&usernames, &HashMap::new(), 1);
+        let mut game = Game::new(initial_data.clone());
+        let mut recorder =
+            ReplayRecorder::new(path, &initial_data, game.current_tick, game.friendly_fire);
+
+        for step in 0..3u32 {
+            for player in &mut game.players {
+                player.last_input = PlayerInput {
+                    sim_tick: game.current_tick,
+                    forward: true,
+                    yaw_left: player.index == 0,
+                    fire: step == 0,
+                    fire_nonce: (step == 0).then_some(step),
+                    ..Default::default()
+                };
+            }
+            game.replay = Some(recorder);
+            game.record_replay_tick();
+            recorder = game.replay.take().unwrap();
+            step_simulation(&mut game);
+            game.current_tick += 1;
+        }
+
+        (game, recorder)
+    }
+
+    #[test]
+    fn recorded_game_replays_to_same_state() {
+        let path = std::env::temp_dir().join(format!("replay-test-{}.bin", std::process::id()));
+        let (game, recorder) = record_game(path.clone());
+        assert_eq!(recorder.replay().ticks.len(), 3);
+
+        let saved = recorder
+            .finish(checksum(&game))
+            .expect("failed to save replay");
+        let loaded = load(&saved).expect("failed to load replay");
+        fs::remove_file(&saved).ok();
+
+        let replayed = replay(&loaded).expect("replay diverged");
+        assert_eq!(replayed.bullets.len(), game.bullets.len());
+        for (original, replayed) in game.players.iter().zip(&replayed.players) {
+            assert_eq!(original.state.position, replayed.state.position);
+            assert_eq!(original.state.yaw, replayed.state.yaw);
+        }
+    }
+
+    #[test]
+    fn replay_reports_checksum_mismatch() {
+        let (game, recorder) = record_game(PathBuf::new());
+        let mut recorded = recorder.replay().clone();
+        recorded.checksum = checksum(&game).wrapping_add(1);
+
+        assert!(replay(&recorded).is_err());
+    }
+}
@@ -0,0 +1,702 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    mem,
+    time::Instant,
+};
+
+use bincode::serde::{decode_from_slice, encode_to_vec};
+
+use crate::{
+    chat::{ChatLog, SharedChatLog},
+    net::{
+        SendPriority, ServerNetworkEvent, ServerNetworkHandle, disconnect_with_reason, is_oversized,
+    },
+    run::{sync_clocks, update_server_state},
+    settings::Settings,
+    state::{Lobby, ServerState},
+};
+use common::{
+    auth::Passcode,
+    net::AppChannel,
+    protocol::{ClientMessage, DisconnectReason, RoomSummary, ServerMessage, config},
+};
+
+pub const ROOM_CODE_LENGTH: usize = 4;
+pub const PASSCODE_LENGTH: usize = 6;
+
+pub struct Room {
+    pub code: String,
+    pub passcode: Passcode,
+    pub state: ServerState,
+    pub last_activity: Instant,
+    members: HashSet<u64>,
+    events: VecDeque<ServerNetworkEvent>,
+}
+
+impl Room {
+    pub fn new(code: String, settings: Settings, chat_log: Option<SharedChatLog>) -> Self {
+        let mut lobby = Lobby::with_settings(settings);
+        lobby.chat_log = chat_log;
+        Self {
+            code,
+            passcode: Passcode::generate(PASSCODE_LENGTH),
+            state: ServerState::Lobby(lobby),
+            last_activity: Instant::now(),
+            members: HashSet::new(),
+            events: VecDeque::new(),
+        }
+    }
+
+    pub fn rotate_passcode(&mut self) -> &Passcode {
+        let previous = mem::replace(&mut self.passcode, Passcode::generate(PASSCODE_LENGTH));
+        while self.passcode.bytes == previous.bytes {
+            self.passcode = Passcode::generate(PASSCODE_LENGTH);
+        }
+        &self.passcode
+    }
+
+    pub fn members(&self) -> &HashSet<u64> {
+        &self.members
+    }
+
+    fn update(&mut self, network: &mut dyn ServerNetworkHandle, sync: bool) {
+        let mut handle = RoomNetworkHandle::new(network, &self.members, &mut self.events);
+
+        if sync {
+            sync_clocks(&mut handle, &mut self.state);
+        }
+
+        update_server_state(
+            &mut handle,
+            &mut self.state,
+            &self.passcode,
+            &mut self.last_activity,
+        );
+    }
+}
+
+pub struct Rooms {
+    rooms: Vec<Room>,
+    client_rooms: HashMap<u64, String>,
+    unassigned: HashSet<u64>,
+    settings: Settings,
+    chat_log: Option<SharedChatLog>,
+}
+
+impl Rooms {
+    pub fn new(settings: Settings) -> Self {
+        let chat_log = settings.chat_log.as_deref().and_then(ChatLog::open_shared);
+        Self {
+            rooms: Vec::new(),
+            client_rooms: HashMap::new(),
+            unassigned: HashSet::new(),
+            settings,
+            chat_log,
+        }
+    }
+
+    pub fn create_room(&mut self) -> &Room {
+        let code = loop {
+            let code = generate_code();
+            if self.room(&code).is_none() {
+                break code;
+            }
+        };
+        println!("Room {} created.", code);
+        self.rooms.push(Room::new(
+            code,
+            self.settings.clone(),
+            self.chat_log.clone(),
+        ));
+        self.rooms.last().expect("room was just created")
+    }
+
+    pub fn room(&self, code: &str) -> Option<&Room> {
+        self.rooms.iter().find(|room| room.code == code)
+    }
+
+    pub fn room_mut(&mut self, code: &str) -> Option<&mut Room> {
+        self.rooms.iter_mut().find(|room| room.code == code)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Room> {
+        self.rooms.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Room> {
+        self.rooms.iter_mut()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rooms.is_empty()
+    }
+
+    pub fn room_of(&self, client_id: u64) -> Option<&str> {
+        self.client_rooms.get(&client_id).map(String::as_str)
+    }
+
+    pub fn update(&mut self, network: &mut dyn ServerNetworkHandle, sync: bool) {
+        self.route_events(network);
+        self.assign_unassigned(network);
+
+        for room in &mut self.rooms {
+            room.update(network, sync);
+        }
+
+        self.close_finished_rooms(network);
+    }
+
+    fn route_events(&mut self, network: &mut dyn ServerNetworkHandle) {
+        while let Some(event) = network.get_event() {
+            match event {
+                ServerNetworkEvent::ClientConnected { client_id } => {
+                    if self.unassigned.contains(&client_id)
+                        || self.client_rooms.contains_key(&client_id)
+                    {
+                        eprintln!(
+                            "client {} is already connected; disconnecting duplicate connection",
+                            client_id
+                        );
+                        disconnect_with_reason(
+                            network,
+                            client_id,
+                            DisconnectReason::DuplicateConnection,
+                        );
+                        continue;
+                    }
+                    self.unassigned.insert(client_id);
+                }
+                ServerNetworkEvent::ClientDisconnected { client_id, reason } => {
+                    if self.unassigned.remove(&client_id) {
+                        println!(
+                            "Client {} disconnected before joining a room: {}.",
+                            client_id, reason
+                        );
+                        continue;
+                    }
+                    let Some(code) = self.client_rooms.remove(&client_id) else {
+                        continue;
+                    };
+                    if let Some(room) = self.room_mut(&code) {
+                        room.members.remove(&client_id);
+                        room.events
+                            .push_back(ServerNetworkEvent::ClientDisconnected {
+                                client_id,
+                                reason,
+                            });
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn summaries(&self) -> Vec<RoomSummary> {
+        self.rooms
+            .iter()
+            .map(|room| RoomSummary {
+                code: room.code.clone(),
+                players: room.members.len(),
+                state: room.state.name().to_string(),
+                passcode_protected: !room.passcode.bytes.is_empty(),
+            })
+            .collect()
+    }
+
+    fn send_room_list(&self, network: &mut dyn ServerNetworkHandle, client_id: u64) {
+        let message = ServerMessage::RoomList {
+            rooms: self.summaries(),
+        };
+        let payload = encode_to_vec(&message, config()).expect("failed to serialize RoomList");
+        network.send_message(client_id, AppChannel::ReliableOrdered, payload);
+    }
+
+    fn assign_unassigned(&mut self, network: &mut dyn ServerNetworkHandle) {
+        let unassigned: Vec<u64> = self.unassigned.iter().copied().collect();
+        for client_id in unassigned {
+            let code = loop {
+                let Some(data) = network.receive_message(client_id, AppChannel::ReliableOrdered)
+                else {
+                    break None;
+                };
+                if is_oversized(client_id, &data) {
+                    continue;
+                }
+                match decode_from_slice::<ClientMessage, _>(&data, config()) {
+                    Ok((ClientMessage::ListRooms, _)) => self.send_room_list(network, client_id),
+                    Ok((ClientMessage::JoinRoom(code), _)) => {
+                        break Some(code.trim().to_uppercase());
+                    }
+                    _ => {
+                        eprintln!(
+                            "client {} did not ask to join a room first; disconnecting them",
+                            client_id
+                        );
+                        disconnect_with_reason(network, client_id, DisconnectReason::ProtocolError);
+                        self.unassigned.remove(&client_id);
+                        break None;
+                    }
+                }
+            };
+            let Some(code) = code else {
+                continue;
+            };
+            self.unassigned.remove(&client_id);
+
+            let room = if code.is_empty() {
+                self.rooms.first_mut()
+            } else {
+                self.rooms.iter_mut().find(|room| room.code == code)
+            };
+
+            let Some(room) = room else {
+                eprintln!(
+                    "client {} asked for unknown room '{}'; informing, then disconnecting them",
+                    client_id, code
+                );
+                let message = ServerMessage::ServerInfo {
+                    message: format!("There is no room with code '{}'.", code),
+                };
+                let payload =
+                    encode_to_vec(&message, config()).expect("failed to serialize ServerInfo");
+                network.send_message(client_id, AppChannel::ReliableOrdered, payload);
+                network.disconnect(client_id);
+                continue;
+            };
+
+            println!("Client {} joined room {}.", client_id, room.code);
+            room.members.insert(client_id);
+            room.events
+                .push_back(ServerNetworkEvent::ClientConnected { client_id });
+            self.client_rooms.insert(client_id, room.code.clone());
+        }
+    }
+
+    fn close_finished_rooms(&mut self, network: &mut dyn ServerNetworkHandle) {
+        let (closed, open): (Vec<Room>, Vec<Room>) = std::mem::take(&mut self.rooms)
+            .into_iter()
+            .partition(|room| room.state.is_closed());
+        self.rooms = open;
+
+        for room in closed {
+            println!("Room {} closed.", room.code);
+            for client_id in room.members {
+                self.client_rooms.remove(&client_id);
+                network.disconnect(client_id);
+            }
+        }
+    }
+}
+
+fn generate_code() -> String {
+    (0..ROOM_CODE_LENGTH)
+        .map(|_| char::from(b'A' + rand::random_range(0..26u8)))
+        .collect()
+}
+
+pub struct RoomNetworkHandle<'a> {
+    inner: &'a mut dyn ServerNetworkHandle,
+    members: &'a HashSet<u64>,
+    events: &'a mut VecDeque<ServerNetworkEvent>,
+}
+
+impl<'a> RoomNetworkHandle<'a> {
+    pub fn new(
+        inner: &'a mut dyn ServerNetworkHandle,
+        members: &'a HashSet<u64>,
+        events: &'a mut VecDeque<ServerNetworkEvent>,
+    ) -> Self {
+        Self {
+            inner,
+            members,
+            events,
+        }
+    }
+
+    fn is_member(&self, client_id: u64) -> bool {
+        if self.members.contains(&client_id) {
+            return true;
+        }
+        eprintln!(
+            "client {} is not a member of this room; ignoring",
+            client_id
+        );
+        false
+    }
+}
+
+impl ServerNetworkHandle for RoomNetworkHandle<'_> {
+    fn get_event(&mut self) -> Option<ServerNetworkEvent> {
+        self.events.pop_front()
+    }
+
+    fn clients_id(&self) -> Vec<u64> {
+        self.inner
+            .clients_id()
+            .into_iter()
+            .filter(|client_id| self.members.contains(client_id))
+            .collect()
+    }
+
+    fn receive_message(&mut self, client_id: u64, channel: AppChannel) -> Option<Vec<u8>> {
+        if !self.members.contains(&client_id) {
+            return None;
+        }
+        self.inner.receive_message(client_id, channel)
+    }
+
+    fn send_message(&mut self, client_id: u64, channel: AppChannel, message: Vec<u8>) {
+        if self.is_member(client_id) {
+            self.inner.send_message(client_id, channel, message);
+        }
+    }
+
+    fn broadcast_message(&mut self, channel: AppChannel, message: Vec<u8>) {
+        for client_id in self.clients_id() {
+            self.inner.send_message(client_id, channel, message.clone());
+        }
+    }
+
+    fn disconnect(&mut self, client_id: u64) {
+        if self.is_member(client_id) {
+            self.inner.disconnect(client_id);
+        }
+    }
+
+    fn broadcast_message_except(&mut self, client_id: u64, channel: AppChannel, message: Vec<u8>) {
+        for other_id in self.clients_id() {
+            if other_id != client_id {
+                self.inner.send_message(other_id, channel, message.clone());
+            }
+        }
+    }
+
+    fn send_message_with_priority(
+        &mut self,
+        client_id: u64,
+        channel: AppChannel,
+        message: Vec<u8>,
+        priority: SendPriority,
+    ) {
+        if self.is_member(client_id) {
+            self.inner
+                .send_message_with_priority(client_id, channel, message, priority);
+        }
+    }
+
+    fn broadcast_message_with_priority(
+        &mut self,
+        channel: AppChannel,
+        message: Vec<u8>,
+        priority: SendPriority,
+    ) {
+        for client_id in self.clients_id() {
+            self.inner
+                .send_message_with_priority(client_id, channel, message.clone(), priority);
+        }
+    }
+
+    fn flush_prioritized(&mut self) {
+        self.inner.flush_prioritized();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{net::MAX_RELIABLE_MESSAGE_BYTES, test_helpers::MockServerNetwork};
+
+    fn join(network: &mut MockServerNetwork, client_id: u64, code: &str) {
+        network.add_client(client_id);
+        network.queue_event(ServerNetworkEvent::ClientConnected { client_id });
+        let payload = encode_to_vec(ClientMessage::JoinRoom(code.to_string()), config())
+            .expect("failed to serialize JoinRoom");
+        network.queue_raw_message(client_id, payload);
+    }
+
+    fn name_player(rooms: &mut Rooms, code: &str, client_id: u64, username: &str) {
+        let room = rooms.room_mut(code).expect("room should exist");
+        let ServerState::Lobby(lobby) = &mut room.state else {
+            panic!("room should be in lobby");
+        };
+        lobby.mark_authenticated(client_id);
+        lobby.register_username(client_id, username);
+    }
+
+    fn received_chat(network: &mut MockServerNetwork, client_id: u64) -> Vec<String> {
+        network
+            .get_sent_messages_data(client_id)
+            .iter()
+            .filter_map(|data| {
+                match decode_from_slice::<ServerMessage, _>(data, config())
+                    .ok()?
+                    .0
+                {
+                    ServerMessage::ChatMessage { content, .. } => Some(content),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    fn send_passcode(network: &mut MockServerNetwork, client_id: u64, passcode: &Passcode) {
+        let payload = encode_to_vec(
+            ClientMessage::SendPasscode(passcode.bytes.clone()),
+            config(),
+        )
+        .expect("failed to serialize SendPasscode");
+        network.queue_raw_message(client_id, payload);
+    }
+
+    fn is_authenticated(rooms: &Rooms, code: &str, client_id: u64) -> bool {
+        let room = rooms.room(code).expect("room should exist");
+        let ServerState::Lobby(lobby) = &room.state else {
+            panic!("room should be in lobby");
+        };
+        lobby.needs_username(client_id)
+    }
+
+    #[test]
+    fn rotated_passcode_is_required_for_pending_authentication() {
+        let mut network = MockServerNetwork::new();
+        let mut rooms = Rooms::new(Settings::default());
+        let code = rooms.create_room().code.clone();
+        join(&mut network, 1, &code);
+        join(&mut network, 2, &code);
+        rooms.update(&mut network, false);
+
+        let old = rooms.room(&code).unwrap().passcode.clone();
+        send_passcode(&mut network, 1, &old);
+        rooms.update(&mut network, false);
+        assert!(is_authenticated(&rooms, &code, 1));
+
+        let new = rooms.room_mut(&code).unwrap().rotate_passcode().clone();
+        assert_ne!(new.bytes, old.bytes);
+
+        send_passcode(&mut network, 2, &old);
+        rooms.update(&mut network, false);
+        assert!(!is_authenticated(&rooms, &code, 2));
+
+        send_passcode(&mut network, 2, &new);
+        rooms.update(&mut network, false);
+        assert!(is_authenticated(&rooms, &code, 2));
+        assert!(is_authenticated(&rooms, &code, 1));
+        assert!(rooms.room(&code).unwrap().members().contains(&1));
+    }
+
+    #[test]
+    fn chat_in_one_room_does_not_reach_another() {
+        let mut network = MockServerNetwork::new();
+        let mut rooms = Rooms::new(Settings::default());
+        let first = rooms.create_room().code.clone();
+        let second = rooms.create_room().code.clone();
+
+        join(&mut network, 1, &first);
+        join(&mut network, 2, &first);
+        join(&mut network, 3, &second);
+        rooms.update(&mut network, false);
+
+        assert_eq!(rooms.room_of(1), Some(first.as_str()));
+        assert_eq!(rooms.room_of(3), Some(second.as_str()));
+
+        name_player(&mut rooms, &first, 1, "Alice");
+        name_player(&mut rooms, &first, 2, "Bob");
+        name_player(&mut rooms, &second, 3, "Carol");
+
+        let chat = encode_to_vec(ClientMessage::SendChat("hello".to_string()), config())
+            .expect("failed to serialize SendChat");
+        network.queue_raw_message(1, chat);
+        rooms.update(&mut network, false);
+
+        assert_eq!(received_chat(&mut network, 2), vec!["hello".to_string()]);
+        assert!(received_chat(&mut network, 3).is_empty());
+        assert!(network.get_broadcast_messages_data().is_empty());
+
+        let ServerState::Lobby(lobby) = &rooms.room(&second).unwrap().state else {
+            panic!("second room should be in lobby");
+        };
+        assert_eq!(lobby.username(1), None);
+        assert_eq!(lobby.username(3), Some("Carol"));
+    }
+
+    fn room_handle_fixture() -> (MockServerNetwork, HashSet<u64>) {
+        let mut network = MockServerNetwork::new();
+        for client_id in 1..=4 {
+            network.add_client(client_id);
+        }
+        (network, HashSet::from([1, 2]))
+    }
+
+    #[test]
+    fn broadcast_within_room_handle_reaches_only_members() {
+        let (mut network, members) = room_handle_fixture();
+        let mut events = VecDeque::new();
+        let mut handle = RoomNetworkHandle::new(&mut network, &members, &mut events);
+
+        assert_eq!(handle.clients_id(), vec![1, 2]);
+        handle.broadcast_message(AppChannel::ReliableOrdered, vec![1]);
+        handle.broadcast_message_except(1, AppChannel::ReliableOrdered, vec![2]);
+
+        assert_eq!(network.get_sent_messages_data(1), vec![vec![1]]);
+        assert_eq!(network.get_sent_messages_data(2), vec![vec![1], vec![2]]);
+        assert!(network.get_sent_messages_data(3).is_empty());
+        assert!(network.get_sent_messages_data(4).is_empty());
+        assert!(network.get_broadcast_messages_data().is_empty());
+    }
+
+    #[test]
+    fn room_handle_ignores_clients_outside_the_room() {
+        let (mut network, members) = room_handle_fixture();
+        network.queue_raw_message(3, vec![9]);
+        let mut events = VecDeque::new();
+        let mut handle = RoomNetworkHandle::new(&mut network, &members, &mut events);
+
+        assert_eq!(handle.receive_message(3, AppChannel::ReliableOrdered), None);
+        handle.send_message(3, AppChannel::ReliableOrdered, vec![1]);
+        handle.disconnect(4);
+
+        assert!(network.get_sent_messages_data(3).is_empty());
+        assert!(network.disconnected_clients.is_empty());
+        assert_eq!(
+            network.receive_message(3, AppChannel::ReliableOrdered),
+            Some(vec![9])
+        );
+    }
+
+    #[test]
+    fn room_list_reflects_rooms_occupancy_and_state() {
+        let mut network = MockServerNetwork::new();
+        let mut rooms = Rooms::new(Settings::default());
+        let first = rooms.create_room().code.clone();
+        let second = rooms.create_room().code.clone();
+
+        join(&mut network, 1, &first);
+        join(&mut network, 2, &first);
+        rooms.update(&mut network, false);
+        rooms.room_mut(&second).unwrap().state =
+            ServerState::ChoosingDifficulty(crate::state::ChoosingDifficulty::new(&Lobby::new()));
+
+        network.add_client(3);
+        network.queue_event(ServerNetworkEvent::ClientConnected { client_id: 3 });
+        let payload =
+            encode_to_vec(ClientMessage::ListRooms, config()).expect("failed to serialize");
+        network.queue_raw_message(3, payload);
+        rooms.update(&mut network, false);
+
+        let sent = network.get_sent_messages_data(3);
+        assert_eq!(sent.len(), 1);
+        let ServerMessage::RoomList { rooms: listed } =
+            decode_from_slice::<ServerMessage, _>(&sent[0], config())
+                .unwrap()
+                .0
+        else {
+            panic!("expected RoomList");
+        };
+        assert_eq!(
+            listed,
+            vec![
+                RoomSummary {
+                    code: first,
+                    players: 2,
+                    state: "Lobby".to_string(),
+                    passcode_protected: true,
+                },
+                RoomSummary {
+                    code: second,
+                    players: 0,
+                    state: "ChoosingDifficulty".to_string(),
+                    passcode_protected: true,
+                },
+            ]
+        );
+        assert_eq!(rooms.room_of(3), None);
+        assert!(network.disconnected_clients.is_empty());
+    }
+
+    #[test]
+    fn unknown_room_code_disconnects_client() {
+        let mut network = MockServerNetwork::new();
+        let mut rooms = Rooms::new(Settings::default());
+        rooms.create_room();
+
+        join(&mut network, 1, "ZZZZZ");
+        rooms.update(&mut network, false);
+
+        assert_eq!(rooms.room_of(1), None);
+        assert_eq!(network.disconnected_clients, vec![1]);
+    }
+
+    #[test]
+    fn empty_code_joins_default_room() {
+        let mut network = MockServerNetwork::new();
+        let mut rooms = Rooms::new(Settings::default());
+        let first = rooms.create_room().code.clone();
+        rooms.create_room();
+
+        join(&mut network, 1, "");
+        rooms.update(&mut network, false);
+
+        assert_eq!(rooms.room_of(1), Some(first.as_str()));
+    }
+
+    #[test]
+    fn message_before_joining_a_room_is_a_protocol_error() {
+        let mut network = MockServerNetwork::new();
+        let mut rooms = Rooms::new(Settings::default());
+        rooms.create_room();
+
+        network.add_client(1);
+        network.queue_event(ServerNetworkEvent::ClientConnected { client_id: 1 });
+        let payload = encode_to_vec(ClientMessage::SendChat("hi".to_string()), config())
+            .expect("failed to serialize SendChat");
+        network.queue_raw_message(1, payload);
+        rooms.update(&mut network, false);
+
+        assert_eq!(network.disconnected_clients, vec![1]);
+        let sent = network.get_sent_messages_data(1);
+        let (message, _) = decode_from_slice::<ServerMessage, _>(sent.last().unwrap(), config())
+            .expect("failed to decode");
+        assert!(matches!(
+            message,
+            ServerMessage::DisconnectReason(DisconnectReason::ProtocolError)
+        ));
+    }
+
+    #[test]
+    fn second_connection_with_a_live_id_is_disconnected() {
+        let mut network = MockServerNetwork::new();
+        let mut rooms = Rooms::new(Settings::default());
+        let code = rooms.create_room().code.clone();
+
+        join(&mut network, 1, &code);
+        rooms.update(&mut network, false);
+        network.queue_event(ServerNetworkEvent::ClientConnected { client_id: 1 });
+        rooms.update(&mut network, false);
+
+        assert_eq!(network.disconnected_clients, vec![1]);
+        let sent = network.get_sent_messages_data(1);
+        let (message, _) = decode_from_slice::<ServerMessage, _>(sent.last().unwrap(), config())
+            .expect("failed to decode");
+        assert!(matches!(
+            message,
+            ServerMessage::DisconnectReason(DisconnectReason::DuplicateConnection)
+        ));
+    }
+
+    #[test]
+    fn oversized_message_in_room_browser_is_dropped() {
+        let mut network = MockServerNetwork::new();
+        let mut rooms = Rooms::new(Settings::default());
+        let code = rooms.create_room().code.clone();
+
+        network.add_client(1);
+        network.queue_event(ServerNetworkEvent::ClientConnected { client_id: 1 });
+        network.queue_raw_message(1, vec![0; MAX_RELIABLE_MESSAGE_BYTES + 1]);
+        let payload = encode_to_vec(ClientMessage::JoinRoom(code.clone()), config())
+            .expect("failed to serialize JoinRoom");
+        network.queue_raw_message(1, payload);
+        rooms.update(&mut network, false);
+
+        assert!(network.disconnected_clients.is_empty());
+        assert_eq!(rooms.room_of(1), Some(code.as_str()));
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+
+use common::protocol::RoundStanding;
+
+pub const ROUND_WIN_POINTS: u32 = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundState {
+    pub round: u8,
+    pub total_rounds: u8,
+    pub difficulty: u8,
+    scores: HashMap<String, u32>,
+}
+
+impl RoundState {
+    pub fn new(total_rounds: u8, difficulty: u8) -> Self {
+        Self {
+            round: 1,
+            total_rounds: total_rounds.max(1),
+            difficulty,
+            scores: HashMap::new(),
+        }
+    }
+
+    pub fn record_round<'a>(
+        &mut self,
+        kills: impl IntoIterator<Item = (&'a str, u32)>,
+        winner: Option<&str>,
+    ) {
+        for (name, kills) in kills {
+            *self.scores.entry(name.to_string()).or_default() += kills;
+        }
+        if let Some(winner) = winner {
+            *self.scores.entry(winner.to_string()).or_default() += ROUND_WIN_POINTS;
+        }
+    }
+
+    pub fn is_final_round(&self) -> bool {
+        self.round >= self.total_rounds
+    }
+
+    pub fn advance(&mut self) {
+        self.round += 1;
+    }
+
+    pub fn standings(&self) -> Vec<RoundStanding> {
+        let mut standings: Vec<RoundStanding> = self
+            .scores
+            .iter()
+            .map(|(username, &points)| RoundStanding {
+                username: username.clone(),
+                points,
+            })
+            .collect();
+        standings.sort_by(|a, b| {
+            b.points
+                .cmp(&a.points)
+                .then_with(|| a.username.cmp(&b.username))
+        });
+        standings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn standing(username: &str, points: u32) -> RoundStanding {
+        RoundStanding {
+            username: username.to_string(),
+            points,
+        }
+    }
+
+    #[test]
+    fn scores_accumulate_across_rounds() {
+        let mut rounds = RoundState::new(2, 1);
+        assert!(!rounds.is_final_round());
+
+        rounds.record_round([("alice", 2), ("bob", 1), ("carol", 0)], Some("alice"));
+        assert_eq!(
+            rounds.standings(),
+            vec![
+                standing("alice", 5),
+                standing("bob", 1),
+                standing("carol", 0)
+            ]
+        );
+
+        rounds.advance();
+        assert_eq!(rounds.round, 2);
+        assert!(rounds.is_final_round());
+
+        rounds.record_round([("alice", 0), ("bob", 3), ("carol", 1)], Some("bob"));
+        assert_eq!(
+            rounds.standings(),
+            vec![
+                standing("bob", 7),
+                standing("alice", 5),
+                standing("carol", 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn final_ranking_breaks_ties_by_name() {
+        let mut rounds = RoundState::new(2, 1);
+        rounds.record_round([("dave", 1), ("bob", 4)], None);
+        rounds.advance();
+        rounds.record_round([("dave", 0), ("bob", 0)], Some("dave"));
+
+        assert_eq!(
+            rounds.standings(),
+            vec![standing("bob", 4), standing("dave", 4)]
+        );
+    }
+}
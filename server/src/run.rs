@@ -2,11 +2,15 @@ use std::{
     collections::HashSet,
     io::stdout,
     net::{SocketAddr, UdpSocket},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::Receiver,
+    },
     thread,
     time::{Duration, Instant},
 };
 
-use bincode::{config::standard, serde::encode_to_vec};
+use bincode::serde::encode_to_vec;
 use crossterm::{
     cursor::{Hide, MoveToColumn},
     execute,
@@ -16,8 +20,11 @@ use renet::RenetServer;
 use renet_netcode::NetcodeServerTransport;
 
 use crate::{
+    console,
     net::{self, RenetServerNetworkHandle, ServerNetworkEvent, ServerNetworkHandle},
-    state::{Lobby, ServerState},
+    rooms::Rooms,
+    settings::Settings,
+    state::ServerState,
     state_handlers,
 };
 use common::{
@@ -25,15 +32,21 @@ use common::{
     auth::Passcode,
     constants::{BROADCAST_INTERVAL, IDEAL_TICK_DURATION},
     net::AppChannel,
-    protocol::{GAME_ALREADY_STARTED_MESSAGE, ServerMessage},
+    protocol::{DisconnectReason, GAME_ALREADY_STARTED_MESSAGE, ServerMessage, config},
     time,
 };
 
-// Server exits from `Lobby` and `ChoosingDifficulty` states if there's been no
+// A room closes from `Lobby` and `ChoosingDifficulty` states if there's been no
 // activity (no messages from clients) for 5 minutes.
 const INACTIVITY_TIMEOUT: Duration = Duration::from_secs(300);
 
-pub fn run_server(socket: UdpSocket, connectable_addr: SocketAddr, private_key: [u8; 32]) {
+pub fn run_server(
+    socket: UdpSocket,
+    connectable_addr: SocketAddr,
+    private_key: [u8; 32],
+    settings: Settings,
+    shutdown: &AtomicBool,
+) {
     let current_time = common::time::now();
     let protocol_id = common::protocol::version();
 
@@ -46,53 +59,73 @@ pub fn run_server(socket: UdpSocket, connectable_addr: SocketAddr, private_key:
         NetcodeServerTransport::new(server_config, socket).expect("failed to create transport");
     let connection_config = common::net::connection_config();
     let mut server = RenetServer::new(connection_config);
-    let passcode = Passcode::generate(6);
-    let mut state = ServerState::Lobby(Lobby::new());
+    let mut rooms = Rooms::new(settings);
+    rooms.create_room();
 
-    print_server_banner(protocol_id, &passcode, server_connectable_addr);
-    server_loop(&mut server, &mut transport, &mut state, &passcode);
+    print_server_banner(protocol_id, &rooms, server_connectable_addr);
+    let console = console::spawn_stdin_reader();
+    server_loop(&mut server, &mut transport, &mut rooms, &console, shutdown);
     println!("Server shutting down.");
 }
 
-fn print_server_banner(protocol_id: u64, passcode: &Passcode, server_connectable_addr: SocketAddr) {
+fn print_server_banner(protocol_id: u64, rooms: &Rooms, server_connectable_addr: SocketAddr) {
     println!("  Game version:   {}", protocol_id);
     println!("  Server address: {}", server_connectable_addr);
-    println!("  Passcode:       {}", passcode.string);
+    for room in rooms.iter() {
+        println!("  Room code:      {}", room.code);
+        println!("  Passcode:       {}", room.passcode.string);
+    }
 }
 
 fn server_loop(
     server: &mut RenetServer,
     transport: &mut NetcodeServerTransport,
-    state: &mut ServerState,
-    passcode: &Passcode,
+    rooms: &mut Rooms,
+    console: &Receiver<String>,
+    shutdown: &AtomicBool,
 ) {
     let mut next_tick_time = Instant::now();
     let mut last_updated = Instant::now();
     let mut last_sync_time = Instant::now();
-    let mut last_activity = Instant::now();
 
     loop {
         let now = Instant::now();
         let duration = now - last_updated;
         last_updated = now;
 
+        console::process_console_input(
+            console,
+            rooms,
+            &mut RenetServerNetworkHandle::new(server),
+            shutdown,
+        );
+
         transport
             .update(duration, server)
             .expect("failed to update transport");
         server.update(duration);
 
-        let mut network_handle = RenetServerNetworkHandle { server };
+        let mut network_handle = RenetServerNetworkHandle::new(server);
 
-        if now.duration_since(last_sync_time) > BROADCAST_INTERVAL {
-            sync_clocks(&mut network_handle, state);
+        let sync = now.duration_since(last_sync_time) > BROADCAST_INTERVAL;
+        if sync {
             last_sync_time = now;
         }
 
-        update_server_state(&mut network_handle, state, passcode, &mut last_activity);
+        rooms.update(&mut network_handle, sync);
+        network_handle.flush_prioritized();
 
         transport.send_packets(server);
 
-        if matches!(state, ServerState::Ending) {
+        if rooms.is_empty() {
+            break;
+        }
+
+        if shutdown.load(Ordering::Relaxed) {
+            broadcast_shutdown(&mut RenetServerNetworkHandle::new(server));
+            transport.send_packets(server);
+            thread::sleep(IDEAL_TICK_DURATION);
+            transport.disconnect_all(server);
             break;
         }
 
@@ -118,11 +151,9 @@ pub fn update_server_state(
         ServerState::Lobby(_) | ServerState::ChoosingDifficulty(_)
     );
     if check_inactivity && last_activity.elapsed() > INACTIVITY_TIMEOUT {
-        println!(
-            "No activity for {:#?}. Server exiting...",
-            INACTIVITY_TIMEOUT
-        );
-        std::process::exit(0);
+        println!("No activity for {:#?}. Closing room...", INACTIVITY_TIMEOUT);
+        *state = ServerState::Ending;
+        return;
     }
 
     process_events(network, state);
@@ -142,6 +173,7 @@ pub fn update_server_state(
     };
 
     if let Some(new_state) = next_state {
+        *last_activity = Instant::now();
         apply_server_transition(state, new_state, network);
     }
 }
@@ -171,7 +203,7 @@ fn apply_server_transition(
                 .expect("host should have a username");
             println!("Host ({}) is choosing a difficulty.", host_name);
             let message = ServerMessage::BeginDifficultySelection;
-            let payload = encode_to_vec(&message, standard())
+            let payload = encode_to_vec(&message, config())
                 .expect("failed to serialize BeginDifficultySelection");
             network.send_message(host_id, AppChannel::ReliableOrdered, payload);
 
@@ -186,7 +218,7 @@ fn apply_server_transition(
                     message: GAME_ALREADY_STARTED_MESSAGE.to_string(),
                 };
                 let payload =
-                    encode_to_vec(&message, standard()).expect("failed to serialize ServerInfo");
+                    encode_to_vec(&message, config()).expect("failed to serialize ServerInfo");
                 network.send_message(client_id, AppChannel::ReliableOrdered, payload);
             }
 
@@ -197,7 +229,7 @@ fn apply_server_transition(
                 ),
             };
             let info_payload =
-                encode_to_vec(&info, standard()).expect("failed to serialize ServerInfo");
+                encode_to_vec(&info, config()).expect("failed to serialize ServerInfo");
 
             for client_id in network.clients_id() {
                 let has_username = difficulty_state.lobby.username(client_id).is_some();
@@ -222,7 +254,7 @@ fn apply_server_transition(
                 end_time,
                 game_data: game_data_clone,
             };
-            let payload = encode_to_vec(&message, standard())
+            let payload = encode_to_vec(&message, config())
                 .expect("failed to serialize CountDownStarted mesage");
             network.broadcast_message(AppChannel::ReliableOrdered, payload);
 
@@ -255,10 +287,16 @@ pub fn process_events(network: &mut dyn ServerNetworkHandle, state: &mut ServerS
     }
 }
 
-fn sync_clocks(network: &mut dyn ServerNetworkHandle, state: &mut ServerState) {
+pub fn broadcast_shutdown(network: &mut dyn ServerNetworkHandle) {
+    let message = ServerMessage::DisconnectReason(DisconnectReason::ShuttingDown);
+    let payload = encode_to_vec(&message, config()).expect("failed to serialize DisconnectReason");
+    network.broadcast_message(AppChannel::ReliableOrdered, payload);
+}
+
+pub(crate) fn sync_clocks(network: &mut dyn ServerNetworkHandle, state: &mut ServerState) {
     let server_time_f64 = common::time::now().as_secs_f64();
     let message = ServerMessage::ServerTime(server_time_f64);
-    let payload = encode_to_vec(&message, standard()).expect("failed to serialize ServerTime");
+    let payload = encode_to_vec(&message, config()).expect("failed to serialize ServerTime");
     let payload_len = payload.len();
     let client_count = network.clients_id().len();
     if let ServerState::Game(game) = state {
@@ -269,7 +307,6 @@ fn sync_clocks(network: &mut dyn ServerNetworkHandle, state: &mut ServerState) {
 
 #[cfg(test)]
 mod tests {
-    use bincode::config::standard;
     use bincode::serde::decode_from_slice;
 
     use super::*;
@@ -318,8 +355,8 @@ mod tests {
         }
 
         let broadcasts = network.get_broadcast_messages_data();
-        assert_eq!(broadcasts.len(), 1);
-        let msg = decode_from_slice::<ServerMessage, _>(&broadcasts[0], standard())
+        assert_eq!(broadcasts.len(), 2);
+        let msg = decode_from_slice::<ServerMessage, _>(&broadcasts[0], config())
             .unwrap()
             .0;
         if let ServerMessage::UserLeft { username } = msg {
@@ -327,5 +364,66 @@ mod tests {
         } else {
             panic!("expected UserLeft message, got {:?}", msg);
         }
+        let msg = decode_from_slice::<ServerMessage, _>(&broadcasts[1], config())
+            .unwrap()
+            .0;
+        assert!(matches!(msg, ServerMessage::PlayerCount(0)));
+    }
+
+    #[test]
+    fn test_process_events_announces_pending_player_to_named_clients() {
+        let mut network = MockServerNetwork::new();
+        let mut lobby = Lobby::new();
+        lobby.set_announce_pending_players(true);
+        lobby.register_connection(1);
+        lobby.mark_authenticated(1);
+        lobby.register_username(1, "Alice");
+        lobby.register_connection(2);
+        let mut state = ServerState::Lobby(lobby);
+
+        network.queue_event(ServerNetworkEvent::ClientConnected { client_id: 3 });
+
+        process_events(&mut network, &mut state);
+
+        let alice_msgs = network.get_sent_messages_data(1);
+        assert_eq!(alice_msgs.len(), 1);
+        let msg = decode_from_slice::<ServerMessage, _>(&alice_msgs[0], config())
+            .unwrap()
+            .0;
+        assert!(
+            matches!(msg, ServerMessage::ServerInfo { ref message } if message == "A new player is connecting."),
+            "expected connecting announcement, got {:?}",
+            msg
+        );
+        assert!(network.get_sent_messages_data(2).is_empty());
+        let newcomer_msgs = network.get_sent_messages_data(3);
+        assert_eq!(newcomer_msgs.len(), 1);
+        assert!(matches!(
+            decode_from_slice::<ServerMessage, _>(&newcomer_msgs[0], config())
+                .unwrap()
+                .0,
+            ServerMessage::AuthRequired { max_attempts: 3 }
+        ));
+    }
+
+    #[test]
+    fn shutdown_notice_is_broadcast_to_all_clients() {
+        let mut network = MockServerNetwork::new();
+        network.add_client(1);
+        network.add_client(2);
+
+        broadcast_shutdown(&mut network);
+
+        let broadcasts = network.get_broadcast_messages_data();
+        assert_eq!(broadcasts.len(), 1);
+        let msg = decode_from_slice::<ServerMessage, _>(&broadcasts[0], config())
+            .unwrap()
+            .0;
+        assert!(matches!(
+            msg,
+            ServerMessage::DisconnectReason(DisconnectReason::ShuttingDown)
+        ));
+        assert!(network.get_sent_messages_data(1).is_empty());
+        assert!(network.get_sent_messages_data(2).is_empty());
     }
 }
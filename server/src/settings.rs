@@ -0,0 +1,280 @@
+use std::{fmt, ops::RangeInclusive, path::PathBuf, str::FromStr, time::Duration};
+
+use crate::chat::CHAT_LOG_PATH;
+use common::{
+    bullets::BulletRadii,
+    chat::MAX_CHAT_MESSAGE_BYTES,
+    constants::{TICK_RATE, TICKS_PER_BROADCAST},
+    maze::Symmetry,
+    player::{LOW_GRAVITY, MAX_USERNAME_LENGTH, MovementProfile},
+    snapshot::MapOptions,
+    spawn::SpawnLayout,
+};
+
+pub const AUTH_ATTEMPTS_RANGE: RangeInclusive<u8> = 1..=10;
+pub const DEFAULT_MIN_PLAYERS: usize = 2;
+pub const ROUNDS_RANGE: RangeInclusive<u8> = 1..=9;
+pub const SNAPSHOT_RATE_RANGE: RangeInclusive<u32> = 10..=60;
+pub const MAGAZINE_SIZE_RANGE: RangeInclusive<u8> = 1..=24;
+
+#[derive(Debug, Clone, Default)]
+pub struct Settings {
+    pub announce_pending_players: bool,
+    pub no_friendly_fire: bool,
+    pub teams: bool,
+    pub spawn_layout: SpawnLayout,
+    pub symmetry: Option<Symmetry>,
+    pub record_replay: bool,
+    pub chat_log: Option<PathBuf>,
+    pub log_events: bool,
+    pub start_policy: StartPolicy,
+    pub host_idle_timeout: Option<Duration>,
+    pub afk_timeout: Option<Duration>,
+    pub afk_action: AfkAction,
+    pub bots: usize,
+    pub max_auth_attempts: Option<u8>,
+    pub bullet_collisions: bool,
+    pub min_players: Option<usize>,
+    pub max_chat_bytes: Option<usize>,
+    pub arcade_movement: bool,
+    pub low_gravity: bool,
+    pub max_username_length: Option<usize>,
+    pub rounds: Option<u8>,
+    pub snapshot_rate: Option<u32>,
+    pub seed: Option<u64>,
+    pub magazine_size: Option<u8>,
+    pub return_to_lobby: bool,
+    pub bullet_radii: Option<BulletRadii>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StartPolicy {
+    #[default]
+    Host,
+    Majority,
+}
+
+impl fmt::Display for StartPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            StartPolicy::Host => "host",
+            StartPolicy::Majority => "majority",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for StartPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "host" => Ok(StartPolicy::Host),
+            "majority" => Ok(StartPolicy::Majority),
+            _ => Err(format!("unknown start policy '{}'", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AfkAction {
+    #[default]
+    Eliminate,
+    Teleport,
+    Flag,
+}
+
+impl fmt::Display for AfkAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            AfkAction::Eliminate => "eliminate",
+            AfkAction::Teleport => "teleport",
+            AfkAction::Flag => "flag",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for AfkAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "eliminate" => Ok(AfkAction::Eliminate),
+            "teleport" => Ok(AfkAction::Teleport),
+            "flag" => Ok(AfkAction::Flag),
+            _ => Err(format!("unknown afk action '{}'", s)),
+        }
+    }
+}
+
+impl Settings {
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Self {
+        let mut settings = Self::default();
+        for arg in args {
+            match arg.as_str() {
+                "--announce-pending-players" => settings.announce_pending_players = true,
+                "--no-friendly-fire" => settings.no_friendly_fire = true,
+                "--teams" => settings.teams = true,
+                "--record-replay" => settings.record_replay = true,
+                "--log-chat" => settings.chat_log = Some(PathBuf::from(CHAT_LOG_PATH)),
+                "--log-events" => settings.log_events = true,
+                "--bullet-collisions" => settings.bullet_collisions = true,
+                "--arcade" => settings.arcade_movement = true,
+                "--low-gravity" => settings.low_gravity = true,
+                "--return-to-lobby" => settings.return_to_lobby = true,
+                _ => {
+                    if let Some(value) = arg.strip_prefix("--log-chat=") {
+                        settings.chat_log = Some(PathBuf::from(value));
+                    } else if let Some(value) = arg.strip_prefix("--spawn-layout=") {
+                        match value.parse() {
+                            Ok(layout) => settings.spawn_layout = layout,
+                            Err(e) => eprintln!("{}; ignoring", e),
+                        }
+                    } else if let Some(value) = arg.strip_prefix("--start-policy=") {
+                        match value.parse() {
+                            Ok(policy) => settings.start_policy = policy,
+                            Err(e) => eprintln!("{}; ignoring", e),
+                        }
+                    } else if let Some(value) = arg.strip_prefix("--host-idle-secs=") {
+                        match value.parse() {
+                            Ok(secs) => {
+                                settings.host_idle_timeout = Some(Duration::from_secs(secs))
+                            }
+                            Err(_) => eprintln!("invalid host idle period '{}'; ignoring", value),
+                        }
+                    } else if let Some(value) = arg.strip_prefix("--afk-secs=") {
+                        match value.parse() {
+                            Ok(secs) => settings.afk_timeout = Some(Duration::from_secs(secs)),
+                            Err(_) => eprintln!("invalid afk period '{}'; ignoring", value),
+                        }
+                    } else if let Some(value) = arg.strip_prefix("--afk-action=") {
+                        match value.parse() {
+                            Ok(action) => settings.afk_action = action,
+                            Err(e) => eprintln!("{}; ignoring", e),
+                        }
+                    } else if let Some(value) = arg.strip_prefix("--bots=") {
+                        match value.parse() {
+                            Ok(count) => settings.bots = count,
+                            Err(_) => eprintln!("invalid bot count '{}'; ignoring", value),
+                        }
+                    } else if let Some(value) = arg.strip_prefix("--auth-attempts=") {
+                        match value.parse() {
+                            Ok(attempts) if AUTH_ATTEMPTS_RANGE.contains(&attempts) => {
+                                settings.max_auth_attempts = Some(attempts)
+                            }
+                            _ => eprintln!(
+                                "invalid auth attempt limit '{}' (expected {}-{}); ignoring",
+                                value,
+                                AUTH_ATTEMPTS_RANGE.start(),
+                                AUTH_ATTEMPTS_RANGE.end()
+                            ),
+                        }
+                    } else if let Some(value) = arg.strip_prefix("--min-players=") {
+                        match value.parse() {
+                            Ok(count) if count >= 1 => settings.min_players = Some(count),
+                            _ => eprintln!("invalid minimum player count '{}'; ignoring", value),
+                        }
+                    } else if let Some(value) = arg.strip_prefix("--chat-limit=") {
+                        match value.parse() {
+                            Ok(bytes) if (1..=MAX_CHAT_MESSAGE_BYTES).contains(&bytes) => {
+                                settings.max_chat_bytes = Some(bytes)
+                            }
+                            _ => eprintln!(
+                                "invalid chat limit '{}' (expected 1-{}); ignoring",
+                                value, MAX_CHAT_MESSAGE_BYTES
+                            ),
+                        }
+                    } else if let Some(value) = arg.strip_prefix("--name-limit=") {
+                        match value.parse() {
+                            Ok(length) if (1..=MAX_USERNAME_LENGTH).contains(&length) => {
+                                settings.max_username_length = Some(length)
+                            }
+                            _ => eprintln!(
+                                "invalid username length limit '{}' (expected 1-{}); ignoring",
+                                value, MAX_USERNAME_LENGTH
+                            ),
+                        }
+                    } else if let Some(value) = arg.strip_prefix("--rounds=") {
+                        match value.parse() {
+                            Ok(rounds) if ROUNDS_RANGE.contains(&rounds) => {
+                                settings.rounds = Some(rounds)
+                            }
+                            _ => eprintln!(
+                                "invalid round count '{}' (expected {}-{}); ignoring",
+                                value,
+                                ROUNDS_RANGE.start(),
+                                ROUNDS_RANGE.end()
+                            ),
+                        }
+                    } else if let Some(value) = arg.strip_prefix("--snapshot-rate=") {
+                        match value.parse() {
+                            Ok(rate) if SNAPSHOT_RATE_RANGE.contains(&rate) => {
+                                settings.snapshot_rate = Some(rate)
+                            }
+                            _ => eprintln!(
+                                "snapshot rate must be between {} and {} per second; ignoring",
+                                SNAPSHOT_RATE_RANGE.start(),
+                                SNAPSHOT_RATE_RANGE.end()
+                            ),
+                        }
+                    } else if let Some(value) = arg.strip_prefix("--seed=") {
+                        match value.parse() {
+                            Ok(seed) => settings.seed = Some(seed),
+                            Err(_) => eprintln!("invalid seed '{}'; ignoring", value),
+                        }
+                    } else if let Some(value) = arg.strip_prefix("--magazine=") {
+                        match value.parse() {
+                            Ok(size) if MAGAZINE_SIZE_RANGE.contains(&size) => {
+                                settings.magazine_size = Some(size)
+                            }
+                            _ => eprintln!(
+                                "invalid magazine size '{}' (expected {}-{}); ignoring",
+                                value,
+                                MAGAZINE_SIZE_RANGE.start(),
+                                MAGAZINE_SIZE_RANGE.end()
+                            ),
+                        }
+                    } else if let Some(value) = arg.strip_prefix("--bullet-radii=") {
+                        match value.parse() {
+                            Ok(radii) => settings.bullet_radii = Some(radii),
+                            Err(e) => eprintln!("{}; ignoring", e),
+                        }
+                    } else if let Some(value) = arg.strip_prefix("--symmetry=") {
+                        match value.parse() {
+                            Ok(symmetry) => settings.symmetry = Some(symmetry),
+                            Err(e) => eprintln!("{}; ignoring", e),
+                        }
+                    } else {
+                        eprintln!("unrecognized argument '{}'; ignoring", arg);
+                    }
+                }
+            }
+        }
+        settings
+    }
+
+    pub fn map_options(&self) -> MapOptions {
+        MapOptions {
+            spawn_layout: self.spawn_layout,
+            symmetry: self.symmetry,
+            movement_profile: if self.arcade_movement {
+                MovementProfile::arcade()
+            } else {
+                MovementProfile::default()
+            },
+            gravity: self.low_gravity.then_some(LOW_GRAVITY),
+            ticks_per_snapshot: self.ticks_per_snapshot(),
+            seed: self.seed,
+            magazine_size: self.magazine_size,
+            bullet_radii: self.bullet_radii,
+        }
+    }
+
+    pub fn ticks_per_snapshot(&self) -> u64 {
+        match self.snapshot_rate {
+            Some(rate) => (TICK_RATE / rate as f64).round().max(1.0) as u64,
+            None => TICKS_PER_BROADCAST,
+        }
+    }
+}
@@ -3,32 +3,44 @@ use std::{
     time::{Duration, Instant},
 };
 
-use rand::{Rng as _, rng};
-
-use bincode::{config::standard, serde::encode_to_vec};
+use bincode::serde::encode_to_vec;
 
 use crate::{
-    net::ServerNetworkHandle,
+    awards::{self, HitRecord},
+    bot::{self, Bot, BotSkill},
+    chat::{ChatDeduplicator, ChatRateLimiter, SharedChatLog},
+    event_log::{EventLog, MatchEvent},
+    net::{SendPriority, ServerNetworkHandle, disconnect_with_reason},
     player::{ServerPlayer, Status},
+    replay::{self, ReplayRecorder},
+    rounds::RoundState,
+    settings::{AfkAction, DEFAULT_MIN_PLAYERS, Settings, StartPolicy},
 };
 use common::{
-    bullets::Bullet,
-    constants::TICK_SECS,
+    auth::MAX_ATTEMPTS,
+    bullets::{Ammo, Bullet, BulletTuning},
+    chat::MAX_CHAT_MESSAGE_BYTES,
+    constants::{MAX_PLAYERS, TICK_SECS},
     maze::Maze,
     net::AppChannel,
-    player::{COLORS, Color, WirePlayerLocal, WirePlayerRemote},
+    pickups::Pickup,
+    player::{
+        COLORS, Color, MAX_USERNAME_LENGTH, MovementProfile, WirePlayerLocal, WirePlayerRemote,
+        next_available_color,
+    },
     protocol::{
-        AfterGameExitReason, AfterGameLeaderboardEntry, GAME_ALREADY_STARTED_MESSAGE,
-        PlayerRosterEntry, ServerMessage,
+        AfterGameAward, AfterGameExitReason, AfterGameLeaderboardEntry, DisconnectReason,
+        GAME_ALREADY_STARTED_MESSAGE, PlayerRosterEntry, RoundSummary, ServerMessage, config,
     },
-    snapshot::{InitialData, Snapshot},
+    snapshot::{InitialData, MapOptions, Snapshot},
+    team,
 };
 
 pub enum ServerState {
     Lobby(Lobby),
     ChoosingDifficulty(ChoosingDifficulty),
     Countdown(Countdown),
-    Game(Game),
+    Game(Box<Game>),
     Ending,
 }
 
@@ -43,9 +55,54 @@ impl ServerState {
         }
     }
 
+    pub fn is_closed(&self) -> bool {
+        match self {
+            ServerState::Lobby(lobby) => lobby.is_abandoned(),
+            ServerState::ChoosingDifficulty(state) => state.lobby.is_abandoned(),
+            ServerState::Ending => true,
+            _ => false,
+        }
+    }
+
+    pub fn server_info_recipients(&self) -> Vec<u64> {
+        match self {
+            ServerState::Lobby(lobby) => lobby.usernames.keys().copied().collect(),
+            ServerState::ChoosingDifficulty(state) => {
+                state.lobby.usernames.keys().copied().collect()
+            }
+            ServerState::Game(game) => game.client_id_to_index.keys().copied().collect(),
+            ServerState::Countdown(_) | ServerState::Ending => Vec::new(),
+        }
+    }
+
     pub fn register_connection(&mut self, client_id: u64, network: &mut dyn ServerNetworkHandle) {
         match self {
-            ServerState::Lobby(lobby) => lobby.register_connection(client_id),
+            ServerState::Lobby(lobby) if lobby.is_connected(client_id) => {
+                eprintln!(
+                    "client {} is already connected; disconnecting duplicate connection",
+                    client_id
+                );
+                disconnect_with_reason(network, client_id, DisconnectReason::DuplicateConnection);
+            }
+            ServerState::Lobby(lobby) if lobby.is_full() => {
+                eprintln!(
+                    "client {} connected, but the lobby is full; disconnecting them",
+                    client_id
+                );
+                disconnect_with_reason(network, client_id, DisconnectReason::ServerFull);
+            }
+            ServerState::Lobby(lobby) => {
+                lobby.register_connection(client_id);
+                let message = ServerMessage::AuthRequired {
+                    max_attempts: lobby.max_auth_attempts(),
+                };
+                let payload =
+                    encode_to_vec(&message, config()).expect("failed to serialize AuthRequired");
+                network.send_message(client_id, AppChannel::ReliableOrdered, payload);
+                if lobby.announces_pending_players() {
+                    lobby.announce_pending_player(network);
+                }
+            }
             _ => {
                 eprintln!(
                     "client {} connected, but server is not in lobby state; informing, then disconnecting them",
@@ -55,7 +112,7 @@ impl ServerState {
                 let message = ServerMessage::ServerInfo {
                     message: GAME_ALREADY_STARTED_MESSAGE.to_string(),
                 };
-                let payload = encode_to_vec(&message, standard())
+                let payload = encode_to_vec(&message, config())
                     .expect("failed to serialize ServerInfo message");
 
                 network.send_message(client_id, AppChannel::ReliableOrdered, payload);
@@ -92,6 +149,21 @@ pub struct Game {
     pub timer_expiration_tick: Option<u64>,
     pub is_solo_mode: bool,
     pub winner_index: Option<usize>,
+    pub draw_declared: bool,
+    pub friendly_fire: bool,
+    pub last_spectator_count: usize,
+    pub host_id: Option<u64>,
+    pub paused_at: Option<f64>,
+    pub settings: Settings,
+    pub chat_log: Option<SharedChatLog>,
+    pub replay: Option<ReplayRecorder>,
+    pub event_log: Option<EventLog>,
+    pub bullet_tuning: BulletTuning,
+    pub movement_profile: MovementProfile,
+    pub rounds: Option<RoundState>,
+    pub hit_records: Vec<HitRecord>,
+    pub pickups: Vec<Pickup>,
+    pub pickup_respawns: Vec<(u64, Pickup)>,
 }
 
 impl Game {
@@ -104,8 +176,19 @@ impl Game {
             .players
             .into_iter()
             .map(|player| {
-                client_id_to_index.insert(player.client_id, player.index);
-                ServerPlayer::new(player, current_tick)
+                let is_bot = bot::is_bot(player.client_id);
+                if !is_bot {
+                    client_id_to_index.insert(player.client_id, player.index);
+                }
+                let mut player = ServerPlayer::new(player, current_tick);
+                player.ammo = initial_data.bullet_tuning.magazine.as_ref().map(Ammo::full);
+                if is_bot {
+                    player.bot = Some(Bot::new(
+                        BotSkill::for_difficulty(initial_data.difficulty),
+                        initial_data.bullet_tuning.speed,
+                    ));
+                }
+                player
             })
             .collect();
 
@@ -124,11 +207,26 @@ impl Game {
             leaderboard_sent: false,
             net_stats: NetStats::new(),
             exit_coords: initial_data.exit_coords,
+            bullet_tuning: initial_data.bullet_tuning,
+            movement_profile: initial_data.movement_profile,
+            rounds: None,
             timer_duration,
             timer_start_time,
             timer_expiration_tick: None,
             is_solo_mode,
             winner_index: None,
+            draw_declared: false,
+            friendly_fire: true,
+            last_spectator_count: 0,
+            host_id: None,
+            paused_at: None,
+            settings: Settings::default(),
+            chat_log: None,
+            replay: None,
+            event_log: None,
+            hit_records: Vec::new(),
+            pickups: initial_data.pickups,
+            pickup_respawns: Vec::new(),
         }
     }
 
@@ -141,22 +239,29 @@ impl Game {
                 client_id, player.name
             );
             self.players[index].status = Status::Disconnected;
+            self.log_event(MatchEvent::Left {
+                player: name.clone(),
+            });
             if self.players[index].exit_tick.is_none() {
                 self.players[index].exit_tick = Some(self.current_tick);
             }
             let message = ServerMessage::UserLeft { username: name };
-            let payload =
-                encode_to_vec(&message, standard()).expect("failed to serialize UserLeft");
+            let payload = encode_to_vec(&message, config()).expect("failed to serialize UserLeft");
             let recipients_count = self.client_id_to_index.len();
             self.note_egress_bytes(payload.len().saturating_mul(recipients_count));
             network.broadcast_message(AppChannel::ReliableOrdered, payload);
 
-            // If there are no connected players left, exit.
             self.client_id_to_index.remove(&client_id);
             self.after_game_chat_clients.remove(&client_id);
-            if self.client_id_to_index.is_empty() {
-                println!("All players have disconnected. Server exiting...");
-                std::process::exit(0);
+            if self.host_id == Some(client_id) {
+                self.host_id = None;
+                if self.is_paused() {
+                    self.toggle_pause(network);
+                }
+            }
+            if self.is_empty() {
+                println!("All players have disconnected. Returning to lobby...");
+                return;
             }
 
             self.send_leaderboard_if_ready(network);
@@ -165,6 +270,233 @@ impl Game {
         }
     }
 
+    pub fn record_replay_tick(&mut self) {
+        let Some(recorder) = &mut self.replay else {
+            return;
+        };
+        let inputs = self
+            .players
+            .iter()
+            .map(|player| match player.status {
+                Status::Alive => Some(player.last_input),
+                Status::Dead | Status::Disconnected => None,
+            })
+            .collect();
+        recorder.record_tick(self.current_tick, inputs);
+    }
+
+    pub fn finish_replay(&mut self) {
+        let Some(recorder) = self.replay.take() else {
+            return;
+        };
+        match recorder.finish(replay::checksum(self)) {
+            Ok(path) => println!("Replay saved to {}.", path.display()),
+            Err(e) => eprintln!("failed to save replay: {}", e),
+        }
+    }
+
+    pub fn log_event(&self, event: MatchEvent) {
+        let Some(log) = &self.event_log else {
+            return;
+        };
+        if let Err(e) = log.record(self.current_tick, &event) {
+            eprintln!("failed to write event log: {}", e);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.client_id_to_index.is_empty()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    pub fn toggle_pause(&mut self, network: &mut dyn ServerNetworkHandle) {
+        let now = common::time::now_as_secs_f64();
+        let (paused, timer_shift_secs) = match self.paused_at.take() {
+            Some(paused_at) => {
+                let paused_secs = now - paused_at;
+                self.timer_start_time += paused_secs;
+                println!("Game resumed after {:.1}s.", paused_secs);
+                (false, paused_secs)
+            }
+            None => {
+                self.paused_at = Some(now);
+                println!("Game paused.");
+                (true, 0.0)
+            }
+        };
+
+        let message = ServerMessage::GamePaused {
+            paused,
+            timer_shift_secs,
+        };
+        let payload = encode_to_vec(&message, config()).expect("failed to serialize GamePaused");
+        let recipients_count = self.client_id_to_index.len();
+        for &client_id in self.client_id_to_index.keys() {
+            network.send_message(client_id, AppChannel::ReliableOrdered, payload.clone());
+        }
+        self.note_egress_bytes(payload.len().saturating_mul(recipients_count));
+    }
+
+    pub fn hold_paused_tick(&mut self) {
+        for bullet in &mut self.bullets {
+            bullet.spawn_tick += 1;
+        }
+        for player in &mut self.players {
+            player.input_buffer.advance_tail(self.current_tick);
+            player.last_active_tick += 1;
+        }
+    }
+
+    pub fn afk_players(&self) -> Vec<usize> {
+        let Some(timeout) = self.settings.afk_timeout else {
+            return Vec::new();
+        };
+        let timeout_ticks = (timeout.as_secs_f64() / TICK_SECS) as u64;
+
+        self.players
+            .iter()
+            .enumerate()
+            .filter(|(_, player)| matches!(player.status, Status::Alive) && !player.afk)
+            .filter(|(_, player)| {
+                self.current_tick.saturating_sub(player.last_active_tick) >= timeout_ticks
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    pub fn handle_afk_players(&mut self, network: &mut dyn ServerNetworkHandle) {
+        for index in self.afk_players() {
+            let player = &mut self.players[index];
+            let announcement = match self.settings.afk_action {
+                AfkAction::Flag => None,
+                AfkAction::Eliminate => {
+                    player.health = 0;
+                    player.status = Status::Dead;
+                    if player.exit_tick.is_none() {
+                        player.exit_tick = Some(self.current_tick);
+                    }
+                    let event = MatchEvent::Idle {
+                        player: player.name.clone(),
+                    };
+                    let announcement =
+                        format!("{} was idle too long and has been eliminated", player.name);
+                    self.log_event(event);
+                    Some(announcement)
+                }
+                AfkAction::Teleport => {
+                    if let Some(position) = contested_position(&self.maze, player.state.position.y)
+                    {
+                        player.state.position = position;
+                    }
+                    player.last_active_tick = self.current_tick;
+                    Some(format!(
+                        "{} was idle too long and has been moved to the middle of the maze",
+                        player.name
+                    ))
+                }
+            };
+            if let Some(announcement) = announcement {
+                println!("{}.", announcement);
+                let message = ServerMessage::ServerInfo {
+                    message: announcement,
+                };
+                self.broadcast_to_players(network, &message);
+            }
+            self.set_afk(network, index, true);
+        }
+    }
+
+    pub fn set_afk(&mut self, network: &mut dyn ServerNetworkHandle, index: usize, afk: bool) {
+        let player = &mut self.players[index];
+        if player.afk == afk {
+            return;
+        }
+        player.afk = afk;
+        if afk {
+            println!("{} is AFK.", player.name);
+        } else {
+            println!("{} is back.", player.name);
+        }
+
+        self.broadcast_to_players(network, &ServerMessage::PlayerAfk { index, afk });
+    }
+
+    pub fn broadcast_to_players(
+        &mut self,
+        network: &mut dyn ServerNetworkHandle,
+        message: &ServerMessage,
+    ) {
+        let payload = encode_to_vec(message, config()).expect("failed to serialize message");
+        let recipients = self.in_game_recipients();
+        let recipients_count = recipients.len();
+        for client_id in recipients {
+            network.send_message(client_id, AppChannel::ReliableOrdered, payload.clone());
+        }
+        self.note_egress_bytes(payload.len().saturating_mul(recipients_count));
+    }
+
+    pub fn broadcast_to_players_with_priority(
+        &mut self,
+        network: &mut dyn ServerNetworkHandle,
+        message: &ServerMessage,
+        priority: SendPriority,
+    ) {
+        let payload = encode_to_vec(message, config()).expect("failed to serialize message");
+        let recipients = self.in_game_recipients();
+        let recipients_count = recipients.len();
+        for client_id in recipients {
+            network.send_message_with_priority(
+                client_id,
+                AppChannel::ReliableOrdered,
+                payload.clone(),
+                priority,
+            );
+        }
+        self.note_egress_bytes(payload.len().saturating_mul(recipients_count));
+    }
+
+    fn in_game_recipients(&self) -> Vec<u64> {
+        self.client_id_to_index
+            .keys()
+            .copied()
+            .filter(|client_id| !self.after_game_chat_clients.contains(client_id))
+            .collect()
+    }
+
+    pub fn active_count(&self) -> usize {
+        self.players
+            .iter()
+            .filter(|player| matches!(player.status, Status::Alive))
+            .count()
+    }
+
+    pub fn spectator_count(&self) -> usize {
+        self.players
+            .iter()
+            .filter(|player| matches!(player.status, Status::Dead))
+            .count()
+    }
+
+    pub fn broadcast_spectator_count_if_changed(&mut self, network: &mut dyn ServerNetworkHandle) {
+        let watching = self.spectator_count();
+        if watching == self.last_spectator_count {
+            return;
+        }
+        self.last_spectator_count = watching;
+
+        let message = ServerMessage::SpectatorCount { watching };
+        let payload =
+            encode_to_vec(&message, config()).expect("failed to serialize SpectatorCount");
+        let recipients_count = self.client_id_to_index.len();
+        for &client_id in self.client_id_to_index.keys() {
+            network.send_message(client_id, AppChannel::ReliableOrdered, payload.clone());
+        }
+        self.note_egress_bytes(payload.len().saturating_mul(recipients_count));
+    }
+
     pub fn snapshot_for(&self, i: usize) -> Snapshot {
         let local = WirePlayerLocal::from(self.players[i].state);
 
@@ -176,7 +508,11 @@ impl Game {
             .map(|(_, p)| WirePlayerRemote::from(p.state))
             .collect();
 
-        Snapshot { local, remote }
+        Snapshot {
+            local,
+            remote,
+            ammo: self.players[i].ammo,
+        }
     }
 
     pub fn note_ingress_bytes(&mut self, bytes: usize) {
@@ -197,9 +533,15 @@ impl Game {
         }
 
         let entries = self.build_leaderboard_entries();
-        let message = ServerMessage::AfterGameLeaderboard { entries };
+        let awards = self.build_awards();
+        let rounds = self.record_round();
+        let message = ServerMessage::AfterGameLeaderboard {
+            entries,
+            awards,
+            rounds,
+        };
         let payload =
-            encode_to_vec(&message, standard()).expect("failed to serialize AfterGameLeaderboard");
+            encode_to_vec(&message, config()).expect("failed to serialize AfterGameLeaderboard");
         let payload_len = payload.len();
         let mut egress_bytes = 0usize;
 
@@ -212,6 +554,169 @@ impl Game {
         self.leaderboard_sent = true;
     }
 
+    fn record_round(&mut self) -> Option<RoundSummary> {
+        let winner = self
+            .winner_index
+            .map(|index| self.players[index].name.clone());
+        let next_round_follows = self.has_next_round();
+        let rounds = self.rounds.as_mut()?;
+        rounds.record_round(
+            self.players
+                .iter()
+                .map(|player| (player.name.as_str(), player.kills)),
+            winner.as_deref(),
+        );
+        Some(RoundSummary {
+            round: rounds.round,
+            total_rounds: rounds.total_rounds,
+            standings: rounds.standings(),
+            next_round_follows,
+        })
+    }
+
+    fn has_next_round(&self) -> bool {
+        let remaining = self
+            .players
+            .iter()
+            .filter(|player| !matches!(player.status, Status::Disconnected))
+            .count();
+        self.rounds
+            .as_ref()
+            .is_some_and(|rounds| !rounds.is_final_round())
+            && remaining >= 2
+    }
+
+    pub fn next_round(&mut self) -> Option<Countdown> {
+        if !self.has_next_round() {
+            return None;
+        }
+        let rounds = self.rounds.as_mut()?;
+        let remaining: Vec<&ServerPlayer> = self
+            .players
+            .iter()
+            .filter(|player| !matches!(player.status, Status::Disconnected))
+            .collect();
+        rounds.advance();
+        println!(
+            "Starting round {} of {}.",
+            rounds.round, rounds.total_rounds
+        );
+
+        let usernames: HashMap<u64, String> = remaining
+            .iter()
+            .map(|player| (player.client_id, player.name.clone()))
+            .collect();
+        let colors: HashMap<u64, Color> = remaining
+            .iter()
+            .map(|player| (player.client_id, player.color))
+            .collect();
+        let teams: HashMap<u64, u8> = remaining
+            .iter()
+            .filter_map(|player| Some((player.client_id, player.team?)))
+            .collect();
+        let mut options = self.settings.map_options();
+        options.seed = options
+            .seed
+            .map(|seed| seed.wrapping_add(rounds.round as u64));
+        let mut game_data =
+            InitialData::with_options(&usernames, &colors, rounds.difficulty, options);
+        game_data.assign_teams(&teams);
+
+        Some(Countdown {
+            usernames: usernames
+                .into_iter()
+                .filter(|&(client_id, _)| !bot::is_bot(client_id))
+                .collect(),
+            host_id: self.host_id,
+            end_time: Instant::now() + COUNTDOWN_DURATION,
+            game_data,
+            settings: self.settings.clone(),
+            chat_log: self.chat_log.clone(),
+            rounds: self.rounds.clone(),
+        })
+    }
+
+    pub fn return_to_lobby(&self, network: &mut dyn ServerNetworkHandle) -> Option<Lobby> {
+        if !self.settings.return_to_lobby {
+            return None;
+        }
+        let connected = network.clients_id();
+        let returning: Vec<&ServerPlayer> = self
+            .players
+            .iter()
+            .filter(|player| player.bot.is_none())
+            .filter(|player| !matches!(player.status, Status::Disconnected))
+            .filter(|player| connected.contains(&player.client_id))
+            .collect();
+        let first = returning.first()?;
+        println!("Returning {} player(s) to the lobby.", returning.len());
+
+        let mut lobby = Lobby::with_settings(self.settings.clone());
+        lobby.chat_log = self.chat_log.clone();
+        for player in &returning {
+            let client_id = player.client_id;
+            lobby.register_connection(client_id);
+            lobby.mark_authenticated(client_id);
+            lobby.request_color(client_id, player.color);
+            lobby.register_username(client_id, &player.name);
+            if let Some(team) = player.team {
+                lobby.teams.insert(client_id, team);
+            }
+        }
+
+        for player in &returning {
+            let client_id = player.client_id;
+            let messages = [
+                ServerMessage::Welcome {
+                    username: player.name.clone(),
+                    color: player.color,
+                    team: lobby.team(client_id),
+                    available_colors: lobby.available_colors(client_id),
+                    max_chat_bytes: lobby.max_chat_bytes(),
+                },
+                ServerMessage::Roster {
+                    online: lobby.roster_except(client_id),
+                    connecting: 0,
+                },
+            ];
+            for message in messages {
+                let payload =
+                    encode_to_vec(&message, config()).expect("failed to serialize lobby welcome");
+                network.send_message(client_id, AppChannel::ReliableOrdered, payload);
+            }
+        }
+
+        let host_id = self
+            .host_id
+            .filter(|host_id| returning.iter().any(|player| player.client_id == *host_id))
+            .unwrap_or(first.client_id);
+        lobby.set_host(host_id, network);
+        lobby.broadcast_player_count(network);
+        Some(lobby)
+    }
+
+    fn build_awards(&self) -> Vec<AfterGameAward> {
+        let ticks_survived: Vec<u64> = self
+            .players
+            .iter()
+            .map(|player| {
+                let end_tick = player.exit_tick.unwrap_or(self.current_tick);
+                end_tick.saturating_sub(self.game_start_tick)
+            })
+            .collect();
+        awards::compute_awards(&self.hit_records, &ticks_survived)
+            .into_iter()
+            .map(|award| AfterGameAward {
+                title: award.title.to_string(),
+                recipients: award
+                    .recipients
+                    .into_iter()
+                    .map(|index| self.players[index].name.clone())
+                    .collect(),
+            })
+            .collect()
+    }
+
     fn build_leaderboard_entries(&self) -> Vec<AfterGameLeaderboardEntry> {
         let mut entries = self
             .players
@@ -228,11 +733,17 @@ impl Game {
                     color: player.color,
                     ticks_survived,
                     exit_reason,
+                    kills: player.kills,
+                    deaths: player.deaths,
+                    self_kills: player.self_kills,
+                    shots_fired: player.shots_fired,
+                    hits_landed: player.hits_landed,
+                    damage_dealt: player.damage_dealt,
                 }
             })
             .collect::<Vec<_>>();
 
-        entries.sort_by(|a, b| b.ticks_survived.cmp(&a.ticks_survived));
+        entries.sort_by(leaderboard_order);
 
         // Handle timer expiration deaths.
         if let Some(timer_tick) = self.timer_expiration_tick {
@@ -275,7 +786,7 @@ impl Game {
             match (a_is_winner, b_is_winner) {
                 (true, false) => std::cmp::Ordering::Less,
                 (false, true) => std::cmp::Ordering::Greater,
-                _ => b.ticks_survived.cmp(&a.ticks_survived),
+                _ => leaderboard_order(a, b),
             }
         });
 
@@ -283,6 +794,16 @@ impl Game {
     }
 }
 
+fn leaderboard_order(
+    a: &AfterGameLeaderboardEntry,
+    b: &AfterGameLeaderboardEntry,
+) -> std::cmp::Ordering {
+    b.ticks_survived
+        .cmp(&a.ticks_survived)
+        .then_with(|| b.kills.cmp(&a.kills))
+        .then_with(|| a.username.cmp(&b.username))
+}
+
 pub struct NetStats {
     ingress_bytes: u64,
     egress_bytes: u64,
@@ -323,6 +844,18 @@ impl NetStats {
     }
 }
 
+fn contested_position(maze: &Maze, height: f32) -> Option<glam::Vec3> {
+    let centre_z = maze.grid.len() as f32 / 2.0;
+    let centre_x = maze.grid.first().map_or(0, Vec::len) as f32 / 2.0;
+    let &(z, x) = maze.spaces.iter().min_by(|a, b| {
+        let distance = |&(z, x): &(usize, usize)| {
+            (z as f32 + 0.5 - centre_z).powi(2) + (x as f32 + 0.5 - centre_x).powi(2)
+        };
+        distance(a).total_cmp(&distance(b))
+    })?;
+    maze.position_from_grid_coordinates(height, z, x)
+}
+
 fn format_bytes_per_second(bytes_per_second: f64) -> String {
     const KIBIBYTE: f64 = 1024.0;
     const MEBIBYTE: f64 = 1024.0 * 1024.0;
@@ -365,21 +898,32 @@ impl ChoosingDifficulty {
     }
 }
 
+pub const COUNTDOWN_DURATION: Duration = Duration::from_secs(11);
+
 #[derive(Clone)]
 pub struct Countdown {
     pub usernames: HashMap<u64, String>,
     pub host_id: Option<u64>,
     pub end_time: Instant,
     pub game_data: InitialData,
+    pub settings: Settings,
+    pub chat_log: Option<SharedChatLog>,
+    pub rounds: Option<RoundState>,
 }
 
 impl Countdown {
     pub fn new(state: &ChoosingDifficulty, end_time: Instant, game_data: InitialData) -> Self {
+        let total_rounds = state.lobby.rounds();
+        let rounds =
+            (total_rounds > 1).then(|| RoundState::new(total_rounds, game_data.difficulty));
         Self {
             usernames: state.lobby.usernames.clone(),
             host_id: state.host_id,
             end_time,
             game_data,
+            settings: state.lobby.settings().clone(),
+            chat_log: state.lobby.chat_log.clone(),
+            rounds,
         }
     }
 
@@ -390,8 +934,7 @@ impl Countdown {
                 client_id, username
             );
             let message = ServerMessage::UserLeft { username };
-            let payload =
-                encode_to_vec(&message, standard()).expect("failed to serialize UserLeft");
+            let payload = encode_to_vec(&message, config()).expect("failed to serialize UserLeft");
             network.broadcast_message(AppChannel::ReliableOrdered, payload);
         } else {
             println!(
@@ -425,18 +968,41 @@ impl Countdown {
     }
 }
 
+pub const HOST_RECLAIM_WINDOW: Duration = Duration::from_secs(30);
+
+#[derive(Clone)]
+struct PendingHostReassignment {
+    client_id: u64,
+    deadline: Instant,
+}
+
 #[derive(Clone)]
 pub struct Lobby {
     pub usernames: HashMap<u64, String>,
     pub player_colors: HashMap<u64, Color>,
+    pub chat_log: Option<SharedChatLog>,
     auth_attempts: HashMap<u64, u8>,
     pending_usernames: HashSet<u64>,
     host_client_id: Option<u64>,
+    settings: Settings,
+    teams: HashMap<u64, u8>,
+    start_votes: HashSet<u64>,
+    client_activity: HashMap<u64, Instant>,
+    pending_host_reassignment: Option<PendingHostReassignment>,
+    chat_limiter: ChatRateLimiter,
+    chat_deduplicator: ChatDeduplicator,
+    abandoned: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Occupancy {
+    pub named: usize,
+    pub pending: usize,
 }
 
 fn notify_new_host(network: &mut dyn ServerNetworkHandle, id: u64) {
     let message = ServerMessage::AppointHost;
-    let payload = encode_to_vec(&message, standard()).expect("failed to serialize AppointHost");
+    let payload = encode_to_vec(&message, config()).expect("failed to serialize AppointHost");
     network.send_message(id, AppChannel::ReliableOrdered, payload);
 }
 
@@ -447,12 +1013,233 @@ impl Lobby {
             pending_usernames: HashSet::new(),
             usernames: HashMap::new(),
             player_colors: HashMap::new(),
+            chat_log: None,
             host_client_id: None,
+            settings: Settings::default(),
+            teams: HashMap::new(),
+            start_votes: HashSet::new(),
+            client_activity: HashMap::new(),
+            pending_host_reassignment: None,
+            chat_limiter: ChatRateLimiter::new(),
+            chat_deduplicator: ChatDeduplicator::new(),
+            abandoned: false,
         }
     }
 
+    pub fn with_settings(settings: Settings) -> Self {
+        Self {
+            settings,
+            ..Self::new()
+        }
+    }
+
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    pub fn set_announce_pending_players(&mut self, enabled: bool) {
+        self.settings.announce_pending_players = enabled;
+    }
+
+    pub fn announces_pending_players(&self) -> bool {
+        self.settings.announce_pending_players
+    }
+
+    pub fn set_teams_enabled(&mut self, enabled: bool) {
+        self.settings.teams = enabled;
+    }
+
+    pub fn set_start_policy(&mut self, policy: StartPolicy) {
+        self.settings.start_policy = policy;
+    }
+
+    pub fn start_policy(&self) -> StartPolicy {
+        self.settings.start_policy
+    }
+
+    pub fn set_host_idle_timeout(&mut self, timeout: Option<Duration>) {
+        self.settings.host_idle_timeout = timeout;
+    }
+
+    pub fn max_auth_attempts(&self) -> u8 {
+        self.settings.max_auth_attempts.unwrap_or(MAX_ATTEMPTS)
+    }
+
+    pub fn min_players(&self) -> usize {
+        self.settings.min_players.unwrap_or(DEFAULT_MIN_PLAYERS)
+    }
+
+    pub fn max_chat_bytes(&self) -> usize {
+        self.settings
+            .max_chat_bytes
+            .unwrap_or(MAX_CHAT_MESSAGE_BYTES)
+            .min(MAX_CHAT_MESSAGE_BYTES)
+    }
+
+    pub fn set_max_username_length(&mut self, length: Option<usize>) {
+        self.settings.max_username_length = length;
+    }
+
+    pub fn max_username_length(&self) -> usize {
+        self.settings
+            .max_username_length
+            .unwrap_or(MAX_USERNAME_LENGTH)
+            .min(MAX_USERNAME_LENGTH)
+    }
+
+    pub fn rounds(&self) -> u8 {
+        self.settings.rounds.unwrap_or(1)
+    }
+
+    pub fn has_enough_players_to_start(&self) -> bool {
+        self.usernames.len() + self.settings.bots >= self.min_players()
+    }
+
+    pub fn note_client_activity(&mut self, client_id: u64, now: Instant) {
+        self.client_activity.insert(client_id, now);
+    }
+
+    pub fn reassign_idle_host(
+        &mut self,
+        now: Instant,
+        network: &mut dyn ServerNetworkHandle,
+    ) -> Option<u64> {
+        let timeout = self.settings.host_idle_timeout?;
+        let host_id = self.host_client_id?;
+        if self.usernames.len() < 2 {
+            return None;
+        }
+
+        let host_last_active = self.client_activity.get(&host_id).copied()?;
+        if now.saturating_duration_since(host_last_active) <= timeout {
+            return None;
+        }
+
+        let new_host_id = self
+            .usernames
+            .keys()
+            .copied()
+            .filter(|&id| id != host_id)
+            .filter_map(|id| self.client_activity.get(&id).map(|&at| (id, at)))
+            .filter(|&(_, at)| now.saturating_duration_since(at) <= timeout)
+            .max_by_key(|&(_, at)| at)
+            .map(|(id, _)| id)?;
+
+        let old_name = self.username(host_id).unwrap_or("The host").to_string();
+        let new_name = self
+            .username(new_host_id)
+            .unwrap_or("another player")
+            .to_string();
+        println!(
+            "Host {} idle for over {:?}; reassigning host to client {}.",
+            host_id, timeout, new_host_id
+        );
+        self.set_host(new_host_id, network);
+
+        self.send_server_info(
+            network,
+            format!(
+                "{} has been idle, so {} is now the host.",
+                old_name, new_name
+            ),
+        );
+
+        Some(new_host_id)
+    }
+
+    pub fn reclaim_host(
+        &mut self,
+        client_id: u64,
+        now: Instant,
+        network: &mut dyn ServerNetworkHandle,
+    ) -> bool {
+        let Some(pending) = &self.pending_host_reassignment else {
+            return false;
+        };
+        if now > pending.deadline || client_id != pending.client_id {
+            return false;
+        }
+
+        self.pending_host_reassignment = None;
+        self.set_host(client_id, network);
+
+        let name = self.username(client_id).unwrap_or("The host").to_string();
+        self.send_server_info(
+            network,
+            format!("{} reconnected and is the host again.", name),
+        );
+
+        true
+    }
+
+    pub fn expire_host_reclaim(&mut self, now: Instant) {
+        if self
+            .pending_host_reassignment
+            .as_ref()
+            .is_some_and(|pending| now > pending.deadline)
+        {
+            self.pending_host_reassignment = None;
+        }
+    }
+
+    pub fn vote_to_start(&mut self, client_id: u64) -> bool {
+        if self.usernames.contains_key(&client_id) {
+            self.start_votes.insert(client_id);
+        }
+        let passed = self.start_votes.len() * 2 > self.usernames.len();
+        if passed {
+            self.start_votes.clear();
+        }
+        passed
+    }
+
+    pub fn start_votes(&self) -> usize {
+        self.start_votes.len()
+    }
+
+    pub fn votes_needed_to_start(&self) -> usize {
+        self.usernames.len() / 2 + 1
+    }
+
+    pub fn map_options(&self) -> MapOptions {
+        self.settings().map_options()
+    }
+
+    pub fn team(&self, client_id: u64) -> Option<u8> {
+        self.teams.get(&client_id).copied()
+    }
+
+    pub fn teams(&self) -> &HashMap<u64, u8> {
+        &self.teams
+    }
+
+    pub fn balance_teams(&mut self) {
+        team::balance(&mut self.teams);
+    }
+
+    pub fn announce_pending_player(&self, network: &mut dyn ServerNetworkHandle) {
+        self.send_server_info(network, "A new player is connecting.".to_string());
+    }
+
+    fn send_server_info(&self, network: &mut dyn ServerNetworkHandle, message: String) {
+        let message = ServerMessage::ServerInfo { message };
+        let payload = encode_to_vec(&message, config()).expect("failed to serialize ServerInfo");
+        for &client_id in self.usernames.keys() {
+            network.send_message(client_id, AppChannel::ReliableOrdered, payload.clone());
+        }
+    }
+
+    pub fn chat_cooldown(&mut self, client_id: u64, now: Instant) -> Option<Duration> {
+        self.chat_limiter.check(client_id, now)
+    }
+
+    pub fn is_duplicate_chat(&mut self, client_id: u64, content: &str, now: Instant) -> bool {
+        self.chat_deduplicator.is_duplicate(client_id, content, now)
+    }
+
     pub fn set_host(&mut self, id: u64, network: &mut dyn ServerNetworkHandle) {
         self.host_client_id = Some(id);
+        self.client_activity.insert(id, Instant::now());
         notify_new_host(network, id);
     }
 
@@ -467,18 +1254,47 @@ impl Lobby {
         self.auth_attempts.insert(client_id, 0);
     }
 
+    pub fn is_full(&self) -> bool {
+        self.auth_attempts.len()
+            + self.pending_usernames.len()
+            + self.usernames.len()
+            + self.settings.bots
+            >= MAX_PLAYERS
+    }
+
+    pub fn is_connected(&self, client_id: u64) -> bool {
+        self.auth_attempts.contains_key(&client_id)
+            || self.pending_usernames.contains(&client_id)
+            || self.usernames.contains_key(&client_id)
+    }
+
     pub fn remove_client(&mut self, client_id: u64, network: &mut dyn ServerNetworkHandle) {
         self.auth_attempts.remove(&client_id);
         self.pending_usernames.remove(&client_id);
         self.player_colors.remove(&client_id);
+        self.teams.remove(&client_id);
+        self.start_votes.remove(&client_id);
+        self.client_activity.remove(&client_id);
+        self.chat_limiter.remove_client(client_id);
+        self.chat_deduplicator.remove_client(client_id);
 
         let name_removed = self.usernames.remove(&client_id);
 
+        if self.host_client_id == Some(client_id)
+            && name_removed.is_some()
+            && !self.usernames.is_empty()
+        {
+            self.pending_host_reassignment = Some(PendingHostReassignment {
+                client_id,
+                deadline: Instant::now() + HOST_RECLAIM_WINDOW,
+            });
+        }
+
         if let Some(username) = name_removed {
             let message = ServerMessage::UserLeft { username };
-            let payload =
-                encode_to_vec(&message, standard()).expect("failed to serialize UserLeft");
+            let payload = encode_to_vec(&message, config()).expect("failed to serialize UserLeft");
             network.broadcast_message(AppChannel::ReliableOrdered, payload);
+            self.broadcast_player_count(network);
         }
 
         if self.host_client_id == Some(client_id) {
@@ -501,11 +1317,21 @@ impl Lobby {
             && self.usernames.is_empty()
             && self.pending_usernames.is_empty()
         {
-            println!("All clients have disconnected. Server exiting...");
-            std::process::exit(0);
+            println!("All clients have disconnected. Closing room...");
+            self.abandoned = true;
         }
     }
 
+    pub fn broadcast_player_count(&self, network: &mut dyn ServerNetworkHandle) {
+        let message = ServerMessage::PlayerCount(self.usernames.len());
+        let payload = encode_to_vec(&message, config()).expect("failed to serialize PlayerCount");
+        network.broadcast_message(AppChannel::ReliableOrdered, payload);
+    }
+
+    pub fn is_abandoned(&self) -> bool {
+        self.abandoned
+    }
+
     pub fn authentication_attempts(&mut self, client_id: u64) -> Option<&mut u8> {
         self.auth_attempts.get_mut(&client_id)
     }
@@ -527,6 +1353,10 @@ impl Lobby {
         if self.pending_usernames.remove(&client_id) {
             self.usernames.insert(client_id, username.to_string());
             self.assign_color(client_id);
+            if self.settings.teams {
+                let team = team::smallest_team(self.teams.values().copied());
+                self.teams.insert(client_id, team);
+            }
         }
         self.usernames.get(&client_id).map(|s| s.as_str())
     }
@@ -554,27 +1384,41 @@ impl Lobby {
                 Some(PlayerRosterEntry {
                     username: name.clone(),
                     color,
+                    team: self.team(id),
                 })
             })
             .collect()
     }
 
+    pub fn available_colors(&self, client_id: u64) -> Vec<Color> {
+        COLORS
+            .iter()
+            .copied()
+            .filter(|&candidate| {
+                !self
+                    .player_colors
+                    .iter()
+                    .any(|(&id, &used)| id != client_id && used == candidate)
+            })
+            .collect()
+    }
+
+    pub fn request_color(&mut self, client_id: u64, color: Color) -> Color {
+        if self.available_colors(client_id).contains(&color) {
+            self.player_colors.insert(client_id, color);
+            return color;
+        }
+        self.assign_color(client_id)
+    }
+
     fn assign_color(&mut self, client_id: u64) -> Color {
         if let Some(color) = self.player_colors.get(&client_id).copied() {
             return color;
         }
 
-        let available_colors: Vec<Color> = COLORS
-            .iter()
-            .copied()
-            .filter(|&candidate| !self.player_colors.values().any(|&used| used == candidate))
-            .collect();
-
-        let color = if available_colors.is_empty() {
-            COLORS[self.player_colors.len() % COLORS.len()]
-        } else {
-            available_colors[rng().random_range(0..available_colors.len())]
-        };
+        let taken: Vec<Color> = self.player_colors.values().copied().collect();
+        let color =
+            next_available_color(&taken).unwrap_or(COLORS[self.player_colors.len() % COLORS.len()]);
 
         self.player_colors.insert(client_id, color);
         color
@@ -584,6 +1428,14 @@ impl Lobby {
         self.usernames.values().any(|existing| existing == username)
     }
 
+    pub fn claim_username(&mut self, client_id: u64, username: &str) -> UsernameClaim {
+        if self.is_username_taken(username) {
+            return UsernameClaim::Taken;
+        }
+        self.register_username(client_id, username);
+        UsernameClaim::Registered
+    }
+
     pub fn usernames_except(&self, client_id: u64) -> Vec<String> {
         self.usernames
             .iter()
@@ -602,6 +1454,29 @@ impl Lobby {
         pending.extend(self.pending_usernames.iter().cloned());
         pending.into_iter().collect()
     }
+
+    pub fn occupancy(&self) -> Occupancy {
+        Occupancy {
+            named: self.usernames.len(),
+            pending: self.pending_clients().len(),
+        }
+    }
+
+    pub fn placeholder_count_except(&self, client_id: u64) -> usize {
+        if !self.settings.announce_pending_players {
+            return 0;
+        }
+        self.pending_clients()
+            .into_iter()
+            .filter(|&id| id != client_id)
+            .count()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum UsernameClaim {
+    Registered,
+    Taken,
 }
 
 #[derive(Debug, PartialEq)]
@@ -631,7 +1506,7 @@ pub fn evaluate_passcode_attempt(
 
 #[cfg(test)]
 mod tests {
-    use bincode::{config::standard, serde::decode_from_slice};
+    use bincode::serde::decode_from_slice;
 
     use super::*;
     use crate::test_helpers::MockServerNetwork;
@@ -650,13 +1525,16 @@ mod tests {
             host_id: None,
             end_time: Instant::now(),
             game_data,
+            settings: Settings::default(),
+            chat_log: None,
+            rounds: None,
         });
 
         state.register_connection(7, &mut network);
 
         let messages = network.get_sent_messages_data(7);
         assert_eq!(messages.len(), 1);
-        let msg = decode_from_slice::<ServerMessage, _>(&messages[0], standard())
+        let msg = decode_from_slice::<ServerMessage, _>(&messages[0], config())
             .expect("failed to deserialize server message")
             .0;
         if let ServerMessage::ServerInfo { message } = msg {
@@ -716,6 +1594,61 @@ mod tests {
         assert!(state.is_authenticating(42));
     }
 
+    #[test]
+    fn duplicate_connection_is_disconnected_with_a_reason() {
+        let mut lobby = Lobby::new();
+        lobby.register_connection(7);
+        lobby.mark_authenticated(7);
+        lobby.register_username(7, "alice");
+        let mut state = ServerState::Lobby(lobby);
+        let mut network = MockServerNetwork::new();
+        network.add_client(7);
+
+        state.register_connection(7, &mut network);
+
+        let ServerState::Lobby(lobby) = &state else {
+            panic!("expected lobby state");
+        };
+        assert!(!lobby.is_authenticating(7));
+        assert!(network.disconnected_clients.contains(&7));
+        let sent = network.get_sent_messages_data(7);
+        let msg = decode_from_slice::<ServerMessage, _>(&sent[0], config())
+            .unwrap()
+            .0;
+        assert!(matches!(
+            msg,
+            ServerMessage::DisconnectReason(DisconnectReason::DuplicateConnection)
+        ));
+    }
+
+    #[test]
+    fn connection_to_a_full_lobby_is_refused_with_a_reason() {
+        let mut lobby = Lobby::with_settings(Settings {
+            bots: MAX_PLAYERS - 1,
+            ..Settings::default()
+        });
+        lobby.register_connection(1);
+        let mut state = ServerState::Lobby(lobby);
+        let mut network = MockServerNetwork::new();
+        network.add_client(2);
+
+        state.register_connection(2, &mut network);
+
+        let ServerState::Lobby(lobby) = &state else {
+            panic!("expected lobby state");
+        };
+        assert!(!lobby.is_connected(2));
+        assert!(network.disconnected_clients.contains(&2));
+        let sent = network.get_sent_messages_data(2);
+        let msg = decode_from_slice::<ServerMessage, _>(&sent[0], config())
+            .unwrap()
+            .0;
+        assert!(matches!(
+            msg,
+            ServerMessage::DisconnectReason(DisconnectReason::ServerFull)
+        ));
+    }
+
     #[test]
     fn remove_client_clears_authentication_state() {
         let mut state = Lobby::new();
@@ -781,17 +1714,32 @@ mod tests {
 
     #[test]
     fn username_sanitization_enforces_case_insensitive_storage() {
-        use common::player::sanitize_username;
+        use common::player::{MAX_USERNAME_LENGTH, sanitize_username};
 
-        assert_eq!(sanitize_username("PlayerOne"), Ok("playerone".to_string()));
-        assert_eq!(sanitize_username("PLAYERONE"), Ok("playerone".to_string()));
-        assert_eq!(sanitize_username("playerone"), Ok("playerone".to_string()));
-        assert_eq!(sanitize_username("pLaYeRoNe"), Ok("playerone".to_string()));
+        assert_eq!(
+            sanitize_username("PlayerOne", MAX_USERNAME_LENGTH),
+            Ok("playerone".to_string())
+        );
+        assert_eq!(
+            sanitize_username("PLAYERONE", MAX_USERNAME_LENGTH),
+            Ok("playerone".to_string())
+        );
+        assert_eq!(
+            sanitize_username("playerone", MAX_USERNAME_LENGTH),
+            Ok("playerone".to_string())
+        );
+        assert_eq!(
+            sanitize_username("pLaYeRoNe", MAX_USERNAME_LENGTH),
+            Ok("playerone".to_string())
+        );
 
-        assert_eq!(sanitize_username("PlayerTwo"), Ok("playertwo".to_string()));
+        assert_eq!(
+            sanitize_username("PlayerTwo", MAX_USERNAME_LENGTH),
+            Ok("playertwo".to_string())
+        );
         assert_ne!(
-            sanitize_username("PlayerOne"),
-            sanitize_username("PlayerTwo")
+            sanitize_username("PlayerOne", MAX_USERNAME_LENGTH),
+            sanitize_username("PlayerTwo", MAX_USERNAME_LENGTH)
         );
     }
 
@@ -830,7 +1778,7 @@ mod tests {
         let messages = network.get_sent_messages_data(123);
         assert_eq!(messages.len(), 1);
 
-        let msg = decode_from_slice::<ServerMessage, _>(&messages[0], standard())
+        let msg = decode_from_slice::<ServerMessage, _>(&messages[0], config())
             .unwrap()
             .0;
         if let ServerMessage::AppointHost = msg {
@@ -840,6 +1788,118 @@ mod tests {
         }
     }
 
+    #[test]
+    fn idle_host_is_replaced_by_an_active_player() {
+        let mut network = MockServerNetwork::new();
+        let mut state = Lobby::new();
+        let timeout = Duration::from_secs(60);
+        state.set_host_idle_timeout(Some(timeout));
+        for (client_id, name) in [(1, "alice"), (2, "bob")] {
+            network.add_client(client_id);
+            state.register_connection(client_id);
+            state.mark_authenticated(client_id);
+            state.register_username(client_id, name);
+        }
+        state.set_host(1, &mut network);
+
+        let start = Instant::now();
+        state.note_client_activity(1, start);
+        state.note_client_activity(2, start + timeout);
+
+        assert_eq!(
+            state.reassign_idle_host(start + timeout, &mut network),
+            None
+        );
+        assert!(state.is_host(1));
+
+        let later = start + timeout + Duration::from_secs(1);
+        assert_eq!(state.reassign_idle_host(later, &mut network), Some(2));
+        assert!(state.is_host(2));
+        assert!(network.get_sent_messages_data(1).iter().any(|data| {
+            matches!(
+                decode_from_slice::<ServerMessage, _>(data, config()).unwrap().0,
+                ServerMessage::ServerInfo { message } if message.contains("bob is now the host")
+            )
+        }));
+        assert!(network.get_broadcast_messages_data().is_empty());
+    }
+
+    #[test]
+    fn occupancy_counts_pending_clients_separately_from_named_players() {
+        let mut state = Lobby::new();
+        state.register_connection(1);
+        state.mark_authenticated(1);
+        state.register_username(1, "alice");
+        state.register_connection(2);
+        state.register_connection(3);
+        state.mark_authenticated(3);
+
+        assert_eq!(
+            state.occupancy(),
+            Occupancy {
+                named: 1,
+                pending: 2
+            }
+        );
+        assert_eq!(state.placeholder_count_except(1), 0);
+
+        state.set_announce_pending_players(true);
+        assert_eq!(state.placeholder_count_except(1), 2);
+        assert_eq!(state.placeholder_count_except(3), 1);
+    }
+
+    fn lobby_with_departed_host(network: &mut MockServerNetwork) -> Lobby {
+        let mut state = Lobby::new();
+        for (client_id, name) in [(1, "alice"), (2, "bob")] {
+            state.register_connection(client_id);
+            state.mark_authenticated(client_id);
+            state.register_username(client_id, name);
+        }
+        state.set_host(1, network);
+        state.remove_client(1, network);
+        assert_eq!(state.host_client_id, Some(2));
+
+        for (client_id, name) in [(1, "alice"), (3, "alice")] {
+            state.register_connection(client_id);
+            state.mark_authenticated(client_id);
+            state.register_username(client_id, name);
+        }
+        state
+    }
+
+    #[test]
+    fn host_reconnecting_within_window_reclaims_host() {
+        let mut network = MockServerNetwork::new();
+        let mut state = lobby_with_departed_host(&mut network);
+
+        assert!(!state.reclaim_host(3, Instant::now(), &mut network));
+        assert!(state.reclaim_host(1, Instant::now(), &mut network));
+
+        assert_eq!(state.host_client_id, Some(1));
+        assert!(!state.reclaim_host(1, Instant::now(), &mut network));
+        assert!(network.get_broadcast_messages_data().iter().all(|data| {
+            !matches!(
+                decode_from_slice::<ServerMessage, _>(data, config())
+                    .unwrap()
+                    .0,
+                ServerMessage::ServerInfo { .. }
+            )
+        }));
+    }
+
+    #[test]
+    fn host_reconnecting_after_window_does_not_reclaim_host() {
+        let mut network = MockServerNetwork::new();
+        let mut state = lobby_with_departed_host(&mut network);
+        let later = Instant::now() + HOST_RECLAIM_WINDOW + Duration::from_secs(1);
+
+        assert!(!state.reclaim_host(1, later, &mut network));
+        state.expire_host_reclaim(later);
+        assert!(!state.reclaim_host(1, Instant::now(), &mut network));
+
+        assert_eq!(state.host_client_id, Some(2));
+    }
+
     #[test]
     fn test_remove_last_client_with_username_clears_host() {
         let mut state = Lobby::new();
@@ -854,6 +1914,247 @@ mod tests {
         assert_eq!(state.host_client_id, None);
     }
 
+    #[test]
+    fn lobby_assigns_joiners_to_balanced_teams() {
+        let mut lobby = Lobby::new();
+        lobby.set_teams_enabled(true);
+
+        for (client_id, name) in [(1, "alice"), (2, "bob"), (3, "carol")] {
+            lobby.register_connection(client_id);
+            lobby.mark_authenticated(client_id);
+            lobby.register_username(client_id, name);
+        }
+
+        assert_eq!(lobby.team(1), Some(0));
+        assert_eq!(lobby.team(2), Some(1));
+        assert_eq!(lobby.team(3), Some(0));
+        assert!(
+            lobby
+                .roster_except(1)
+                .iter()
+                .all(|entry| entry.team.is_some())
+        );
+    }
+
+    #[test]
+    fn elimination_moves_player_from_active_to_spectators() {
+        let mut network = MockServerNetwork::new();
+        network.add_client(1);
+        network.add_client(2);
+        network.add_client(3);
+
+        let usernames = HashMap::from([
+            (1, "Alice".to_string()),
+            (2, "Bob".to_string()),
+            (3, "Carol".to_string()),
+        ]);
+        let colors = HashMap::new();
+        let mut game = Game::new(InitialData::new(&usernames, &colors, 1));
+
+        assert_eq!(game.active_count(), 3);
+        assert_eq!(game.spectator_count(), 0);
+
+        game.broadcast_spectator_count_if_changed(&mut network);
+        assert!(network.get_sent_messages_data(1).is_empty());
+
+        let bob_index = game.client_id_to_index[&2];
+        game.players[bob_index].status = Status::Dead;
+
+        assert_eq!(game.active_count(), 2);
+        assert_eq!(game.spectator_count(), 1);
+
+        game.broadcast_spectator_count_if_changed(&mut network);
+        for client_id in [1, 2, 3] {
+            let messages = network.get_sent_messages_data(client_id);
+            assert_eq!(messages.len(), 1);
+            let (message, _) =
+                decode_from_slice::<ServerMessage, _>(&messages[0], config()).unwrap();
+            assert!(matches!(
+                message,
+                ServerMessage::SpectatorCount { watching: 1 }
+            ));
+        }
+    }
+
+    #[test]
+    fn idle_player_past_afk_threshold_is_flagged_and_eliminated() {
+        let mut network = MockServerNetwork::new();
+        network.add_client(1);
+        network.add_client(2);
+
+        let usernames = HashMap::from([(1, "Alice".to_string()), (2, "Bob".to_string())]);
+        let mut game = Game::new(InitialData::new(&usernames, &HashMap::new(), 1));
+        game.settings.afk_timeout = Some(Duration::from_secs(10));
+        let timeout_ticks = (10.0 / TICK_SECS) as u64;
+        let log_path = std::env::temp_dir().join(format!("afk-events-{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&log_path);
+        game.event_log = Some(EventLog::new(log_path.clone()));
+
+        let idle = game.client_id_to_index[&1];
+        let active = game.client_id_to_index[&2];
+        game.current_tick += timeout_ticks;
+        game.players[active].last_active_tick = game.current_tick - 1;
+
+        assert_eq!(game.afk_players(), vec![idle]);
+
+        game.handle_afk_players(&mut network);
+        assert!(matches!(game.players[idle].status, Status::Dead));
+        assert!(matches!(game.players[active].status, Status::Alive));
+        let (message, _) =
+            decode_from_slice::<ServerMessage, _>(&network.get_sent_messages_data(2)[0], config())
+                .unwrap();
+        assert!(matches!(
+            message,
+            ServerMessage::ServerInfo { message } if message.contains("Alice")
+        ));
+        let contents = std::fs::read_to_string(&log_path).unwrap();
+        std::fs::remove_file(&log_path).unwrap();
+        assert_eq!(contents, format!("{}\tidle\tAlice\n", game.current_tick));
+    }
+
+    #[test]
+    fn equal_survival_times_are_ranked_by_kills_then_username() {
+        let usernames = HashMap::from([
+            (1, "dora".to_string()),
+            (2, "bob".to_string()),
+            (3, "carol".to_string()),
+            (4, "alice".to_string()),
+        ]);
+        let mut game = Game::new(InitialData::new(&usernames, &HashMap::new(), 1));
+        game.current_tick = game.game_start_tick + 500;
+        for (client_id, kills) in [(1, 2), (2, 0), (3, 0)] {
+            game.players[game.client_id_to_index[&client_id]].kills = kills;
+        }
+        let alice = game.client_id_to_index[&4];
+        game.players[alice].exit_tick = Some(game.game_start_tick + 100);
+        game.players[alice].kills = 5;
+
+        let order: Vec<String> = game
+            .build_leaderboard_entries()
+            .into_iter()
+            .map(|entry| entry.username)
+            .collect();
+
+        assert_eq!(order, ["dora", "bob", "carol", "alice"]);
+    }
+
+    fn round_summary(network: &mut MockServerNetwork, client_id: u64) -> Option<RoundSummary> {
+        network
+            .get_sent_messages_data(client_id)
+            .iter()
+            .find_map(|data| match decode_from_slice(data, config()).unwrap().0 {
+                ServerMessage::AfterGameLeaderboard { rounds, .. } => rounds,
+                _ => None,
+            })
+    }
+
+    fn finish_round(game: &mut Game, network: &mut MockServerNetwork, alice_kills: u32) {
+        let alice = game.client_id_to_index[&1];
+        game.players[alice].kills = alice_kills;
+        game.winner_index = Some(alice);
+        game.after_game_chat_clients.extend([1, 2]);
+        game.send_leaderboard_if_ready(network);
+    }
+
+    #[test]
+    fn rounds_accumulate_scores_and_end_after_the_last() {
+        let mut network = MockServerNetwork::new();
+        network.add_client(1);
+        network.add_client(2);
+        let usernames = HashMap::from([(1, "alice".to_string()), (2, "bob".to_string())]);
+        let mut game = Game::new(InitialData::new(&usernames, &HashMap::new(), 3));
+        game.rounds = Some(RoundState::new(2, 3));
+
+        finish_round(&mut game, &mut network, 1);
+        let summary = round_summary(&mut network, 1).expect("round summary should be sent");
+        assert_eq!((summary.round, summary.total_rounds), (1, 2));
+        assert_eq!(summary.standings[0].points, 4);
+        assert!(summary.next_round_follows);
+
+        let countdown = game.next_round().expect("a second round should follow");
+        assert_eq!(countdown.usernames, usernames);
+        assert_eq!(countdown.game_data.difficulty, 3);
+        assert_eq!(
+            countdown.rounds.as_ref().map(|rounds| rounds.round),
+            Some(2)
+        );
+
+        let mut network = MockServerNetwork::new();
+        network.add_client(1);
+        network.add_client(2);
+        let mut game = Game::new(countdown.game_data);
+        game.rounds = countdown.rounds;
+        finish_round(&mut game, &mut network, 2);
+        let summary = round_summary(&mut network, 2).expect("round summary should be sent");
+        assert_eq!(summary.round, 2);
+        assert_eq!(summary.standings[0].username, "alice");
+        assert_eq!(summary.standings[0].points, 9);
+        assert!(!summary.next_round_follows);
+        assert!(game.next_round().is_none());
+    }
+
+    #[test]
+    fn rounds_end_early_when_too_few_players_remain() {
+        let mut network = MockServerNetwork::new();
+        network.add_client(1);
+        network.add_client(2);
+        let usernames = HashMap::from([(1, "alice".to_string()), (2, "bob".to_string())]);
+        let mut game = Game::new(InitialData::new(&usernames, &HashMap::new(), 3));
+        game.rounds = Some(RoundState::new(3, 3));
+        let bob = game.client_id_to_index[&2];
+        game.players[bob].status = Status::Disconnected;
+
+        finish_round(&mut game, &mut network, 1);
+        let summary = round_summary(&mut network, 1).expect("round summary should be sent");
+        assert_eq!((summary.round, summary.total_rounds), (1, 3));
+        assert!(!summary.next_round_follows);
+        assert!(game.next_round().is_none());
+    }
+
+    #[test]
+    fn finished_game_returns_connected_players_to_the_lobby() {
+        let mut network = MockServerNetwork::new();
+        network.add_client(1);
+        network.add_client(2);
+        let usernames = HashMap::from([
+            (1, "alice".to_string()),
+            (2, "bob".to_string()),
+            (3, "carol".to_string()),
+        ]);
+        let mut game = Game::new(InitialData::new(&usernames, &HashMap::new(), 1));
+        game.host_id = Some(3);
+        assert!(game.return_to_lobby(&mut network).is_none());
+
+        game.settings.return_to_lobby = true;
+        let lobby = game
+            .return_to_lobby(&mut network)
+            .expect("connected players should return to the lobby");
+
+        for client_id in [1, 2] {
+            let player = &game.players[game.client_id_to_index[&client_id]];
+            assert_eq!(lobby.username(client_id), Some(player.name.as_str()));
+            assert_eq!(lobby.color(client_id), Some(player.color));
+            let messages: Vec<ServerMessage> = network
+                .get_sent_messages_data(client_id)
+                .iter()
+                .map(|data| decode_from_slice(data, config()).unwrap().0)
+                .collect();
+            assert!(
+                matches!(&messages[0], ServerMessage::Welcome { username, .. } if *username == player.name),
+                "expected Welcome, got {:?}",
+                messages[0]
+            );
+            assert!(
+                matches!(&messages[1], ServerMessage::Roster { online, .. } if online.len() == 1),
+                "expected a roster of the other player, got {:?}",
+                messages[1]
+            );
+        }
+        assert_eq!(lobby.username(3), None);
+        assert!(lobby.is_host(1));
+        assert!(lobby.settings().return_to_lobby);
+    }
+
     #[test]
     fn countdown_reassigns_host_and_notifies_when_host_leaves() {
         let mut network = MockServerNetwork::new();
@@ -869,6 +2170,9 @@ mod tests {
             host_id: Some(1),
             end_time: Instant::now(),
             game_data,
+            settings: Settings::default(),
+            chat_log: None,
+            rounds: None,
         };
 
         countdown.remove_client(1, &mut network);
@@ -877,7 +2181,7 @@ mod tests {
         let messages = network.get_sent_messages_data(2);
         assert!(
             messages.iter().any(|m| matches!(
-                decode_from_slice::<ServerMessage, _>(m, standard())
+                decode_from_slice::<ServerMessage, _>(m, config())
                     .unwrap()
                     .0,
                 ServerMessage::AppointHost
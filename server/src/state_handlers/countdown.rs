@@ -8,7 +8,9 @@ use crossterm::{
 };
 
 use crate::{
+    event_log::{self, EventLog, MatchEvent},
     net::ServerNetworkHandle,
+    replay::{self, ReplayRecorder},
     state::{Countdown, Game, ServerState},
 };
 
@@ -44,6 +46,32 @@ pub fn handle(
 
         let game_data = std::mem::take(&mut state.game_data);
 
-        Some(ServerState::Game(Game::new(game_data)))
+        let replay_data = state.settings.record_replay.then(|| game_data.clone());
+        let mut game = Game::new(game_data);
+        game.friendly_fire = !state.settings.no_friendly_fire;
+        game.host_id = state.host_id;
+        game.settings = state.settings.clone();
+        game.chat_log = state.chat_log.clone();
+        game.rounds = state.rounds.take();
+        if let Some(replay_data) = replay_data {
+            game.replay = Some(ReplayRecorder::new(
+                replay::default_path(game.current_tick),
+                &replay_data,
+                game.current_tick,
+                game.friendly_fire,
+            ));
+        }
+        if state.settings.log_events {
+            let log = EventLog::new(event_log::default_path(game.current_tick));
+            println!("Logging match events to {}.", log.path().display());
+            game.event_log = Some(log);
+            for player in &game.players {
+                game.log_event(MatchEvent::Joined {
+                    player: player.name.clone(),
+                });
+            }
+        }
+
+        Some(ServerState::Game(Box::new(game)))
     }
 }
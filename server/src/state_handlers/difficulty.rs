@@ -1,19 +1,20 @@
-use std::time::{Duration, Instant};
+use std::time::Instant;
 
-use bincode::{
-    config::standard,
-    serde::{decode_from_slice, encode_to_vec},
-};
+use bincode::serde::{decode_from_slice, encode_to_vec};
 
 use crate::{
-    net::ServerNetworkHandle,
-    state::{ChoosingDifficulty, Countdown, ServerState},
+    bot,
+    chat::{log_chat, send_chat_cooldown},
+    net::{ServerNetworkHandle, disconnect_with_reason, is_oversized},
+    state::{COUNTDOWN_DURATION, ChoosingDifficulty, Countdown, ServerState},
+    state_handlers::lobby::{handle_set_color, send_pong},
 };
 use common::{
     self,
-    chat::MAX_CHAT_MESSAGE_BYTES,
     net::AppChannel,
-    protocol::{ClientMessage, GAME_ALREADY_STARTED_MESSAGE, ServerMessage},
+    protocol::{
+        ClientMessage, DisconnectReason, GAME_ALREADY_STARTED_MESSAGE, ServerMessage, config,
+    },
     snapshot::InitialData,
 };
 
@@ -30,13 +31,16 @@ pub fn handle(
     for client_id in network.clients_id() {
         while let Some(data) = network.receive_message(client_id, AppChannel::ReliableOrdered) {
             *last_activity = Instant::now();
+            if is_oversized(client_id, &data) {
+                continue;
+            }
 
-            let Ok((message, _)) = decode_from_slice::<ClientMessage, _>(&data, standard()) else {
+            let Ok((message, _)) = decode_from_slice::<ClientMessage, _>(&data, config()) else {
                 eprintln!(
                     "client {} sent malformed data; disconnecting them",
                     client_id
                 );
-                network.disconnect(client_id);
+                disconnect_with_reason(network, client_id, DisconnectReason::ProtocolError);
                 continue;
             };
 
@@ -52,16 +56,33 @@ pub fn handle(
                         let msg = ServerMessage::ServerInfo {
                             message: "Invalid choice. Please press 0 through 9.".to_string(),
                         };
-                        let payload = encode_to_vec(&msg, standard()).expect("failed to serialize");
+                        let payload = encode_to_vec(&msg, config()).expect("failed to serialize");
                         network.send_message(host_id, AppChannel::ReliableOrdered, payload);
                         return None;
                     }
 
                     println!("Host selected difficulty {}.", level);
                     state.set_difficulty(level);
+                }
+                ClientMessage::ConfirmDifficulty => {
+                    if client_id != host_id {
+                        eprintln!("non-host {} tried to confirm difficulty", client_id);
+                        continue;
+                    }
 
-                    let game_data =
-                        InitialData::new(&state.lobby.usernames, state.lobby.colors(), level);
+                    let level = state.difficulty;
+                    println!("Host confirmed difficulty {}.", level);
+
+                    state.lobby.balance_teams();
+                    let mut usernames = state.lobby.usernames.clone();
+                    bot::add_bots(&mut usernames, state.lobby.settings().bots);
+                    let mut game_data = InitialData::with_options(
+                        &usernames,
+                        state.lobby.colors(),
+                        level,
+                        state.lobby.map_options(),
+                    );
+                    game_data.assign_teams(state.lobby.teams());
 
                     println!("\n{}", game_data.maze);
                     println!();
@@ -69,8 +90,7 @@ pub fn handle(
                         println!("{:#?}\n", player);
                     }
 
-                    let countdown_duration = Duration::from_secs(11);
-                    let end_time_instant = Instant::now() + countdown_duration;
+                    let end_time_instant = Instant::now() + COUNTDOWN_DURATION;
 
                     return Some(ServerState::Countdown(Countdown::new(
                         state,
@@ -79,7 +99,7 @@ pub fn handle(
                     )));
                 }
                 ClientMessage::SendChat(content) => {
-                    if let Some(username) = state.username(client_id) {
+                    if let Some(username) = state.username(client_id).map(str::to_string) {
                         let clean_content: String =
                             content.chars().filter(|c| !c.is_control()).collect();
 
@@ -87,21 +107,33 @@ pub fn handle(
                         if trimmed_content.is_empty() {
                             continue;
                         }
-                        if trimmed_content.len() > MAX_CHAT_MESSAGE_BYTES {
+                        if trimmed_content.len() > state.lobby.max_chat_bytes() {
+                            continue;
+                        }
+                        let now = Instant::now();
+                        if state
+                            .lobby
+                            .is_duplicate_chat(client_id, trimmed_content, now)
+                        {
+                            continue;
+                        }
+                        if let Some(remaining) = state.lobby.chat_cooldown(client_id, now) {
+                            send_chat_cooldown(network, client_id, remaining);
                             continue;
                         }
 
                         println!("{}: {}", username, trimmed_content);
+                        log_chat(state.lobby.chat_log.as_ref(), &username, trimmed_content);
                         let color = state
                             .lobby
                             .color(client_id)
                             .expect("missing player color for chat");
                         let message = ServerMessage::ChatMessage {
-                            username: username.to_string(),
+                            username,
                             color,
                             content: trimmed_content.to_string(),
                         };
-                        let payload = encode_to_vec(&message, standard())
+                        let payload = encode_to_vec(&message, config())
                             .expect("failed to serialize ChatMessage");
                         network.broadcast_message(AppChannel::ReliableOrdered, payload);
                     }
@@ -111,7 +143,7 @@ pub fn handle(
                         message: GAME_ALREADY_STARTED_MESSAGE.to_string(),
                     };
                     let payload =
-                        encode_to_vec(&msg, standard()).expect("failed to serialize ServerInfo");
+                        encode_to_vec(&msg, config()).expect("failed to serialize ServerInfo");
                     network.send_message(client_id, AppChannel::ReliableOrdered, payload);
                 }
                 ClientMessage::RequestStartGame => {
@@ -120,6 +152,9 @@ pub fn handle(
                         client_id
                     );
                 }
+                ClientMessage::SetColor(color) => {
+                    handle_set_color(network, &mut state.lobby, client_id, color);
+                }
                 ClientMessage::EnterAfterGameChat => {
                     eprintln!(
                         "client {} sent EnterAfterGameChat while choosing difficulty; ignoring",
@@ -129,6 +164,25 @@ pub fn handle(
                 ClientMessage::Input(_) => {
                     eprintln!("client {} sent in-game input; ignoring", client_id);
                 }
+                ClientMessage::TogglePause => {
+                    eprintln!(
+                        "client {} tried to pause while choosing difficulty; ignoring",
+                        client_id
+                    );
+                }
+                ClientMessage::JoinRoom(_) => {
+                    eprintln!(
+                        "client {} tried to join a room while choosing difficulty; ignoring",
+                        client_id
+                    );
+                }
+                ClientMessage::ListRooms => {
+                    eprintln!(
+                        "client {} asked for the room list while choosing difficulty; ignoring",
+                        client_id
+                    );
+                }
+                ClientMessage::Ping(timestamp) => send_pong(network, client_id, timestamp),
             }
         }
     }
@@ -138,14 +192,20 @@ pub fn handle(
 
 #[cfg(test)]
 mod tests {
-    use bincode::{config::standard, serde::decode_from_slice, serde::encode_to_vec};
+    use std::collections::HashMap;
+
+    use bincode::{serde::decode_from_slice, serde::encode_to_vec};
 
     use super::*;
     use crate::{
+        settings::Settings,
         state::{ChoosingDifficulty, Lobby},
         test_helpers::MockServerNetwork,
     };
-    use common::protocol::{ClientMessage, ServerMessage};
+    use common::{
+        player::LOW_GRAVITY,
+        protocol::{ClientMessage, ServerMessage},
+    };
 
     #[test]
     fn test_handle_choosing_difficulty_chat_sanitization() {
@@ -171,7 +231,7 @@ mod tests {
         let expected_content = "HiHost![2J";
 
         let msg = ClientMessage::SendChat(malicious_content.to_string());
-        let payload = encode_to_vec(&msg, standard()).unwrap();
+        let payload = encode_to_vec(&msg, config()).unwrap();
         network.queue_raw_message(user_id, payload);
 
         let mut last_activity = Instant::now();
@@ -181,7 +241,7 @@ mod tests {
 
         let broadcasts = network.get_broadcast_messages_data();
         assert_eq!(broadcasts.len(), 1);
-        let (msg, _) = decode_from_slice::<ServerMessage, _>(&broadcasts[0], standard()).unwrap();
+        let (msg, _) = decode_from_slice::<ServerMessage, _>(&broadcasts[0], config()).unwrap();
 
         let user_color = choosing_state
             .lobby
@@ -203,4 +263,126 @@ mod tests {
             panic!("expected ChatMessage, got {:?}", msg);
         }
     }
+
+    fn choosing_state_with_guest(network: &mut MockServerNetwork) -> ChoosingDifficulty {
+        let mut lobby_state = Lobby::new();
+        for (client_id, username) in [(1, "Host"), (2, "Guest")] {
+            network.add_client(client_id);
+            lobby_state.register_connection(client_id);
+            lobby_state.mark_authenticated(client_id);
+            lobby_state.register_username(client_id, username);
+        }
+        let mut choosing_state = ChoosingDifficulty::new(&lobby_state);
+        choosing_state.host_id = Some(1);
+        choosing_state
+    }
+
+    fn send(network: &mut MockServerNetwork, client_id: u64, message: ClientMessage) {
+        let payload = encode_to_vec(&message, config()).unwrap();
+        network.queue_raw_message(client_id, payload);
+    }
+
+    #[test]
+    fn host_can_change_difficulty_until_confirming() {
+        let mut network = MockServerNetwork::new();
+        let mut choosing_state = choosing_state_with_guest(&mut network);
+        let mut last_activity = Instant::now();
+
+        send(&mut network, 1, ClientMessage::SetDifficulty(4));
+        send(&mut network, 1, ClientMessage::SetDifficulty(7));
+        let next_state = handle(&mut network, &mut choosing_state, &mut last_activity);
+        assert!(next_state.is_none());
+        assert_eq!(choosing_state.difficulty, 7);
+
+        send(&mut network, 1, ClientMessage::SetDifficulty(2));
+        let next_state = handle(&mut network, &mut choosing_state, &mut last_activity);
+        assert!(next_state.is_none());
+        assert_eq!(choosing_state.difficulty, 2);
+
+        send(&mut network, 1, ClientMessage::ConfirmDifficulty);
+        let next_state = handle(&mut network, &mut choosing_state, &mut last_activity);
+        match next_state {
+            Some(ServerState::Countdown(countdown)) => {
+                assert_eq!(countdown.game_data.difficulty, 2);
+            }
+            _ => panic!("expected countdown after confirming difficulty"),
+        }
+    }
+
+    #[test]
+    fn only_the_host_can_change_or_confirm_difficulty() {
+        let mut network = MockServerNetwork::new();
+        let mut choosing_state = choosing_state_with_guest(&mut network);
+        let mut last_activity = Instant::now();
+
+        send(&mut network, 2, ClientMessage::SetDifficulty(9));
+        send(&mut network, 2, ClientMessage::ConfirmDifficulty);
+        let next_state = handle(&mut network, &mut choosing_state, &mut last_activity);
+
+        assert!(next_state.is_none());
+        assert_eq!(choosing_state.difficulty, 1);
+    }
+
+    #[test]
+    fn low_gravity_applies_to_players_and_bullets_alike() {
+        let usernames = HashMap::from([(1, "Host".to_string())]);
+        let options = Settings {
+            low_gravity: true,
+            ..Settings::default()
+        }
+        .map_options();
+
+        let data = InitialData::with_options(&usernames, &HashMap::new(), 1, options);
+
+        assert_eq!(data.movement_profile.gravity, LOW_GRAVITY);
+        assert_eq!(data.bullet_tuning.gravity, LOW_GRAVITY);
+        assert_eq!(
+            InitialData::new(&usernames, &HashMap::new(), 1)
+                .bullet_tuning
+                .gravity,
+            0.0
+        );
+    }
+
+    fn seeded_countdown(seed: u64) -> Countdown {
+        let mut network = MockServerNetwork::new();
+        let mut lobby_state = Lobby::with_settings(Settings {
+            seed: Some(seed),
+            ..Settings::default()
+        });
+        for (client_id, username) in [(1, "Host"), (2, "Guest"), (3, "Latecomer")] {
+            network.add_client(client_id);
+            lobby_state.register_connection(client_id);
+            lobby_state.mark_authenticated(client_id);
+            lobby_state.register_username(client_id, username);
+        }
+        let mut choosing_state = ChoosingDifficulty::new(&lobby_state);
+        choosing_state.host_id = Some(1);
+
+        send(&mut network, 1, ClientMessage::SetDifficulty(5));
+        send(&mut network, 1, ClientMessage::ConfirmDifficulty);
+        match handle(&mut network, &mut choosing_state, &mut Instant::now()) {
+            Some(ServerState::Countdown(countdown)) => countdown,
+            _ => panic!("expected countdown after confirming difficulty"),
+        }
+    }
+
+    #[test]
+    fn same_seed_reproduces_maze_spawns_and_colors() {
+        let first = seeded_countdown(42).game_data;
+        let second = seeded_countdown(42).game_data;
+
+        assert_eq!(first.maze.grid, second.maze.grid);
+        assert_eq!(first.maze.teleporters, second.maze.teleporters);
+        let players = |data: &InitialData| {
+            data.players
+                .iter()
+                .map(|player| (player.client_id, player.state.position, player.color))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(players(&first), players(&second));
+
+        let other = seeded_countdown(43).game_data;
+        assert_ne!(first.maze.grid, other.maze.grid);
+    }
 }
@@ -1,34 +1,145 @@
-use bincode::{
-    config::standard,
-    serde::{decode_from_slice, encode_to_vec},
-};
+use std::collections::HashMap;
+
+use bincode::serde::{decode_from_slice, encode_to_vec};
 use glam::Vec3;
 
 use crate::{
+    awards::HitRecord,
+    bot::Target,
+    chat::log_chat,
+    event_log::MatchEvent,
     input,
-    net::ServerNetworkHandle,
-    player::Status,
-    state::{Game, ServerState},
+    net::{SendPriority, ServerNetworkHandle, disconnect_with_reason, is_oversized},
+    player::{ServerPlayer, Status, max_step_per_tick},
+    state::{Game, Lobby, ServerState},
 };
 use common::{
-    bullets::{self, Bullet, check_player_collision, update_bullet_position},
+    bullets::{self, Ammo, Bullet, Magazine, check_player_collision, update_bullet_position},
     chat::MAX_CHAT_MESSAGE_BYTES,
-    constants::TICKS_PER_BROADCAST,
     input::sanitize,
+    maze::CELL_SIZE,
     net::AppChannel,
-    protocol::{BulletEvent, ClientMessage, ServerMessage},
+    pickups::{self, PickupKind},
+    player::PlayerInput,
+    protocol::{BulletEvent, ClientMessage, DisconnectReason, PickupEvent, ServerMessage, config},
     ring::WireItem,
     snapshot::Snapshot,
     time,
 };
 
+const FIRE_VIOLATION_LOG_INTERVAL: u32 = 10;
+
 // TODO: Consider if any of this logic belongs with the `Game` struct in `server/src/state.rs`.
 pub fn handle(network: &mut dyn ServerNetworkHandle, state: &mut Game) -> Option<ServerState> {
+    if state.is_empty() {
+        state.finish_replay();
+        let mut lobby = Lobby::with_settings(state.settings.clone());
+        lobby.chat_log = state.chat_log.clone();
+        return Some(ServerState::Lobby(lobby));
+    }
+
     handle_reliable_messages(network, state);
     input::receive_inputs(network, state);
 
+    if state.is_paused() {
+        state.hold_paused_tick();
+        broadcast_snapshots(network, state);
+        state.current_tick += 1;
+        return None;
+    }
+
     check_timer_expiration(network, state);
 
+    let alive: Vec<(usize, Target)> = state
+        .players
+        .iter()
+        .filter(|player| matches!(player.status, Status::Alive))
+        .map(|player| {
+            let target = Target {
+                position: player.state.position,
+                velocity: player.state.velocity,
+            };
+            (player.index, target)
+        })
+        .collect();
+    let mut returned = Vec::new();
+    for player in &mut state.players {
+        if !matches!(player.status, Status::Alive) {
+            player.last_input = PlayerInput::default();
+            player.input_buffer.advance_tail(state.current_tick);
+            continue;
+        }
+        if let Some(bot) = &mut player.bot {
+            let targets: Vec<Target> = alive
+                .iter()
+                .filter(|&&(index, _)| index != player.index)
+                .map(|&(_, target)| target)
+                .collect();
+            player.last_input =
+                bot.next_input(&state.maze, &player.state, &targets, state.current_tick);
+            player.last_active_tick = state.current_tick;
+        } else if let Some(&input) = player.input_buffer.get(state.current_tick) {
+            player.last_input = input;
+            if !input.is_idle() {
+                player.last_active_tick = state.current_tick;
+                if player.afk {
+                    returned.push(player.index);
+                }
+            }
+        }
+        player.input_buffer.advance_tail(state.current_tick);
+    }
+    for index in returned {
+        state.set_afk(network, index, false);
+    }
+    state.handle_afk_players(network);
+    state.record_replay_tick();
+
+    let (bullet_events, pickup_events, kills) = step_simulation(state);
+
+    for event in bullet_events {
+        log_bullet_event(state, &event);
+        state.broadcast_to_players_with_priority(
+            network,
+            &ServerMessage::BulletEvent(event),
+            SendPriority::High,
+        );
+    }
+
+    for event in pickup_events {
+        state.broadcast_to_players(network, &ServerMessage::PickupEvent(event));
+    }
+
+    for (victim, killer) in kills {
+        broadcast_kill(network, state, victim, killer);
+    }
+
+    check_multiplayer_winner(network, state);
+    state.broadcast_spectator_count_if_changed(network);
+
+    broadcast_snapshots(network, state);
+
+    state.current_tick += 1;
+    // // Uncomment to log ingress and egress rates.
+    // state.net_stats.log_if_ready();
+
+    if state.leaderboard_sent {
+        state.finish_replay();
+        if let Some(countdown) = state.next_round() {
+            Some(ServerState::Countdown(countdown))
+        } else if let Some(lobby) = state.return_to_lobby(network) {
+            Some(ServerState::Lobby(lobby))
+        } else {
+            Some(ServerState::Ending)
+        }
+    } else {
+        None
+    }
+}
+
+pub(crate) fn step_simulation(
+    state: &mut Game,
+) -> (Vec<BulletEvent>, Vec<PickupEvent>, Vec<(usize, usize)>) {
     let player_positions: Vec<(usize, Vec3)> = state
         .players
         .iter()
@@ -37,21 +148,25 @@ pub fn handle(network: &mut dyn ServerNetworkHandle, state: &mut Game) -> Option
         .map(|(i, p)| (i, p.state.position))
         .collect();
 
+    let max_step = max_step_per_tick(&state.movement_profile);
     for player in &mut state.players {
-        if let Some(&input) = player.input_buffer.get(state.current_tick) {
-            player.last_input = input;
-        }
-
-        let input = player.last_input;
-
         if matches!(player.status, crate::player::Status::Alive) {
-            player
-                .state
-                .update(&state.maze, &input, player.index, &player_positions, 1.0);
+            let input = player.last_input;
+            let previous = player.state.position;
+            player.state.update(
+                &state.maze,
+                &input,
+                &state.movement_profile,
+                player.index,
+                &player_positions,
+                1.0,
+            );
+            player.check_step(&state.maze, previous, max_step);
         }
-        player.input_buffer.advance_tail(state.current_tick);
     }
 
+    let pickup_events = update_pickups(state);
+
     let mut bullet_events = Vec::new();
 
     for (player_index, player) in state.players.iter_mut().enumerate() {
@@ -74,7 +189,28 @@ pub fn handle(network: &mut dyn ServerNetworkHandle, state: &mut Game) -> Option
             .map(|tick| state.current_tick.saturating_sub(tick) >= cooldown_ticks)
             .unwrap_or(true);
 
-        if !can_fire || player.bullets_in_air >= bullets::MAX_BULLETS_PER_PLAYER {
+        if !can_fire {
+            player.fire_cooldown_violations = player.fire_cooldown_violations.saturating_add(1);
+            if player
+                .fire_cooldown_violations
+                .is_multiple_of(FIRE_VIOLATION_LOG_INTERVAL)
+            {
+                eprintln!(
+                    "client {} ({}) has fired within the cooldown {} times",
+                    player.client_id, player.name, player.fire_cooldown_violations
+                );
+            }
+            continue;
+        }
+
+        if player.bullets_in_air >= bullets::MAX_BULLETS_PER_PLAYER {
+            continue;
+        }
+
+        let magazine = state.bullet_tuning.magazine;
+        if let (Some(magazine), Some(ammo)) = (&magazine, &mut player.ammo)
+            && !ammo.ready(magazine, state.current_tick)
+        {
             continue;
         }
 
@@ -83,8 +219,9 @@ pub fn handle(network: &mut dyn ServerNetworkHandle, state: &mut Game) -> Option
             continue;
         }
 
-        let position = bullets::spawn_position(player.state.position, direction);
-        let velocity = direction * bullets::SPEED;
+        let position =
+            bullets::spawn_position(player.state.position, direction, &state.bullet_tuning.radii);
+        let velocity = bullets::spawn_velocity(direction, &state.bullet_tuning);
         let bullet_id = state.next_bullet_id;
         state.next_bullet_id = state.next_bullet_id.wrapping_add(1);
 
@@ -96,7 +233,11 @@ pub fn handle(network: &mut dyn ServerNetworkHandle, state: &mut Game) -> Option
             state.current_tick,
         ));
         player.last_fire_tick = Some(state.current_tick);
+        if let (Some(magazine), Some(ammo)) = (&magazine, &mut player.ammo) {
+            ammo.spend(magazine, state.current_tick);
+        }
         player.bullets_in_air += 1;
+        player.shots_fired += 1;
 
         bullet_events.push(BulletEvent::Spawn {
             bullet_id,
@@ -108,56 +249,99 @@ pub fn handle(network: &mut dyn ServerNetworkHandle, state: &mut Game) -> Option
         });
     }
 
-    update_bullets(state, &mut bullet_events);
+    let mut kills = Vec::new();
+    update_bullets(state, &mut bullet_events, &mut kills);
+    if state.settings.bullet_collisions {
+        collide_bullets(state, &mut bullet_events);
+    }
 
-    if !bullet_events.is_empty() {
-        for event in bullet_events {
-            let message = ServerMessage::BulletEvent(event);
-            let payload =
-                encode_to_vec(&message, standard()).expect("failed to serialize bullet event");
-            let payload_len = payload.len();
-            let recipients: Vec<u64> = state
-                .client_id_to_index
-                .keys()
-                .copied()
-                .filter(|client_id| !state.after_game_chat_clients.contains(client_id))
-                .collect();
-            let recipients_count = recipients.len();
-            for client_id in recipients {
-                network.send_message(client_id, AppChannel::ReliableOrdered, payload.clone());
+    (bullet_events, pickup_events, kills)
+}
+
+fn update_pickups(state: &mut Game) -> Vec<PickupEvent> {
+    let mut events = Vec::new();
+
+    let current_tick = state.current_tick;
+    let (due, waiting) = state
+        .pickup_respawns
+        .drain(..)
+        .partition(|&(tick, _)| tick <= current_tick);
+    state.pickup_respawns = waiting;
+    for (_, pickup) in due {
+        state.pickups.push(pickup);
+        events.push(PickupEvent::Spawned(pickup));
+    }
+
+    let magazine = state.bullet_tuning.magazine;
+    let mut remaining = Vec::with_capacity(state.pickups.len());
+    for pickup in state.pickups.drain(..) {
+        let collector = state.players.iter_mut().find(|player| {
+            matches!(player.status, Status::Alive)
+                && pickup.is_touching(player.state.position)
+                && wants_pickup(player, pickup.kind, magazine.as_ref())
+        });
+        match collector {
+            Some(player) => {
+                match pickup.kind {
+                    PickupKind::Health => {
+                        player.health =
+                            pickups::restored_health(player.health).unwrap_or(player.health)
+                    }
+                    PickupKind::Ammo => player.ammo = magazine.as_ref().map(Ammo::full),
+                }
+                events.push(PickupEvent::Collected {
+                    pickup_id: pickup.id,
+                    player_index: player.index,
+                });
+                state
+                    .pickup_respawns
+                    .push((current_tick + pickups::respawn_ticks(), pickup));
             }
-            state.note_egress_bytes(payload_len.saturating_mul(recipients_count));
+            None => remaining.push(pickup),
         }
     }
+    state.pickups = remaining;
 
-    check_multiplayer_winner(network, state);
+    events
+}
 
-    // Only send snapshots every third tick. Send to all clients until the leaderboard is sent.
-    if state.current_tick % TICKS_PER_BROADCAST == 0 {
-        let mut egress_bytes = 0usize;
-        for (&client_id, &player_index) in &state.client_id_to_index {
-            let snapshot = state.snapshot_for(player_index);
-            let message = ServerMessage::Snapshot(WireItem::<Snapshot> {
-                id: state.current_tick as u16,
-                data: snapshot,
-            });
-            let payload =
-                encode_to_vec(&message, standard()).expect("failed to serialize ServerTime");
-            egress_bytes = egress_bytes.saturating_add(payload.len());
-            network.send_message(client_id, AppChannel::Unreliable, payload);
-        }
-        state.note_egress_bytes(egress_bytes);
+fn wants_pickup(player: &ServerPlayer, kind: PickupKind, magazine: Option<&Magazine>) -> bool {
+    match kind {
+        PickupKind::Health => pickups::restored_health(player.health).is_some(),
+        PickupKind::Ammo => match (magazine, player.ammo) {
+            (Some(magazine), Some(ammo)) => ammo != Ammo::full(magazine),
+            _ => false,
+        },
     }
+}
 
-    state.current_tick += 1;
-    // // Uncomment to log ingress and egress rates.
-    // state.net_stats.log_if_ready();
+// Only send snapshots at the snapshot rate. Send to all clients until the leaderboard is sent.
+fn broadcast_snapshots(network: &mut dyn ServerNetworkHandle, state: &mut Game) {
+    if !should_broadcast(state.current_tick, state.settings.ticks_per_snapshot()) {
+        return;
+    }
 
-    if state.leaderboard_sent {
-        Some(ServerState::Ending)
-    } else {
-        None
+    let mut egress_bytes = 0usize;
+    for (&client_id, &player_index) in &state.client_id_to_index {
+        let snapshot = state.snapshot_for(player_index);
+        let message = ServerMessage::Snapshot(WireItem::<Snapshot> {
+            id: state.current_tick as u16,
+            data: snapshot,
+        });
+        let payload = encode_to_vec(&message, config()).expect("failed to serialize ServerTime");
+        egress_bytes = egress_bytes.saturating_add(payload.len());
+        network.send_message_with_priority(
+            client_id,
+            AppChannel::Unreliable,
+            payload,
+            SendPriority::Low,
+        );
     }
+    state.note_egress_bytes(egress_bytes);
+}
+
+fn should_broadcast(tick: u64, ticks_per_snapshot: u64) -> bool {
+    tick.is_multiple_of(ticks_per_snapshot.max(1))
 }
 
 fn handle_reliable_messages(network: &mut dyn ServerNetworkHandle, state: &mut Game) {
@@ -165,12 +349,15 @@ fn handle_reliable_messages(network: &mut dyn ServerNetworkHandle, state: &mut G
         let mut ingress_bytes = 0usize;
         while let Some(data) = network.receive_message(client_id, AppChannel::ReliableOrdered) {
             ingress_bytes = ingress_bytes.saturating_add(data.len());
-            let Ok((message, _)) = decode_from_slice::<ClientMessage, _>(&data, standard()) else {
+            if is_oversized(client_id, &data) {
+                continue;
+            }
+            let Ok((message, _)) = decode_from_slice::<ClientMessage, _>(&data, config()) else {
                 eprintln!(
                     "client {} sent malformed data during game; disconnecting them",
                     client_id
                 );
-                network.disconnect(client_id);
+                disconnect_with_reason(network, client_id, DisconnectReason::ProtocolError);
                 continue;
             };
 
@@ -201,6 +388,7 @@ fn handle_reliable_messages(network: &mut dyn ServerNetworkHandle, state: &mut G
                         .map(|player| common::protocol::PlayerRosterEntry {
                             username: player.name.clone(),
                             color: player.color,
+                            team: player.team,
                         })
                         .collect::<Vec<_>>();
 
@@ -208,15 +396,15 @@ fn handle_reliable_messages(network: &mut dyn ServerNetworkHandle, state: &mut G
                         hades_shades: online,
                     };
                     let payload =
-                        encode_to_vec(&message, standard()).expect("failed to serialize Roster");
+                        encode_to_vec(&message, config()).expect("failed to serialize Roster");
                     state.note_egress_bytes(payload.len());
                     network.send_message(client_id, AppChannel::ReliableOrdered, payload);
 
                     let message = ServerMessage::UserJoined {
                         username: state.players[player_index].name.clone(),
                     };
-                    let payload = encode_to_vec(&message, standard())
-                        .expect("failed to serialize UserJoined");
+                    let payload =
+                        encode_to_vec(&message, config()).expect("failed to serialize UserJoined");
                     let payload_len = payload.len();
                     let mut egress_bytes = 0usize;
 
@@ -254,7 +442,12 @@ fn handle_reliable_messages(network: &mut dyn ServerNetworkHandle, state: &mut G
                     if trimmed_content.is_empty() {
                         continue;
                     }
-                    if trimmed_content.len() > MAX_CHAT_MESSAGE_BYTES {
+                    let max_chat_bytes = state
+                        .settings
+                        .max_chat_bytes
+                        .unwrap_or(MAX_CHAT_MESSAGE_BYTES)
+                        .min(MAX_CHAT_MESSAGE_BYTES);
+                    if trimmed_content.len() > max_chat_bytes {
                         println!(
                             "Client {} sent an overly long chat message; ignoring.",
                             client_id
@@ -263,13 +456,18 @@ fn handle_reliable_messages(network: &mut dyn ServerNetworkHandle, state: &mut G
                     }
 
                     println!("{}: {}", state.players[player_index].name, trimmed_content);
+                    log_chat(
+                        state.chat_log.as_ref(),
+                        &state.players[player_index].name,
+                        trimmed_content,
+                    );
                     let message = ServerMessage::ChatMessage {
                         username: state.players[player_index].name.clone(),
                         color: state.players[player_index].color,
                         content: trimmed_content.to_string(),
                     };
-                    let payload = encode_to_vec(&message, standard())
-                        .expect("failed to serialize ChatMessage");
+                    let payload =
+                        encode_to_vec(&message, config()).expect("failed to serialize ChatMessage");
                     let mut egress_bytes = 0usize;
                     for other_id in &state.after_game_chat_clients {
                         egress_bytes = egress_bytes.saturating_add(payload.len());
@@ -281,6 +479,19 @@ fn handle_reliable_messages(network: &mut dyn ServerNetworkHandle, state: &mut G
                     }
                     state.note_egress_bytes(egress_bytes);
                 }
+                ClientMessage::TogglePause => {
+                    if state.host_id != Some(client_id) {
+                        eprintln!(
+                            "client {} tried to pause but is not host; ignoring",
+                            client_id
+                        );
+                        continue;
+                    }
+                    if state.leaderboard_sent {
+                        continue;
+                    }
+                    state.toggle_pause(network);
+                }
                 _ => {}
             }
         }
@@ -288,17 +499,53 @@ fn handle_reliable_messages(network: &mut dyn ServerNetworkHandle, state: &mut G
     }
 }
 
-fn update_bullets(state: &mut Game, events: &mut Vec<BulletEvent>) {
+fn broadcast_kill(
+    network: &mut dyn ServerNetworkHandle,
+    state: &mut Game,
+    victim: usize,
+    killer: usize,
+) {
+    state.log_event(MatchEvent::Eliminated {
+        victim: state.players[victim].name.clone(),
+        killer: state.players[killer].name.clone(),
+    });
+    if victim == killer {
+        println!(
+            "{} was shot by their own bullet.",
+            state.players[victim].name
+        );
+    } else {
+        println!(
+            "{} shot {}.",
+            state.players[killer].name, state.players[victim].name
+        );
+    }
+
+    state.broadcast_to_players(network, &ServerMessage::PlayerKilled { victim, killer });
+}
+
+fn update_bullets(
+    state: &mut Game,
+    events: &mut Vec<BulletEvent>,
+    kills: &mut Vec<(usize, usize)>,
+) {
     let mut index = 0;
     while index < state.bullets.len() {
         let mut remove = false;
-        let mut hit_inanimate = false;
+        let mut impact = None;
         let mut hit_player_event = None;
+        let mut kill = None;
+        let mut hit = None;
 
         {
             let bullet = &mut state.bullets[index];
 
-            let update_result = update_bullet_position(bullet, &state.maze, state.current_tick);
+            let update_result = update_bullet_position(
+                bullet,
+                &state.maze,
+                state.current_tick,
+                &state.bullet_tuning,
+            );
 
             if update_result.should_remove {
                 events.push(BulletEvent::Expire {
@@ -309,23 +556,39 @@ fn update_bullets(state: &mut Game, events: &mut Vec<BulletEvent>) {
                 });
                 remove = true;
             } else {
-                hit_inanimate = update_result.hit_inanimate;
+                impact = update_result.impact;
+                let shooter = state.players.get(bullet.shooter_index);
+                let shooter_team = shooter.and_then(|shooter| shooter.team);
+                let shooter_position = shooter.map(|shooter| shooter.state.position);
 
                 for (player_index, player) in state.players.iter_mut().enumerate() {
                     if !matches!(player.status, crate::player::Status::Alive) {
                         continue;
                     }
 
-                    let collision_result =
-                        check_player_collision(bullet, player.state.position, player.health);
+                    let collision_result = check_player_collision(
+                        bullet,
+                        player.state.position,
+                        player.health,
+                        shooter_team,
+                        player.team,
+                        state.friendly_fire,
+                        &state.bullet_tuning.radii,
+                    );
 
                     if collision_result.hit_player {
+                        let damage = player.health.saturating_sub(collision_result.new_health);
+                        let distance = shooter_position
+                            .map(|position| position.distance(player.state.position))
+                            .unwrap_or_default();
+                        hit = Some((player_index, damage, distance, bullet.bounces));
                         player.health = collision_result.new_health;
                         if collision_result.new_health == 0 {
                             player.status = crate::player::Status::Dead;
                             if player.exit_tick.is_none() {
                                 player.exit_tick = Some(state.current_tick);
                             }
+                            kill = Some((player_index, bullet.shooter_index));
                         }
 
                         if collision_result.should_remove_bullet {
@@ -346,15 +609,48 @@ fn update_bullets(state: &mut Game, events: &mut Vec<BulletEvent>) {
             }
         }
 
+        if let Some((victim, damage, distance, bounces)) = hit {
+            let shooter_index = state.bullets[index].shooter_index;
+            if victim != shooter_index
+                && let Some(shooter) = state.players.get_mut(shooter_index)
+            {
+                shooter.hits_landed += 1;
+                shooter.damage_dealt += u32::from(damage);
+                state.hit_records.push(HitRecord {
+                    shooter: shooter_index,
+                    victim,
+                    distance,
+                    bounces,
+                    lethal: kill.is_some(),
+                });
+            }
+        }
+
+        if let Some((victim, killer)) = kill {
+            if let Some(dead) = state.players.get_mut(victim) {
+                dead.deaths += 1;
+                if victim == killer {
+                    dead.self_kills += 1;
+                }
+            }
+            if victim != killer
+                && let Some(shooter) = state.players.get_mut(killer)
+            {
+                shooter.kills += 1;
+            }
+            kills.push((victim, killer));
+        }
+
         if let Some(event) = hit_player_event {
             events.push(event);
-        } else if hit_inanimate {
+        } else if let Some(surface) = impact {
             let bullet = &state.bullets[index];
             events.push(BulletEvent::HitInanimate {
                 bullet_id: bullet.id,
                 tick: state.current_tick,
                 position: bullet.position,
                 velocity: bullet.velocity,
+                surface,
             });
         }
 
@@ -370,6 +666,72 @@ fn update_bullets(state: &mut Game, events: &mut Vec<BulletEvent>) {
     }
 }
 
+fn collide_bullets(state: &mut Game, events: &mut Vec<BulletEvent>) {
+    let cell_of = |position: Vec3| {
+        (
+            (position.x / CELL_SIZE).floor() as i32,
+            (position.z / CELL_SIZE).floor() as i32,
+        )
+    };
+
+    let mut buckets: HashMap<(i32, i32), Vec<usize>> = HashMap::new();
+    for (index, bullet) in state.bullets.iter().enumerate() {
+        buckets
+            .entry(cell_of(bullet.position))
+            .or_default()
+            .push(index);
+    }
+
+    let mut partners: Vec<Option<usize>> = vec![None; state.bullets.len()];
+    for (index, bullet) in state.bullets.iter().enumerate() {
+        if partners[index].is_some() {
+            continue;
+        }
+        let (x, z) = cell_of(bullet.position);
+        let neighbours = (x - 1..=x + 1)
+            .flat_map(|nx| (z - 1..=z + 1).map(move |nz| (nx, nz)))
+            .filter_map(|cell| buckets.get(&cell))
+            .flatten();
+        for &other in neighbours {
+            if other > index
+                && partners[other].is_none()
+                && bullets::is_bullet_colliding_with_bullet(
+                    bullet.position,
+                    state.bullets[other].position,
+                    &state.bullet_tuning.radii,
+                )
+            {
+                partners[index] = Some(other);
+                partners[other] = Some(index);
+                break;
+            }
+        }
+    }
+
+    for (index, partner) in partners.iter().enumerate() {
+        if let Some(other) = *partner {
+            let bullet = &state.bullets[index];
+            events.push(BulletEvent::HitBullet {
+                bullet_id: bullet.id,
+                other_bullet_id: state.bullets[other].id,
+                tick: state.current_tick,
+                position: bullet.position,
+                velocity: bullet.velocity,
+            });
+        }
+    }
+
+    let mut index = 0;
+    state.bullets.retain(|bullet| {
+        let collided = partners[index].is_some();
+        index += 1;
+        if collided && let Some(shooter) = state.players.get_mut(bullet.shooter_index) {
+            shooter.bullets_in_air = shooter.bullets_in_air.saturating_sub(1);
+        }
+        !collided
+    });
+}
+
 fn check_multiplayer_winner(network: &mut dyn ServerNetworkHandle, state: &mut Game) {
     if state.is_solo_mode {
         return;
@@ -383,6 +745,19 @@ fn check_multiplayer_winner(network: &mut dyn ServerNetworkHandle, state: &mut G
         .map(|(i, _)| i)
         .collect();
 
+    if alive_players.is_empty() {
+        let is_undecided = state.winner_index.is_none()
+            && !state.draw_declared
+            && state.timer_expiration_tick.is_none();
+        if is_undecided {
+            state.draw_declared = true;
+            println!("Nobody survived; the game is a draw.");
+            state.log_event(MatchEvent::Draw);
+            state.broadcast_to_players(network, &ServerMessage::GameOver { winner: None });
+        }
+        return;
+    }
+
     if alive_players.len() != 1 {
         return;
     }
@@ -393,25 +768,35 @@ fn check_multiplayer_winner(network: &mut dyn ServerNetworkHandle, state: &mut G
     let winner = &mut state.players[winner_index];
 
     println!("Player {} wins! Last survivor in multiplayer.", winner.name);
+    let name = winner.name.clone();
+    let event = MatchEvent::Won {
+        winner: name.clone(),
+    };
 
     winner.health = 0;
     winner.status = Status::Dead;
     winner.exit_tick = Some(state.current_tick);
 
-    let message = ServerMessage::Victory { winner_index };
-    let payload = encode_to_vec(&message, standard()).expect("failed to serialize victory message");
-    let payload_len = payload.len();
-    let recipients: Vec<u64> = state
-        .client_id_to_index
-        .keys()
-        .copied()
-        .filter(|client_id| !state.after_game_chat_clients.contains(client_id))
-        .collect();
-    let recipients_count = recipients.len();
-    for client_id in recipients {
-        network.send_message(client_id, AppChannel::ReliableOrdered, payload.clone());
-    }
-    state.note_egress_bytes(payload_len.saturating_mul(recipients_count));
+    state.log_event(event);
+    state.broadcast_to_players(network, &ServerMessage::GameOver { winner: Some(name) });
+}
+
+fn log_bullet_event(state: &Game, event: &BulletEvent) {
+    let event = match *event {
+        BulletEvent::Spawn { shooter_index, .. } => MatchEvent::Shot {
+            shooter: state.players[shooter_index].name.clone(),
+        },
+        BulletEvent::HitPlayer {
+            target_index,
+            target_health,
+            ..
+        } if target_health > 0 => MatchEvent::Hit {
+            victim: state.players[target_index].name.clone(),
+            health: target_health,
+        },
+        _ => return,
+    };
+    state.log_event(event);
 }
 
 fn check_timer_expiration(network: &mut dyn ServerNetworkHandle, state: &mut Game) {
@@ -426,7 +811,8 @@ fn check_timer_expiration(network: &mut dyn ServerNetworkHandle, state: &mut Gam
 
     let current_tick = state.current_tick;
     let mut events = Vec::new();
-    let mut total_egress_bytes = 0usize;
+    let mut results = Vec::new();
+    let mut victories = Vec::new();
     let is_solo_mode = state.players.len() == 1;
 
     for player in &mut state.players {
@@ -449,25 +835,13 @@ fn check_timer_expiration(network: &mut dyn ServerNetworkHandle, state: &mut Gam
                 "Player {} escaped! Timer expired but they reached the exit.",
                 player.name
             );
+            results.push(MatchEvent::Escaped {
+                player: player.name.clone(),
+            });
 
-            let message = ServerMessage::Victory {
-                winner_index: player.index,
-            };
-            let payload =
-                encode_to_vec(&message, standard()).expect("failed to serialize victory message");
-            let payload_len = payload.len();
-            let recipients: Vec<u64> = state
-                .client_id_to_index
-                .keys()
-                .copied()
-                .filter(|client_id| !state.after_game_chat_clients.contains(client_id))
-                .collect();
-            let recipients_count = recipients.len();
-            for client_id in recipients {
-                network.send_message(client_id, AppChannel::ReliableOrdered, payload.clone());
-            }
-            total_egress_bytes =
-                total_egress_bytes.saturating_add(payload_len.saturating_mul(recipients_count));
+            victories.push(ServerMessage::GameOver {
+                winner: Some(player.name.clone()),
+            });
         } else {
             let event = BulletEvent::HitPlayer {
                 bullet_id: 0,
@@ -487,25 +861,561 @@ fn check_timer_expiration(network: &mut dyn ServerNetworkHandle, state: &mut Gam
         }
     }
 
+    if !events.is_empty() {
+        results.push(MatchEvent::TimeUp);
+    }
+    for result in results {
+        state.log_event(result);
+    }
+
+    for message in victories {
+        state.broadcast_to_players(network, &message);
+    }
     for event in events {
-        let message = ServerMessage::BulletEvent(event);
-        let payload =
-            encode_to_vec(&message, standard()).expect("failed to serialize bullet event");
-        let payload_len = payload.len();
-        let recipients: Vec<u64> = state
-            .client_id_to_index
-            .keys()
-            .copied()
-            .filter(|client_id| !state.after_game_chat_clients.contains(client_id))
+        state.broadcast_to_players_with_priority(
+            network,
+            &ServerMessage::BulletEvent(event),
+            SendPriority::High,
+        );
+    }
+
+    state.timer_expiration_tick = Some(current_tick);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{collections::HashMap, time::Duration};
+
+    use crate::{
+        settings::{AfkAction, Settings},
+        test_helpers::MockServerNetwork,
+    };
+    use common::{
+        bullets::{Ammo, Magazine},
+        constants::TICK_SECS,
+        pickups::Pickup,
+        player::MAX_HEALTH,
+        snapshot::InitialData,
+    };
+
+    fn game_with_host(network: &mut MockServerNetwork) -> Game {
+        network.add_client(1);
+        network.add_client(2);
+        let usernames = HashMap::from([(1, "Alice".to_string()), (2, "Bob".to_string())]);
+        let mut game = Game::new(InitialData::new(&usernames, &HashMap::new(), 1));
+        game.host_id = Some(1);
+        game
+    }
+
+    fn queue_toggle_pause(network: &mut MockServerNetwork, client_id: u64) {
+        let payload = encode_to_vec(&ClientMessage::TogglePause, config()).unwrap();
+        network.queue_raw_message(client_id, payload);
+    }
+
+    #[test]
+    fn snapshots_are_broadcast_at_the_configured_rate() {
+        let broadcast_ticks = |snapshot_rate| {
+            let settings = Settings {
+                snapshot_rate,
+                ..Default::default()
+            };
+            (0..60)
+                .filter(|&tick| should_broadcast(tick, settings.ticks_per_snapshot()))
+                .count()
+        };
+
+        assert_eq!(broadcast_ticks(None), 20);
+        assert_eq!(broadcast_ticks(Some(30)), 30);
+        assert_eq!(broadcast_ticks(Some(20)), 20);
+        assert_eq!(broadcast_ticks(Some(10)), 10);
+        assert_eq!(broadcast_ticks(Some(60)), 60);
+    }
+
+    #[test]
+    fn bullet_events_are_flushed_ahead_of_snapshots() {
+        let mut network = MockServerNetwork::new();
+        let mut game = game_with_host(&mut network);
+        game.settings.snapshot_rate = Some(60);
+        game.players[0].last_input = PlayerInput {
+            fire: true,
+            fire_nonce: Some(1),
+            ..Default::default()
+        };
+
+        handle(&mut network, &mut game);
+        assert!(network.get_sent_messages_data(1).is_empty());
+
+        network.flush_prioritized();
+        let messages: Vec<ServerMessage> = network
+            .get_sent_messages_data(1)
+            .iter()
+            .map(|data| decode_from_slice(data, config()).unwrap().0)
+            .collect();
+        assert!(matches!(
+            messages.first(),
+            Some(ServerMessage::BulletEvent(BulletEvent::Spawn { .. }))
+        ));
+        assert!(matches!(messages.last(), Some(ServerMessage::Snapshot(_))));
+    }
+
+    #[test]
+    fn bullets_do_not_move_while_paused() {
+        let mut network = MockServerNetwork::new();
+        let mut game = game_with_host(&mut network);
+        let position = game.players[0].state.position;
+        game.bullets.push(Bullet::new(
+            0,
+            0,
+            position,
+            Vec3::new(1.0, 0.0, 0.0),
+            game.current_tick,
+        ));
+
+        queue_toggle_pause(&mut network, 1);
+        handle(&mut network, &mut game);
+        assert!(game.is_paused());
+
+        let start_tick = game.current_tick;
+        for _ in 0..5 {
+            handle(&mut network, &mut game);
+        }
+
+        assert_eq!(game.current_tick, start_tick + 5);
+        assert_eq!(game.bullets.len(), 1);
+        assert_eq!(game.bullets[0].position, position);
+    }
+
+    #[test]
+    fn only_host_can_toggle_pause() {
+        let mut network = MockServerNetwork::new();
+        let mut game = game_with_host(&mut network);
+
+        queue_toggle_pause(&mut network, 2);
+        handle(&mut network, &mut game);
+        assert!(!game.is_paused());
+
+        queue_toggle_pause(&mut network, 1);
+        handle(&mut network, &mut game);
+        assert!(game.is_paused());
+
+        queue_toggle_pause(&mut network, 1);
+        handle(&mut network, &mut game);
+        assert!(!game.is_paused());
+
+        let paused_flags: Vec<bool> = network
+            .get_sent_messages_data(2)
+            .iter()
+            .filter_map(
+                |data| match decode_from_slice::<ServerMessage, _>(data, config()) {
+                    Ok((ServerMessage::GamePaused { paused, .. }, _)) => Some(paused),
+                    _ => None,
+                },
+            )
             .collect();
-        let recipients_count = recipients.len();
-        for client_id in recipients {
-            network.send_message(client_id, AppChannel::ReliableOrdered, payload.clone());
+        assert_eq!(paused_flags, vec![true, false]);
+    }
+
+    #[test]
+    fn lethal_hit_records_kill_with_shooter_as_killer() {
+        let mut network = MockServerNetwork::new();
+        let mut game = game_with_host(&mut network);
+        let victim = game.client_id_to_index[&2];
+        let killer = game.client_id_to_index[&1];
+        game.players[victim].health = 1;
+        let position = game.players[victim].state.position;
+        game.bullets.push(Bullet::new(
+            0,
+            killer,
+            position,
+            Vec3::new(0.1, 0.0, 0.0),
+            game.current_tick,
+        ));
+
+        let mut events = Vec::new();
+        let mut kills = Vec::new();
+        update_bullets(&mut game, &mut events, &mut kills);
+
+        assert_eq!(kills, vec![(victim, killer)]);
+        assert_eq!(game.players[killer].kills, 1);
+        assert_eq!(game.players[killer].deaths, 0);
+        assert_eq!(game.players[victim].deaths, 1);
+        assert_eq!(game.players[victim].self_kills, 0);
+        assert!(matches!(game.players[victim].status, Status::Dead));
+    }
+
+    #[test]
+    fn self_kill_does_not_count_toward_kills() {
+        let mut network = MockServerNetwork::new();
+        let mut game = game_with_host(&mut network);
+        let index = game.client_id_to_index[&1];
+        game.players[index].health = 1;
+        let position = game.players[index].state.position;
+        game.bullets.push(Bullet::new(
+            0,
+            index,
+            position,
+            Vec3::new(0.1, 0.0, 0.0),
+            game.current_tick,
+        ));
+
+        let mut events = Vec::new();
+        let mut kills = Vec::new();
+        update_bullets(&mut game, &mut events, &mut kills);
+
+        assert_eq!(kills, vec![(index, index)]);
+        assert_eq!(game.players[index].kills, 0);
+        assert_eq!(game.players[index].deaths, 1);
+        assert_eq!(game.players[index].self_kills, 1);
+    }
+
+    #[test]
+    fn fire_input_from_a_dead_player_spawns_no_bullet() {
+        let mut network = MockServerNetwork::new();
+        let mut game = game_with_host(&mut network);
+        let index = game.client_id_to_index[&1];
+        game.players[index].health = 0;
+        game.players[index].status = Status::Dead;
+        let tick = game.current_tick;
+        game.players[index].input_buffer.insert(WireItem {
+            id: tick as u16,
+            data: PlayerInput {
+                sim_tick: tick,
+                forward: true,
+                fire: true,
+                fire_nonce: Some(1),
+                ..Default::default()
+            },
+        });
+        let position = game.players[index].state.position;
+
+        handle(&mut network, &mut game);
+
+        assert!(game.bullets.is_empty());
+        assert!(!game.players[index].last_input.fire);
+        assert_eq!(game.players[index].shots_fired, 0);
+        assert_eq!(game.players[index].state.position, position);
+    }
+
+    fn afk_updates(network: &mut MockServerNetwork, client_id: u64) -> Vec<(usize, bool)> {
+        network
+            .get_sent_messages_data(client_id)
+            .iter()
+            .filter_map(|data| match decode_from_slice(data, config()).unwrap().0 {
+                ServerMessage::PlayerAfk { index, afk } => Some((index, afk)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn idle_player_is_flagged_afk_and_cleared_on_next_input() {
+        let mut network = MockServerNetwork::new();
+        let mut game = game_with_host(&mut network);
+        game.settings.afk_timeout = Some(Duration::from_secs(10));
+        game.settings.afk_action = AfkAction::Flag;
+        let timeout_ticks = (10.0 / TICK_SECS) as u64;
+        let idle = game.client_id_to_index[&1];
+        let active = game.client_id_to_index[&2];
+
+        game.current_tick += timeout_ticks - 1;
+        game.players[active].last_active_tick = game.current_tick;
+        handle(&mut network, &mut game);
+        assert!(!game.players[idle].afk);
+        assert!(afk_updates(&mut network, 2).is_empty());
+
+        game.players[active].last_active_tick = game.current_tick;
+        handle(&mut network, &mut game);
+        assert!(game.players[idle].afk);
+        assert!(!game.players[active].afk);
+        assert!(matches!(game.players[idle].status, Status::Alive));
+        assert_eq!(afk_updates(&mut network, 2), vec![(idle, true)]);
+
+        let tick = game.current_tick;
+        game.players[idle].input_buffer.insert(WireItem {
+            id: tick as u16,
+            data: PlayerInput {
+                sim_tick: tick,
+                forward: true,
+                ..Default::default()
+            },
+        });
+        handle(&mut network, &mut game);
+        assert!(!game.players[idle].afk);
+        assert_eq!(
+            afk_updates(&mut network, 2),
+            vec![(idle, true), (idle, false)]
+        );
+    }
+
+    #[test]
+    fn firing_and_hitting_update_accuracy_stats() {
+        let mut network = MockServerNetwork::new();
+        let mut game = game_with_host(&mut network);
+        let shooter = game.client_id_to_index[&1];
+        let victim = game.client_id_to_index[&2];
+        game.players[shooter].last_input.fire = true;
+        game.players[shooter].last_input.fire_nonce = Some(1);
+
+        step_simulation(&mut game);
+
+        assert_eq!(game.players[shooter].shots_fired, 1);
+        assert_eq!(game.players[shooter].hits_landed, 0);
+        assert_eq!(game.players[shooter].damage_dealt, 0);
+
+        let health_before = game.players[victim].health;
+        game.bullets[0].position = game.players[victim].state.position;
+        game.bullets[0].velocity = Vec3::new(0.1, 0.0, 0.0);
+        let mut events = Vec::new();
+        let mut kills = Vec::new();
+        update_bullets(&mut game, &mut events, &mut kills);
+
+        let damage = u32::from(health_before - game.players[victim].health);
+        assert!(damage > 0);
+        assert_eq!(game.players[shooter].shots_fired, 1);
+        assert_eq!(game.players[shooter].hits_landed, 1);
+        assert_eq!(game.players[shooter].damage_dealt, damage);
+    }
+
+    #[test]
+    fn head_on_bullets_within_combined_radius_are_both_removed() {
+        let mut network = MockServerNetwork::new();
+        let mut game = game_with_host(&mut network);
+        let position = Vec3::new(100.0, 10.0, 100.0);
+        let offset = Vec3::new(bullets::BULLET_SHELL_RADIUS, 0.0, 0.0);
+        game.bullets.push(Bullet::new(
+            0,
+            0,
+            position - offset,
+            Vec3::new(1.0, 0.0, 0.0),
+            game.current_tick,
+        ));
+        game.bullets.push(Bullet::new(
+            1,
+            1,
+            position + offset * 0.9,
+            Vec3::new(-1.0, 0.0, 0.0),
+            game.current_tick,
+        ));
+        game.bullets.push(Bullet::new(
+            2,
+            1,
+            position + Vec3::new(0.0, 0.0, CELL_SIZE),
+            Vec3::new(-1.0, 0.0, 0.0),
+            game.current_tick,
+        ));
+        game.players[0].bullets_in_air = 1;
+        game.players[1].bullets_in_air = 2;
+
+        let mut events = Vec::new();
+        collide_bullets(&mut game, &mut events);
+
+        assert_eq!(game.bullets.len(), 1);
+        assert_eq!(game.bullets[0].id, 2);
+        assert_eq!(game.players[0].bullets_in_air, 0);
+        assert_eq!(game.players[1].bullets_in_air, 1);
+        let impacts: Vec<(u32, u32)> = events
+            .iter()
+            .filter_map(|event| match event {
+                BulletEvent::HitBullet {
+                    bullet_id,
+                    other_bullet_id,
+                    ..
+                } => Some((*bullet_id, *other_bullet_id)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(impacts, vec![(0, 1), (1, 0)]);
+    }
+
+    #[test]
+    fn fires_within_cooldown_spawn_only_one_bullet() {
+        let mut network = MockServerNetwork::new();
+        let mut game = game_with_host(&mut network);
+        let index = game.client_id_to_index[&1];
+        game.players[index].last_input.fire = true;
+        let first_bullet_id = game.next_bullet_id;
+
+        game.players[index].last_input.fire_nonce = Some(1);
+        step_simulation(&mut game);
+        game.current_tick += 1;
+        game.players[index].last_input.fire_nonce = Some(2);
+        let (events, _, _) = step_simulation(&mut game);
+
+        assert!(
+            !events
+                .iter()
+                .any(|event| matches!(event, BulletEvent::Spawn { .. }))
+        );
+        assert_eq!(game.next_bullet_id, first_bullet_id + 1);
+        assert_eq!(game.players[index].fire_cooldown_violations, 1);
+
+        game.current_tick += bullets::cooldown_ticks();
+        game.players[index].last_input.fire_nonce = Some(3);
+        step_simulation(&mut game);
+        assert_eq!(game.next_bullet_id, first_bullet_id + 2);
+    }
+
+    #[test]
+    fn firing_empties_the_magazine_and_reloading_refills_it() {
+        let mut network = MockServerNetwork::new();
+        let mut game = game_with_host(&mut network);
+        let magazine = Magazine::for_difficulty(2, 1);
+        game.bullet_tuning.magazine = Some(magazine);
+        let index = game.client_id_to_index[&1];
+        game.players[index].ammo = Some(Ammo::full(&magazine));
+        game.players[index].last_input.fire = true;
+        let first_bullet_id = game.next_bullet_id;
+
+        let fire = |game: &mut Game, nonce| {
+            game.current_tick += bullets::cooldown_ticks();
+            game.players[index].last_input.fire_nonce = Some(nonce);
+            step_simulation(game);
+        };
+
+        fire(&mut game, 1);
+        assert_eq!(game.players[index].ammo.unwrap().rounds, 1);
+        fire(&mut game, 2);
+        let ammo = game.players[index].ammo.unwrap();
+        assert_eq!(ammo.rounds, 0);
+        let reloaded_tick = game.current_tick + magazine.reload_ticks();
+        assert_eq!(ammo.reloading_until_tick, Some(reloaded_tick));
+        assert_eq!(game.snapshot_for(index).ammo, Some(ammo));
+
+        fire(&mut game, 3);
+        assert_eq!(game.next_bullet_id, first_bullet_id + 2);
+
+        game.current_tick = reloaded_tick - bullets::cooldown_ticks();
+        fire(&mut game, 4);
+        assert_eq!(game.next_bullet_id, first_bullet_id + 3);
+        assert_eq!(
+            game.players[index].ammo,
+            Some(Ammo {
+                rounds: 1,
+                reloading_until_tick: None,
+            })
+        );
+    }
+
+    #[test]
+    fn player_next_to_a_health_pickup_is_healed_and_the_pickup_is_consumed() {
+        let mut network = MockServerNetwork::new();
+        let mut game = game_with_host(&mut network);
+        let index = game.client_id_to_index[&1];
+        game.players[index].health = 4;
+        let pickup = Pickup {
+            id: 7,
+            kind: PickupKind::Health,
+            position: game.players[index].state.position + Vec3::new(10.0, 0.0, 0.0),
+        };
+        game.pickups = vec![pickup];
+
+        handle(&mut network, &mut game);
+
+        assert_eq!(game.players[index].health, 4 + pickups::HEALTH_RESTORED);
+        assert!(game.pickups.is_empty());
+        assert_eq!(game.pickup_respawns.len(), 1);
+        let collected = network
+            .get_sent_messages_data(2)
+            .iter()
+            .filter_map(|data| decode_from_slice::<ServerMessage, _>(data, config()).ok())
+            .any(|(message, _)| {
+                matches!(
+                    message,
+                    ServerMessage::PickupEvent(PickupEvent::Collected {
+                        pickup_id: 7,
+                        player_index,
+                    }) if player_index == index
+                )
+            });
+        assert!(collected);
+
+        game.players[index].health = MAX_HEALTH;
+        game.current_tick = game.pickup_respawns[0].0;
+        step_simulation(&mut game);
+        assert_eq!(game.pickups, vec![pickup]);
+    }
+
+    #[test]
+    fn removing_last_player_returns_to_lobby() {
+        let mut network = MockServerNetwork::new();
+        let mut game = game_with_host(&mut network);
+        game.settings.teams = true;
+
+        game.remove_client(1, &mut network);
+        assert!(handle(&mut network, &mut game).is_none());
+
+        game.remove_client(2, &mut network);
+        match handle(&mut network, &mut game) {
+            Some(ServerState::Lobby(lobby)) => {
+                assert!(lobby.usernames.is_empty());
+                assert!(lobby.settings().teams);
+            }
+            _ => panic!("expected transition to lobby"),
         }
-        total_egress_bytes =
-            total_egress_bytes.saturating_add(payload_len.saturating_mul(recipients_count));
     }
 
-    state.note_egress_bytes(total_egress_bytes);
-    state.timer_expiration_tick = Some(current_tick);
+    fn kill(game: &mut Game, client_id: u64) {
+        let index = game.client_id_to_index[&client_id];
+        game.players[index].health = 0;
+        game.players[index].status = Status::Dead;
+    }
+
+    fn received_results(network: &mut MockServerNetwork, client_id: u64) -> Vec<ServerMessage> {
+        network
+            .get_sent_messages_data(client_id)
+            .iter()
+            .filter_map(|data| decode_from_slice::<ServerMessage, _>(data, config()).ok())
+            .map(|(message, _)| message)
+            .filter(|message| matches!(message, ServerMessage::GameOver { .. }))
+            .collect()
+    }
+
+    #[test]
+    fn last_player_alive_is_announced_as_winner() {
+        let mut network = MockServerNetwork::new();
+        for client_id in [1, 2, 3] {
+            network.add_client(client_id);
+        }
+        let usernames = HashMap::from([
+            (1, "Alice".to_string()),
+            (2, "Bob".to_string()),
+            (3, "Carol".to_string()),
+        ]);
+        let mut game = Game::new(InitialData::new(&usernames, &HashMap::new(), 1));
+
+        kill(&mut game, 1);
+        kill(&mut game, 3);
+        check_multiplayer_winner(&mut network, &mut game);
+
+        let winner = game.client_id_to_index[&2];
+        assert_eq!(game.winner_index, Some(winner));
+        for client_id in [1, 2, 3] {
+            let results = received_results(&mut network, client_id);
+            assert_eq!(results.len(), 1);
+            assert!(matches!(
+                &results[0],
+                ServerMessage::GameOver { winner: Some(name) } if name == "Bob"
+            ));
+        }
+    }
+
+    #[test]
+    fn simultaneous_deaths_are_announced_once_as_a_draw() {
+        let mut network = MockServerNetwork::new();
+        let mut game = game_with_host(&mut network);
+
+        kill(&mut game, 1);
+        kill(&mut game, 2);
+        check_multiplayer_winner(&mut network, &mut game);
+        check_multiplayer_winner(&mut network, &mut game);
+
+        assert_eq!(game.winner_index, None);
+        let results = received_results(&mut network, 1);
+        assert_eq!(results.len(), 1);
+        assert!(matches!(
+            results[0],
+            ServerMessage::GameOver { winner: None }
+        ));
+    }
 }
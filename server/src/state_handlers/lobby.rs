@@ -1,25 +1,24 @@
 use std::time::Instant;
 
-use bincode::{
-    config::standard,
-    serde::{decode_from_slice, encode_to_vec},
-};
+use bincode::serde::{decode_from_slice, encode_to_vec};
 
 use crate::{
-    net::ServerNetworkHandle,
+    chat::{log_chat, send_chat_cooldown},
+    net::{ServerNetworkHandle, disconnect_with_reason, is_oversized},
+    settings::StartPolicy,
     state::{
-        AuthAttemptOutcome, ChoosingDifficulty, Lobby, ServerState, evaluate_passcode_attempt,
+        AuthAttemptOutcome, ChoosingDifficulty, Lobby, ServerState, UsernameClaim,
+        evaluate_passcode_attempt,
     },
 };
 use common::{
     self,
-    auth::{MAX_ATTEMPTS, Passcode},
-    chat::MAX_CHAT_MESSAGE_BYTES,
+    auth::Passcode,
     net::AppChannel,
-    player::{MAX_USERNAME_LENGTH, UsernameError, sanitize_username},
+    player::{Color, UsernameError, sanitize_username},
     protocol::{
-        AUTH_INCORRECT_PASSCODE_DISCONNECTING_MESSAGE, AUTH_INCORRECT_PASSCODE_TRY_AGAIN_MESSAGE,
-        ClientMessage, ServerMessage, auth_success_message,
+        AUTH_INCORRECT_PASSCODE_TRY_AGAIN_MESSAGE, ClientMessage, DisconnectReason, ServerMessage,
+        config,
     },
 };
 
@@ -37,12 +36,16 @@ pub fn handle(
     for client_id in network.clients_id() {
         while let Some(data) = network.receive_message(client_id, AppChannel::ReliableOrdered) {
             *last_activity = Instant::now();
-            let Ok((message, _)) = decode_from_slice::<ClientMessage, _>(&data, standard()) else {
+            state.note_client_activity(client_id, *last_activity);
+            if is_oversized(client_id, &data) {
+                continue;
+            }
+            let Ok((message, _)) = decode_from_slice::<ClientMessage, _>(&data, config()) else {
                 eprintln!(
                     "client {} sent malformed data; disconnecting them",
                     client_id
                 );
-                network.disconnect(client_id);
+                disconnect_with_reason(network, client_id, DisconnectReason::ProtocolError);
                 continue;
             };
 
@@ -53,6 +56,7 @@ pub fn handle(
                         continue;
                     }
 
+                    let max_attempts = state.max_auth_attempts();
                     let (outcome, attempts_count) = {
                         let attempts_entry = state
                             .authentication_attempts(client_id)
@@ -61,7 +65,7 @@ pub fn handle(
                             passcode.bytes.as_slice(),
                             attempts_entry,
                             &guess_bytes,
-                            MAX_ATTEMPTS,
+                            max_attempts,
                         );
                         let count = *attempts_entry;
                         (outcome, count)
@@ -72,10 +76,11 @@ pub fn handle(
                             println!("Client {} authenticated successfully.", client_id);
                             state.mark_authenticated(client_id);
 
-                            let prompt = auth_success_message(MAX_USERNAME_LENGTH);
-                            let message = ServerMessage::ServerInfo { message: prompt };
-                            let payload = encode_to_vec(&message, standard())
-                                .expect("failed to serialize ServerInfo");
+                            let message = ServerMessage::AuthSucceeded {
+                                max_username_length: state.max_username_length(),
+                            };
+                            let payload = encode_to_vec(&message, config())
+                                .expect("failed to serialize AuthSucceeded");
                             network.send_message(client_id, AppChannel::ReliableOrdered, payload);
                         }
                         AuthAttemptOutcome::TryAgain => {
@@ -87,7 +92,7 @@ pub fn handle(
                             let message = ServerMessage::ServerInfo {
                                 message: AUTH_INCORRECT_PASSCODE_TRY_AGAIN_MESSAGE.to_string(),
                             };
-                            let payload = encode_to_vec(&message, standard())
+                            let payload = encode_to_vec(&message, config())
                                 .expect("failed to serialize ServerInfo");
                             network.send_message(client_id, AppChannel::ReliableOrdered, payload);
                         }
@@ -96,13 +101,11 @@ pub fn handle(
                                 "client {} failed authentication; disconnecting them",
                                 client_id
                             );
-                            let message = ServerMessage::ServerInfo {
-                                message: AUTH_INCORRECT_PASSCODE_DISCONNECTING_MESSAGE.to_string(),
-                            };
-                            let payload = encode_to_vec(&message, standard())
-                                .expect("failed to serialize ServerInfo");
-                            network.send_message(client_id, AppChannel::ReliableOrdered, payload);
-                            network.disconnect(client_id);
+                            disconnect_with_reason(
+                                network,
+                                client_id,
+                                DisconnectReason::FailedAuth,
+                            );
                             state.remove_client(client_id, network);
                         }
                     }
@@ -113,9 +116,9 @@ pub fn handle(
                         continue;
                     }
 
-                    match sanitize_username(&username_text) {
+                    match sanitize_username(&username_text, state.max_username_length()) {
                         Ok(username) => {
-                            if state.is_username_taken(&username) {
+                            if state.claim_username(client_id, &username) == UsernameClaim::Taken {
                                 send_username_error(
                                     network,
                                     client_id,
@@ -125,7 +128,6 @@ pub fn handle(
                                 continue;
                             }
 
-                            state.register_username(client_id, &username);
                             println!("Client {} set username to '{}'.", client_id, username);
 
                             let color = state
@@ -134,31 +136,41 @@ pub fn handle(
                             let message = ServerMessage::Welcome {
                                 username: username.to_string(),
                                 color,
+                                team: state.team(client_id),
+                                available_colors: state.available_colors(client_id),
+                                max_chat_bytes: state.max_chat_bytes(),
                             };
-                            let payload = encode_to_vec(&message, standard())
+                            let payload = encode_to_vec(&message, config())
                                 .expect("failed to serialize Welcome");
                             network.send_message(client_id, AppChannel::ReliableOrdered, payload);
 
                             let others = state.roster_except(client_id);
-                            let message = ServerMessage::Roster { online: others };
-                            let payload = encode_to_vec(&message, standard())
+                            let connecting = state.placeholder_count_except(client_id);
+                            let message = ServerMessage::Roster {
+                                online: others,
+                                connecting,
+                            };
+                            let payload = encode_to_vec(&message, config())
                                 .expect("failed to serialize Roster");
                             network.send_message(client_id, AppChannel::ReliableOrdered, payload);
 
                             if state.usernames_except(client_id).is_empty() {
                                 state.set_host(client_id, network);
+                            } else {
+                                state.reclaim_host(client_id, Instant::now(), network);
                             }
 
                             let message = ServerMessage::UserJoined {
                                 username: username.to_string(),
                             };
-                            let payload = encode_to_vec(&message, standard())
+                            let payload = encode_to_vec(&message, config())
                                 .expect("failed to serialize UserJoined");
                             network.broadcast_message_except(
                                 client_id,
                                 AppChannel::ReliableOrdered,
                                 payload,
                             );
+                            state.broadcast_player_count(network);
                         }
                         Err(err) => {
                             let error_text = match err {
@@ -178,32 +190,52 @@ pub fn handle(
                         }
                     }
                 }
+                ClientMessage::SetColor(color) => {
+                    handle_set_color(network, state, client_id, color);
+                }
                 ClientMessage::SendChat(content) => {
-                    if let Some(username) = state.username(client_id) {
+                    if let Some(username) = state.username(client_id).map(str::to_string) {
                         let clean_content = common::input::sanitize(&content);
                         let trimmed_content = clean_content.trim();
 
                         if trimmed_content.is_empty() {
                             continue;
                         }
-                        if trimmed_content.len() > MAX_CHAT_MESSAGE_BYTES {
+                        if trimmed_content.len() > state.max_chat_bytes() {
                             println!(
                                 "Client {} sent an overly long chat message; ignoring.",
                                 client_id
                             );
                             continue;
                         }
+                        let now = Instant::now();
+                        if state.is_duplicate_chat(client_id, trimmed_content, now) {
+                            println!(
+                                "Client {} repeated the same chat message; ignoring.",
+                                client_id
+                            );
+                            continue;
+                        }
+                        if let Some(remaining) = state.chat_cooldown(client_id, now) {
+                            println!(
+                                "Client {} is sending chat messages too quickly; ignoring.",
+                                client_id
+                            );
+                            send_chat_cooldown(network, client_id, remaining);
+                            continue;
+                        }
 
                         println!("{}: {}", username, trimmed_content);
+                        log_chat(state.chat_log.as_ref(), &username, trimmed_content);
                         let color = state
                             .color(client_id)
                             .expect("missing player color for chat");
                         let message = ServerMessage::ChatMessage {
-                            username: username.to_string(),
+                            username,
                             color,
                             content: trimmed_content.to_string(),
                         };
-                        let payload = encode_to_vec(&message, standard())
+                        let payload = encode_to_vec(&message, config())
                             .expect("failed to serialize ChatMessage");
                         network.broadcast_message(AppChannel::ReliableOrdered, payload);
                     } else {
@@ -211,7 +243,44 @@ pub fn handle(
                     }
                 }
                 ClientMessage::RequestStartGame => {
-                    if state.is_host(client_id) {
+                    if !state.has_enough_players_to_start() {
+                        let message = ServerMessage::ServerInfo {
+                            message: format!(
+                                "Need at least {} players to start.",
+                                state.min_players()
+                            ),
+                        };
+                        let payload = encode_to_vec(&message, config())
+                            .expect("failed to serialize ServerInfo");
+                        network.send_message(client_id, AppChannel::ReliableOrdered, payload);
+
+                        let message = ServerMessage::DenyDifficultySelection;
+                        let payload = encode_to_vec(&message, config())
+                            .expect("failed to serialize DenyDifficultySelection");
+                        network.send_message(client_id, AppChannel::ReliableOrdered, payload);
+                    } else if state.start_policy() == StartPolicy::Majority {
+                        if state.vote_to_start(client_id) {
+                            return Some(ServerState::ChoosingDifficulty(ChoosingDifficulty::new(
+                                state,
+                            )));
+                        }
+
+                        let message = ServerMessage::DenyDifficultySelection;
+                        let payload = encode_to_vec(&message, config())
+                            .expect("failed to serialize DenyDifficultySelection");
+                        network.send_message(client_id, AppChannel::ReliableOrdered, payload);
+
+                        if let Some(username) = state.username(client_id) {
+                            let message = ServerMessage::StartVote {
+                                username: username.to_string(),
+                                votes: state.start_votes(),
+                                needed: state.votes_needed_to_start(),
+                            };
+                            let payload = encode_to_vec(&message, config())
+                                .expect("failed to serialize StartVote");
+                            network.broadcast_message(AppChannel::ReliableOrdered, payload);
+                        }
+                    } else if state.is_host(client_id) {
                         return Some(ServerState::ChoosingDifficulty(ChoosingDifficulty::new(
                             state,
                         )));
@@ -222,14 +291,14 @@ pub fn handle(
                         // choice state. This guarantees it.
                         eprintln!("client {} (not host) tried to start game", client_id);
                         let message = ServerMessage::DenyDifficultySelection;
-                        let payload = encode_to_vec(&message, standard())
+                        let payload = encode_to_vec(&message, config())
                             .expect("failed to serialize NoHost message");
                         network.send_message(client_id, AppChannel::ReliableOrdered, payload);
                     }
                 }
-                ClientMessage::SetDifficulty(_) => {
+                ClientMessage::SetDifficulty(_) | ClientMessage::ConfirmDifficulty => {
                     eprintln!(
-                        "client {} sent SetDifficulty in lobby state; ignoring",
+                        "client {} sent a difficulty choice in lobby state; ignoring",
                         client_id
                     );
                 }
@@ -242,13 +311,71 @@ pub fn handle(
                 ClientMessage::Input(_) => {
                     eprintln!("client {} sent game input; ignoring", client_id)
                 }
+                ClientMessage::TogglePause => {
+                    eprintln!(
+                        "client {} tried to pause in lobby state; ignoring",
+                        client_id
+                    )
+                }
+                ClientMessage::JoinRoom(_) => {
+                    eprintln!(
+                        "client {} tried to join a room from inside one; ignoring",
+                        client_id
+                    )
+                }
+                ClientMessage::ListRooms => {
+                    eprintln!(
+                        "client {} asked for the room list in lobby state; ignoring",
+                        client_id
+                    )
+                }
+                ClientMessage::Ping(timestamp) => send_pong(network, client_id, timestamp),
             }
         }
     }
 
+    let now = Instant::now();
+    state.expire_host_reclaim(now);
+    state.reassign_idle_host(now, network);
+
     None
 }
 
+pub fn handle_set_color(
+    network: &mut dyn ServerNetworkHandle,
+    state: &mut Lobby,
+    client_id: u64,
+    color: Color,
+) {
+    if state.username(client_id).is_none() {
+        eprintln!(
+            "client {} chose a color before a username; ignoring",
+            client_id
+        );
+        return;
+    }
+
+    let assigned = state.request_color(client_id, color);
+    if assigned == color {
+        println!("Client {} chose {}.", client_id, color);
+    } else {
+        println!(
+            "Client {} asked for {}, which is taken; assigned {}.",
+            client_id, color, assigned
+        );
+    }
+
+    let message = ServerMessage::ColorAssigned { color: assigned };
+    let payload = encode_to_vec(&message, config()).expect("failed to serialize ColorAssigned");
+    network.send_message(client_id, AppChannel::ReliableOrdered, payload);
+}
+
+pub fn send_pong(network: &mut dyn ServerNetworkHandle, client_id: u64, timestamp: f64) {
+    let payload =
+        encode_to_vec(ServerMessage::Pong(timestamp), config()).expect("failed to serialize Pong");
+    network.send_message(client_id, AppChannel::ReliableOrdered, payload);
+}
+
 fn send_username_error(
     network: &mut dyn ServerNetworkHandle,
     client_id: u64,
@@ -258,7 +385,7 @@ fn send_username_error(
     let message = ServerMessage::UsernameError {
         message: message.to_string(),
     };
-    let payload = encode_to_vec(&message, standard()).expect("failed to serialize UsernameError");
+    let payload = encode_to_vec(&message, config()).expect("failed to serialize UsernameError");
     network.send_message(client_id, AppChannel::ReliableOrdered, payload);
 }
 
@@ -267,19 +394,58 @@ mod tests {
     use super::*;
     use std::time::Instant;
 
+    use crate::chat::{CHAT_BURST_LIMIT, CHAT_BURST_WINDOW};
+    use crate::net::MAX_RELIABLE_MESSAGE_BYTES;
+    use crate::settings::Settings;
     use crate::state::Lobby;
     use crate::test_helpers::MockServerNetwork;
-    use bincode::config::standard;
     use bincode::serde::decode_from_slice;
     use bincode::serde::encode_to_vec;
     use common::{
         auth::{MAX_ATTEMPTS, Passcode},
-        protocol::{
-            AUTH_INCORRECT_PASSCODE_DISCONNECTING_MESSAGE, ClientMessage, ServerMessage,
-            auth_success_message,
-        },
+        chat::MAX_CHAT_MESSAGE_BYTES,
+        player::MAX_USERNAME_LENGTH,
+        protocol::{ClientMessage, DisconnectReason, ServerMessage},
     };
 
+    #[test]
+    fn oversized_payload_is_dropped_before_decoding() {
+        let mut network = MockServerNetwork::new();
+        let mut lobby_state = Lobby::new();
+        let passcode =
+            Passcode::from_string("123456").expect("failed to create passcode from string");
+
+        network.add_client(1);
+        lobby_state.register_connection(1);
+
+        let oversized = ClientMessage::SendPasscode(vec![0; MAX_RELIABLE_MESSAGE_BYTES]);
+        let payload = encode_to_vec(&oversized, config()).unwrap();
+        assert!(payload.len() > MAX_RELIABLE_MESSAGE_BYTES);
+        network.queue_raw_message(1, payload);
+        let valid = ClientMessage::SendPasscode(vec![1, 2, 3, 4, 5, 6]);
+        network.queue_raw_message(1, encode_to_vec(&valid, config()).unwrap());
+
+        handle(
+            &mut network,
+            &mut lobby_state,
+            &passcode,
+            &mut Instant::now(),
+        );
+
+        assert!(network.disconnected_clients.is_empty());
+        assert!(lobby_state.needs_username(1));
+        let client_msgs = network.get_sent_messages_data(1);
+        assert_eq!(client_msgs.len(), 1);
+        let msg = decode_from_slice::<ServerMessage, _>(&client_msgs[0], config())
+            .unwrap()
+            .0;
+        assert!(
+            matches!(msg, ServerMessage::AuthSucceeded { .. }),
+            "expected AuthSucceeded, got {:?}",
+            msg
+        );
+    }
+
     #[test]
     fn auth_success() {
         let mut network = MockServerNetwork::new();
@@ -291,7 +457,7 @@ mod tests {
         lobby_state.register_connection(1);
 
         let msg = ClientMessage::SendPasscode(vec![1, 2, 3, 4, 5, 6]);
-        let payload = encode_to_vec(&msg, standard()).unwrap();
+        let payload = encode_to_vec(&msg, config()).unwrap();
         network.queue_raw_message(1, payload);
 
         let mut last_activity = Instant::now();
@@ -307,13 +473,16 @@ mod tests {
 
         let client_msgs = network.get_sent_messages_data(1);
         assert_eq!(client_msgs.len(), 1);
-        let msg = decode_from_slice::<ServerMessage, _>(&client_msgs[0], standard())
+        let msg = decode_from_slice::<ServerMessage, _>(&client_msgs[0], config())
             .unwrap()
             .0;
-        if let ServerMessage::ServerInfo { message } = msg {
-            assert_eq!(message, auth_success_message(MAX_USERNAME_LENGTH));
+        if let ServerMessage::AuthSucceeded {
+            max_username_length,
+        } = msg
+        {
+            assert_eq!(max_username_length, MAX_USERNAME_LENGTH);
         } else {
-            panic!("expected ServerInfo message, got {:?}", msg);
+            panic!("expected AuthSucceeded message, got {:?}", msg);
         }
     }
 
@@ -329,7 +498,7 @@ mod tests {
 
         for _ in 0..MAX_ATTEMPTS {
             let msg = ClientMessage::SendPasscode(vec![0, 0, 0, 0, 0, 0]);
-            let payload = encode_to_vec(&msg, standard()).unwrap();
+            let payload = encode_to_vec(&msg, config()).unwrap();
             network.queue_raw_message(1, payload);
         }
 
@@ -347,14 +516,50 @@ mod tests {
         assert!(network.disconnected_clients.contains(&1));
         let client_msgs = network.get_sent_messages_data(1);
         let last_msg_data = client_msgs.last().unwrap();
-        let msg = decode_from_slice::<ServerMessage, _>(last_msg_data, standard())
+        let msg = decode_from_slice::<ServerMessage, _>(last_msg_data, config())
             .unwrap()
             .0;
-        if let ServerMessage::ServerInfo { message } = msg {
-            assert_eq!(message, AUTH_INCORRECT_PASSCODE_DISCONNECTING_MESSAGE);
-        } else {
-            panic!("expected ServerInfo message, got {:?}", msg);
-        }
+        assert!(matches!(
+            msg,
+            ServerMessage::DisconnectReason(DisconnectReason::FailedAuth)
+        ));
+    }
+
+    #[test]
+    fn configured_auth_limit_of_one_disconnects_on_first_wrong_guess() {
+        let mut network = MockServerNetwork::new();
+        let mut lobby_state = Lobby::with_settings(Settings {
+            max_auth_attempts: Some(1),
+            ..Settings::default()
+        });
+        let passcode =
+            Passcode::from_string("123456").expect("failed to create passcode from string");
+
+        network.add_client(1);
+        lobby_state.register_connection(1);
+
+        let msg = ClientMessage::SendPasscode(vec![0, 0, 0, 0, 0, 0]);
+        let payload = encode_to_vec(&msg, config()).unwrap();
+        network.queue_raw_message(1, payload);
+
+        let mut last_activity = Instant::now();
+        handle(
+            &mut network,
+            &mut lobby_state,
+            &passcode,
+            &mut last_activity,
+        );
+
+        assert!(network.disconnected_clients.contains(&1));
+        let client_msgs = network.get_sent_messages_data(1);
+        let last_msg_data = client_msgs.last().unwrap();
+        let msg = decode_from_slice::<ServerMessage, _>(last_msg_data, config())
+            .unwrap()
+            .0;
+        assert!(matches!(
+            msg,
+            ServerMessage::DisconnectReason(DisconnectReason::FailedAuth)
+        ));
     }
 
     #[test]
@@ -374,7 +579,7 @@ mod tests {
         lobby_state.mark_authenticated(2);
 
         let msg = ClientMessage::SetUsername("Bob".to_string());
-        let payload = encode_to_vec(&msg, standard()).unwrap();
+        let payload = encode_to_vec(&msg, config()).unwrap();
         network.queue_raw_message(2, payload);
 
         let mut last_activity = Instant::now();
@@ -391,22 +596,34 @@ mod tests {
         let bob_msgs = network.get_sent_messages_data(2);
         assert_eq!(bob_msgs.len(), 2);
 
-        let msg1 = decode_from_slice::<ServerMessage, _>(&bob_msgs[0], standard())
+        let msg1 = decode_from_slice::<ServerMessage, _>(&bob_msgs[0], config())
             .unwrap()
             .0;
         let bob_color = lobby_state.color(2).expect("missing color for Bob");
-        if let ServerMessage::Welcome { username, color } = msg1 {
+        if let ServerMessage::Welcome {
+            username,
+            color,
+            team,
+            available_colors,
+            max_chat_bytes,
+        } = msg1
+        {
             assert_eq!(username, "bob");
+            assert_eq!(max_chat_bytes, MAX_CHAT_MESSAGE_BYTES);
             assert_eq!(color, bob_color);
+            assert_eq!(team, None);
+            assert!(available_colors.contains(&bob_color));
+            assert!(!available_colors.contains(&lobby_state.color(1).unwrap()));
         } else {
             panic!("expected Welcome message, got {:?}", msg1);
         }
 
-        let msg2 = decode_from_slice::<ServerMessage, _>(&bob_msgs[1], standard())
+        let msg2 = decode_from_slice::<ServerMessage, _>(&bob_msgs[1], config())
             .unwrap()
             .0;
         let alice_color = lobby_state.color(1).expect("missing color for Alice");
-        if let ServerMessage::Roster { online } = msg2 {
+        if let ServerMessage::Roster { online, connecting } = msg2 {
+            assert_eq!(connecting, 0);
             assert_eq!(online.len(), 1);
             assert_eq!(online[0].username, "alice");
             assert_eq!(online[0].color, alice_color);
@@ -416,7 +633,7 @@ mod tests {
 
         let alice_msgs = network.get_sent_messages_data(1);
         assert_eq!(alice_msgs.len(), 1);
-        let msg_alice = decode_from_slice::<ServerMessage, _>(&alice_msgs[0], standard())
+        let msg_alice = decode_from_slice::<ServerMessage, _>(&alice_msgs[0], config())
             .unwrap()
             .0;
         if let ServerMessage::UserJoined { username } = msg_alice {
@@ -426,6 +643,376 @@ mod tests {
         }
     }
 
+    fn broadcast_player_counts(network: &MockServerNetwork) -> Vec<usize> {
+        network
+            .get_broadcast_messages_data()
+            .iter()
+            .filter_map(|data| {
+                match decode_from_slice::<ServerMessage, _>(data, config())
+                    .unwrap()
+                    .0
+                {
+                    ServerMessage::PlayerCount(count) => Some(count),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn join_broadcasts_updated_player_count() {
+        let mut network = MockServerNetwork::new();
+        let mut lobby_state = named_lobby(&mut network);
+        let passcode =
+            Passcode::from_string("123456").expect("failed to create passcode from string");
+
+        network.add_client(3);
+        lobby_state.register_connection(3);
+        lobby_state.mark_authenticated(3);
+
+        let msg = ClientMessage::SetUsername("Carol".to_string());
+        let payload = encode_to_vec(&msg, config()).unwrap();
+        network.queue_raw_message(3, payload);
+
+        let mut last_activity = Instant::now();
+        handle(
+            &mut network,
+            &mut lobby_state,
+            &passcode,
+            &mut last_activity,
+        );
+
+        assert_eq!(broadcast_player_counts(&network), vec![3]);
+    }
+
+    #[test]
+    fn leave_broadcasts_updated_player_count() {
+        let mut network = MockServerNetwork::new();
+        let mut lobby_state = named_lobby(&mut network);
+
+        lobby_state.remove_client(2, &mut network);
+
+        assert_eq!(broadcast_player_counts(&network), vec![1]);
+    }
+
+    #[test]
+    fn ping_is_answered_with_a_pong_to_the_sender_only() {
+        let mut network = MockServerNetwork::new();
+        let mut lobby_state = named_lobby(&mut network);
+        let passcode =
+            Passcode::from_string("123456").expect("failed to create passcode from string");
+
+        let payload = encode_to_vec(ClientMessage::Ping(1234.5), config()).unwrap();
+        network.queue_raw_message(1, payload);
+
+        let mut last_activity = Instant::now();
+        handle(
+            &mut network,
+            &mut lobby_state,
+            &passcode,
+            &mut last_activity,
+        );
+
+        let alice_msgs = network.get_sent_messages_data(1);
+        assert_eq!(alice_msgs.len(), 1);
+        let msg = decode_from_slice::<ServerMessage, _>(&alice_msgs[0], config())
+            .unwrap()
+            .0;
+        assert!(matches!(msg, ServerMessage::Pong(timestamp) if timestamp == 1234.5));
+        assert!(network.get_sent_messages_data(2).is_empty());
+        assert!(network.get_broadcast_messages_data().is_empty());
+    }
+
+    fn named_lobby(network: &mut MockServerNetwork) -> Lobby {
+        let mut lobby_state = Lobby::new();
+        for (client_id, name) in [(1, "alice"), (2, "bob")] {
+            network.add_client(client_id);
+            lobby_state.register_connection(client_id);
+            lobby_state.mark_authenticated(client_id);
+            lobby_state.register_username(client_id, name);
+        }
+        lobby_state
+    }
+
+    fn assigned_color(network: &mut MockServerNetwork, client_id: u64) -> Color {
+        let sent = network.get_sent_messages_data(client_id);
+        assert_eq!(sent.len(), 1);
+        match decode_from_slice::<ServerMessage, _>(&sent[0], config())
+            .unwrap()
+            .0
+        {
+            ServerMessage::ColorAssigned { color } => color,
+            other => panic!("expected ColorAssigned message, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn set_color_assigns_a_free_color() {
+        let mut network = MockServerNetwork::new();
+        let mut lobby_state = named_lobby(&mut network);
+        let passcode = Passcode::from_string("123456").unwrap();
+        let free = lobby_state.available_colors(2)[0];
+        assert_ne!(Some(free), lobby_state.color(1));
+
+        let msg = ClientMessage::SetColor(free);
+        let payload = encode_to_vec(&msg, config()).unwrap();
+        network.queue_raw_message(2, payload);
+        handle(
+            &mut network,
+            &mut lobby_state,
+            &passcode,
+            &mut Instant::now(),
+        );
+
+        assert_eq!(lobby_state.color(2), Some(free));
+        assert_eq!(assigned_color(&mut network, 2), free);
+    }
+
+    #[test]
+    fn set_color_rejects_a_color_taken_by_another_player() {
+        let mut network = MockServerNetwork::new();
+        let mut lobby_state = named_lobby(&mut network);
+        let passcode = Passcode::from_string("123456").unwrap();
+        let alice_color = lobby_state.color(1).unwrap();
+        let bob_color = lobby_state.color(2).unwrap();
+
+        let msg = ClientMessage::SetColor(alice_color);
+        let payload = encode_to_vec(&msg, config()).unwrap();
+        network.queue_raw_message(2, payload);
+        handle(
+            &mut network,
+            &mut lobby_state,
+            &passcode,
+            &mut Instant::now(),
+        );
+
+        assert_eq!(lobby_state.color(1), Some(alice_color));
+        assert_eq!(lobby_state.color(2), Some(bob_color));
+        assert_eq!(assigned_color(&mut network, 2), bob_color);
+        assert!(!lobby_state.available_colors(2).contains(&alice_color));
+    }
+
+    #[test]
+    fn identical_usernames_in_one_pass_register_only_once() {
+        let mut network = MockServerNetwork::new();
+        let mut lobby_state = Lobby::new();
+        let passcode =
+            Passcode::from_string("123456").expect("failed to create passcode from string");
+
+        for client_id in [1, 2] {
+            network.add_client(client_id);
+            lobby_state.register_connection(client_id);
+            lobby_state.mark_authenticated(client_id);
+            let msg = ClientMessage::SetUsername("Alice".to_string());
+            let payload = encode_to_vec(&msg, config()).unwrap();
+            network.queue_raw_message(client_id, payload);
+        }
+
+        let mut last_activity = Instant::now();
+        handle(
+            &mut network,
+            &mut lobby_state,
+            &passcode,
+            &mut last_activity,
+        );
+
+        let registered = [1, 2]
+            .into_iter()
+            .filter(|&client_id| lobby_state.username(client_id) == Some("alice"))
+            .count();
+        assert_eq!(registered, 1);
+
+        let rejected = [1, 2]
+            .into_iter()
+            .filter(|&client_id| {
+                network
+                    .get_sent_messages_data(client_id)
+                    .iter()
+                    .any(|data| {
+                        matches!(
+                            decode_from_slice::<ServerMessage, _>(data, config())
+                                .unwrap()
+                                .0,
+                            ServerMessage::UsernameError { .. }
+                        )
+                    })
+            })
+            .count();
+        assert_eq!(rejected, 1);
+    }
+
+    fn request_start(
+        network: &mut MockServerNetwork,
+        lobby_state: &mut Lobby,
+    ) -> Option<ServerState> {
+        let passcode =
+            Passcode::from_string("123456").expect("failed to create passcode from string");
+        let payload = encode_to_vec(&ClientMessage::RequestStartGame, config()).unwrap();
+        network.queue_raw_message(1, payload);
+        let mut last_activity = Instant::now();
+        handle(network, lobby_state, &passcode, &mut last_activity)
+    }
+
+    #[test]
+    fn start_is_blocked_below_minimum_player_count() {
+        let mut network = MockServerNetwork::new();
+        let mut lobby_state = Lobby::new();
+        network.add_client(1);
+        lobby_state.register_connection(1);
+        lobby_state.mark_authenticated(1);
+        lobby_state.register_username(1, "alice");
+        lobby_state.set_host(1, &mut network);
+
+        let next_state = request_start(&mut network, &mut lobby_state);
+
+        assert!(next_state.is_none());
+        let replies: Vec<ServerMessage> = network
+            .get_sent_messages_data(1)
+            .iter()
+            .map(|data| {
+                decode_from_slice::<ServerMessage, _>(data, config())
+                    .unwrap()
+                    .0
+            })
+            .collect();
+        assert!(replies.iter().any(|message| matches!(
+            message,
+            ServerMessage::ServerInfo { message } if message == "Need at least 2 players to start."
+        )));
+        assert!(matches!(
+            replies.last(),
+            Some(ServerMessage::DenyDifficultySelection)
+        ));
+    }
+
+    #[test]
+    fn start_is_allowed_at_minimum_player_count() {
+        let mut network = MockServerNetwork::new();
+        let mut lobby_state = named_lobby(&mut network);
+        lobby_state.set_host(1, &mut network);
+
+        let next_state = request_start(&mut network, &mut lobby_state);
+        assert!(matches!(
+            next_state,
+            Some(ServerState::ChoosingDifficulty(_))
+        ));
+
+        let mut network = MockServerNetwork::new();
+        let mut solo_lobby = Lobby::with_settings(Settings {
+            min_players: Some(1),
+            ..Settings::default()
+        });
+        network.add_client(1);
+        solo_lobby.register_connection(1);
+        solo_lobby.mark_authenticated(1);
+        solo_lobby.register_username(1, "alice");
+        solo_lobby.set_host(1, &mut network);
+
+        let next_state = request_start(&mut network, &mut solo_lobby);
+        assert!(matches!(
+            next_state,
+            Some(ServerState::ChoosingDifficulty(_))
+        ));
+    }
+
+    #[test]
+    fn majority_policy_waits_for_enough_votes_before_starting() {
+        let mut network = MockServerNetwork::new();
+        let mut lobby_state = Lobby::new();
+        lobby_state.set_start_policy(StartPolicy::Majority);
+        let passcode =
+            Passcode::from_string("123456").expect("failed to create passcode from string");
+
+        for (client_id, name) in [(1, "alice"), (2, "bob"), (3, "carol")] {
+            network.add_client(client_id);
+            lobby_state.register_connection(client_id);
+            lobby_state.mark_authenticated(client_id);
+            lobby_state.register_username(client_id, name);
+        }
+        lobby_state.set_host(1, &mut network);
+
+        let payload = encode_to_vec(&ClientMessage::RequestStartGame, config()).unwrap();
+        network.queue_raw_message(1, payload.clone());
+        let mut last_activity = Instant::now();
+        let next_state = handle(
+            &mut network,
+            &mut lobby_state,
+            &passcode,
+            &mut last_activity,
+        );
+
+        assert!(next_state.is_none());
+        assert_eq!(lobby_state.start_votes(), 1);
+        assert!(network.get_broadcast_messages_data().iter().any(|data| {
+            matches!(
+                decode_from_slice::<ServerMessage, _>(data, config())
+                    .unwrap()
+                    .0,
+                ServerMessage::StartVote {
+                    votes: 1,
+                    needed: 2,
+                    ..
+                }
+            )
+        }));
+
+        network.queue_raw_message(2, payload);
+        let next_state = handle(
+            &mut network,
+            &mut lobby_state,
+            &passcode,
+            &mut last_activity,
+        );
+
+        assert!(matches!(
+            next_state,
+            Some(ServerState::ChoosingDifficulty(_))
+        ));
+        assert_eq!(lobby_state.start_votes(), 0);
+    }
+
+    #[test]
+    fn identical_back_to_back_chats_are_broadcast_once() {
+        let mut network = MockServerNetwork::new();
+        let mut lobby_state = Lobby::new();
+        let passcode =
+            Passcode::from_string("123456").expect("failed to create passcode from string");
+
+        network.add_client(1);
+        lobby_state.register_connection(1);
+        lobby_state.mark_authenticated(1);
+        lobby_state.register_username(1, "alice");
+
+        for content in ["Hello!", "Hello!", "Goodbye!"] {
+            let msg = ClientMessage::SendChat(content.to_string());
+            let payload = encode_to_vec(&msg, config()).unwrap();
+            network.queue_raw_message(1, payload);
+        }
+
+        let mut last_activity = Instant::now();
+        handle(
+            &mut network,
+            &mut lobby_state,
+            &passcode,
+            &mut last_activity,
+        );
+
+        let contents: Vec<String> = network
+            .get_broadcast_messages_data()
+            .iter()
+            .filter_map(|data| {
+                match decode_from_slice::<ServerMessage, _>(data, config())
+                    .unwrap()
+                    .0
+                {
+                    ServerMessage::ChatMessage { content, .. } => Some(content),
+                    _ => None,
+                }
+            })
+            .collect();
+        assert_eq!(contents, vec!["Hello!", "Goodbye!"]);
+    }
+
     #[test]
     fn chat_message() {
         let mut network = MockServerNetwork::new();
@@ -444,7 +1031,7 @@ mod tests {
         lobby_state.register_username(2, "bob");
 
         let msg = ClientMessage::SendChat("Hello Bob!".to_string());
-        let payload = encode_to_vec(&msg, standard()).unwrap();
+        let payload = encode_to_vec(&msg, config()).unwrap();
         network.queue_raw_message(1, payload);
 
         let mut last_activity = Instant::now();
@@ -459,7 +1046,7 @@ mod tests {
 
         let broadcasts = network.get_broadcast_messages_data();
         assert_eq!(broadcasts.len(), 1);
-        let msg = decode_from_slice::<ServerMessage, _>(&broadcasts[0], standard())
+        let msg = decode_from_slice::<ServerMessage, _>(&broadcasts[0], config())
             .unwrap()
             .0;
         let alice_color = lobby_state.color(1).expect("missing color for Alice");
@@ -491,7 +1078,7 @@ mod tests {
 
         let long_message = "a".repeat(MAX_CHAT_MESSAGE_BYTES + 1);
         let msg = ClientMessage::SendChat(long_message);
-        let payload = encode_to_vec(&msg, standard()).unwrap();
+        let payload = encode_to_vec(&msg, config()).unwrap();
         network.queue_raw_message(1, payload);
 
         let mut last_activity = Instant::now();
@@ -512,6 +1099,51 @@ mod tests {
         );
     }
 
+    #[test]
+    fn chat_over_rate_limit_triggers_cooldown() {
+        let mut network = MockServerNetwork::new();
+        let mut lobby_state = Lobby::new();
+        let passcode =
+            Passcode::from_string("123456").expect("failed to create passcode from string");
+
+        network.add_client(1);
+        lobby_state.register_connection(1);
+        lobby_state.mark_authenticated(1);
+        lobby_state.register_username(1, "Alice");
+
+        for i in 0..=CHAT_BURST_LIMIT {
+            let msg = ClientMessage::SendChat(format!("message {}", i));
+            let payload = encode_to_vec(&msg, config()).unwrap();
+            network.queue_raw_message(1, payload);
+        }
+
+        let mut last_activity = Instant::now();
+        let next_state = handle(
+            &mut network,
+            &mut lobby_state,
+            &passcode,
+            &mut last_activity,
+        );
+
+        assert!(next_state.is_none());
+        assert_eq!(
+            network.get_broadcast_messages_data().len(),
+            CHAT_BURST_LIMIT
+        );
+
+        let client_msgs = network.get_sent_messages_data(1);
+        let last = client_msgs.last().expect("expected a message to client");
+        let msg = decode_from_slice::<ServerMessage, _>(last, config())
+            .unwrap()
+            .0;
+        if let ServerMessage::ChatCooldown { seconds_remaining } = msg {
+            assert!(seconds_remaining > 0.0);
+            assert!(seconds_remaining <= CHAT_BURST_WINDOW.as_secs_f32());
+        } else {
+            panic!("expected ChatCooldown message, got {:?}", msg);
+        }
+    }
+
     #[test]
     fn chat_sanitization() {
         let mut network = MockServerNetwork::new();
@@ -533,7 +1165,7 @@ mod tests {
         let expected_content = "HelloBob!";
 
         let msg = ClientMessage::SendChat(malicious_content.to_string());
-        let payload = encode_to_vec(&msg, standard()).unwrap();
+        let payload = encode_to_vec(&msg, config()).unwrap();
         network.queue_raw_message(1, payload);
 
         let mut last_activity = Instant::now();
@@ -548,7 +1180,7 @@ mod tests {
 
         let broadcasts = network.get_broadcast_messages_data();
         assert_eq!(broadcasts.len(), 1);
-        let msg = decode_from_slice::<ServerMessage, _>(&broadcasts[0], standard())
+        let msg = decode_from_slice::<ServerMessage, _>(&broadcasts[0], config())
             .unwrap()
             .0;
 
@@ -582,7 +1214,7 @@ mod tests {
         lobby_state.mark_authenticated(1);
 
         let msg = ClientMessage::SetUsername("sErVeR".to_string());
-        let payload = encode_to_vec(&msg, standard()).unwrap();
+        let payload = encode_to_vec(&msg, config()).unwrap();
         network.queue_raw_message(1, payload);
 
         let mut last_activity = Instant::now();
@@ -599,7 +1231,7 @@ mod tests {
         let client_msgs = network.get_sent_messages_data(1);
         assert_eq!(client_msgs.len(), 1);
 
-        let msg = decode_from_slice::<ServerMessage, _>(&client_msgs[0], standard())
+        let msg = decode_from_slice::<ServerMessage, _>(&client_msgs[0], config())
             .unwrap()
             .0;
 
@@ -610,4 +1242,45 @@ mod tests {
             panic!("expected UsernameError, got {:?}", msg);
         }
     }
+
+    #[test]
+    fn server_username_limit_is_announced_and_enforced() {
+        let mut network = MockServerNetwork::new();
+        let mut lobby_state = Lobby::new();
+        lobby_state.set_max_username_length(Some(8));
+        let passcode =
+            Passcode::from_string("123456").expect("failed to create passcode from string");
+
+        network.add_client(1);
+        lobby_state.register_connection(1);
+        for msg in [
+            ClientMessage::SendPasscode(vec![1, 2, 3, 4, 5, 6]),
+            ClientMessage::SetUsername("ninechars".to_string()),
+        ] {
+            network.queue_raw_message(1, encode_to_vec(&msg, config()).unwrap());
+        }
+
+        handle(
+            &mut network,
+            &mut lobby_state,
+            &passcode,
+            &mut Instant::now(),
+        );
+
+        assert_eq!(lobby_state.username(1), None);
+        let messages: Vec<ServerMessage> = network
+            .get_sent_messages_data(1)
+            .iter()
+            .map(|data| decode_from_slice(data, config()).unwrap().0)
+            .collect();
+        assert!(matches!(
+            &messages[..],
+            [
+                ServerMessage::AuthSucceeded {
+                    max_username_length: 8,
+                },
+                ServerMessage::UsernameError { message: error },
+            ] if error == "username is too long"
+        ));
+    }
 }
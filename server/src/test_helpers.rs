@@ -1,6 +1,8 @@
 use std::collections::{HashMap, VecDeque};
 
-use crate::net::{ServerNetworkEvent, ServerNetworkHandle};
+use crate::net::{
+    PrioritizedMessage, PriorityOutbox, SendPriority, ServerNetworkEvent, ServerNetworkHandle,
+};
 use common::net::AppChannel;
 
 #[derive(Default)]
@@ -39,6 +41,8 @@ pub struct MockServerNetwork {
     /// to in `broadcast_message_except`.
     /// We add to this in tests using `add_client`.
     client_ids: Vec<u64>,
+
+    outbox: PriorityOutbox,
 }
 
 impl MockServerNetwork {
@@ -126,4 +130,42 @@ impl ServerNetworkHandle for MockServerNetwork {
         self.disconnected_clients.push(client_id);
         self.client_ids.retain(|&id| id != client_id);
     }
+
+    fn send_message_with_priority(
+        &mut self,
+        client_id: u64,
+        channel: AppChannel,
+        message: Vec<u8>,
+        priority: SendPriority,
+    ) {
+        self.outbox.push(PrioritizedMessage {
+            priority,
+            client_id: Some(client_id),
+            channel,
+            message,
+        });
+    }
+
+    fn broadcast_message_with_priority(
+        &mut self,
+        channel: AppChannel,
+        message: Vec<u8>,
+        priority: SendPriority,
+    ) {
+        self.outbox.push(PrioritizedMessage {
+            priority,
+            client_id: None,
+            channel,
+            message,
+        });
+    }
+
+    fn flush_prioritized(&mut self) {
+        for entry in self.outbox.drain() {
+            match entry.client_id {
+                Some(client_id) => self.send_message(client_id, entry.channel, entry.message),
+                None => self.broadcast_message(entry.channel, entry.message),
+            }
+        }
+    }
 }
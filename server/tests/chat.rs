@@ -1,15 +1,12 @@
 use std::time::{Duration, Instant};
 
-use bincode::{
-    config::standard,
-    serde::{decode_from_slice, encode_to_vec},
-};
+use bincode::serde::{decode_from_slice, encode_to_vec};
 use renet::{ChannelConfig, ClientNotFound, ConnectionConfig, RenetServer, SendType};
 
 use common::{
     auth::Passcode,
     net::AppChannel,
-    protocol::{ClientMessage, ServerMessage},
+    protocol::{ClientMessage, ServerMessage, config},
 };
 use server::{
     net::RenetServerNetworkHandle,
@@ -76,6 +73,21 @@ fn full_tick(
     server.update(tick_duration);
 }
 
+fn expect_auth_required(client: &mut renet::RenetClient) {
+    client.update(Duration::from_millis(16));
+    let data = client
+        .receive_message(AppChannel::ReliableOrdered)
+        .expect("new connections should be told the auth attempt limit");
+    let message = decode_from_slice::<ServerMessage, _>(&data, config())
+        .expect("failed to deserialize message")
+        .0;
+    assert!(
+        matches!(message, ServerMessage::AuthRequired { .. }),
+        "expected AuthRequired, got {:?}",
+        message
+    );
+}
+
 #[test]
 fn chat_messages_are_broadcast_to_other_clients() {
     let mut server = setup_test_server();
@@ -91,13 +103,18 @@ fn chat_messages_are_broadcast_to_other_clients() {
     full_tick(&mut server, &mut alice, &mut bob);
 
     {
-        let mut network_handle = RenetServerNetworkHandle {
-            server: &mut server,
-        };
-        update_server_state(&mut network_handle, &mut state, &passcode, &mut last_activity);
+        let mut network_handle = RenetServerNetworkHandle::new(&mut server);
+        update_server_state(
+            &mut network_handle,
+            &mut state,
+            &passcode,
+            &mut last_activity,
+        );
     }
 
     full_tick(&mut server, &mut alice, &mut bob);
+    expect_auth_required(&mut alice);
+    expect_auth_required(&mut bob);
 
     if let ServerState::Lobby(lobby) = &mut state {
         lobby.mark_authenticated(alice_id);
@@ -109,16 +126,19 @@ fn chat_messages_are_broadcast_to_other_clients() {
     }
 
     let msg = ClientMessage::SendChat("Hello, Bob!".to_string());
-    let payload = encode_to_vec(&msg, standard()).expect("failed to serialize message");
+    let payload = encode_to_vec(&msg, config()).expect("failed to serialize message");
     alice.send_message(AppChannel::ReliableOrdered, payload);
 
     full_tick(&mut server, &mut alice, &mut bob);
 
     {
-        let mut network_handle = RenetServerNetworkHandle {
-            server: &mut server,
-        };
-        update_server_state(&mut network_handle, &mut state, &passcode, &mut last_activity);
+        let mut network_handle = RenetServerNetworkHandle::new(&mut server);
+        update_server_state(
+            &mut network_handle,
+            &mut state,
+            &passcode,
+            &mut last_activity,
+        );
     }
 
     server.update(Duration::from_millis(16));
@@ -134,7 +154,7 @@ fn chat_messages_are_broadcast_to_other_clients() {
     let message_data = bob
         .receive_message(AppChannel::ReliableOrdered)
         .expect("Bob should receive the chat message");
-    let message = decode_from_slice::<ServerMessage, _>(&message_data, standard())
+    let message = decode_from_slice::<ServerMessage, _>(&message_data, config())
         .expect("failed to deserialize message")
         .0;
 
@@ -173,13 +193,18 @@ fn players_are_notified_when_others_join_and_leave() {
     full_tick(&mut server, &mut alice, &mut bob);
 
     {
-        let mut network_handle = RenetServerNetworkHandle {
-            server: &mut server,
-        };
-        update_server_state(&mut network_handle, &mut state, &passcode, &mut last_activity);
+        let mut network_handle = RenetServerNetworkHandle::new(&mut server);
+        update_server_state(
+            &mut network_handle,
+            &mut state,
+            &passcode,
+            &mut last_activity,
+        );
     }
 
     full_tick(&mut server, &mut alice, &mut bob);
+    expect_auth_required(&mut alice);
+    expect_auth_required(&mut bob);
 
     if let ServerState::Lobby(lobby) = &mut state {
         lobby.mark_authenticated(alice_id);
@@ -190,16 +215,19 @@ fn players_are_notified_when_others_join_and_leave() {
     }
 
     let msg = ClientMessage::SetUsername("Bob".to_string());
-    let payload = encode_to_vec(&msg, standard()).expect("failed to serialize message");
+    let payload = encode_to_vec(&msg, config()).expect("failed to serialize message");
     bob.send_message(AppChannel::ReliableOrdered, payload);
 
     full_tick(&mut server, &mut alice, &mut bob);
 
     {
-        let mut network_handle = RenetServerNetworkHandle {
-            server: &mut server,
-        };
-        update_server_state(&mut network_handle, &mut state, &passcode, &mut last_activity);
+        let mut network_handle = RenetServerNetworkHandle::new(&mut server);
+        update_server_state(
+            &mut network_handle,
+            &mut state,
+            &passcode,
+            &mut last_activity,
+        );
     }
 
     server.update(Duration::from_millis(16));
@@ -215,7 +243,7 @@ fn players_are_notified_when_others_join_and_leave() {
     let join_data = alice
         .receive_message(AppChannel::ReliableOrdered)
         .expect("Alice should be notified when bob joins");
-    let join_message = decode_from_slice::<ServerMessage, _>(&join_data, standard())
+    let join_message = decode_from_slice::<ServerMessage, _>(&join_data, config())
         .expect("failed to deserialize join message")
         .0;
 
@@ -225,15 +253,26 @@ fn players_are_notified_when_others_join_and_leave() {
         panic!("expected UserJoined message, got {:?}", join_message);
     }
 
+    let count_data = alice
+        .receive_message(AppChannel::ReliableOrdered)
+        .expect("Alice should receive the player count after bob joins");
+    let count_message = decode_from_slice::<ServerMessage, _>(&count_data, config())
+        .expect("failed to deserialize player count message")
+        .0;
+    assert!(matches!(count_message, ServerMessage::PlayerCount(2)));
+
     server.disconnect_local_client(bob_id, &mut bob);
 
     full_tick(&mut server, &mut alice, &mut bob);
 
     {
-        let mut network_handle = RenetServerNetworkHandle {
-            server: &mut server,
-        };
-        update_server_state(&mut network_handle, &mut state, &passcode, &mut last_activity);
+        let mut network_handle = RenetServerNetworkHandle::new(&mut server);
+        update_server_state(
+            &mut network_handle,
+            &mut state,
+            &passcode,
+            &mut last_activity,
+        );
     }
 
     server.update(Duration::from_millis(16));
@@ -249,7 +288,7 @@ fn players_are_notified_when_others_join_and_leave() {
     let leave_data = alice
         .receive_message(AppChannel::ReliableOrdered)
         .expect("Alice should be notified when Bob leaves");
-    let leave_message = decode_from_slice::<ServerMessage, _>(&leave_data, standard())
+    let leave_message = decode_from_slice::<ServerMessage, _>(&leave_data, config())
         .expect("failed to deserialize leave message")
         .0;
 
@@ -258,6 +297,14 @@ fn players_are_notified_when_others_join_and_leave() {
     } else {
         panic!("expected UserLeft message, got {:?}", leave_message);
     }
+
+    let count_data = alice
+        .receive_message(AppChannel::ReliableOrdered)
+        .expect("Alice should receive the player count after Bob leaves");
+    let count_message = decode_from_slice::<ServerMessage, _>(&count_data, config())
+        .expect("failed to deserialize player count message")
+        .0;
+    assert!(matches!(count_message, ServerMessage::PlayerCount(1)));
 }
 
 #[test]
@@ -275,13 +322,18 @@ fn test_handle_messages_username_success_and_broadcast() {
     full_tick(&mut server, &mut alice, &mut bob);
 
     {
-        let mut network_handle = RenetServerNetworkHandle {
-            server: &mut server,
-        };
-        update_server_state(&mut network_handle, &mut state, &passcode, &mut last_activity);
+        let mut network_handle = RenetServerNetworkHandle::new(&mut server);
+        update_server_state(
+            &mut network_handle,
+            &mut state,
+            &passcode,
+            &mut last_activity,
+        );
     }
 
     full_tick(&mut server, &mut alice, &mut bob);
+    expect_auth_required(&mut alice);
+    expect_auth_required(&mut bob);
 
     if let ServerState::Lobby(lobby) = &mut state {
         lobby.mark_authenticated(alice_id);
@@ -292,16 +344,19 @@ fn test_handle_messages_username_success_and_broadcast() {
     }
 
     let msg = ClientMessage::SetUsername("Bob".to_string());
-    let payload = encode_to_vec(&msg, standard()).expect("failed to serialize message");
+    let payload = encode_to_vec(&msg, config()).expect("failed to serialize message");
     bob.send_message(AppChannel::ReliableOrdered, payload);
 
     full_tick(&mut server, &mut alice, &mut bob);
 
     {
-        let mut network_handle = RenetServerNetworkHandle {
-            server: &mut server,
-        };
-        update_server_state(&mut network_handle, &mut state, &passcode, &mut last_activity);
+        let mut network_handle = RenetServerNetworkHandle::new(&mut server);
+        update_server_state(
+            &mut network_handle,
+            &mut state,
+            &passcode,
+            &mut last_activity,
+        );
     }
 
     server.update(Duration::from_millis(16));
@@ -324,7 +379,7 @@ fn test_handle_messages_username_success_and_broadcast() {
 
     let mut bob_msgs = Vec::new();
     while let Some(message_data) = bob.receive_message(AppChannel::ReliableOrdered) {
-        let msg = decode_from_slice::<ServerMessage, _>(&message_data, standard())
+        let msg = decode_from_slice::<ServerMessage, _>(&message_data, config())
             .unwrap()
             .0;
         bob_msgs.push(msg);
@@ -345,7 +400,7 @@ fn test_handle_messages_username_success_and_broadcast() {
 
     assert!(
         bob_msgs.iter().any(|msg| {
-            matches!(msg, ServerMessage::Roster { online } if online.len() == 1
+            matches!(msg, ServerMessage::Roster { online, .. } if online.len() == 1
                 && online[0].username == "alice"
                 && online[0].color == alice_color)
         }),
@@ -362,7 +417,7 @@ fn test_handle_messages_username_success_and_broadcast() {
     let alice_data = alice
         .receive_message(AppChannel::ReliableOrdered)
         .expect("Alice should have received a message");
-    let alice_msg = decode_from_slice::<ServerMessage, _>(&alice_data, standard())
+    let alice_msg = decode_from_slice::<ServerMessage, _>(&alice_data, config())
         .unwrap()
         .0;
 